@@ -0,0 +1,54 @@
+//! Timings of the generators, run with `cargo bench -p fractal-viz-core --bench generators`.
+//! Each fractal is drawn at several resolutions with its default iterations,
+//! then at one resolution with increasing iteration counts.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use fractal_viz_core::fractals::{Fractal, Julia, Mandelbrot, Newton};
+use fractal_viz_core::view::View;
+
+const RESOLUTIONS: [(u32, u32); 3] = [(320, 240), (1280, 720), (1920, 1080)];
+/// Resolution of the iteration count benchmarks
+const ITERATIONS_RESOLUTION: (u32, u32) = (640, 480);
+const ITERATIONS: [u32; 3] = [100, 500, 2000];
+
+/// The view each fractal is drawn at, covering the same area at every resolution
+fn view(centre: (f64, f64), width: u32) -> View {
+    View { zoom: 3.0 / width as f64, offset_x: centre.0, offset_y: centre.1, ..View::default() }
+}
+
+fn bench_fractal(c: &mut Criterion, name: &str, centre: (f64, f64), with_iterations: impl Fn(u32) -> Box<dyn Fractal>) {
+    let mut group = c.benchmark_group(name);
+    // whole frames are slow enough that fewer samples still give a stable estimate
+    group.sample_size(10);
+
+    let fractal = with_iterations(100);
+    for (width, height) in RESOLUTIONS {
+        let viewport = view(centre, width).viewport(width, height);
+        let mut pixels = vec![0; (width * height * 4) as usize];
+        group.throughput(Throughput::Elements((width * height) as u64));
+        group.bench_with_input(BenchmarkId::new("resolution", format!("{}x{}", width, height)), &viewport, |b, &viewport| {
+            b.iter(|| fractal.draw(&mut pixels, viewport));
+        });
+    }
+
+    let (width, height) = ITERATIONS_RESOLUTION;
+    let viewport = view(centre, width).viewport(width, height);
+    let mut pixels = vec![0; (width * height * 4) as usize];
+    group.throughput(Throughput::Elements((width * height) as u64));
+    for max_iterations in ITERATIONS {
+        let fractal = with_iterations(max_iterations);
+        group.bench_with_input(BenchmarkId::new("iterations", max_iterations), &viewport, |b, &viewport| {
+            b.iter(|| fractal.draw(&mut pixels, viewport));
+        });
+    }
+    group.finish();
+}
+
+fn generators(c: &mut Criterion) {
+    bench_fractal(c, "Mandelbrot", (-0.5, 0.0), |max_iterations| Box::new(Mandelbrot { max_iterations, ..Mandelbrot::default() }));
+    bench_fractal(c, "Julia", (0.0, 0.0), |max_iterations| Box::new(Julia { max_iterations, ..Julia::default() }));
+    bench_fractal(c, "Newton", (0.0, 0.0), |max_iterations| Box::new(Newton { max_iterations, ..Newton::default() }));
+}
+
+criterion_group!(benches, generators);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+use rayon::prelude::*;
+
+use crate::fractals::{Fractal, PointSample};
+use crate::viewport::Viewport;
+
+/// Colours of the boundary only rendering
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct EdgeStyle {
+    pub line: [u8; 3],
+    pub background: [u8; 3],
+}
+
+impl Default for EdgeStyle {
+    fn default() -> Self {
+        Self { line: [20, 20, 20], background: [245, 240, 230] }
+    }
+}
+
+/// Samples taken for each pixel, its four corners and its centre
+const SAMPLES: usize = 5;
+
+/// Draw only the boundaries between regions of the fractal, the edge of the set or of a Newton root's basin.
+/// Each pixel is sampled at its corners and centre, pixels where the samples disagree are on the boundary
+/// and how evenly they're split gives the anti-aliasing.
+pub fn draw(fractal: &dyn Fractal, pixels: &mut [u8], viewport: Viewport, style: EdgeStyle) {
+    let max_iterations = fractal.max_iterations();
+    let region = |x: f64, y: f64| region(fractal.sample(viewport.pixel_to_complex(x, y)), max_iterations);
+
+    // corners are shared between neighbouring pixels so they're only sampled once
+    let corners_width = viewport.width as usize + 1;
+    let corners: Vec<u8> = (0..corners_width * (viewport.height as usize + 1)).into_par_iter().map(|i| {
+        region((i % corners_width) as f64 - 0.5, (i / corners_width) as f64 - 0.5)
+    }).collect();
+
+    pixels.par_chunks_mut(4).enumerate().for_each(|(i, pixel)| {
+        let (x, y) = (i % viewport.width as usize, i / viewport.width as usize);
+        let corner = |dx: usize, dy: usize| corners[(y + dy) * corners_width + x + dx];
+        let samples = [corner(0, 0), corner(1, 0), corner(0, 1), corner(1, 1), region(x as f64, y as f64)];
+        let majority = samples.iter().map(|a| samples.iter().filter(|b| a == *b).count()).max().unwrap_or(SAMPLES);
+        // all agreeing is background, an even split is the middle of the line
+        let alpha = ((SAMPLES - majority) as f32 / (SAMPLES as f32 / 2.0)).min(1.0);
+        for (channel, (line, background)) in pixel.iter_mut().zip(style.line.iter().zip(style.background)) {
+            *channel = (background as f32 + (*line as f32 - background as f32) * alpha).round() as u8;
+        }
+        pixel[3] = 255;
+    });
+}
+
+/// Which part of the fractal a point is in, escaped or inside the set, or which root a Newton point converged to
+fn region(sample: PointSample, max_iterations: u32) -> u8 {
+    match sample.root {
+        // the roots are told apart by where they are to a thousandth, enough to separate them without the
+        // small differences in where each point stopped. Roots that land on the same number just don't get an edge between them
+        Some(root) => {
+            let (re, im) = ((root.re * 1000.0).round() as i64, (root.im * 1000.0).round() as i64);
+            2 + (re.wrapping_mul(31).wrapping_add(im).rem_euclid(254)) as u8
+        }
+        None => (sample.iterations >= max_iterations) as u8,
+    }
+}
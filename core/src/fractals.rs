@@ -0,0 +1,434 @@
+/* 
+Helpful resource for fractals/mandlebrot: https://complex-analysis.com/content/mandelbrot_set.html
+*/
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::RwLock;
+
+use colorgrad::Gradient;
+use num::complex::Complex64;
+use rayon::prelude::*;
+use serde::de::{DeserializeOwned, Error};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::double_double::DoubleDouble;
+use crate::inverse_iteration::InverseIteration;
+use crate::palette;
+use crate::transfer::Levels;
+use crate::random::Random;
+use crate::transfer;
+use crate::viewport::Viewport;
+
+mod julia;
+mod mandelbrot;
+mod newton;
+
+pub use julia::Julia;
+pub use mandelbrot::Mandelbrot;
+pub use newton::{Newton, NewtonFunction};
+
+/// Escape tests in the order they're listed in the gui
+pub const BAILOUTS: [Bailout; 3] = [Bailout::Circle, Bailout::Square, Bailout::Strip];
+
+pub const COLOUR_GRADIENTS: [&str; 11] = ["Magma", "Rainbow", "Plasma", "Inferno", "Viridis", "Cividis", "Turbo", "Sinebow", "Okabe-Ito", "Blue-Orange", "Purple-Orange"];
+/// Gradients that stay distinguishable with the common colour vision deficiencies
+pub const COLOUR_BLIND_SAFE: [&str; 5] = ["Viridis", "Cividis", "Okabe-Ito", "Blue-Orange", "Purple-Orange"];
+/// Upper limit of the max iterations setting, high enough for deep zooms
+pub const MAX_ITERATIONS: u32 = 1_000_000;
+
+/// Every type of fractal, in the order they're listed in the gui. The first one is the default.
+pub const REGISTRY: &[Registration] = &[
+    Registration::new::<Mandelbrot>("Mandelbrot"),
+    Registration::new::<Julia>("Julia"),
+    Registration::new::<Newton>("Newton"),
+];
+
+/// Fractals added at runtime, like ones defined by scripts, listed after the built in ones
+static CUSTOM_FRACTALS: RwLock<Vec<Registration>> = RwLock::new(Vec::new());
+
+/// A type of fractal that can be chosen in the gui and loaded from saved files
+#[derive(Clone, Copy)]
+pub struct Registration {
+    pub name: &'static str,
+    /// The fractal with its default settings
+    pub create: fn() -> Box<dyn Fractal>,
+    load: fn(serde_json::Value) -> serde_json::Result<Box<dyn Fractal>>,
+}
+
+impl Registration {
+    pub const fn new<T: Fractal + Default + DeserializeOwned>(name: &'static str) -> Self {
+        Self { name, create: create_fractal::<T>, load: load_fractal::<T> }
+    }
+}
+
+fn create_fractal<T: Fractal + Default>() -> Box<dyn Fractal> {
+    Box::<T>::default()
+}
+
+fn load_fractal<T: Fractal + DeserializeOwned>(value: serde_json::Value) -> serde_json::Result<Box<dyn Fractal>> {
+    Ok(Box::new(serde_json::from_value::<T>(value)?))
+}
+
+/// Add a type of fractal at runtime, replacing any registered fractal with the same name
+pub fn register(registration: Registration) {
+    let mut custom = CUSTOM_FRACTALS.write().unwrap();
+    custom.retain(|existing| existing.name != registration.name);
+    custom.push(registration);
+}
+
+/// Every type of fractal, built in and registered
+fn registrations() -> Vec<Registration> {
+    let custom = CUSTOM_FRACTALS.read().unwrap();
+    REGISTRY.iter().chain(custom.iter()).copied().collect()
+}
+
+fn find(name: &str) -> Option<Registration> {
+    registrations().into_iter().find(|registration| registration.name == name)
+}
+
+/// Names of every type of fractal
+pub fn names() -> Vec<&'static str> {
+    registrations().iter().map(|registration| registration.name).collect()
+}
+
+/// Create a fractal from its name with the default settings, unknown names give the default fractal
+pub fn create(name: &str) -> Box<dyn Fractal> {
+    let registration = find(name).unwrap_or(REGISTRY[0]);
+    (registration.create)()
+}
+
+/// The controls for a fractal's settings, implemented by the gui so fractals don't depend on it
+pub trait SettingsUi {
+    /// The maximum number of iterations, which needs a much wider range than other settings
+    fn iterations(&mut self, max_iterations: &mut u32);
+    fn slider(&mut self, label: &str, value: &mut f64, range: RangeInclusive<f64>);
+    fn checkbox(&mut self, label: &str, value: &mut bool);
+    /// Pick one of the options, `selected` is its index
+    fn choice(&mut self, label: &str, selected: &mut usize, options: &[&str]);
+    /// Text that can't be changed, like where the fractal came from
+    fn label(&mut self, text: &str);
+}
+
+/// A type of fractal. New fractals implement this in their own file and are added to `REGISTRY`,
+/// or passed to `register` from outside this crate.
+pub trait Fractal: FractalBase + Debug + Send + Sync + 'static {
+    /// The display name, which also tags the fractal in saved files
+    fn name(&self) -> &'static str;
+
+    /// Iterate a single point in the complex plane
+    fn sample(&self, point: (f64, f64)) -> PointSample;
+
+    /// Iterate a single point given in double-double precision, rounded to f64 unless the fractal draws in double-double
+    fn sample_dd(&self, (real, imaginary): (DoubleDouble, DoubleDouble)) -> PointSample {
+        self.sample((real.to_f64(), imaginary.to_f64()))
+    }
+
+    /// Every value of z a single point goes through, starting with z0 and
+    /// ending when it escapes, converges on a root or reaches max iterations
+    fn orbit(&self, point: (f64, f64)) -> Vec<Complex64>;
+
+    /// Render into an RGBA buffer the size of the viewport, with the palette spread over the whole iteration range
+    fn draw(&self, pixels: &mut [u8], viewport: Viewport) {
+        self.draw_levels(pixels, viewport, Levels::FULL);
+    }
+
+    /// `draw` with the palette stretched across part of the iteration range, for a frame coloured from its own counts
+    fn draw_levels(&self, pixels: &mut [u8], viewport: Viewport, levels: Levels);
+
+    /// Whether `draw` switches to double-double precision once the viewport needs it, zooming further than f64 allows
+    fn double_double(&self) -> bool {
+        false
+    }
+
+    /// A renderer that builds the image up over several frames, None for fractals drawn in one go by `draw`
+    fn progressive(&self, _transform: Viewport) -> Option<InverseIteration> {
+        None
+    }
+
+    /// Add the controls for the fractal's settings
+    fn settings(&mut self, ui: &mut dyn SettingsUi);
+
+    /// The same fractal with its default settings, the colour gradient and colouring options are kept
+    fn default_parameters(&self) -> Box<dyn Fractal>;
+
+    fn max_iterations(&self) -> u32;
+
+    fn colour_gradient_mut(&mut self) -> &mut String;
+
+    /// Numeric settings that can be animated, named the same as in the settings window
+    fn animatable_parameters(&self) -> &'static [&'static str];
+
+    fn parameter(&self, name: &str) -> Option<f64>;
+
+    /// Set a parameter by name, names the fractal doesn't have are ignored
+    fn set_parameter(&mut self, name: &str, value: f64);
+
+    /// True if `randomize` has parameters to pick
+    fn randomizable(&self) -> bool {
+        false
+    }
+
+    /// Pick random parameters from ranges that tend to give interesting pictures, for exploring without knowing good values.
+    /// The max iterations and colouring are kept
+    fn randomize(&mut self, _random: &mut Random) {}
+
+    /// The iteration rule starting from a point, shown when stepping through its orbit
+    fn rule(&self, point: (f64, f64)) -> String;
+
+    /// Escape time fractals stop iterating once |z| is further out than this
+    fn escape_radius(&self) -> Option<f64> {
+        None
+    }
+
+    /// The shape z is measured against the escape radius with
+    fn bailout(&self) -> Bailout {
+        Bailout::Circle
+    }
+
+    /// Opposite corners of a box the whole set is inside, None if there is no bounded set
+    fn bounds(&self) -> Option<((f64, f64), (f64, f64))> {
+        None
+    }
+}
+
+/// Cloning, saving and downcasting a `Box<dyn Fractal>`, implemented for every fractal
+pub trait FractalBase {
+    fn clone_box(&self) -> Box<dyn Fractal>;
+    fn to_json(&self) -> serde_json::Value;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Fractal + Clone + Serialize> FractalBase for T {
+    fn clone_box(&self) -> Box<dyn Fractal> {
+        Box::new(self.clone())
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl dyn Fractal {
+    pub fn is<T: Fractal>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    pub fn downcast_ref<T: Fractal>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn downcast_mut<T: Fractal>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+
+    /// Smooth iteration counts for every pixel in row major order, for colouring the fractal yourself.
+    /// Points that never escape are `max_iterations`.
+    pub fn draw_values(&self, values: &mut [f64], viewport: Viewport) {
+        values.par_iter_mut().enumerate().for_each(|(i, value)| *value = self.sample_pixel(&viewport, i).smooth);
+    }
+
+    /// Check every animatable parameter is in `parameter_range`, for settings loaded from outside the app
+    pub fn check_parameters(&self) -> Result<(), String> {
+        self.animatable_parameters().iter()
+            .filter_map(|name| Some((*name, self.parameter(name)?)))
+            .try_for_each(|(name, value)| check_parameter(name, value))
+    }
+
+    /// Sample the pixel at `index` in a row major frame, in double-double precision if the fractal supports it
+    /// and the viewport is zoomed in far enough to need it
+    #[inline]
+    pub fn sample_pixel(&self, viewport: &Viewport, index: usize) -> PointSample {
+        if self.double_double() && viewport.needs_double_double() {
+            self.sample_dd(viewport.point_dd(index))
+        } else {
+            self.sample(viewport.point(index))
+        }
+    }
+}
+
+impl Clone for Box<dyn Fractal> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl PartialEq for dyn Fractal {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name() && self.to_json() == other.to_json()
+    }
+}
+
+/// Saved as `{"Name": {settings}}`, the same as when the fractals were an enum so older files still load
+impl Serialize for Box<dyn Fractal> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(self.name(), &self.to_json())?;
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<dyn Fractal> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut tagged = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?.into_iter();
+        let (Some((name, settings)), None) = (tagged.next(), tagged.next()) else {
+            return Err(D::Error::custom("expected a single fractal"));
+        };
+        let registration = find(&name).ok_or_else(|| D::Error::custom(format!("unknown fractal {}", name)))?;
+        (registration.load)(settings).map_err(D::Error::custom)
+    }
+}
+
+/// Values a parameter can be set to from outside the app, the generators assume they're finite and the escape radius above 0
+pub fn parameter_range(name: &str) -> RangeInclusive<f64> {
+    match name {
+        "Max Iterations" => 1.0..=MAX_ITERATIONS as f64,
+        "Escape Radius" => 1.0..=1e6,
+        _ => -1e6..=1e6,
+    }
+}
+
+/// Err saying what the parameter can be set to if `value` is outside `parameter_range`
+pub fn check_parameter(name: &str, value: f64) -> Result<(), String> {
+    let range = parameter_range(name);
+    if range.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("{} has to be from {} to {}, not {}", name, range.start(), range.end(), value))
+    }
+}
+
+/// The built in gradient or loaded palette with this name, unknown names fall back to Sinebow.
+/// The transfer curve is applied so every way of drawing colours the same.
+pub fn string_to_colour_gradient(s: &str) -> Gradient {
+    transfer::apply(base_colour_gradient(s))
+}
+
+fn base_colour_gradient(s: &str) -> Gradient {
+    if COLOUR_GRADIENTS.contains(&s) {
+        match s {
+            "Magma" => colorgrad::magma(),
+            "Rainbow" => colorgrad::rainbow(),
+            "Plasma" => colorgrad::plasma(),
+            "Inferno" => colorgrad::inferno(),
+            "Viridis" => colorgrad::viridis(),
+            "Cividis" => colorgrad::cividis(),
+            "Turbo" => colorgrad::turbo(),
+            "Sinebow" => colorgrad::sinebow(),
+            "Okabe-Ito" => colorgrad::CustomGradient::new()
+                .html_colors(&["#000000", "#0072b2", "#56b4e9", "#009e73", "#f0e442", "#e69f00", "#d55e00", "#cc79a7"])
+                .build().unwrap_or_else(|_| colorgrad::sinebow()),
+            "Blue-Orange" => colorgrad::CustomGradient::new()
+                .html_colors(&["#053061", "#2166ac", "#f7f7f7", "#e08214", "#7f3b08"])
+                .build().unwrap_or_else(|_| colorgrad::sinebow()),
+            "Purple-Orange" => colorgrad::pu_or(),
+            _ => colorgrad::sinebow(),
+        }
+    } else {
+        palette::gradient(s).unwrap_or_else(colorgrad::sinebow) // default
+    }
+}
+
+/// Names of the built in colour gradients followed by any loaded palettes
+pub fn colour_gradient_names() -> Vec<String> {
+    COLOUR_GRADIENTS.iter().map(|s| s.to_string()).chain(palette::names()).collect()
+}
+
+/// The result of iterating a single point
+#[derive(Clone, Copy, Debug)]
+pub struct PointSample {
+    pub iterations: u32,
+    /// Continuous iteration count, equal to `iterations` for points that didn't escape
+    pub smooth: f64,
+    /// The value of z when the iteration stopped
+    pub z: Complex64,
+    /// The root a Newton fractal converged to
+    pub root: Option<Complex64>,
+    /// Period of the attracting cycle an interior Mandelbrot point settles into
+    pub period: Option<u32>,
+    /// The iteration where |z| was smallest, only tracked for the Mandelbrot set
+    pub atom_domain: Option<u32>,
+}
+
+/// The shape z has to leave for a point to count as escaped, each gives the outside different bands
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Bailout {
+    /// |z| past the escape radius
+    #[default]
+    Circle,
+    /// Either part of z past the escape radius
+    Square,
+    /// The real part of z past the escape radius, however big the imaginary part gets
+    Strip,
+}
+
+impl Bailout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Bailout::Circle => "Circle",
+            Bailout::Square => "Square",
+            Bailout::Strip => "Strip",
+        }
+    }
+
+    /// Compared with the escape radius squared, from the squares of the real and imaginary parts of z
+    #[inline]
+    pub fn norm(&self, x2: f64, y2: f64) -> f64 {
+        match self {
+            Bailout::Circle => x2 + y2,
+            Bailout::Square => x2.max(y2),
+            Bailout::Strip => x2,
+        }
+    }
+
+    /// The bailout setting, shared by the escape time fractals
+    fn settings(&mut self, ui: &mut dyn SettingsUi) {
+        let mut selected = BAILOUTS.iter().position(|bailout| bailout == self).unwrap_or(0);
+        let names = BAILOUTS.map(|bailout| bailout.name());
+        ui.choice("Bailout", &mut selected, &names);
+        *self = BAILOUTS[selected];
+    }
+}
+
+/// Continuous iteration count for an escaped point, removes the banding between iteration counts
+#[inline]
+pub fn smooth_iterations(iterations: u32, max_iterations: u32, z_norm_sqr: f64) -> f64 {
+    if iterations >= max_iterations {
+        return iterations as f64;
+    }
+    let log_z = z_norm_sqr.ln() / 2.0;
+    iterations as f64 + 1.0 - (log_z.ln() / std::f64::consts::LN_2)
+}
+
+/// A colour from the gradient for each whole number, stepping by the golden ratio so neighbouring numbers differ
+fn index_colour(colour_gradient: &Gradient, index: u32) -> colorgrad::Color {
+    colour_gradient.at((index as f64 * 0.618_033_988_75).fract())
+}
+
+/// Periods are darkened so the interior stands out from the outside
+fn period_colour(colour_gradient: &Gradient, period: u32) -> colorgrad::Color {
+    let colour = index_colour(colour_gradient, period);
+    colorgrad::Color::new(colour.r * 0.6, colour.g * 0.6, colour.b * 0.6, 1.0)
+}
+
+/// z -> z^2 + c from z0, including the first value outside the escape radius
+fn escape_orbit(mut z: Complex64, c: Complex64, r: f64, bailout: Bailout, max_iterations: u32) -> Vec<Complex64> {
+    let mut orbit = vec![z];
+    while bailout.norm(z.re * z.re, z.im * z.im) <= r && orbit.len() <= max_iterations as usize {
+        z = z * z + c;
+        orbit.push(z);
+    }
+    orbit
+}
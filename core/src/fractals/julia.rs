@@ -0,0 +1,205 @@
+use colorgrad::Gradient;
+use num::complex::Complex64;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::double_double::DoubleDouble;
+use crate::inverse_iteration::InverseIteration;
+use crate::random::Random;
+use crate::transfer::Levels;
+use crate::viewport::Viewport;
+
+use super::mandelbrot::mandelbrot_point;
+use super::{escape_orbit, Bailout, smooth_iterations, string_to_colour_gradient, Fractal, PointSample, SettingsUi};
+
+/// Random values of c tried before giving up on finding one near the edge of the Mandelbrot set
+const RANDOM_TRIES: usize = 10000;
+/// A random c has to take between this many and RANDOM_DEPTH iterations to escape the Mandelbrot set
+const RANDOM_NEAR: u32 = 20;
+const RANDOM_DEPTH: u32 = 500;
+
+/// z -> z^2 + c for a fixed c, starting from z at each point
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Julia {
+    pub max_iterations: u32,
+    pub escape_radius: f64,
+    pub c: (f64, f64),
+    pub colour_gradient: String,
+    /// Draw only the boundary by inverse iteration instead of escape time
+    #[serde(default)]
+    pub inverse_iteration: bool,
+    #[serde(default)]
+    pub bailout: Bailout,
+}
+
+impl Default for Julia {
+    fn default() -> Self {
+        Self { max_iterations: 100, escape_radius: 2.0, c: (-0.7, 0.27015), colour_gradient: "Magma".into(), inverse_iteration: false, bailout: Bailout::Circle }
+    }
+}
+
+impl Fractal for Julia {
+    fn name(&self) -> &'static str {
+        "Julia"
+    }
+
+    fn sample(&self, (real, imaginary): (f64, f64)) -> PointSample {
+        julia_point(real, imaginary, self.escape_radius * self.escape_radius, self.bailout, self.c, self.max_iterations)
+    }
+
+    fn sample_dd(&self, (real, imaginary): (DoubleDouble, DoubleDouble)) -> PointSample {
+        julia_point_dd(real, imaginary, self.escape_radius * self.escape_radius, self.bailout, self.c, self.max_iterations)
+    }
+
+    fn orbit(&self, (real, imaginary): (f64, f64)) -> Vec<Complex64> {
+        escape_orbit(Complex64::new(real, imaginary), Complex64::new(self.c.0, self.c.1), self.escape_radius * self.escape_radius, self.bailout, self.max_iterations)
+    }
+
+    fn draw_levels(&self, pixels: &mut [u8], viewport: Viewport, levels: Levels) {
+        if let Some(mut renderer) = self.progressive(viewport) {
+            while !renderer.finished() {
+                renderer.accumulate();
+            }
+            renderer.draw(pixels);
+            return;
+        }
+        generate_julia(self, pixels, viewport, string_to_colour_gradient(&self.colour_gradient), levels);
+    }
+
+    fn double_double(&self) -> bool {
+        !self.inverse_iteration
+    }
+
+    fn progressive(&self, viewport: Viewport) -> Option<InverseIteration> {
+        self.inverse_iteration.then(|| InverseIteration::new(self.c, string_to_colour_gradient(&self.colour_gradient), viewport))
+    }
+
+    fn settings(&mut self, ui: &mut dyn SettingsUi) {
+        ui.iterations(&mut self.max_iterations);
+        ui.slider("Escape Radius", &mut self.escape_radius, 1.0..=10.0);
+        self.bailout.settings(ui);
+        ui.slider("Real", &mut self.c.0, -1.5..=1.5);
+        ui.slider("Imaginary", &mut self.c.1, -1.5..=1.5);
+        ui.checkbox("Boundary by inverse iteration", &mut self.inverse_iteration);
+    }
+
+    fn default_parameters(&self) -> Box<dyn Fractal> {
+        Box::new(Self { colour_gradient: self.colour_gradient.clone(), inverse_iteration: self.inverse_iteration, bailout: self.bailout, ..Self::default() })
+    }
+
+    fn max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+
+    fn colour_gradient_mut(&mut self) -> &mut String {
+        &mut self.colour_gradient
+    }
+
+    fn animatable_parameters(&self) -> &'static [&'static str] {
+        &["Real", "Imaginary", "Escape Radius", "Max Iterations"]
+    }
+
+    fn parameter(&self, name: &str) -> Option<f64> {
+        match name {
+            "Max Iterations" => Some(self.max_iterations as f64),
+            "Escape Radius" => Some(self.escape_radius),
+            "Real" => Some(self.c.0),
+            "Imaginary" => Some(self.c.1),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) {
+        match name {
+            "Max Iterations" => self.max_iterations = value.round().max(1.0) as u32,
+            "Escape Radius" => self.escape_radius = value,
+            "Real" => self.c.0 = value,
+            "Imaginary" => self.c.1 = value,
+            _ => {}
+        }
+    }
+
+    fn randomizable(&self) -> bool {
+        true
+    }
+
+    fn randomize(&mut self, random: &mut Random) {
+        // the Julia sets worth looking at come from c close to the edge of the Mandelbrot set,
+        // where the point takes a long time to escape it
+        for _ in 0..RANDOM_TRIES {
+            let c = (random.next_f64() * 2.5 - 2.0, random.next_f64() * 2.4 - 1.2);
+            let iterations = mandelbrot_point(c.0, c.1, 4.0, Bailout::Circle, RANDOM_DEPTH).iterations;
+            if (RANDOM_NEAR..RANDOM_DEPTH).contains(&iterations) {
+                self.c = c;
+                return;
+            }
+        }
+    }
+
+    fn rule(&self, point: (f64, f64)) -> String {
+        format!("z → z² + c,  z₀ = {:.6} {:+.6}i,  c = {:.6} {:+.6}i", point.0, point.1, self.c.0, self.c.1)
+    }
+
+    fn escape_radius(&self) -> Option<f64> {
+        Some(self.escape_radius)
+    }
+
+    fn bailout(&self) -> Bailout {
+        self.bailout
+    }
+
+    fn bounds(&self) -> Option<((f64, f64), (f64, f64))> {
+        // anything further out than this escapes
+        let radius = 2f64.max(self.c.0.hypot(self.c.1));
+        Some(((-radius, -radius), (radius, radius)))
+    }
+}
+
+#[inline]
+fn julia_point(mut real: f64, mut imaginary: f64, r: f64, bailout: Bailout, (cx, cy): (f64, f64), max_iterations: u32) -> PointSample {
+    let mut iteration = 0;
+    while bailout.norm(real * real, imaginary * imaginary) < r && iteration < max_iterations {
+        let xtemp = real * real - imaginary * imaginary + cx;
+        imaginary = 2.0 * real * imaginary + cy;
+        real = xtemp;
+        iteration += 1;
+    }
+    let smooth = smooth_iterations(iteration, max_iterations, real * real + imaginary * imaginary);
+    PointSample { iterations: iteration, smooth, z: Complex64::new(real, imaginary), root: None, period: None, atom_domain: None }
+}
+
+/// `julia_point` in double-double precision for deep zooms
+fn julia_point_dd(mut real: DoubleDouble, mut imaginary: DoubleDouble, r: f64, bailout: Bailout, (cx, cy): (f64, f64), max_iterations: u32) -> PointSample {
+    let mut iteration = 0;
+    let (mut x2, mut y2) = (real.sqr(), imaginary.sqr());
+    while bailout.norm(x2.to_f64(), y2.to_f64()) < r && iteration < max_iterations {
+        imaginary = real * imaginary * 2.0 + cy;
+        real = x2 - y2 + cx;
+        (x2, y2) = (real.sqr(), imaginary.sqr());
+        iteration += 1;
+    }
+    let smooth = smooth_iterations(iteration, max_iterations, (x2 + y2).to_f64());
+    PointSample { iterations: iteration, smooth, z: Complex64::new(real.to_f64(), imaginary.to_f64()), root: None, period: None, atom_domain: None }
+}
+
+fn generate_julia(julia: &Julia, pixels: &mut [u8], viewport: Viewport, colour_gradient: Gradient, levels: Levels) {
+    let Julia { escape_radius, bailout, c, max_iterations, .. } = *julia;
+    assert!(escape_radius > 0.0);
+    let r = escape_radius * escape_radius;
+    let double_double = viewport.needs_double_double();
+    pixels.into_par_iter().chunks(4).enumerate().for_each(|(i, mut pixel)| {
+        let sample = if double_double {
+            let (real, imaginary) = viewport.point_dd(i);
+            julia_point_dd(real, imaginary, r, bailout, c, max_iterations)
+        } else {
+            let (real, imaginary) = viewport.point(i);
+            julia_point(real, imaginary, r, bailout, c, max_iterations)
+        };
+        let iteration = sample.iterations as f64;
+        let [c1, c2, c3, c4] = colour_gradient.at(levels.stretch(iteration / max_iterations as f64)).to_rgba8();
+        *pixel[0] = c1;
+        *pixel[1] = c2;
+        *pixel[2] = c3;
+        *pixel[3] = c4;
+    });
+}
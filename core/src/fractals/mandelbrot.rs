@@ -0,0 +1,226 @@
+use colorgrad::Gradient;
+use num::complex::Complex64;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{escape_orbit, Bailout, index_colour, period_colour, smooth_iterations, string_to_colour_gradient, Fractal, PointSample, SettingsUi};
+use crate::double_double::DoubleDouble;
+use crate::transfer::Levels;
+use crate::viewport::Viewport;
+
+/// How close z has to come back to an earlier value to count as a cycle
+const PERIOD_TOLERANCE: f64 = 1e-12;
+/// The same in double-double precision, where the pixels can be far smaller than `PERIOD_TOLERANCE`
+const PERIOD_TOLERANCE_DD: f64 = 1e-27;
+
+/// z -> z^2 + c starting from z = 0, for each point c
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Mandelbrot {
+    pub max_iterations: u32,
+    pub escape_radius: f64,
+    pub colour_gradient: String,
+    /// Colour the interior by the period of the cycle each point is attracted to
+    #[serde(default)]
+    pub period_tint: bool,
+    /// Colour by the iteration where z came closest to 0, which shows the atoms and bonds of the set
+    #[serde(default)]
+    pub atom_domains: bool,
+    #[serde(default)]
+    pub bailout: Bailout,
+}
+
+impl Default for Mandelbrot {
+    fn default() -> Self {
+        Self { max_iterations: 100, escape_radius: 2.0, colour_gradient: "Magma".into(), period_tint: false, atom_domains: false, bailout: Bailout::Circle }
+    }
+}
+
+impl Fractal for Mandelbrot {
+    fn name(&self) -> &'static str {
+        "Mandelbrot"
+    }
+
+    fn sample(&self, (real, imaginary): (f64, f64)) -> PointSample {
+        mandelbrot_point(real, imaginary, self.escape_radius * self.escape_radius, self.bailout, self.max_iterations)
+    }
+
+    fn sample_dd(&self, (real, imaginary): (DoubleDouble, DoubleDouble)) -> PointSample {
+        mandelbrot_point_dd(real, imaginary, self.escape_radius * self.escape_radius, self.bailout, self.max_iterations)
+    }
+
+    fn orbit(&self, (real, imaginary): (f64, f64)) -> Vec<Complex64> {
+        escape_orbit(Complex64::new(0.0, 0.0), Complex64::new(real, imaginary), self.escape_radius * self.escape_radius, self.bailout, self.max_iterations)
+    }
+
+    fn draw_levels(&self, pixels: &mut [u8], viewport: Viewport, levels: Levels) {
+        generate_mandelbrot(self, pixels, viewport, string_to_colour_gradient(&self.colour_gradient), levels);
+    }
+
+    fn double_double(&self) -> bool {
+        true
+    }
+
+    fn settings(&mut self, ui: &mut dyn SettingsUi) {
+        ui.iterations(&mut self.max_iterations);
+        ui.slider("Escape Radius", &mut self.escape_radius, 1.0..=10.0);
+        self.bailout.settings(ui);
+        ui.checkbox("Tint interior by period", &mut self.period_tint);
+        ui.checkbox("Colour by atom domain", &mut self.atom_domains);
+    }
+
+    fn default_parameters(&self) -> Box<dyn Fractal> {
+        Box::new(Self { colour_gradient: self.colour_gradient.clone(), period_tint: self.period_tint, atom_domains: self.atom_domains, bailout: self.bailout, ..Self::default() })
+    }
+
+    fn max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+
+    fn colour_gradient_mut(&mut self) -> &mut String {
+        &mut self.colour_gradient
+    }
+
+    fn animatable_parameters(&self) -> &'static [&'static str] {
+        &["Escape Radius", "Max Iterations"]
+    }
+
+    fn parameter(&self, name: &str) -> Option<f64> {
+        match name {
+            "Max Iterations" => Some(self.max_iterations as f64),
+            "Escape Radius" => Some(self.escape_radius),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) {
+        match name {
+            "Max Iterations" => self.max_iterations = value.round().max(1.0) as u32,
+            "Escape Radius" => self.escape_radius = value,
+            _ => {}
+        }
+    }
+
+    fn rule(&self, point: (f64, f64)) -> String {
+        format!("z → z² + c,  z₀ = 0,  c = {:.6} {:+.6}i", point.0, point.1)
+    }
+
+    fn escape_radius(&self) -> Option<f64> {
+        Some(self.escape_radius)
+    }
+
+    fn bailout(&self) -> Bailout {
+        self.bailout
+    }
+
+    fn bounds(&self) -> Option<((f64, f64), (f64, f64))> {
+        Some(((-2.0, -1.25), (0.5, 1.25)))
+    }
+}
+
+#[inline]
+pub(super) fn mandelbrot_point(real: f64, imaginary: f64, r: f64, bailout: Bailout, max_iterations: u32) -> PointSample {
+    let mut x = 0.0;
+    let mut y = 0.0;
+    let mut iteration = 0;
+    let mut x2 = 0.0;
+    let mut y2 = 0.0;
+    // brent's cycle detection, z is compared against a checkpoint that moves at doubling intervals
+    let (mut check_x, mut check_y) = (0.0, 0.0);
+    let mut check_iteration = 0;
+    let mut next_check = 1;
+    let mut period = None;
+    let mut min_norm = f64::INFINITY;
+    let mut atom_domain = 0;
+    while bailout.norm(x2, y2) <= r && iteration < max_iterations {
+        y = 2.0 * x * y + imaginary;
+        x = x2 - y2 + real;
+        x2 = x * x;
+        y2 = y * y;
+        iteration += 1;
+        if x2 + y2 < min_norm {
+            min_norm = x2 + y2;
+            atom_domain = iteration;
+        }
+
+        if (x - check_x).abs() < PERIOD_TOLERANCE && (y - check_y).abs() < PERIOD_TOLERANCE {
+            // the point is in the set, no need to keep iterating
+            period = Some(iteration - check_iteration);
+            iteration = max_iterations;
+            break;
+        }
+        if iteration == next_check {
+            (check_x, check_y) = (x, y);
+            check_iteration = iteration;
+            next_check *= 2;
+        }
+    }
+    let smooth = smooth_iterations(iteration, max_iterations, x2 + y2);
+    PointSample { iterations: iteration, smooth, z: Complex64::new(x, y), root: None, period, atom_domain: Some(atom_domain) }
+}
+
+/// `mandelbrot_point` in double-double precision for deep zooms
+fn mandelbrot_point_dd(real: DoubleDouble, imaginary: DoubleDouble, r: f64, bailout: Bailout, max_iterations: u32) -> PointSample {
+    let mut x = DoubleDouble::ZERO;
+    let mut y = DoubleDouble::ZERO;
+    let mut iteration = 0;
+    let mut x2 = DoubleDouble::ZERO;
+    let mut y2 = DoubleDouble::ZERO;
+    let (mut check_x, mut check_y) = (DoubleDouble::ZERO, DoubleDouble::ZERO);
+    let mut check_iteration = 0;
+    let mut next_check = 1;
+    let mut period = None;
+    let mut min_norm = f64::INFINITY;
+    let mut atom_domain = 0;
+    while bailout.norm(x2.to_f64(), y2.to_f64()) <= r && iteration < max_iterations {
+        y = x * y * 2.0 + imaginary;
+        x = x2 - y2 + real;
+        x2 = x.sqr();
+        y2 = y.sqr();
+        iteration += 1;
+        let norm = (x2 + y2).to_f64();
+        if norm < min_norm {
+            min_norm = norm;
+            atom_domain = iteration;
+        }
+
+        if (x - check_x).to_f64().abs() < PERIOD_TOLERANCE_DD && (y - check_y).to_f64().abs() < PERIOD_TOLERANCE_DD {
+            period = Some(iteration - check_iteration);
+            iteration = max_iterations;
+            break;
+        }
+        if iteration == next_check {
+            (check_x, check_y) = (x, y);
+            check_iteration = iteration;
+            next_check *= 2;
+        }
+    }
+    let smooth = smooth_iterations(iteration, max_iterations, (x2 + y2).to_f64());
+    PointSample { iterations: iteration, smooth, z: Complex64::new(x.to_f64(), y.to_f64()), root: None, period, atom_domain: Some(atom_domain) }
+}
+
+fn generate_mandelbrot(mandelbrot: &Mandelbrot, pixels: &mut [u8], viewport: Viewport, colour_gradient: Gradient, levels: Levels) {
+    let Mandelbrot { escape_radius, bailout, max_iterations, period_tint, atom_domains, .. } = *mandelbrot;
+    assert!(escape_radius > 0.0);
+
+    let r = escape_radius * escape_radius;
+    let double_double = viewport.needs_double_double();
+    pixels.into_par_iter().chunks(4).enumerate().for_each(|(i, mut pixel)| {
+        let sample = if double_double {
+            let (real, imaginary) = viewport.point_dd(i);
+            mandelbrot_point_dd(real, imaginary, r, bailout, max_iterations)
+        } else {
+            let (real, imaginary) = viewport.point(i);
+            mandelbrot_point(real, imaginary, r, bailout, max_iterations)
+        };
+        let colour = match sample.period {
+            Some(period) if period_tint => period_colour(&colour_gradient, period),
+            _ if atom_domains => index_colour(&colour_gradient, sample.atom_domain.unwrap_or(0)),
+            _ => colour_gradient.at(levels.stretch(sample.iterations as f64 / max_iterations as f64)),
+        };
+        let [c1, c2, c3, c4] = colour.to_rgba8();
+        *pixel[0] = c1;
+        *pixel[1] = c2;
+        *pixel[2] = c3;
+        *pixel[3] = c4;
+    });
+}
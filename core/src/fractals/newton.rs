@@ -0,0 +1,362 @@
+use colorgrad::Gradient;
+use num::complex::Complex64;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{string_to_colour_gradient, Fractal, PointSample, SettingsUi};
+use crate::random::Random;
+use crate::transfer::Levels;
+use crate::viewport::Viewport;
+
+pub const NEWTON_FUNCTIONS: [NewtonFunction; 4] = [NewtonFunction::Polynomial, NewtonFunction::Sine, NewtonFunction::Cosine, NewtonFunction::ZExp];
+
+/// Seeds along each side of the view that Newton's method is started from to find the roots of a function
+const ROOT_SEEDS: usize = 24;
+/// Roots found closer together than this are the same root
+const ROOT_MERGE: f64 = 0.0001;
+/// Fewest and most roots a random polynomial gets
+const RANDOM_ROOTS: (usize, usize) = (3, 7);
+/// Random roots are kept at least this far apart so none of their basins are too small to see
+const RANDOM_SPACING: f64 = 0.3;
+
+/// The function whose roots Newton's method looks for
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum NewtonFunction {
+    /// The polynomial with the roots that are set
+    #[default]
+    Polynomial,
+    Sine,
+    Cosine,
+    /// z·e^z, whose only root is 0 but whose basins reach off to the left
+    ZExp,
+}
+
+impl NewtonFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NewtonFunction::Polynomial => "Polynomial",
+            NewtonFunction::Sine => "sin z",
+            NewtonFunction::Cosine => "cos z",
+            NewtonFunction::ZExp => "z·e^z",
+        }
+    }
+
+    /// One step of Newton's method, z - f(z) / f'(z). The polynomial is only used by `Polynomial`
+    #[inline]
+    fn step(&self, z: Complex64, polynomial: &[Complex64]) -> Complex64 {
+        match self {
+            NewtonFunction::Polynomial => newton_step(z, polynomial),
+            // sin z / cos z
+            NewtonFunction::Sine => z - z.tan(),
+            // cos z / -sin z
+            NewtonFunction::Cosine => z + 1.0 / z.tan(),
+            // z·e^z / (1 + z)·e^z
+            NewtonFunction::ZExp => z - z / (1.0 + z),
+        }
+    }
+}
+
+/// Newton's method for the polynomial with the given roots, z^3 - 1 by default, or for a trigonometric or exponential function.
+/// Coloured by how long each point takes to reach a root
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Newton {
+    pub max_iterations: u32,
+    pub colour_gradient: String,
+    /// Roots of the polynomial, it is rebuilt from them whenever they change
+    #[serde(default = "cube_roots_of_unity")]
+    pub roots: Vec<(f64, f64)>,
+    /// The other functions have too many roots to list, so they're found as the points converge
+    #[serde(default)]
+    pub function: NewtonFunction,
+    /// Colour by the smooth convergence speed instead of the whole number of steps,
+    /// so the basin boundaries show up as smooth ridges
+    #[serde(default)]
+    pub velocity: bool,
+}
+
+impl Default for Newton {
+    fn default() -> Self {
+        Self { max_iterations: 100, colour_gradient: "Magma".into(), roots: cube_roots_of_unity(), function: NewtonFunction::Polynomial, velocity: false }
+    }
+}
+
+/// Roots of z^3 - 1
+fn cube_roots_of_unity() -> Vec<(f64, f64)> {
+    vec![(1.0, 0.0), (-0.5, 0.8660254037844386), (-0.5, -0.8660254037844386)] // sqrt(3)/2
+}
+
+impl Newton {
+    /// Coefficients of the monic polynomial with these roots, starting from the constant term
+    pub fn polynomial(&self) -> Vec<Complex64> {
+        let mut coefficients = vec![Complex64::new(1.0, 0.0)];
+        // multiply by (z - root) for each root
+        for &(re, im) in self.roots.iter() {
+            let root = Complex64::new(re, im);
+            coefficients.insert(0, Complex64::new(0.0, 0.0));
+            for i in 0..coefficients.len() - 1 {
+                let next = coefficients[i + 1];
+                coefficients[i] -= root * next;
+            }
+        }
+        coefficients
+    }
+
+    /// Roots a point can converge to, none for the functions as their roots are found as the points converge
+    fn roots(&self) -> Vec<Complex64> {
+        match self.function {
+            NewtonFunction::Polynomial => self.roots.iter().map(|&(re, im)| Complex64::new(re, im)).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The roots in the viewport. The polynomial's are the ones that are set, the functions' are found by
+    /// starting Newton's method from a grid of points across the view and keeping where it converges
+    pub fn roots_in(&self, viewport: Viewport) -> Vec<(f64, f64)> {
+        if self.function == NewtonFunction::Polynomial {
+            return self.roots.clone();
+        }
+        let seeds: Vec<(f64, f64)> = (0..ROOT_SEEDS * ROOT_SEEDS).map(|i| {
+            let x = ((i % ROOT_SEEDS) as f64 + 0.5) / ROOT_SEEDS as f64 * viewport.width as f64;
+            let y = ((i / ROOT_SEEDS) as f64 + 0.5) / ROOT_SEEDS as f64 * viewport.height as f64;
+            viewport.pixel_to_complex(x, y)
+        }).collect();
+        let found: Vec<Complex64> = seeds.par_iter()
+            .filter_map(|&(real, imaginary)| newton_point(real, imaginary, self.function, &[], &[], self.max_iterations).root)
+            .collect();
+        let mut roots: Vec<Complex64> = Vec::new();
+        for root in found {
+            if viewport.index(root.re, root.im).is_some() && roots.iter().all(|known| (known - root).norm() > ROOT_MERGE) {
+                roots.push(root);
+            }
+        }
+        roots.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+        roots.into_iter().map(|root| (root.re, root.im)).collect()
+    }
+}
+
+impl Fractal for Newton {
+    fn name(&self) -> &'static str {
+        "Newton"
+    }
+
+    fn sample(&self, (real, imaginary): (f64, f64)) -> PointSample {
+        newton_point(real, imaginary, self.function, &self.polynomial(), &self.roots(), self.max_iterations)
+    }
+
+    fn orbit(&self, (real, imaginary): (f64, f64)) -> Vec<Complex64> {
+        let (polynomial, roots) = (self.polynomial(), self.roots());
+        let mut z = Complex64::new(real, imaginary);
+        let mut orbit = vec![z];
+        while orbit.len() <= self.max_iterations as usize {
+            let previous = z;
+            z = self.function.step(z, &polynomial);
+            orbit.push(z);
+            if !z.is_finite() || converged(self.function, previous, z, &roots).is_some() {
+                break;
+            }
+        }
+        orbit
+    }
+
+    fn draw_levels(&self, pixels: &mut [u8], viewport: Viewport, levels: Levels) {
+        generate_newton(pixels, viewport, self, string_to_colour_gradient(&self.colour_gradient), levels);
+    }
+
+    fn settings(&mut self, ui: &mut dyn SettingsUi) {
+        ui.iterations(&mut self.max_iterations);
+        let mut selected = NEWTON_FUNCTIONS.iter().position(|function| *function == self.function).unwrap_or(0);
+        ui.choice("Function", &mut selected, &NEWTON_FUNCTIONS.map(|function| function.name()));
+        self.function = NEWTON_FUNCTIONS[selected];
+        ui.checkbox("Colour by convergence speed", &mut self.velocity);
+    }
+
+    fn default_parameters(&self) -> Box<dyn Fractal> {
+        Box::new(Self { colour_gradient: self.colour_gradient.clone(), function: self.function, velocity: self.velocity, ..Self::default() })
+    }
+
+    fn max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+
+    fn colour_gradient_mut(&mut self) -> &mut String {
+        &mut self.colour_gradient
+    }
+
+    fn animatable_parameters(&self) -> &'static [&'static str] {
+        &["Max Iterations"]
+    }
+
+    fn parameter(&self, name: &str) -> Option<f64> {
+        (name == "Max Iterations").then_some(self.max_iterations as f64)
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) {
+        if name == "Max Iterations" {
+            self.max_iterations = value.round().max(1.0) as u32;
+        }
+    }
+
+    fn randomizable(&self) -> bool {
+        true
+    }
+
+    fn randomize(&mut self, random: &mut Random) {
+        let (fewest, most) = RANDOM_ROOTS;
+        let count = fewest + (random.next_u64() % (most - fewest + 1) as u64) as usize;
+        let mut roots: Vec<(f64, f64)> = Vec::with_capacity(count);
+        while roots.len() < count {
+            let root = (random.next_f64() * 3.0 - 1.5, random.next_f64() * 3.0 - 1.5);
+            if roots.iter().all(|other| (other.0 - root.0).hypot(other.1 - root.1) >= RANDOM_SPACING) {
+                roots.push(root);
+            }
+        }
+        self.roots = roots;
+        self.function = NewtonFunction::Polynomial;
+    }
+
+    fn rule(&self, point: (f64, f64)) -> String {
+        let function = match self.function {
+            NewtonFunction::Polynomial => format_polynomial(&self.polynomial()),
+            function => function.name().into(),
+        };
+        format!("z → z - f(z) / f'(z),  f(z) = {},  z₀ = {:.6} {:+.6}i", function, point.0, point.1)
+    }
+}
+
+/// The polynomial written out like z³ - 1, leaving out terms too small to matter
+fn format_polynomial(coefficients: &[Complex64]) -> String {
+    const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    // rounded so the error from multiplying out the roots doesn't show, adding 0.0 turns -0 into 0
+    let round = |x: f64| (x * 1e6).round() / 1e6 + 0.0;
+    let mut text = String::new();
+    for (power, coefficient) in coefficients.iter().enumerate().rev() {
+        let (re, im) = (round(coefficient.re), round(coefficient.im));
+        if re == 0.0 && im == 0.0 {
+            continue;
+        }
+        // real coefficients carry their own sign, complex ones are bracketed
+        let (negative, value) = if im == 0.0 {
+            (re < 0.0, format!("{}", re.abs()))
+        } else {
+            (false, format!("({} {} {}i)", re, if im < 0.0 { '-' } else { '+' }, im.abs()))
+        };
+        if !text.is_empty() {
+            text.push_str(if negative { " - " } else { " + " });
+        } else if negative {
+            text.push('-');
+        }
+        let variable = match power {
+            0 => String::new(),
+            1 => "z".into(),
+            _ => format!("z{}", power.to_string().chars().map(|digit| SUPERSCRIPTS[digit as usize - '0' as usize]).collect::<String>()),
+        };
+        if power == 0 || value != "1" {
+            text.push_str(&value);
+        }
+        text.push_str(&variable);
+    }
+    if text.is_empty() { "0".into() } else { text }
+}
+
+const NEWTON_TOLERANCE: f64 = 0.000001;
+
+/// One step of Newton's method, z - p(z) / p'(z) with both evaluated by Horner's method
+#[inline]
+fn newton_step(z: Complex64, polynomial: &[Complex64]) -> Complex64 {
+    let mut value = Complex64::new(0.0, 0.0);
+    let mut derivative = Complex64::new(0.0, 0.0);
+    for coefficient in polynomial.iter().rev() {
+        derivative = derivative * z + value;
+        value = value * z + coefficient;
+    }
+    z - value / derivative
+}
+
+/// The root z has converged to, if it is close enough to one
+#[inline]
+fn nearby_root(z: Complex64, roots: &[Complex64]) -> Option<Complex64> {
+    roots.iter().copied().find(|root| {
+        let diff = z - root;
+        diff.re.abs() < NEWTON_TOLERANCE && diff.im.abs() < NEWTON_TOLERANCE
+    })
+}
+
+/// The root z has converged to. The polynomial's roots are known, the functions' are where the steps become too small to matter
+#[inline]
+fn converged(function: NewtonFunction, previous: Complex64, z: Complex64, roots: &[Complex64]) -> Option<Complex64> {
+    match function {
+        NewtonFunction::Polynomial => nearby_root(z, roots),
+        _ => {
+            let step = z - previous;
+            (step.re.abs() < NEWTON_TOLERANCE && step.im.abs() < NEWTON_TOLERANCE).then_some(z)
+        }
+    }
+}
+
+#[inline]
+fn newton_point(real: f64, imaginary: f64, function: NewtonFunction, polynomial: &[Complex64], roots: &[Complex64], max_iterations: u32) -> PointSample {
+    let mut z = Complex64::new(real, imaginary);
+    
+    let mut iteration = 0;
+    let mut found_root = None;
+    // the step before last, to tell how fast z was closing in on the root
+    let mut previous = z;
+    // how far the step before was, for the functions whose root isn't known until z gets there
+    let mut previous_step = f64::INFINITY;
+    while iteration < max_iterations && found_root.is_none() {
+        previous_step = (z - previous).norm();
+        previous = z;
+        z = function.step(z, polynomial);
+        iteration += 1;
+        // a pole sends z off to infinity, it never reaches a root from there
+        if !z.is_finite() {
+            iteration = max_iterations;
+            break;
+        }
+        found_root = converged(function, previous, z, roots);
+    }
+    let smooth = match found_root {
+        // each step is about as far as z still had to go, so the last two steps stand in for the distances
+        Some(_) if function != NewtonFunction::Polynomial => smooth_convergence(iteration - 1, previous_step, (z - previous).norm()),
+        Some(root) => smooth_convergence(iteration, (previous - root).norm(), (z - root).norm()),
+        None => iteration as f64,
+    };
+    PointSample { iterations: iteration, smooth, z, root: found_root, period: None, atom_domain: None }
+}
+
+/// Continuous count of the steps taken to get within the tolerance of a root.
+/// Newton's method roughly doubles the number of correct digits each step, so log(-log(distance)) grows by a
+/// similar amount every step. The count is where the tolerance falls between the last two steps on that scale
+#[inline]
+fn smooth_convergence(iterations: u32, previous_distance: f64, distance: f64) -> f64 {
+    let log_log = |distance: f64| (-distance.max(f64::MIN_POSITIVE).ln()).max(f64::MIN_POSITIVE).ln();
+    let (before, after, tolerance) = (log_log(previous_distance), log_log(distance), log_log(NEWTON_TOLERANCE));
+    // a step that starts further than 1 from the root has no log(-log) so the whole step is counted
+    if previous_distance >= 1.0 || after <= before {
+        return iterations as f64;
+    }
+    iterations as f64 - 1.0 + ((tolerance - before) / (after - before)).clamp(0.0, 1.0)
+}
+
+fn generate_newton(pixels: &mut [u8], viewport: Viewport, newton: &Newton, colour_gradient: Gradient, levels: Levels) {
+    let (polynomial, roots) = (newton.polynomial(), newton.roots());
+    let Newton { max_iterations, function, velocity, .. } = *newton;
+    // spread on a log scale as most points reach a root in a few steps
+    let log_max = (max_iterations as f64).ln_1p();
+    // Parallel loop that takes 4 values at a time (r,g,b,a) and processes them in parallel
+    pixels.into_par_iter().chunks(4).enumerate().for_each(|(i, mut pixel)| {
+        let (real, imaginary) = viewport.point(i);
+
+        let sample = newton_point(real, imaginary, function, &polynomial, &roots, max_iterations);
+        let position = if velocity {
+            sample.smooth.ln_1p() / log_max
+        } else {
+            levels.stretch(sample.iterations as f64 / max_iterations as f64)
+        };
+        let [c1, c2, c3, c4] = colour_gradient.at(position).to_rgba8();
+        *pixel[0] = c1;
+        *pixel[1] = c2;
+        *pixel[2] = c3;
+        *pixel[3] = c4;
+    });
+}
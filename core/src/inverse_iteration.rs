@@ -0,0 +1,72 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use colorgrad::Gradient;
+use num::complex::Complex64;
+use rayon::prelude::*;
+
+use crate::viewport::Viewport;
+use crate::random::Random;
+
+/// Random walks run side by side
+const WALKERS: usize = 64;
+/// Points each walker plots per frame
+const STEPS_PER_FRAME: usize = 4096;
+/// Points plotted for every pixel on screen before the image is finished
+const POINTS_PER_PIXEL: usize = 4;
+
+/// Draws the boundary of a Julia set by plotting preimages of its repelling fixed point.
+/// Every z has two preimages ±sqrt(z - c) and walking back through random ones stays on the Julia set,
+/// points are added a frame at a time so the image builds up while it's being explored.
+pub struct InverseIteration {
+    c: Complex64,
+    viewport: Viewport,
+    colour_gradient: Gradient,
+    /// How many points landed in each pixel
+    hits: Vec<AtomicU32>,
+    walkers: Vec<(Complex64, Random)>,
+    plotted: usize,
+}
+
+impl InverseIteration {
+    pub fn new(c: (f64, f64), colour_gradient: Gradient, viewport: Viewport) -> Self {
+        let c = Complex64::new(c.0, c.1);
+        // the fixed points of z^2 + c are (1 ± sqrt(1 - 4c)) / 2, the one further from the origin is repelling
+        let root = (1.0 - 4.0 * c).sqrt();
+        let fixed = if (1.0 + root).norm() >= (1.0 - root).norm() { (1.0 + root) / 2.0 } else { (1.0 - root) / 2.0 };
+
+        let seed = Random::seed();
+        let walkers = (0..WALKERS).map(|i| (fixed, Random::new(seed ^ (i as u64).wrapping_mul(0x2545f4914f6cdd1d)))).collect();
+        let hits = (0..viewport.width * viewport.height).map(|_| AtomicU32::new(0)).collect();
+        Self { c, viewport, colour_gradient, hits, walkers, plotted: 0 }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.plotted >= self.hits.len() * POINTS_PER_PIXEL
+    }
+
+    /// Plot another frame's worth of points
+    pub fn accumulate(&mut self) {
+        let (c, viewport, hits) = (self.c, self.viewport, &self.hits);
+        self.walkers.par_iter_mut().for_each(|(z, random)| {
+            for _ in 0..STEPS_PER_FRAME {
+                let root = (*z - c).sqrt();
+                *z = if random.next_u64() & 1 == 0 { root } else { -root };
+                if let Some(index) = viewport.index(z.re, z.im) {
+                    hits[index].fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        });
+        self.plotted += WALKERS * STEPS_PER_FRAME;
+    }
+
+    /// Colour pixels by how often they were hit, on a log scale so rarely visited parts of the boundary still show
+    pub fn draw(&self, pixels: &mut [u8]) {
+        let max_hits = self.hits.iter().map(|hits| hits.load(Ordering::Relaxed)).max().unwrap_or(0).max(1);
+        let scale = 1.0 / (max_hits as f64).ln_1p();
+        pixels.par_chunks_mut(4).zip(self.hits.par_iter()).for_each(|(pixel, hits)| {
+            let hits = hits.load(Ordering::Relaxed);
+            let t = if hits == 0 { 0.0 } else { 0.3 + 0.7 * (hits as f64).ln_1p() * scale };
+            pixel.copy_from_slice(&self.colour_gradient.at(t).to_rgba8());
+        });
+    }
+}
@@ -0,0 +1,15 @@
+//! Fractal generators and viewport maths without any windowing or gui dependencies.
+//!
+//! Render a fractal into an RGBA buffer with [`fractals::Fractal::draw`],
+//! or get the raw iteration counts with `draw_values` to colour them yourself.
+//! New types of fractal implement [`fractals::Fractal`] and are added to [`fractals::REGISTRY`].
+
+pub mod double_double;
+pub mod edges;
+pub mod fractals;
+pub mod inverse_iteration;
+pub mod palette;
+pub mod random;
+pub mod transfer;
+pub mod view;
+pub mod viewport;
@@ -0,0 +1,74 @@
+use std::path::Path;
+use std::sync::RwLock;
+
+use colorgrad::{Color, Gradient};
+
+/// Colour gradients loaded from palette files, as (name, colours).
+/// The fractals refer to gradients by name so these are kept globally alongside the built in ones.
+static CUSTOM_PALETTES: RwLock<Vec<(String, Vec<[u8; 3]>)>> = RwLock::new(Vec::new());
+
+/// Add a custom palette, replacing any palette with the same name
+pub fn register(name: String, colours: Vec<[u8; 3]>) {
+    let mut palettes = CUSTOM_PALETTES.write().unwrap();
+    palettes.retain(|(existing, _)| *existing != name);
+    palettes.push((name, colours));
+}
+
+/// Names of all the custom palettes
+pub fn names() -> Vec<String> {
+    CUSTOM_PALETTES.read().unwrap().iter().map(|(name, _)| name.clone()).collect()
+}
+
+/// Build the gradient for a custom palette
+pub fn gradient(name: &str) -> Option<Gradient> {
+    let palettes = CUSTOM_PALETTES.read().unwrap();
+    let (_, colours) = palettes.iter().find(|(existing, _)| existing == name)?;
+    let colours: Vec<Color> = colours.iter().map(|[r, g, b]| Color::from_rgba8(*r, *g, *b, 255)).collect();
+    colorgrad::CustomGradient::new().colors(&colours).build().ok()
+}
+
+/// Read a palette file, either a GIMP palette (.gpl) or a list of hex colours one per line.
+/// Returns the palette name and its colours.
+pub fn load(path: &Path) -> Result<(String, Vec<[u8; 3]>), String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| "Custom".into());
+    let mut colours = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with("GIMP Palette") || line.starts_with("Columns:") {
+            continue;
+        }
+        if let Some(palette_name) = line.strip_prefix("Name:") {
+            name = palette_name.trim().to_string();
+            continue;
+        }
+        if let Some(colour) = parse_hex(line) {
+            colours.push(colour);
+            continue;
+        }
+        // comments start with # but so do hex colours, so only skip them after trying to parse a colour
+        if line.starts_with('#') {
+            continue;
+        }
+        // gimp palettes have a colour per line as "r g b name"
+        let channels: Vec<u8> = line.split_whitespace().take(3).map_while(|c| c.parse().ok()).collect();
+        match channels[..] {
+            [r, g, b] => colours.push([r, g, b]),
+            _ => return Err(format!("Couldn't read the colour \"{}\"", line)),
+        }
+    }
+
+    if colours.len() < 2 {
+        return Err("A palette needs at least 2 colours".into());
+    }
+    Ok((name, colours))
+}
+
+fn parse_hex(s: &str) -> Option<[u8; 3]> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
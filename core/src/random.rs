@@ -0,0 +1,47 @@
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Characters made up text seeds are written with, leaving out the ones easily mistaken for each other
+const SEED_CHARACTERS: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+/// Length of a made up text seed
+const SEED_LENGTH: usize = 8;
+
+/// splitmix64, plenty for sampling and avoids pulling in a dependency
+#[derive(Clone, Copy)]
+pub struct Random(u64);
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// A seed that differs every run
+    pub fn seed() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64)
+    }
+
+    /// A generator seeded from text, the same on every platform so the text can be shared to get the same numbers
+    pub fn from_text(text: &str) -> Self {
+        // FNV-1a
+        let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+        Self(hash)
+    }
+
+    /// A short random seed written out as text, to be passed to `from_text`
+    pub fn text_seed() -> String {
+        let mut random = Self::new(Self::seed());
+        (0..SEED_LENGTH).map(|_| SEED_CHARACTERS[(random.next_u64() % SEED_CHARACTERS.len() as u64) as usize] as char).collect()
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform between 0 and 1
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
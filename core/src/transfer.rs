@@ -0,0 +1,137 @@
+use std::sync::RwLock;
+
+use colorgrad::{Color, Gradient};
+use serde::{Deserialize, Serialize};
+
+/// Colours sampled from a gradient to build it again with the curve applied
+const CURVE_SAMPLES: usize = 256;
+
+/// The curve every gradient is looked up through, None for a straight line.
+/// Global like the custom palettes so the fractals don't need to carry it around.
+static TRANSFER_CURVE: RwLock<Option<TransferCurve>> = RwLock::new(None);
+
+/// Range of normalised iteration values stretched over the whole gradient, so a frame that only uses
+/// a sliver of 0..max iterations still gets every colour. Values outside it take the colour at the nearer end.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Levels {
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+impl Levels {
+    pub const FULL: Levels = Levels { low: 0.0, high: 1.0 };
+
+    /// Where a normalised iteration value lands once the range is stretched to 0..=1
+    pub fn stretch(&self, t: f64) -> f64 {
+        if self.high <= self.low {
+            return t.clamp(0.0, 1.0);
+        }
+        ((t - self.low) / (self.high - self.low)).clamp(0.0, 1.0)
+    }
+}
+
+/// Maps normalised iteration values to positions on the colour gradient,
+/// so detail can be brought out near the boundary without changing palette.
+/// A smooth curve through the points that doesn't overshoot between them.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TransferCurve {
+    /// (iteration value, gradient position) in 0..=1, sorted by iteration value.
+    /// The first and last points are always at 0 and 1 across.
+    pub points: Vec<(f64, f64)>,
+}
+
+impl Default for TransferCurve {
+    fn default() -> Self {
+        Self { points: vec![(0.0, 0.0), (1.0, 1.0)] }
+    }
+}
+
+impl TransferCurve {
+    /// True if the curve leaves the gradient as it is
+    pub fn is_identity(&self) -> bool {
+        self.points.iter().all(|(x, y)| (x - y).abs() < 1e-9)
+    }
+
+    /// The gradient position for a normalised iteration value
+    pub fn at(&self, t: f64) -> f64 {
+        let points = &self.points;
+        if points.len() < 2 {
+            return t.clamp(0.0, 1.0);
+        }
+        let t = t.clamp(points[0].0, points[points.len() - 1].0);
+        let tangents = self.tangents();
+        let i = points.partition_point(|(x, _)| *x <= t).clamp(1, points.len() - 1);
+        let ((x0, y0), (x1, y1)) = (points[i - 1], points[i]);
+        let h = x1 - x0;
+        if h <= 0.0 {
+            return y1;
+        }
+        // cubic hermite spline
+        let s = (t - x0) / h;
+        let (s2, s3) = (s * s, s * s * s);
+        let y = (2.0 * s3 - 3.0 * s2 + 1.0) * y0
+            + (s3 - 2.0 * s2 + s) * h * tangents[i - 1]
+            + (-2.0 * s3 + 3.0 * s2) * y1
+            + (s3 - s2) * h * tangents[i];
+        y.clamp(0.0, 1.0)
+    }
+
+    /// Slopes at each point, limited so the curve doesn't overshoot (Fritsch-Carlson)
+    fn tangents(&self) -> Vec<f64> {
+        let points = &self.points;
+        let secants: Vec<f64> = points.windows(2)
+            .map(|pair| {
+                let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+                if x1 > x0 { (y1 - y0) / (x1 - x0) } else { 0.0 }
+            })
+            .collect();
+        let mut tangents: Vec<f64> = (0..points.len()).map(|i| match i {
+            0 => secants[0],
+            i if i == points.len() - 1 => secants[i - 1],
+            i if secants[i - 1] * secants[i] <= 0.0 => 0.0,
+            i => (secants[i - 1] + secants[i]) / 2.0,
+        }).collect();
+        for (i, secant) in secants.iter().enumerate() {
+            if *secant == 0.0 {
+                tangents[i] = 0.0;
+                tangents[i + 1] = 0.0;
+                continue;
+            }
+            let (a, b) = (tangents[i] / secant, tangents[i + 1] / secant);
+            let length = (a * a + b * b).sqrt();
+            if length > 3.0 {
+                tangents[i] = 3.0 / length * a * secant;
+                tangents[i + 1] = 3.0 / length * b * secant;
+            }
+        }
+        tangents
+    }
+}
+
+/// Use a curve for every gradient from now on
+pub fn set(curve: TransferCurve) {
+    *TRANSFER_CURVE.write().unwrap() = (!curve.is_identity()).then_some(curve);
+}
+
+/// The curve gradients are looked up through
+pub fn current() -> TransferCurve {
+    TRANSFER_CURVE.read().unwrap().clone().unwrap_or_default()
+}
+
+/// The gradient with the transfer curve applied
+pub fn apply(gradient: Gradient) -> Gradient {
+    let curve = TRANSFER_CURVE.read().unwrap();
+    let Some(curve) = curve.as_ref() else {
+        return gradient;
+    };
+    let colours: Vec<Color> = (0..CURVE_SAMPLES)
+        .map(|i| gradient.at(curve.at(i as f64 / (CURVE_SAMPLES - 1) as f64)))
+        .collect();
+    colorgrad::CustomGradient::new().colors(&colours).build().unwrap_or(gradient)
+}
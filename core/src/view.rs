@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+use crate::double_double::DoubleDouble;
+use crate::viewport::{Viewport, DOUBLE_DOUBLE_BELOW};
+
+/// Zoom of a new view, the size of a pixel in the complex plane
+pub const INIT_ZOOM: f64 = 0.003;
+
+/// The position, zoom and rotation of the camera
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct View {
+    pub zoom: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+    /// The low parts of the offset in double-double precision, only non-zero on zooms past what f64 can place
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset_lo: (f64, f64),
+    /// Rotation in degrees
+    pub rotation: f64,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self { zoom: INIT_ZOOM, offset_x: 0.0, offset_y: 0.0, offset_lo: (0.0, 0.0), rotation: 0.0 }
+    }
+}
+
+fn is_zero(lo: &(f64, f64)) -> bool {
+    *lo == (0.0, 0.0)
+}
+
+/// How well f64, or double-double for the fractals drawn in it on deep zooms, can represent the pixels of a view
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Precision {
+    Ok,
+    /// Past f64 but drawn in double-double, which is a few times slower
+    Extended,
+    /// Getting close to the limit, errors start to show up after many iterations
+    Approaching,
+    /// Neighbouring pixels can't be told apart so the image goes blocky
+    Exhausted,
+}
+
+impl View {
+    /// The part of the complex plane this view shows on a screen of the given size
+    pub fn viewport(&self, width: u32, height: u32) -> Viewport {
+        let mut viewport = Viewport::new(width as i32, height as i32, (self.offset_x, self.offset_y), self.zoom, self.rotation);
+        viewport.centre_lo = self.offset_lo;
+        viewport
+    }
+
+    /// Move and zoom to match a viewport, after it was panned or zoomed on the screen
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        (self.offset_x, self.offset_y) = viewport.centre;
+        self.offset_lo = viewport.centre_lo;
+        self.zoom = viewport.scale;
+    }
+
+    /// Centre the view on a point given in f64
+    pub fn set_centre(&mut self, (x, y): (f64, f64)) {
+        (self.offset_x, self.offset_y) = (x, y);
+        self.offset_lo = (0.0, 0.0);
+    }
+
+    /// Move the view by a distance in the complex plane, adding in double-double precision so small steps aren't lost on deep zooms
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let x = DoubleDouble::new(self.offset_x, self.offset_lo.0) + dx;
+        let y = DoubleDouble::new(self.offset_y, self.offset_lo.1) + dy;
+        (self.offset_x, self.offset_y) = (x.hi, y.hi);
+        self.offset_lo = (x.lo, y.lo);
+    }
+
+    /// Estimate whether f64 has enough precision for this view, or double-double when the fractal switches to it.
+    /// Compares the size of a pixel with the size of the coordinates,
+    /// z stays around 1 while iterating so small coordinates don't gain any precision.
+    pub fn precision(&self, double_double: bool) -> Precision {
+        let magnitude = self.offset_x.abs().max(self.offset_y.abs()).max(1.0);
+        let relative_pixel_size = self.zoom / magnitude;
+        let (exhausted, approaching) = if double_double { (1e-30, 1e-28) } else { (1e-15, DOUBLE_DOUBLE_BELOW) };
+        if relative_pixel_size < exhausted {
+            Precision::Exhausted
+        } else if relative_pixel_size < approaching {
+            Precision::Approaching
+        } else if relative_pixel_size < DOUBLE_DOUBLE_BELOW {
+            Precision::Extended
+        } else {
+            Precision::Ok
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use crate::double_double::DoubleDouble;
+
+/// Pixels smaller than this relative to the centre's coordinates are drawn in double-double precision
+/// by the fractals that support it, f64 starts to go blocky not far past here
+pub const DOUBLE_DOUBLE_BELOW: f64 = 1e-13;
+
+/// The part of the complex plane shown on a screen of a given size.
+/// The view is scaled so each pixel is `scale` wide, rotated about the centre of the screen and then moved to `centre`.
+/// The generators, input handling and overlays all go through this so they agree on where a pixel is.
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    pub width: i32,
+    pub height: i32,
+    /// The point in the complex plane at the centre of the screen
+    pub centre: (f64, f64),
+    /// The low parts of the centre in double-double precision, so deep zooms can still be placed between f64s
+    pub centre_lo: (f64, f64),
+    /// The size of a pixel in the complex plane
+    pub scale: f64,
+    sin: f64,
+    cos: f64,
+}
+
+impl Viewport {
+    /// `rotation` is in degrees
+    pub fn new(width: i32, height: i32, centre: (f64, f64), scale: f64, rotation: f64) -> Self {
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        Self { width, height, centre, centre_lo: (0.0, 0.0), scale, sin, cos }
+    }
+
+    /// The position on the screen the centre of the view is drawn at.
+    /// Not rounded to a whole pixel so odd sizes are handled the same as even ones.
+    #[inline]
+    pub fn screen_centre(&self) -> (f64, f64) {
+        (self.width as f64 / 2.0, self.height as f64 / 2.0)
+    }
+
+    /// Convert a position on the screen in pixels to a point in the complex plane
+    #[inline]
+    pub fn pixel_to_complex(&self, x_pixel: f64, y_pixel: f64) -> (f64, f64) {
+        let (centre_x, centre_y) = self.screen_centre();
+        let (dx, dy) = self.delta_to_complex(x_pixel - centre_x, y_pixel - centre_y);
+        (dx + self.centre.0, dy + self.centre.1)
+    }
+
+    /// Convert a point in the complex plane to a position on the screen, the inverse of `pixel_to_complex`
+    #[inline]
+    pub fn complex_to_pixel(&self, real: f64, imaginary: f64) -> (f64, f64) {
+        let (dx, dy) = (real - self.centre.0 - self.centre_lo.0, imaginary - self.centre.1 - self.centre_lo.1);
+        let (dx, dy) = (dx * self.cos + dy * self.sin, dy * self.cos - dx * self.sin);
+        let (centre_x, centre_y) = self.screen_centre();
+        (dx / self.scale + centre_x, dy / self.scale + centre_y)
+    }
+
+    /// `complex_to_pixel` for a point in double-double precision, so points a fraction of an f64 apart on deep zooms
+    /// land on different pixels
+    #[inline]
+    pub fn complex_to_pixel_dd(&self, (real, imaginary): (DoubleDouble, DoubleDouble)) -> (f64, f64) {
+        let (x, y) = self.centre_dd();
+        let (dx, dy) = ((real - x).to_f64(), (imaginary - y).to_f64());
+        let (dx, dy) = (dx * self.cos + dy * self.sin, dy * self.cos - dx * self.sin);
+        let (centre_x, centre_y) = self.screen_centre();
+        (dx / self.scale + centre_x, dy / self.scale + centre_y)
+    }
+
+    /// Convert a distance in pixels to a distance in the complex plane
+    #[inline]
+    pub fn delta_to_complex(&self, dx: f64, dy: f64) -> (f64, f64) {
+        let (dx, dy) = (dx * self.scale, dy * self.scale);
+        (dx * self.cos - dy * self.sin, dx * self.sin + dy * self.cos)
+    }
+
+    /// The point in the complex plane of the pixel at `index` in a row major frame
+    #[inline]
+    pub fn point(&self, index: usize) -> (f64, f64) {
+        let width = self.width as usize;
+        self.pixel_to_complex((index % width) as f64, (index / width) as f64)
+    }
+
+    /// The point in the complex plane of the pixel at `index` in double-double precision, for deep zooms
+    #[inline]
+    pub fn point_dd(&self, index: usize) -> (DoubleDouble, DoubleDouble) {
+        let width = self.width as usize;
+        let (centre_x, centre_y) = self.screen_centre();
+        let (dx, dy) = self.delta_to_complex((index % width) as f64 - centre_x, (index / width) as f64 - centre_y);
+        let (x, y) = self.centre_dd();
+        (x + dx, y + dy)
+    }
+
+    /// The centre in double-double precision
+    pub fn centre_dd(&self) -> (DoubleDouble, DoubleDouble) {
+        (DoubleDouble::new(self.centre.0, self.centre_lo.0), DoubleDouble::new(self.centre.1, self.centre_lo.1))
+    }
+
+    /// Whether neighbouring pixels are too close together for f64 to draw them well
+    pub fn needs_double_double(&self) -> bool {
+        let magnitude = self.centre.0.abs().max(self.centre.1.abs()).max(1.0);
+        self.scale / magnitude < DOUBLE_DOUBLE_BELOW
+    }
+
+    /// The same view moved by a distance in the complex plane, adding to the centre in double-double precision
+    /// so small steps aren't lost on deep zooms
+    pub fn moved(&self, (dx, dy): (f64, f64)) -> Self {
+        let (x, y) = self.centre_dd();
+        let (x, y) = (x + dx, y + dy);
+        Self { centre: (x.hi, y.hi), centre_lo: (x.lo, y.lo), ..*self }
+    }
+
+    /// The same view centred on a position on the screen
+    pub fn centred_on(&self, x_pixel: f64, y_pixel: f64) -> Self {
+        let (centre_x, centre_y) = self.screen_centre();
+        self.moved(self.delta_to_complex(x_pixel - centre_x, y_pixel - centre_y))
+    }
+
+    /// The index of the pixel a point in the complex plane lands on, None if it is off the screen
+    #[inline]
+    pub fn index(&self, real: f64, imaginary: f64) -> Option<usize> {
+        let (x, y) = self.complex_to_pixel(real, imaginary);
+        (x >= 0.0 && y >= 0.0 && x < self.width as f64 && y < self.height as f64).then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    /// The viewport for `rows` rows of the frame starting at row `start`, so a frame can be drawn a band at a time
+    pub fn rows(&self, start: i32, rows: i32) -> Self {
+        self.region(0, start, self.width, rows)
+    }
+
+    /// The viewport for a rectangle of the frame with its top left corner at pixel `(x, y)`, so part of a frame can be drawn again
+    pub fn region(&self, x: i32, y: i32, width: i32, height: i32) -> Self {
+        let (centre_x, centre_y) = self.screen_centre();
+        let delta = self.delta_to_complex(x as f64 + width as f64 / 2.0 - centre_x, y as f64 + height as f64 / 2.0 - centre_y);
+        Self { width, height, ..self.moved(delta) }
+    }
+
+    /// The same part of the complex plane drawn with a different number of pixels across it
+    pub fn resized(&self, width: i32, height: i32) -> Self {
+        Self { width, height, scale: self.scale * self.width as f64 / width as f64, ..*self }
+    }
+
+    /// The same view with pixels `factor` times the size, keeping whatever is at a position on the screen where it is
+    pub fn zoom_about(&self, x_pixel: f64, y_pixel: f64, factor: f64) -> Self {
+        let (centre_x, centre_y) = self.screen_centre();
+        let (dx, dy) = self.delta_to_complex(x_pixel - centre_x, y_pixel - centre_y);
+        Self { scale: self.scale * factor, ..self.moved((dx * (1.0 - factor), dy * (1.0 - factor))) }
+    }
+}
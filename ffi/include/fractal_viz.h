@@ -0,0 +1,77 @@
+/*
+C interface to the fractal-viz generators, built by `cargo build --release -p fractal-viz-ffi`
+into target/release/libfractal_viz_ffi.so (fractal_viz_ffi.dll on Windows, .dylib on macOS).
+
+    FvContext *context = fv_context_new("Mandelbrot");
+    fv_set_param(context, "Max Iterations", 500);
+    fv_set_view(context, -0.745, 0.1, 0.00001, 0.0);
+    uint8_t *pixels = malloc(1920 * 1080 * 4);
+    if (fv_render(context, pixels, 1920 * 1080 * 4, 1920, 1080) != FV_OK) {
+        fprintf(stderr, "%s\n", fv_last_error(context));
+    }
+    fv_context_free(context);
+
+Calls on one context must not overlap, separate contexts can be used from different threads.
+*/
+
+#ifndef FRACTAL_VIZ_H
+#define FRACTAL_VIZ_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define FV_OK 0
+/* A pointer that can't be null was */
+#define FV_ERROR_NULL -1
+/* An argument wasn't valid, fv_last_error says which */
+#define FV_ERROR_INVALID -2
+/* Something went wrong inside the library, the context can still be used */
+#define FV_ERROR_PANIC -3
+
+/* A fractal and the view of it to render */
+typedef struct FvContext FvContext;
+
+/* Create a context showing the named fractal ("Mandelbrot", "Julia" or "Newton") with its default
+   settings and view. Returns NULL if there is no fractal with that name. */
+FvContext *fv_context_new(const char *fractal);
+
+/* Free a context, NULL is ignored */
+void fv_context_free(FvContext *context);
+
+/* Why the last call on the context failed, an empty string if it succeeded.
+   The string belongs to the context and is valid until the next call on it. */
+const char *fv_last_error(const FvContext *context);
+
+/* Switch to another type of fractal with its default settings, keeping the colours and the view */
+int fv_set_fractal(FvContext *context, const char *fractal);
+
+/* Set or read one of the fractal's parameters by the name shown in the explorer, like "Max Iterations" or "Real".
+   Values the generators can't use, like an escape radius of 0 or NaN, return FV_ERROR_INVALID */
+int fv_set_param(FvContext *context, const char *name, double value);
+int fv_get_param(FvContext *context, const char *name, double *value);
+
+/* Set the colour gradient by name, like "Magma" or "Sinebow", unknown names use Sinebow */
+int fv_set_colour(FvContext *context, const char *gradient);
+
+/* Replace the fractal and all of its settings with json in the format of saved sessions, like
+   {"Julia": {"max_iterations": 200, "escape_radius": 2.0, "c": [-0.8, 0.156], "colour_gradient": "Magma"}}.
+   The parameters are checked the same as by fv_set_param, and the settings are kept if one is out of range */
+int fv_set_settings_json(FvContext *context, const char *json);
+
+/* Centre the view on a point, zoom is the size of a pixel in the complex plane and rotation is in degrees.
+   The zoom has to be above 0 and everything finite */
+int fv_set_view(FvContext *context, double centre_x, double centre_y, double zoom, double rotation);
+
+/* Render into an RGBA buffer of width * height * 4 bytes, rows top to bottom.
+   Blocks until the image is done, using every core. */
+int fv_render(FvContext *context, uint8_t *buffer, size_t buffer_len, uint32_t width, uint32_t height);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,224 @@
+//! C interface to the generators, for embedding them in applications that aren't written in Rust.
+//!
+//! The declarations are in `include/fractal_viz.h`. A context holds a fractal and a view,
+//! set them up with the `fv_set_*` functions and then render into an RGBA buffer with `fv_render`.
+
+use std::ffi::{c_char, c_double, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use fractal_viz_core::fractals::{self, Fractal};
+use fractal_viz_core::view::View;
+
+pub const FV_OK: c_int = 0;
+/// A pointer that can't be null was
+pub const FV_ERROR_NULL: c_int = -1;
+/// An argument wasn't valid, `fv_last_error` says which
+pub const FV_ERROR_INVALID: c_int = -2;
+/// Something went wrong inside the library, the context can still be used
+pub const FV_ERROR_PANIC: c_int = -3;
+
+/// A fractal and the view of it to render, created by `fv_context_new` and freed by `fv_context_free`
+pub struct FvContext {
+    fractal: Box<dyn Fractal>,
+    view: View,
+    /// Why the last call failed, kept here so the pointer `fv_last_error` returns stays valid
+    error: CString,
+}
+
+enum Error {
+    Null,
+    Invalid(String),
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Invalid(message)
+    }
+}
+
+/// Run a call on a context, turning errors and panics into return codes
+unsafe fn with_context(context: *mut FvContext, f: impl FnOnce(&mut FvContext) -> Result<(), Error>) -> c_int {
+    let Some(context) = context.as_mut() else {
+        return FV_ERROR_NULL;
+    };
+    let (code, message) = match catch_unwind(AssertUnwindSafe(|| f(&mut *context))) {
+        Ok(Ok(())) => (FV_OK, String::new()),
+        Ok(Err(Error::Null)) => (FV_ERROR_NULL, "a pointer argument was null".to_string()),
+        Ok(Err(Error::Invalid(message))) => (FV_ERROR_INVALID, message),
+        Err(_) => (FV_ERROR_PANIC, "the library panicked".to_string()),
+    };
+    context.error = CString::new(message).unwrap_or_default();
+    code
+}
+
+/// Borrow a nul terminated UTF-8 string from C
+unsafe fn string<'a>(string: *const c_char) -> Result<&'a str, Error> {
+    if string.is_null() {
+        return Err(Error::Null);
+    }
+    CStr::from_ptr(string).to_str().map_err(|_| Error::Invalid("strings must be UTF-8".into()))
+}
+
+fn check_fractal(name: &str) -> Result<(), Error> {
+    if fractals::names().contains(&name) {
+        Ok(())
+    } else {
+        Err(format!("no fractal called \"{}\"", name).into())
+    }
+}
+
+/// Create a context showing the named fractal ("Mandelbrot", "Julia" or "Newton") with its default settings and view.
+/// Returns null if there is no fractal with that name.
+///
+/// # Safety
+/// `fractal` must be null or a nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fv_context_new(fractal: *const c_char) -> *mut FvContext {
+    let Ok(name) = string(fractal) else {
+        return std::ptr::null_mut();
+    };
+    if check_fractal(name).is_err() {
+        return std::ptr::null_mut();
+    }
+    let context = FvContext { fractal: fractals::create(name), view: View::default(), error: CString::default() };
+    Box::into_raw(Box::new(context))
+}
+
+/// Free a context, null is ignored
+///
+/// # Safety
+/// `context` must be null or come from `fv_context_new`, and not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn fv_context_free(context: *mut FvContext) {
+    if !context.is_null() {
+        drop(Box::from_raw(context));
+    }
+}
+
+/// Why the last call on the context failed, an empty string if it succeeded.
+/// The string belongs to the context and is valid until the next call on it.
+///
+/// # Safety
+/// `context` must be null or a live context.
+#[no_mangle]
+pub unsafe extern "C" fn fv_last_error(context: *const FvContext) -> *const c_char {
+    match context.as_ref() {
+        Some(context) => context.error.as_ptr(),
+        None => c"the context was null".as_ptr(),
+    }
+}
+
+/// Switch to another type of fractal with its default settings, keeping the colours and the view
+///
+/// # Safety
+/// `context` must be a live context and `fractal` a nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fv_set_fractal(context: *mut FvContext, fractal: *const c_char) -> c_int {
+    with_context(context, |context| {
+        let name = string(fractal)?;
+        check_fractal(name)?;
+        let mut new_fractal = fractals::create(name);
+        *new_fractal.colour_gradient_mut() = context.fractal.colour_gradient_mut().clone();
+        context.fractal = new_fractal;
+        Ok(())
+    })
+}
+
+/// Set one of the fractal's parameters by the name shown in the explorer, like "Max Iterations" or "Real".
+/// Values outside `parameter_range` are rejected, the generators would panic on them
+///
+/// # Safety
+/// `context` must be a live context and `name` a nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fv_set_param(context: *mut FvContext, name: *const c_char, value: c_double) -> c_int {
+    with_context(context, |context| {
+        let name = string(name)?;
+        context.fractal.parameter(name).ok_or_else(|| format!("the fractal has no parameter \"{}\"", name))?;
+        fractals::check_parameter(name, value)?;
+        context.fractal.set_parameter(name, value);
+        Ok(())
+    })
+}
+
+/// Read one of the fractal's parameters into `value`
+///
+/// # Safety
+/// `context` must be a live context, `name` a nul terminated string and `value` point to a double.
+#[no_mangle]
+pub unsafe extern "C" fn fv_get_param(context: *mut FvContext, name: *const c_char, value: *mut c_double) -> c_int {
+    with_context(context, |context| {
+        let name = string(name)?;
+        let value = value.as_mut().ok_or(Error::Null)?;
+        *value = context.fractal.parameter(name).ok_or_else(|| format!("the fractal has no parameter \"{}\"", name))?;
+        Ok(())
+    })
+}
+
+/// Set the colour gradient by name, like "Magma" or "Sinebow", unknown names use Sinebow
+///
+/// # Safety
+/// `context` must be a live context and `gradient` a nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fv_set_colour(context: *mut FvContext, gradient: *const c_char) -> c_int {
+    with_context(context, |context| {
+        *context.fractal.colour_gradient_mut() = string(gradient)?.to_string();
+        Ok(())
+    })
+}
+
+/// Replace the fractal and all of its settings with json in the format of saved sessions,
+/// like `{"Julia": {"max_iterations": 200, "escape_radius": 2.0, "c": [-0.8, 0.156], "colour_gradient": "Magma"}}`.
+/// The settings are kept if any parameter is out of range
+///
+/// # Safety
+/// `context` must be a live context and `json` a nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fv_set_settings_json(context: *mut FvContext, json: *const c_char) -> c_int {
+    with_context(context, |context| {
+        let fractal: Box<dyn Fractal> = serde_json::from_str(string(json)?).map_err(|e| e.to_string())?;
+        fractal.check_parameters()?;
+        context.fractal = fractal;
+        Ok(())
+    })
+}
+
+/// Centre the view on a point, `zoom` is the size of a pixel in the complex plane and `rotation` is in degrees
+///
+/// # Safety
+/// `context` must be a live context.
+#[no_mangle]
+pub unsafe extern "C" fn fv_set_view(context: *mut FvContext, centre_x: c_double, centre_y: c_double, zoom: c_double, rotation: c_double) -> c_int {
+    with_context(context, |context| {
+        if !(zoom > 0.0 && zoom.is_finite()) {
+            return Err(format!("the zoom has to be above 0, not {}", zoom).into());
+        }
+        if !(centre_x.is_finite() && centre_y.is_finite() && rotation.is_finite()) {
+            return Err(format!("the centre and rotation have to be finite, not ({}, {}) and {}", centre_x, centre_y, rotation).into());
+        }
+        context.view = View { zoom, offset_x: centre_x, offset_y: centre_y, offset_lo: (0.0, 0.0), rotation };
+        Ok(())
+    })
+}
+
+/// Render into an RGBA buffer of `width` * `height` * 4 bytes, rows top to bottom.
+/// Blocks until the image is done, using every core.
+///
+/// # Safety
+/// `context` must be a live context and `buffer` point to at least `buffer_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fv_render(context: *mut FvContext, buffer: *mut u8, buffer_len: usize, width: u32, height: u32) -> c_int {
+    with_context(context, |context| {
+        if buffer.is_null() {
+            return Err(Error::Null);
+        }
+        let needed = (width as usize).checked_mul(height as usize).and_then(|pixels| pixels.checked_mul(4));
+        match needed {
+            Some(0) | None => return Err(format!("can't render an image {} by {}", width, height).into()),
+            Some(needed) if needed > buffer_len => return Err(format!("the buffer needs {} bytes but has {}", needed, buffer_len).into()),
+            Some(_) => {}
+        }
+        let pixels = std::slice::from_raw_parts_mut(buffer, width as usize * height as usize * 4);
+        context.fractal.draw(pixels, context.view.viewport(width, height));
+        Ok(())
+    })
+}
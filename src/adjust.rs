@@ -0,0 +1,46 @@
+use rayon::prelude::*;
+
+/// Brightness, contrast, gamma and saturation applied to the displayed frame.
+/// The freeze frame keeps the generated colours so these can change without generating the fractal again.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Adjustments {
+    /// Added to every channel, from -1 to 1
+    pub brightness: f32,
+    /// Spreads the channels away from or towards the middle grey
+    pub contrast: f32,
+    /// Above 1 lightens the dark colours, below 1 darkens them
+    pub gamma: f32,
+    /// 0 is greyscale
+    pub saturation: f32,
+}
+
+impl Default for Adjustments {
+    fn default() -> Self {
+        Self { brightness: 0.0, contrast: 1.0, gamma: 1.0, saturation: 1.0 }
+    }
+}
+
+impl Adjustments {
+    /// True if the frame is shown as it was generated
+    pub fn is_neutral(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Adjust every pixel of an rgba frame
+    pub fn apply(&self, pixels: &mut [u8]) {
+        // gamma, contrast and brightness work on each channel alone so they're done with a lookup table
+        let table: Vec<f32> = (0..256).map(|v| {
+            let v = (v as f32 / 255.0).powf(1.0 / self.gamma);
+            (v - 0.5) * self.contrast + 0.5 + self.brightness
+        }).collect();
+        let saturation = self.saturation;
+
+        pixels.par_chunks_mut(4).for_each(|pixel| {
+            let [r, g, b] = [table[pixel[0] as usize], table[pixel[1] as usize], table[pixel[2] as usize]];
+            let luma = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+            for (channel, value) in pixel.iter_mut().zip([r, g, b]) {
+                *channel = ((luma + (value - luma) * saturation).clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        });
+    }
+}
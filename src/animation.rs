@@ -0,0 +1,339 @@
+use std::f64::consts::TAU;
+
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::view::View;
+use web_time::Instant;
+
+use crate::wrap_degrees;
+
+pub const EASINGS: [Easing; 4] = [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut];
+
+/// How the time between two keyframes is mapped to progress through the segment
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Easing::Linear => "Linear",
+            Easing::EaseIn => "Ease in",
+            Easing::EaseOut => "Ease out",
+            Easing::EaseInOut => "Ease in/out",
+        }
+    }
+
+    fn apply(&self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// A captured view and set of parameters
+#[derive(Clone)]
+pub struct Keyframe {
+    pub fractal: Box<dyn Fractal>,
+    pub view: View,
+    /// Seconds to move to the next keyframe
+    pub duration: f32,
+    /// Easing of the segment to the next keyframe
+    pub easing: Easing,
+}
+
+/// Keyframes that are interpolated between to make an animation
+pub struct Timeline {
+    pub keyframes: Vec<Keyframe>,
+    /// Current position in seconds
+    pub time: f32,
+    pub playing: bool,
+    /// Frames per second of exported videos
+    pub fps: u32,
+    /// Scale frames that only zoom or pan from shared keyframes when exporting, much faster for long zooms
+    pub reuse_frames: bool,
+    /// Continuous motion layered on top of the keyframes
+    pub motion: Motion,
+    pub sweep: Sweep,
+    last_tick: Option<Instant>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self { keyframes: Vec::new(), time: 0.0, playing: false, fps: 30, reuse_frames: true, motion: Motion::default(), sweep: Sweep::default(), last_tick: None }
+    }
+}
+
+impl Timeline {
+    /// Length of the animation in seconds, the last keyframe's duration isn't used
+    pub fn duration(&self) -> f32 {
+        let segments = self.keyframes.len().saturating_sub(1);
+        self.keyframes.iter().take(segments).map(|keyframe| keyframe.duration).sum()
+    }
+
+    pub fn add(&mut self, fractal: Box<dyn Fractal>, view: View) {
+        self.keyframes.push(Keyframe { fractal, view, duration: 2.0, easing: Easing::EaseInOut });
+        // move to the new keyframe as that's what is being shown
+        self.time = self.duration();
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.keyframes.len() {
+            self.keyframes.remove(index);
+            self.time = self.time.min(self.duration());
+        }
+    }
+
+    /// Time at which a keyframe is reached
+    pub fn keyframe_time(&self, index: usize) -> f32 {
+        self.keyframes.iter().take(index).map(|keyframe| keyframe.duration).sum()
+    }
+
+    pub fn play(&mut self) {
+        if self.time >= self.duration() {
+            self.time = 0.0;
+        }
+        self.playing = self.keyframes.len() > 1;
+        self.last_tick = None;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Move playback on by the real time since the last call, returning the frame to show.
+    /// Stops at the end of the animation.
+    pub fn advance(&mut self) -> Option<(Box<dyn Fractal>, View)> {
+        if !self.playing {
+            return None;
+        }
+        let now = Instant::now();
+        if let Some(last_tick) = self.last_tick {
+            self.time += (now - last_tick).as_secs_f32();
+        }
+        self.last_tick = Some(now);
+        if self.time >= self.duration() {
+            self.time = self.duration();
+            self.playing = false;
+        }
+        self.sample(self.time)
+    }
+
+    /// The interpolated fractal and view at a time in seconds
+    pub fn sample(&self, time: f32) -> Option<(Box<dyn Fractal>, View)> {
+        let mut start = 0.0;
+        for pair in self.keyframes.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            if time <= start + a.duration {
+                let t = if a.duration > 0.0 { ((time - start) / a.duration).clamp(0.0, 1.0) } else { 1.0 };
+                let t = a.easing.apply(t as f64);
+                return Some((interpolate_fractal(a.fractal.as_ref(), b.fractal.as_ref(), t), interpolate_view(a.view, b.view, t)));
+            }
+            start += a.duration;
+        }
+        self.keyframes.last().map(|keyframe| (keyframe.fractal.clone(), keyframe.view))
+    }
+
+    /// Every frame of the parameter sweep with the motion applied
+    pub fn sweep_frames(&self, fractal: &dyn Fractal, view: View) -> Vec<(Box<dyn Fractal>, View)> {
+        (0..self.sweep.frames).map(|i| {
+            let mut fractal = fractal.clone_box();
+            fractal.set_parameter(self.sweep.parameter, self.sweep.value(i));
+            (fractal, self.motion.apply(view, i as f64 / self.fps as f64))
+        }).collect()
+    }
+
+    /// Every frame of the animation at the export frame rate with the motion applied.
+    /// Without keyframes to animate between, a clip of the motion from the current view is made instead.
+    pub fn frames(&self, fractal: &dyn Fractal, view: View) -> Vec<(Box<dyn Fractal>, View)> {
+        let fps = self.fps as f32;
+        if self.keyframes.len() > 1 {
+            let count = (self.duration() * fps).ceil() as usize + 1;
+            (0..count)
+                .filter_map(|i| self.sample(i as f32 / fps))
+                .enumerate()
+                .map(|(i, (fractal, view))| (fractal, self.motion.apply(view, i as f64 / fps as f64)))
+                .collect()
+        } else {
+            let count = (self.motion.clip_length * fps).ceil() as usize;
+            (0..count).map(|i| (fractal.clone_box(), self.motion.apply(view, i as f64 / fps as f64))).collect()
+        }
+    }
+}
+
+/// Moves one parameter of the fractal across a range over a number of frames
+pub struct Sweep {
+    /// Name of the parameter, from `Fractal::animatable_parameters`
+    pub parameter: &'static str,
+    pub from: f64,
+    pub to: f64,
+    pub frames: u32,
+    /// The next frame to preview, None when the preview isn't playing
+    preview_frame: Option<u32>,
+    /// The parameter and its value before the preview started, put back when it stops or ends
+    original: Option<(&'static str, f64)>,
+}
+
+impl Default for Sweep {
+    fn default() -> Self {
+        Self { parameter: "Escape Radius", from: 2.0, to: 10.0, frames: 120, preview_frame: None, original: None }
+    }
+}
+
+impl Sweep {
+    /// True until the frame after the preview, which puts the parameter back
+    pub fn playing(&self) -> bool {
+        self.preview_frame.is_some() || self.original.is_some()
+    }
+
+    pub fn play(&mut self) {
+        self.preview_frame = Some(0);
+    }
+
+    pub fn stop(&mut self) {
+        self.preview_frame = None;
+    }
+
+    fn value(&self, frame: u32) -> f64 {
+        let t = frame as f64 / self.frames.saturating_sub(1).max(1) as f64;
+        self.from + (self.to - self.from) * t
+    }
+
+    /// Show the next frame of the preview, one frame is shown per redraw, and restore the parameter once it's over.
+    /// Returns true if the fractal changed.
+    pub fn advance(&mut self, fractal: &mut dyn Fractal) -> bool {
+        let Some(frame) = self.preview_frame else {
+            let Some((parameter, value)) = self.original.take() else {
+                return false;
+            };
+            fractal.set_parameter(parameter, value);
+            return true;
+        };
+        if self.original.is_none() {
+            self.original = fractal.parameter(self.parameter).map(|value| (self.parameter, value));
+        }
+        fractal.set_parameter(self.parameter, self.value(frame));
+        self.preview_frame = (frame + 1 < self.frames).then_some(frame + 1);
+        true
+    }
+}
+
+/// Simple built in animations that run continuously
+pub struct Motion {
+    pub rotate: bool,
+    /// Degrees per second
+    pub rotation_speed: f64,
+    /// Orbit slightly around the centre of the view
+    pub wobble: bool,
+    /// Orbits per second
+    pub wobble_speed: f64,
+    /// Radius of the orbit in pixels, so it looks the same at any zoom
+    pub wobble_radius: f64,
+    /// Length in seconds of exported clips when there are no keyframes
+    pub clip_length: f32,
+    time: f64,
+    last_tick: Option<Instant>,
+}
+
+impl Default for Motion {
+    fn default() -> Self {
+        Self {
+            rotate: false,
+            rotation_speed: 10.0,
+            wobble: false,
+            wobble_speed: 0.25,
+            wobble_radius: 20.0,
+            clip_length: 10.0,
+            time: 0.0,
+            last_tick: None,
+        }
+    }
+}
+
+impl Motion {
+    pub fn active(&self) -> bool {
+        self.rotate || self.wobble
+    }
+
+    /// How far the wobble has moved the view from its centre after some time
+    fn wobble_offset(&self, zoom: f64, time: f64) -> (f64, f64) {
+        if !self.wobble {
+            return (0.0, 0.0);
+        }
+        let angle = TAU * self.wobble_speed * time;
+        let radius = self.wobble_radius * zoom;
+        (radius * (angle.cos() - 1.0), radius * angle.sin())
+    }
+
+    /// The view after the motion has run for some time
+    pub fn apply(&self, view: View, time: f64) -> View {
+        let (dx, dy) = self.wobble_offset(view.zoom, time);
+        let rotation = if self.rotate { wrap_degrees(view.rotation + self.rotation_speed * time) } else { view.rotation };
+        let mut view = View { rotation, ..view };
+        view.pan(dx, dy);
+        view
+    }
+
+    /// Move the view on by the real time since the last frame.
+    /// Only the change is applied so the view can still be moved by hand while it runs.
+    /// Returns true if the view changed.
+    pub fn tick(&mut self, view: &mut View) -> bool {
+        if !self.active() {
+            self.last_tick = None;
+            return false;
+        }
+        let now = Instant::now();
+        // capped so switching back to a session that was in the background doesn't jump
+        let dt = self.last_tick.map_or(0.0, |last_tick| (now - last_tick).as_secs_f64().min(0.25));
+        self.last_tick = Some(now);
+
+        let (x0, y0) = self.wobble_offset(view.zoom, self.time);
+        let (x1, y1) = self.wobble_offset(view.zoom, self.time + dt);
+        view.pan(x1 - x0, y1 - y0);
+        if self.rotate {
+            view.rotation = wrap_degrees(view.rotation + self.rotation_speed * dt);
+        }
+        self.time += dt;
+        true
+    }
+}
+
+/// Zoom exponentially so the speed looks constant, and move the offset with the zoom
+/// so the destination stays still on screen rather than drifting out of view on deep zooms
+fn interpolate_view(a: View, b: View, t: f64) -> View {
+    let zoom = a.zoom * (b.zoom / a.zoom).powf(t);
+    let progress = if (a.zoom - b.zoom).abs() > f64::EPSILON * a.zoom {
+        (a.zoom - zoom) / (a.zoom - b.zoom)
+    } else {
+        t
+    };
+    // rotate the short way round
+    let rotation_change = (b.rotation - a.rotation + 540.0).rem_euclid(360.0) - 180.0;
+    // the offsets are moved in double-double precision so deep keyframes are reached exactly
+    let mut view = View { zoom, rotation: wrap_degrees(a.rotation + rotation_change * t), ..a };
+    let dx = (b.offset_x - a.offset_x) + (b.offset_lo.0 - a.offset_lo.0);
+    let dy = (b.offset_y - a.offset_y) + (b.offset_lo.1 - a.offset_lo.1);
+    view.pan(dx * progress, dy * progress);
+    view
+}
+
+/// Morph between the parameters of the same type of fractal, different types switch half way through.
+/// Settings that can't be animated are taken from the first fractal.
+fn interpolate_fractal(a: &dyn Fractal, b: &dyn Fractal, t: f64) -> Box<dyn Fractal> {
+    if a.name() != b.name() {
+        return if t < 0.5 { a.clone_box() } else { b.clone_box() };
+    }
+    let mut fractal = a.clone_box();
+    for name in a.animatable_parameters() {
+        if let (Some(x), Some(y)) = (a.parameter(name), b.parameter(name)) {
+            fractal.set_parameter(name, x + (y - x) * t);
+        }
+    }
+    fractal
+}
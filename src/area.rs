@@ -0,0 +1,144 @@
+use std::sync::mpsc;
+
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::viewport::Viewport;
+use fractal_viz_core::random::Random;
+use rayon::prelude::*;
+
+/// Samples taken before the estimate stops
+const TARGET_SAMPLES: u64 = 10_000_000;
+/// Samples between progress updates, split into chunks that run in parallel
+const BATCH_SIZE: u64 = 1 << 16;
+const CHUNK_SIZE: u64 = 1 << 12;
+/// z score of a 95% confidence interval
+const CONFIDENCE_Z: f64 = 1.96;
+
+pub const REGIONS: [Region; 2] = [Region::Visible, Region::WholeSet];
+
+/// The part of the complex plane to estimate the area of the set in
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Region {
+    Visible,
+    WholeSet,
+}
+
+impl Region {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Region::Visible => "Visible area",
+            Region::WholeSet => "Whole set",
+        }
+    }
+}
+
+/// A parallelogram in the complex plane, points are origin + s * u + t * v for s and t between 0 and 1
+#[derive(Clone, Copy)]
+struct Bounds {
+    origin: (f64, f64),
+    u: (f64, f64),
+    v: (f64, f64),
+}
+
+impl Bounds {
+    /// The visible part of the plane, which is rotated with the view
+    fn visible(viewport: Viewport) -> Self {
+        let origin = viewport.pixel_to_complex(0.0, 0.0);
+        let right = viewport.pixel_to_complex(viewport.width as f64, 0.0);
+        let bottom = viewport.pixel_to_complex(0.0, viewport.height as f64);
+        Self { origin, u: (right.0 - origin.0, right.1 - origin.1), v: (bottom.0 - origin.0, bottom.1 - origin.1) }
+    }
+
+    /// A box the whole set is known to be inside, if the fractal has a bounded set
+    fn whole_set(fractal: &dyn Fractal) -> Option<Self> {
+        let (min, max) = fractal.bounds()?;
+        Some(Self { origin: min, u: (max.0 - min.0, 0.0), v: (0.0, max.1 - min.1) })
+    }
+
+    fn area(&self) -> f64 {
+        (self.u.0 * self.v.1 - self.u.1 * self.v.0).abs()
+    }
+
+    fn point(&self, s: f64, t: f64) -> (f64, f64) {
+        (self.origin.0 + s * self.u.0 + t * self.v.0, self.origin.1 + s * self.u.1 + t * self.v.1)
+    }
+}
+
+/// Estimates the area of the set by sampling random points on a background thread.
+/// Points that don't escape within the max iterations count as inside.
+pub struct AreaEstimate {
+    /// Dropped to stop the thread
+    receiver: Option<mpsc::Receiver<u64>>,
+    pub samples: u64,
+    inside: u64,
+    region_area: f64,
+}
+
+impl AreaEstimate {
+    /// Returns None if the fractal has no set to measure in the region.
+    /// The thread stops once the estimate is stopped or dropped.
+    pub fn start(fractal: Box<dyn Fractal>, region: Region, viewport: Viewport) -> Option<Self> {
+        let bounds = match region {
+            Region::Visible => Bounds::visible(viewport),
+            Region::WholeSet => Bounds::whole_set(fractal.as_ref())?,
+        };
+        let seed = Random::seed();
+        let (sender, receiver) = mpsc::channel();
+        crate::background(move || {
+            let max_iterations = fractal.max_iterations();
+            for batch in 0..TARGET_SAMPLES / BATCH_SIZE {
+                let inside = (0..BATCH_SIZE / CHUNK_SIZE).into_par_iter().map(|chunk| {
+                    let mut random = Random::new(seed ^ (batch * BATCH_SIZE + chunk).wrapping_mul(0x2545f4914f6cdd1d));
+                    (0..CHUNK_SIZE).filter(|_| {
+                        let point = bounds.point(random.next_f64(), random.next_f64());
+                        fractal.sample(point).iterations >= max_iterations
+                    }).count() as u64
+                }).sum();
+                if sender.send(inside).is_err() {
+                    return;
+                }
+            }
+        });
+        Some(Self { receiver: Some(receiver), samples: 0, inside: 0, region_area: bounds.area() })
+    }
+
+    /// Add the batches sampled since the last poll
+    pub fn poll(&mut self) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        for inside in receiver.try_iter() {
+            self.samples += BATCH_SIZE;
+            self.inside += inside;
+        }
+        if self.samples >= TARGET_SAMPLES / BATCH_SIZE * BATCH_SIZE {
+            self.receiver = None;
+        }
+    }
+
+    /// Stop sampling, the estimate so far is kept
+    pub fn stop(&mut self) {
+        self.receiver = None;
+    }
+
+    pub fn running(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.samples as f32 / TARGET_SAMPLES as f32
+    }
+
+    fn fraction_inside(&self) -> f64 {
+        self.inside as f64 / self.samples.max(1) as f64
+    }
+
+    pub fn area(&self) -> f64 {
+        self.region_area * self.fraction_inside()
+    }
+
+    /// Half the width of the 95% confidence interval of the area
+    pub fn error(&self) -> f64 {
+        let p = self.fraction_inside();
+        self.region_area * CONFIDENCE_Z * (p * (1.0 - p) / self.samples.max(1) as f64).sqrt()
+    }
+}
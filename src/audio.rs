@@ -0,0 +1,198 @@
+use fractal_viz_core::fractals::{Fractal, Julia};
+use fractal_viz_core::view::View;
+
+/// How quickly the automatic gain forgets loud sounds, per frame
+const PEAK_DECAY: f32 = 0.995;
+/// Smoothing of the band levels so the visuals don't flicker
+const LEVEL_SMOOTHING: f32 = 0.6;
+
+/// Drives fractal parameters from the sound coming into the default input device.
+/// Bass pulses the zoom, mids move the Julia seed and treble sways the rotation.
+pub struct AudioReactive {
+    capture: capture::Capture,
+    pub zoom_pulse: bool,
+    pub julia_c: bool,
+    pub rotation: bool,
+    pub sensitivity: f32,
+    /// Levels of the bass, mid and treble bands between 0 and 1
+    pub levels: [f32; 3],
+    peaks: [f32; 3],
+    /// What is currently applied on top of the fractal and view, so it can be undone next frame
+    applied: Applied,
+}
+
+#[derive(Clone, Copy)]
+struct Applied {
+    zoom: f64,
+    c: f64,
+    rotation: f64,
+}
+
+impl Default for Applied {
+    fn default() -> Self {
+        Self { zoom: 1.0, c: 0.0, rotation: 0.0 }
+    }
+}
+
+impl AudioReactive {
+    pub fn start() -> Result<Self, String> {
+        Ok(Self {
+            capture: capture::Capture::start()?,
+            zoom_pulse: true,
+            julia_c: true,
+            rotation: false,
+            sensitivity: 1.0,
+            levels: [0.0; 3],
+            peaks: [1e-6; 3],
+            applied: Applied::default(),
+        })
+    }
+
+    /// Analyse the latest audio and apply it to the fractal and view.
+    /// Returns true if anything changed.
+    pub fn update(&mut self, fractal: &mut dyn Fractal, view: &mut View) -> bool {
+        let Some(bands) = self.capture.bands() else {
+            return false;
+        };
+        for ((level, peak), band) in self.levels.iter_mut().zip(self.peaks.iter_mut()).zip(bands) {
+            // automatic gain so quiet and loud sources both use the full range
+            *peak = (*peak * PEAK_DECAY).max(band);
+            *level = *level * LEVEL_SMOOTHING + (band / *peak) * (1.0 - LEVEL_SMOOTHING);
+        }
+
+        let [bass, mid, treble] = self.levels.map(|level| (level * self.sensitivity) as f64);
+        let target = Applied {
+            zoom: if self.zoom_pulse { 1.0 - 0.15 * bass.min(1.0) } else { 1.0 },
+            c: if self.julia_c { 0.05 * mid } else { 0.0 },
+            rotation: if self.rotation { 10.0 * treble } else { 0.0 },
+        };
+        self.apply(target, fractal, view);
+        true
+    }
+
+    /// Undo the audio's effect so the fractal and view are left as they were before it started
+    pub fn stop(mut self, fractal: &mut dyn Fractal, view: &mut View) {
+        self.apply(Applied::default(), fractal, view);
+    }
+
+    /// Only the change since last frame is applied so the view can still be moved by hand
+    fn apply(&mut self, target: Applied, fractal: &mut dyn Fractal, view: &mut View) {
+        view.zoom *= target.zoom / self.applied.zoom;
+        view.rotation = crate::wrap_degrees(view.rotation + target.rotation - self.applied.rotation);
+        if let Some(Julia { c, .. }) = fractal.downcast_mut::<Julia>() {
+            c.0 += target.c - self.applied.c;
+            c.1 += target.c - self.applied.c;
+        }
+        self.applied = target;
+    }
+}
+
+#[cfg(feature = "audio")]
+mod capture {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::{FromSample, Sample, SizedSample};
+    use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+    /// Number of samples in each fft
+    const FFT_SIZE: usize = 2048;
+    /// Frequency ranges in Hz of the bass, mid and treble bands
+    const BANDS: [(f32, f32); 3] = [(20.0, 250.0), (250.0, 2000.0), (2000.0, 8000.0)];
+
+    /// Audio from the default input device, the stream stops when this is dropped
+    pub struct Capture {
+        _stream: cpal::Stream,
+        samples: Arc<Mutex<VecDeque<f32>>>,
+        sample_rate: f32,
+        fft: Arc<dyn Fft<f32>>,
+    }
+
+    impl Capture {
+        pub fn start() -> Result<Self, String> {
+            let device = cpal::default_host().default_input_device().ok_or("No audio input device")?;
+            let config = device.default_input_config().map_err(|e| e.to_string())?;
+            let sample_rate = config.sample_rate().0 as f32;
+            let samples = Arc::new(Mutex::new(VecDeque::with_capacity(FFT_SIZE)));
+
+            let stream = match config.sample_format() {
+                cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config.into(), samples.clone()),
+                cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config.into(), samples.clone()),
+                cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config.into(), samples.clone()),
+                format => return Err(format!("Unsupported sample format {}", format)),
+            }.map_err(|e| e.to_string())?;
+            stream.play().map_err(|e| e.to_string())?;
+
+            let fft = FftPlanner::new().plan_fft_forward(FFT_SIZE);
+            Ok(Self { _stream: stream, samples, sample_rate, fft })
+        }
+
+        /// Average magnitude of each band in the latest samples
+        pub fn bands(&mut self) -> Option<[f32; 3]> {
+            let mut buffer: Vec<Complex<f32>> = {
+                let samples = self.samples.lock().ok()?;
+                if samples.len() < FFT_SIZE {
+                    return None;
+                }
+                // hann window to reduce leakage between bins
+                samples.iter().enumerate().map(|(i, sample)| {
+                    let window = 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / FFT_SIZE as f32).cos();
+                    Complex::new(sample * window, 0.0)
+                }).collect()
+            };
+            self.fft.process(&mut buffer);
+
+            let bin_width = self.sample_rate / FFT_SIZE as f32;
+            Some(BANDS.map(|(low, high)| {
+                // at low sample rates the top bands start above the highest bin, they're left empty
+                let end = ((high / bin_width) as usize).min(FFT_SIZE / 2);
+                let bins = ((low / bin_width) as usize).min(end)..end;
+                let count = bins.len().max(1);
+                buffer[bins].iter().map(|bin| bin.norm()).sum::<f32>() / count as f32
+            }))
+        }
+    }
+
+    fn build_stream<T>(device: &cpal::Device, config: &cpal::StreamConfig, samples: Arc<Mutex<VecDeque<f32>>>) -> Result<cpal::Stream, cpal::BuildStreamError>
+    where
+        T: Sample + SizedSample,
+        f32: FromSample<T>,
+    {
+        let channels = config.channels as usize;
+        device.build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let Ok(mut samples) = samples.lock() else {
+                    return;
+                };
+                // mix down to mono and keep the last FFT_SIZE samples
+                for frame in data.chunks(channels) {
+                    let mono = frame.iter().map(|sample| f32::from_sample(*sample)).sum::<f32>() / channels as f32;
+                    if samples.len() == FFT_SIZE {
+                        samples.pop_front();
+                    }
+                    samples.push_back(mono);
+                }
+            },
+            |_| {},
+            None,
+        )
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+mod capture {
+    /// Stand in for builds without the audio feature
+    pub struct Capture;
+
+    impl Capture {
+        pub fn start() -> Result<Self, String> {
+            Err("Built without audio support, rebuild with --features audio".into())
+        }
+
+        pub fn bands(&mut self) -> Option<[f32; 3]> {
+            None
+        }
+    }
+}
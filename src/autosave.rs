@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use crate::session::{Session, SessionFile, Sessions};
+
+/// How often the open sessions are written out, if they've changed
+const INTERVAL: Duration = Duration::from_secs(30);
+
+/// Every open session, as they were when last autosaved
+#[derive(Serialize, Deserialize)]
+pub struct Autosave {
+    pub sessions: Vec<SessionFile>,
+    pub current: usize,
+}
+
+impl Autosave {
+    fn new(sessions: &Sessions) -> Self {
+        let files = sessions.list.iter()
+            .map(|session| SessionFile { fractal: session.fractal.clone(), view: session.view })
+            .collect();
+        Self { sessions: files, current: sessions.current }
+    }
+
+    /// Replace the open sessions with the saved ones
+    pub fn restore(self, sessions: &mut Sessions) {
+        if !self.sessions.is_empty() {
+            sessions.list = self.sessions.into_iter().map(Session::from).collect();
+            sessions.current = self.current.min(sessions.list.len() - 1);
+        }
+    }
+}
+
+/// Writes the open sessions to the data directory every so often, so they can be restored after a crash.
+/// The file is removed when the app exits normally, so finding one at startup means the last run didn't.
+/// The sessions open at exit are kept separately so the start panel can offer to continue from them.
+/// Nothing is saved in the browser as there's no data directory.
+pub struct Autosaver {
+    path: Option<PathBuf>,
+    /// Where the sessions open when the app last exited are saved
+    last_path: Option<PathBuf>,
+    last_saved: Instant,
+    /// What was last written, to skip writing when nothing has changed
+    last_json: String,
+}
+
+impl Default for Autosaver {
+    fn default() -> Self {
+        let dir = dirs::data_dir().map(|dir| dir.join("fractal-viz"));
+        let path = dir.as_ref().map(|dir| dir.join("autosave.json"));
+        let last_path = dir.map(|dir| dir.join("last_session.json"));
+        Self { path, last_path, last_saved: Instant::now(), last_json: String::new() }
+    }
+}
+
+impl Autosaver {
+    /// The sessions left behind by a run that crashed or was killed, if there are any
+    pub fn recover(&self) -> Option<Autosave> {
+        read(self.path.as_ref()?)
+    }
+
+    /// The sessions that were open when the app last exited
+    pub fn last(&self) -> Option<Autosave> {
+        read(self.last_path.as_ref()?).filter(|last| !last.sessions.is_empty())
+    }
+
+    /// Keep the sessions open at exit for the start panel next time
+    pub fn save_last(&self, sessions: &Sessions) -> Result<(), String> {
+        let Some(path) = &self.last_path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string(&Autosave::new(sessions)).map_err(|e| e.to_string())?;
+        write(path, &json)
+    }
+
+    /// Save the sessions if it's been long enough since the last save
+    pub fn tick(&mut self, sessions: &Sessions) {
+        if self.last_saved.elapsed() < INTERVAL {
+            return;
+        }
+        self.last_saved = Instant::now();
+        if let Err(e) = self.save(sessions) {
+            tracing::warn!(error = %e, "Autosave failed");
+        }
+    }
+
+    /// Save the sessions now if they've changed since the last save
+    pub fn save(&mut self, sessions: &Sessions) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string(&Autosave::new(sessions)).map_err(|e| e.to_string())?;
+        if json == self.last_json {
+            return Ok(());
+        }
+        write(path, &json)?;
+        tracing::debug!(path = %path.display(), "Autosaved");
+        self.last_json = json;
+        Ok(())
+    }
+
+    /// Remove the autosave, called when the app exits normally or the user chose not to restore it
+    pub fn clear(&mut self) {
+        if let Some(path) = &self.path {
+            if let Err(e) = std::fs::remove_file(path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!(error = %e, "Couldn't remove the autosave");
+                }
+            }
+        }
+        self.last_json.clear();
+    }
+}
+
+fn read(path: &Path) -> Option<Autosave> {
+    let json = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&json) {
+        Ok(autosave) => Some(autosave),
+        Err(e) => {
+            tracing::warn!(error = %e, path = %path.display(), "Couldn't read the saved sessions");
+            None
+        }
+    }
+}
+
+/// Written alongside and renamed over the old file, so a crash while saving doesn't leave half a file
+fn write(path: &Path, json: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let temporary = path.with_extension("json.tmp");
+    std::fs::write(&temporary, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&temporary, path).map_err(|e| e.to_string())
+}
@@ -0,0 +1,194 @@
+use pixels::{wgpu, Pixels, PixelsContext};
+
+/// The bright parts are blurred at half the size of the buffer, which is quicker and spreads the glow further
+const GLOW_DOWNSCALE: u32 = 2;
+/// The blurred textures only hold light added on top so 8 bits is enough
+const GLOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+/// Size of the uniforms, padded to a multiple of 16 bytes
+const PARAMS_SIZE: u64 = 32;
+
+/// How the bloom looks
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BloomSettings {
+    pub enabled: bool,
+    /// Brightness from 0 to 1 where colours start to glow
+    pub threshold: f32,
+    /// How strongly the glow is added on top
+    pub intensity: f32,
+    /// How far the glow spreads in pixels of the buffer
+    pub radius: f32,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self { enabled: false, threshold: 0.6, intensity: 1.0, radius: 8.0 }
+    }
+}
+
+/// Draws the pixel buffer over the window with a glow around its bright parts, in place of the usual scaling.
+/// A bright pass picks out the colours over the threshold, which are blurred across and then down and added back on top.
+pub struct Bloom {
+    bright: wgpu::RenderPipeline,
+    blur: wgpu::RenderPipeline,
+    composite: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    /// Uniforms for the bright pass, the two blurs and the composite, they differ so each pass has its own
+    params: [wgpu::Buffer; 4],
+    /// The two textures the glow is blurred between, remade when the buffer changes size
+    glow: Option<(wgpu::Extent3d, [wgpu::TextureView; 2])>,
+}
+
+impl Bloom {
+    pub fn new(pixels: &Pixels) -> Self {
+        let device = pixels.device();
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("bloom"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("bloom.wgsl").into()),
+        });
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("bloom"),
+            entries: &[
+                texture_entry(0),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(3),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("bloom"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |entry_point, format| device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(entry_point),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState { module: &shader, entry_point: "vs_main", buffers: &[] },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point,
+                targets: &[Some(wgpu::ColorTargetState { format, blend: None, write_mask: wgpu::ColorWrites::ALL })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("bloom"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let params = std::array::from_fn(|_| device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("bloom"),
+            size: PARAMS_SIZE,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }));
+        Self {
+            bright: pipeline("fs_bright", GLOW_FORMAT),
+            blur: pipeline("fs_blur", GLOW_FORMAT),
+            composite: pipeline("fs_composite", pixels.render_texture_format()),
+            layout,
+            sampler,
+            params,
+            glow: None,
+        }
+    }
+
+    /// Draw the buffer stretched over `target` with the glow added
+    pub fn render(&mut self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView, context: &PixelsContext, settings: &BloomSettings) {
+        let size = wgpu::Extent3d {
+            width: (context.texture_extent.width / GLOW_DOWNSCALE).max(1),
+            height: (context.texture_extent.height / GLOW_DOWNSCALE).max(1),
+            depth_or_array_layers: 1,
+        };
+        if self.glow.as_ref().is_none_or(|(glow_size, _)| *glow_size != size) {
+            let views = std::array::from_fn(|_| context.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("bloom_glow"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: GLOW_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            }).create_view(&wgpu::TextureViewDescriptor::default()));
+            self.glow = Some((size, views));
+        }
+
+        let radius = settings.radius / GLOW_DOWNSCALE as f32;
+        let steps = [(0.0, 0.0), (1.0 / size.width as f32, 0.0), (0.0, 1.0 / size.height as f32), (0.0, 0.0)];
+        for (buffer, (x, y)) in self.params.iter().zip(steps) {
+            let bytes: Vec<u8> = [x, y, settings.threshold, settings.intensity, radius, 0.0, 0.0, 0.0]
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect();
+            context.queue.write_buffer(buffer, 0, &bytes);
+        }
+
+        let Some((_, [a, b])) = &self.glow else {
+            return;
+        };
+        let frame = context.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.pass(encoder, context, &self.bright, &frame, &frame, &self.params[0], a);
+        self.pass(encoder, context, &self.blur, a, a, &self.params[1], b);
+        self.pass(encoder, context, &self.blur, b, b, &self.params[2], a);
+        self.pass(encoder, context, &self.composite, &frame, a, &self.params[3], target);
+    }
+
+    /// Draw `source` into `target` through one of the pipelines, `glow` is only read by the composite
+    #[allow(clippy::too_many_arguments)]
+    fn pass(&self, encoder: &mut wgpu::CommandEncoder, context: &PixelsContext, pipeline: &wgpu::RenderPipeline,
+            source: &wgpu::TextureView, glow: &wgpu::TextureView, params: &wgpu::Buffer, target: &wgpu::TextureView) {
+        let bind_group = context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("bloom"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(source) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+                wgpu::BindGroupEntry { binding: 2, resource: params.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(glow) },
+            ],
+        });
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("bloom"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: true },
+            })],
+            depth_stencil_attachment: None,
+        });
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,61 @@
+// Bloom: the bright parts of the frame are picked out, blurred and added back on top so they glow
+
+struct Params {
+    // step between blur samples in texture coordinates, along one axis at a time
+    direction: vec2<f32>,
+    threshold: f32,
+    intensity: f32,
+    // in texels of the blurred texture
+    radius: f32,
+}
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+@group(0) @binding(2) var<uniform> params: Params;
+// the blurred bright parts, only read when compositing
+@group(0) @binding(3) var glow: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// one triangle big enough to cover the target, the parts outside are clipped
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_bright(in: VertexOutput) -> @location(0) vec4<f32> {
+    let colour = textureSampleLevel(source, source_sampler, in.uv, 0.0).rgb;
+    let brightness = max(colour.r, max(colour.g, colour.b));
+    // fades in above the threshold rather than switching on
+    let amount = clamp((brightness - params.threshold) / max(1.0 - params.threshold, 0.001), 0.0, 1.0);
+    return vec4<f32>(colour * amount, 1.0);
+}
+
+@fragment
+fn fs_blur(in: VertexOutput) -> @location(0) vec4<f32> {
+    let sigma = max(params.radius / 2.0, 0.5);
+    let taps = i32(ceil(params.radius));
+    var sum = vec3<f32>(0.0);
+    var total = 0.0;
+    for (var i = -taps; i <= taps; i++) {
+        let weight = exp(-f32(i * i) / (2.0 * sigma * sigma));
+        sum += textureSampleLevel(source, source_sampler, in.uv + params.direction * f32(i), 0.0).rgb * weight;
+        total += weight;
+    }
+    return vec4<f32>(sum / total, 1.0);
+}
+
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    let colour = textureSampleLevel(source, source_sampler, in.uv, 0.0).rgb;
+    let bloom = textureSampleLevel(glow, source_sampler, in.uv, 0.0).rgb;
+    return vec4<f32>(colour + bloom * params.intensity, 1.0);
+}
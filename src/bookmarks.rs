@@ -0,0 +1,109 @@
+use std::fs;
+use std::path::PathBuf;
+
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::view::View;
+use serde::{Deserialize, Serialize};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+pub const THUMBNAIL_WIDTH: u32 = 160;
+
+/// A saved location with a small preview image
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub fractal: Box<dyn Fractal>,
+    pub view: View,
+    /// File name of the thumbnail, also used as a unique id for the bookmark
+    pub thumbnail: String,
+    /// Rgba pixels of the thumbnail, loaded from the png
+    #[serde(skip)]
+    pub image: Vec<u8>,
+    #[serde(skip)]
+    pub image_size: (u32, u32),
+}
+
+/// Saved bookmarks, stored as json with a png thumbnail for each bookmark in the data directory
+pub struct Bookmarks {
+    pub list: Vec<Bookmark>,
+    dir: Option<PathBuf>,
+}
+
+impl Bookmarks {
+    /// Load the saved bookmarks, starting with none if they can't be read
+    pub fn load() -> Self {
+        let dir = dirs::data_dir().map(|dir| dir.join("fractal-viz").join("bookmarks"));
+        let mut list: Vec<Bookmark> = dir.as_ref()
+            .and_then(|dir| fs::read_to_string(dir.join("bookmarks.json")).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        if let Some(dir) = &dir {
+            for bookmark in list.iter_mut() {
+                if let Ok(image) = image::open(dir.join(&bookmark.thumbnail)) {
+                    let image = image.to_rgba8();
+                    bookmark.image_size = image.dimensions();
+                    bookmark.image = image.into_raw();
+                }
+            }
+        }
+        Self { list, dir }
+    }
+
+    /// Bookmark the view, rendering a thumbnail of it with the same aspect ratio as the window.
+    /// The bookmark is kept for this run even if saving it fails.
+    pub fn add(&mut self, name: String, fractal: &dyn Fractal, view: View, window_size: (u32, u32)) -> Result<(), String> {
+        let (image, (width, height)) = thumbnail(fractal, view, window_size);
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|t| t.as_millis()).unwrap_or(0);
+        self.list.push(Bookmark {
+            name,
+            fractal: fractal.clone_box(),
+            view,
+            thumbnail: format!("{}.png", timestamp),
+            image,
+            image_size: (width, height),
+        });
+
+        let bookmark = self.list.last().unwrap();
+        if let Some(dir) = &self.dir {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            image::save_buffer(dir.join(&bookmark.thumbnail), &bookmark.image, width, height, image::ColorType::Rgba8)
+                .map_err(|e| e.to_string())?;
+        }
+        self.save()
+    }
+
+    pub fn remove(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.list.len() {
+            return Ok(());
+        }
+        let bookmark = self.list.remove(index);
+        if let Some(dir) = &self.dir {
+            let _ = fs::remove_file(dir.join(bookmark.thumbnail));
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Err("No data directory to save bookmarks in".into());
+        };
+        fs::create_dir_all(dir)
+            .and_then(|_| serde_json::to_string_pretty(&self.list).map_err(std::io::Error::other))
+            .and_then(|json| fs::write(dir.join("bookmarks.json"), json))
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Draw a small picture of the view with the same aspect ratio as the window, returning its rgba pixels and size
+pub fn thumbnail(fractal: &dyn Fractal, view: View, (window_width, window_height): (u32, u32)) -> (Vec<u8>, (u32, u32)) {
+    let width = THUMBNAIL_WIDTH;
+    let height = (THUMBNAIL_WIDTH * window_height / window_width.max(1)).max(1);
+    // scale the zoom so the thumbnail covers the same area as the window
+    let zoom = view.zoom * window_width as f64 / width as f64;
+    let viewport = View { zoom, ..view }.viewport(width, height);
+    let mut image = vec![0; (width * height * 4) as usize];
+    fractal.draw(&mut image, viewport);
+    (image, (width, height))
+}
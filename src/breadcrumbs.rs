@@ -0,0 +1,62 @@
+use fractal_viz_core::view::{View, INIT_ZOOM};
+
+/// Waypoints are recorded each time the magnification goes up this many times
+const WAYPOINT_FACTOR: f64 = 10.0;
+
+/// A view passed on the way down with how deep it is
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Waypoint {
+    pub view: View,
+    /// The waypoint is the first view reached at WAYPOINT_FACTOR^level magnification
+    pub level: i32,
+}
+
+/// Views recorded at every 10x of magnification while zooming into one place, to jump back out to.
+/// Going somewhere else drops the waypoints that aren't on the way there
+#[derive(Clone, Default)]
+pub struct Breadcrumbs {
+    /// From the shallowest to the deepest
+    list: Vec<Waypoint>,
+    /// The fractal the waypoints are for
+    fractal: &'static str,
+}
+
+impl Breadcrumbs {
+    pub fn list(&self) -> &[Waypoint] {
+        &self.list
+    }
+
+    /// Follow the view in a window of this size, recording it if it's the first to reach a new level
+    pub fn record(&mut self, fractal: &'static str, view: View, (width, height): (u32, u32)) {
+        if fractal != self.fractal {
+            self.list.clear();
+            self.fractal = fractal;
+        }
+        // the shallower waypoints are on the way if the view is inside them,
+        // the deeper ones can still be gone back into if they're inside the view
+        let inside = |outer: View, inner: View| {
+            let (x, y) = outer.viewport(width, height).complex_to_pixel(inner.offset_x, inner.offset_y);
+            (0.0..width as f64).contains(&x) && (0.0..height as f64).contains(&y)
+        };
+        self.list.retain(|waypoint| if waypoint.view.zoom >= view.zoom { inside(waypoint.view, view) } else { inside(view, waypoint.view) });
+
+        let level = level(view);
+        if level >= 0 && !self.list.iter().any(|waypoint| waypoint.level == level) {
+            let index = self.list.partition_point(|waypoint| waypoint.level < level);
+            self.list.insert(index, Waypoint { view, level });
+        }
+    }
+}
+
+/// How many times over the view has been magnified by WAYPOINT_FACTOR, negative when zoomed out past the start
+fn level(view: View) -> i32 {
+    ((INIT_ZOOM / view.zoom).ln() / WAYPOINT_FACTOR.ln() + 1e-9).floor() as i32
+}
+
+/// Magnification the waypoints at a level are labelled with
+pub fn magnification(level: i32) -> String {
+    match level {
+        0..=5 => format!("{}x", WAYPOINT_FACTOR.powi(level)),
+        _ => format!("1e{}x", level),
+    }
+}
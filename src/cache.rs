@@ -0,0 +1,64 @@
+use std::collections::VecDeque;
+
+use fractal_viz_core::edges::EdgeStyle;
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::transfer::{self, Levels};
+use fractal_viz_core::view::View;
+
+use crate::quality::Quality;
+use crate::renderer::Backend;
+
+/// Most memory the cached frames can take up before the least recently used are dropped
+const CACHE_BUDGET: usize = 256 * 1024 * 1024;
+
+/// Recently generated frames, so going back to a view that was just shown doesn't generate it again.
+/// Frames are keyed by everything that goes into drawing them and kept in order of use, the oldest are dropped first.
+pub struct FrameCache {
+    frames: VecDeque<(String, Vec<u8>)>,
+    size: usize,
+}
+
+impl FrameCache {
+    pub fn new() -> Self {
+        Self { frames: VecDeque::new(), size: 0 }
+    }
+
+    /// What a frame is stored under, frames with the same key are drawn the same.
+    /// The floats are written out in full so nearby views don't share a key
+    pub fn key(fractal: &dyn Fractal, view: View, size: (u32, u32), renderer: Backend, quality: Quality, edges: Option<EdgeStyle>, levels: Levels) -> String {
+        format!("{} {} {:?} {:?} {:?} {:?} {:?} {:?} {:?}", fractal.name(), fractal.to_json(), view, size, renderer, quality, edges, transfer::current(), levels)
+    }
+
+    /// The frame stored under `key`, which becomes the most recently used
+    pub fn get(&mut self, key: &str) -> Option<&[u8]> {
+        let index = self.frames.iter().position(|(frame_key, _)| frame_key == key)?;
+        let frame = self.frames.remove(index)?;
+        self.frames.push_back(frame);
+        self.frames.back().map(|(_, frame)| frame.as_slice())
+    }
+
+    pub fn insert(&mut self, key: String, frame: Vec<u8>) {
+        if frame.len() > CACHE_BUDGET {
+            return;
+        }
+        if let Some(index) = self.frames.iter().position(|(frame_key, _)| *frame_key == key) {
+            if let Some((_, old)) = self.frames.remove(index) {
+                self.size -= old.len();
+            }
+        }
+        self.size += frame.len();
+        self.frames.push_back((key, frame));
+        while self.size > CACHE_BUDGET {
+            let Some((_, oldest)) = self.frames.pop_front() else {
+                break;
+            };
+            self.size -= oldest.len();
+        }
+    }
+
+    /// Forget every frame, for when something not in the key changes how they're drawn
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.size = 0;
+    }
+}
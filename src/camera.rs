@@ -0,0 +1,161 @@
+use std::path::Path;
+
+/// The device `modprobe v4l2loopback video_nr=10` creates, as suggested in the README
+pub const DEFAULT_DEVICE: &str = "/dev/video10";
+
+/// Publishes the displayed frames as a webcam through a v4l2loopback device,
+/// so OBS and VJ software can take the fractal in directly instead of capturing the screen.
+/// The size is fixed when it starts, later frames of another size are scaled to fit.
+pub struct VirtualCamera {
+    device: device::Device,
+    width: u32,
+    height: u32,
+    /// The frame converted to the device's pixel format
+    buffer: Vec<u8>,
+}
+
+impl VirtualCamera {
+    pub fn start(path: &Path, width: u32, height: u32) -> Result<Self, String> {
+        // two pixels share their colour in YUYV so the width has to be even
+        let width = (width & !1).max(2);
+        let height = height.max(1);
+        let device = device::Device::open(path, width, height)?;
+        Ok(Self { device, width, height, buffer: vec![0; (width * height * 2) as usize] })
+    }
+
+    /// Send an rgba frame to the device
+    pub fn send(&mut self, frame: &[u8], frame_width: u32, frame_height: u32) -> Result<(), String> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (frame_width, frame_height) = (frame_width as usize, frame_height as usize);
+        if frame.len() != frame_width * frame_height * 4 || frame_width == 0 || frame_height == 0 {
+            return Ok(());
+        }
+        let pixel = |x: usize, y: usize| {
+            let index = ((y * frame_height / height) * frame_width + x * frame_width / width) * 4;
+            [frame[index] as f32, frame[index + 1] as f32, frame[index + 2] as f32]
+        };
+        for (y, row) in self.buffer.chunks_exact_mut(width * 2).enumerate() {
+            for (pair, out) in row.chunks_exact_mut(4).enumerate() {
+                let (left, right) = (pixel(pair * 2, y), pixel(pair * 2 + 1, y));
+                let [r, g, b] = [0, 1, 2].map(|channel| (left[channel] + right[channel]) / 2.0);
+                out.copy_from_slice(&[
+                    luma(left),
+                    (128.0 + (-37.945 * r - 74.494 * g + 112.439 * b) / 256.0).round() as u8,
+                    luma(right),
+                    (128.0 + (112.439 * r - 94.154 * g - 18.285 * b) / 256.0).round() as u8,
+                ]);
+            }
+        }
+        self.device.write(&self.buffer)
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+/// BT.601 luma in the limited 16 to 235 range webcams use
+fn luma([r, g, b]: [f32; 3]) -> u8 {
+    (16.0 + (65.738 * r + 129.057 * g + 25.064 * b) / 256.0).round() as u8
+}
+
+#[cfg(all(feature = "virtual-camera", target_os = "linux"))]
+mod device {
+    use std::fs::File;
+    use std::io::Write;
+    use std::os::fd::AsRawFd;
+    use std::path::Path;
+
+    const V4L2_BUF_TYPE_VIDEO_OUTPUT: u32 = 2;
+    const V4L2_FIELD_NONE: u32 = 1;
+    const V4L2_COLORSPACE_SMPTE170M: u32 = 1;
+    const V4L2_PIX_FMT_YUYV: u32 = u32::from_le_bytes(*b"YUYV");
+    /// _IOWR('V', 5, struct v4l2_format)
+    const VIDIOC_S_FMT: u64 = (3 << 30) | ((std::mem::size_of::<Format>() as u64) << 16) | ((b'V' as u64) << 8) | 5;
+
+    /// struct v4l2_pix_format
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct PixFormat {
+        width: u32,
+        height: u32,
+        pixel_format: u32,
+        field: u32,
+        bytes_per_line: u32,
+        size_image: u32,
+        colour_space: u32,
+        private: u32,
+        flags: u32,
+        ycbcr_encoding: u32,
+        quantization: u32,
+        transfer_function: u32,
+    }
+
+    /// struct v4l2_format, the union is as big and aligned as the kernel's so the ioctl number matches
+    #[repr(C)]
+    struct Format {
+        kind: u32,
+        format: FormatUnion,
+    }
+
+    #[repr(C)]
+    union FormatUnion {
+        pix: PixFormat,
+        raw: [u8; 200],
+        _align: *const libc::c_void,
+    }
+
+    /// An open v4l2loopback device set up for YUYV frames
+    pub struct Device {
+        file: File,
+    }
+
+    impl Device {
+        pub fn open(path: &Path, width: u32, height: u32) -> Result<Self, String> {
+            let file = File::options().write(true).open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let mut format = Format { kind: V4L2_BUF_TYPE_VIDEO_OUTPUT, format: FormatUnion { raw: [0; 200] } };
+            format.format.pix = PixFormat {
+                width,
+                height,
+                pixel_format: V4L2_PIX_FMT_YUYV,
+                field: V4L2_FIELD_NONE,
+                bytes_per_line: width * 2,
+                size_image: width * height * 2,
+                colour_space: V4L2_COLORSPACE_SMPTE170M,
+                private: 0,
+                flags: 0,
+                ycbcr_encoding: 0,
+                quantization: 0,
+                transfer_function: 0,
+            };
+            // SAFETY: the format is laid out as the kernel expects and outlives the call
+            let result = unsafe { libc::ioctl(file.as_raw_fd(), VIDIOC_S_FMT as _, &mut format as *mut Format) };
+            if result < 0 {
+                return Err(format!("{} isn't a v4l2loopback device: {}", path.display(), std::io::Error::last_os_error()));
+            }
+            Ok(Self { file })
+        }
+
+        pub fn write(&mut self, frame: &[u8]) -> Result<(), String> {
+            self.file.write_all(frame).map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(not(all(feature = "virtual-camera", target_os = "linux")))]
+mod device {
+    use std::path::Path;
+
+    /// Stand in for builds without the virtual camera feature
+    pub struct Device;
+
+    impl Device {
+        pub fn open(_path: &Path, _width: u32, _height: u32) -> Result<Self, String> {
+            Err("Built without virtual camera support, rebuild on Linux with --features virtual-camera".into())
+        }
+
+        pub fn write(&mut self, _frame: &[u8]) -> Result<(), String> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use rayon::prelude::*;
+
+pub const SIMULATIONS: [Simulation; 3] = [Simulation::Protanopia, Simulation::Deuteranopia, Simulation::Tritanopia];
+
+/// A colour vision deficiency to preview the frame as
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Simulation {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl Simulation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Simulation::Protanopia => "Protanopia",
+            Simulation::Deuteranopia => "Deuteranopia",
+            Simulation::Tritanopia => "Tritanopia",
+        }
+    }
+
+    /// Matrices from Machado et al. 2009 at full severity, applied to linear rgb
+    fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            Simulation::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Simulation::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Simulation::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+}
+
+/// Number of entries in the linear to srgb lookup table
+const LINEAR_STEPS: usize = 4096;
+
+/// Transform every pixel of an rgba frame to how it would look with the colour vision deficiency
+pub fn simulate(pixels: &mut [u8], simulation: Simulation) {
+    // lookup tables so the gamma curve isn't evaluated for every pixel
+    let to_linear: Vec<f32> = (0..256).map(|v| srgb_to_linear(v as f32 / 255.0)).collect();
+    let to_srgb: Vec<u8> = (0..LINEAR_STEPS)
+        .map(|i| (linear_to_srgb(i as f32 / (LINEAR_STEPS - 1) as f32) * 255.0).round() as u8)
+        .collect();
+    let m = simulation.matrix();
+
+    pixels.par_chunks_mut(4).for_each(|pixel| {
+        let [r, g, b] = [to_linear[pixel[0] as usize], to_linear[pixel[1] as usize], to_linear[pixel[2] as usize]];
+        for (channel, row) in pixel.iter_mut().zip(m.iter()) {
+            let linear = (row[0] * r + row[1] * g + row[2] * b).clamp(0.0, 1.0);
+            *channel = to_srgb[(linear * (LINEAR_STEPS - 1) as f32) as usize];
+        }
+    });
+}
+
+fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 { v * 12.92 } else { 1.055 * v.powf(1.0 / 2.4) - 0.055 }
+}
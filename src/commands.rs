@@ -0,0 +1,96 @@
+use fractal_viz_core::fractals::{self, colour_gradient_names};
+
+use crate::bookmarks::Bookmarks;
+use crate::history::Step;
+use crate::i18n::tr;
+use crate::keybindings::Action;
+
+pub const OVERLAYS: [Overlay; 9] = [
+    Overlay::Inspector, Overlay::Compare, Overlay::Edges, Overlay::IterationBands,
+    Overlay::JuliaPreview, Overlay::EscapeRadius, Overlay::ExternalRays, Overlay::PalettePreviews, Overlay::RenderStats,
+];
+
+/// Something drawn over or instead of the fractal that can be turned on and off
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Overlay {
+    Inspector,
+    Compare,
+    Edges,
+    IterationBands,
+    JuliaPreview,
+    EscapeRadius,
+    ExternalRays,
+    PalettePreviews,
+    RenderStats,
+}
+
+impl Overlay {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Overlay::Inspector => "Inspector",
+            Overlay::Compare => "A/B Compare",
+            Overlay::Edges => "Boundary only",
+            Overlay::IterationBands => "Iteration bands",
+            Overlay::JuliaPreview => "Julia preview",
+            Overlay::EscapeRadius => "Show escape radius",
+            Overlay::ExternalRays => "External rays and equipotentials",
+            Overlay::PalettePreviews => "Palette previews",
+            Overlay::RenderStats => "Render stats",
+        }
+    }
+}
+
+/// Everything the command palette can do, the keyboard actions along with what's only in the gui
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    /// Done as if one of its keys had been pressed
+    Action(Action),
+    History(Step),
+    SwitchFractal(&'static str),
+    SetPalette(String),
+    GoToBookmark { index: usize, name: String },
+    Toggle(Overlay),
+    SaveSession,
+    SaveMask,
+    ExportVideo,
+}
+
+impl Command {
+    /// The name it's listed and searched by, in the current language
+    pub fn label(&self) -> String {
+        match self {
+            Command::Action(action) => tr(action.description()).to_string(),
+            Command::History(Step::Undo) => tr("Undo").to_string(),
+            Command::History(Step::Redo) => tr("Redo").to_string(),
+            Command::SwitchFractal(name) => format!("{} {}", tr("Fractal:"), name),
+            Command::SetPalette(name) => format!("{} {}", tr("Colour:"), name),
+            Command::GoToBookmark { name, .. } => format!("{}: {}", tr("Go to bookmark"), name),
+            Command::Toggle(overlay) => format!("{}: {}", tr("Toggle"), tr(overlay.name())),
+            Command::SaveSession => tr("Save session").to_string(),
+            Command::SaveMask => tr("Save mask").to_string(),
+            Command::ExportVideo => tr("Export video").to_string(),
+        }
+    }
+}
+
+/// Every command, in the order they're listed before anything is typed
+pub fn all(bookmarks: &Bookmarks) -> Vec<Command> {
+    let actions = [
+        Action::Screenshot, Action::FinalRender, Action::RenderRegion, Action::Bookmarks, Action::Timeline,
+        Action::HideUi, Action::Fullscreen, Action::Help, Action::RotateLeft, Action::RotateRight,
+    ];
+    [Command::History(Step::Undo), Command::History(Step::Redo)].into_iter()
+        .chain(actions.into_iter().map(Command::Action))
+        .chain([Command::SaveSession, Command::SaveMask, Command::ExportVideo])
+        .chain(OVERLAYS.into_iter().map(Command::Toggle))
+        .chain(fractals::names().into_iter().map(Command::SwitchFractal))
+        .chain(colour_gradient_names().into_iter().map(Command::SetPalette))
+        .chain(bookmarks.list.iter().enumerate().map(|(index, bookmark)| Command::GoToBookmark { index, name: bookmark.name.clone() }))
+        .collect()
+}
+
+/// True if every word of the query is in the label, ignoring case
+pub fn matches(label: &str, query: &str) -> bool {
+    let label = label.to_lowercase();
+    query.to_lowercase().split_whitespace().all(|word| label.contains(word))
+}
@@ -0,0 +1,76 @@
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::transfer::Levels;
+use fractal_viz_core::viewport::Viewport;
+
+/// How close (in pixels) the mouse has to be to the divider to start dragging it
+const DIVIDER_GRAB_DISTANCE: f32 = 8.0;
+const DIVIDER_WIDTH: usize = 2;
+
+/// State for the A/B compare mode.
+/// A second parameter set (B) is rendered over the same view as the current fractal (A)
+/// and the two images are split by a draggable vertical divider.
+pub struct Compare {
+    pub enabled: bool,
+    /// The B parameter set
+    pub fractal: Box<dyn Fractal>,
+    /// When true the settings window edits B instead of A
+    pub editing_b: bool,
+    /// Position of the divider as a fraction of the window width
+    pub divider: f32,
+    pub dragging: bool,
+    // Keep both renders so moving the divider doesn't require regenerating the fractals
+    frame_a: Vec<u8>,
+    frame_b: Vec<u8>,
+}
+
+impl Compare {
+    pub fn new(fractal: Box<dyn Fractal>) -> Self {
+        Self {
+            enabled: false,
+            fractal,
+            editing_b: false,
+            divider: 0.5,
+            dragging: false,
+            frame_a: Vec::new(),
+            frame_b: Vec::new(),
+        }
+    }
+
+    /// Render both parameter sets of the same view into their own buffers.
+    /// `levels` come from the counts of A, B is a different fractal so its palette is spread over the whole range
+    pub fn draw(&mut self, fractal_a: &dyn Fractal, viewport: Viewport, levels: Levels) {
+        let len = (viewport.width * viewport.height * 4) as usize;
+        self.frame_a.resize(len, 0);
+        self.frame_b.resize(len, 0);
+        fractal_a.draw_levels(&mut self.frame_a, viewport, levels);
+        self.fractal.draw(&mut self.frame_b, viewport);
+    }
+
+    /// Returns true if the given x position is close enough to the divider to grab it
+    pub fn over_divider(&self, x: f32, screen_width: u32) -> bool {
+        (x - self.divider * screen_width as f32).abs() < DIVIDER_GRAB_DISTANCE
+    }
+
+    /// Move the divider to the given x position
+    pub fn drag_to(&mut self, x: f32, screen_width: u32) {
+        self.divider = (x / screen_width as f32).clamp(0.0, 1.0);
+    }
+
+    /// Combine the A and B renders into `pixels`, A is shown left of the divider and B to the right
+    pub fn composite(&self, pixels: &mut [u8], screen_width: u32) {
+        // the buffers are stale until the next draw after a resize
+        if self.frame_a.len() != pixels.len() || self.frame_b.len() != pixels.len() {
+            return;
+        }
+        let row_len = screen_width as usize * 4;
+        let split = ((self.divider * screen_width as f32) as usize).min(screen_width as usize) * 4;
+        for ((row, a), b) in pixels.chunks_mut(row_len).zip(self.frame_a.chunks(row_len)).zip(self.frame_b.chunks(row_len)) {
+            row[..split].copy_from_slice(&a[..split]);
+            row[split..].copy_from_slice(&b[split..]);
+            // draw the divider line
+            let line_start = split.saturating_sub(DIVIDER_WIDTH * 2);
+            let line_end = (split + DIVIDER_WIDTH * 2).min(row_len);
+            row[line_start..line_end].fill(255);
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use egui::{Color32, Pos2, Rect, Stroke, Vec2, Visuals};
+use fractal_viz_core::transfer::TransferCurve;
+use serde::{Deserialize, Serialize};
+
+use crate::graphics::GraphicsSettings;
+use crate::i18n::Language;
+use crate::keybindings::KeyBindings;
+use crate::profiles::Profiles;
+use crate::quality::QualityProfiles;
+use crate::renderer::Backend;
+use crate::watermark::Watermark;
+
+pub const THEMES: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::HighContrast];
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Theme {
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl Theme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+            Theme::HighContrast => "High contrast",
+        }
+    }
+
+    fn visuals(&self) -> Visuals {
+        match self {
+            Theme::Dark => {
+                let mut visuals = Visuals::dark();
+                visuals.override_text_color = Some(Color32::WHITE);
+                visuals.window_fill = Color32::BLACK;
+                visuals
+            },
+            Theme::Light => Visuals::light(),
+            Theme::HighContrast => {
+                let mut visuals = Visuals::dark();
+                visuals.override_text_color = Some(Color32::WHITE);
+                visuals.window_fill = Color32::BLACK;
+                visuals.panel_fill = Color32::BLACK;
+                visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.widgets.noninteractive.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+                visuals.widgets.inactive.bg_fill = Color32::BLACK;
+                visuals.widgets.inactive.bg_stroke = Stroke::new(1.5, Color32::WHITE);
+                visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+                visuals.widgets.active.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+                visuals.selection.bg_fill = Color32::from_rgb(0, 90, 200);
+                visuals.selection.stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.hyperlink_color = Color32::YELLOW;
+                visuals
+            },
+        }
+    }
+}
+
+pub const DOCKS: [Dock; 5] = [Dock::TopLeft, Dock::TopRight, Dock::BottomLeft, Dock::BottomRight, Dock::Free];
+
+/// Gap between a docked window and the edge of the screen
+const DOCK_MARGIN: f32 = 10.0;
+/// How close a dragged window has to be to a corner to dock to it
+const DOCK_SNAP_DISTANCE: f32 = 30.0;
+
+/// Where the settings window is placed
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Dock {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    /// Wherever it was dragged to
+    Free,
+}
+
+impl Dock {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dock::TopLeft => "Top left",
+            Dock::TopRight => "Top right",
+            Dock::BottomLeft => "Bottom left",
+            Dock::BottomRight => "Bottom right",
+            Dock::Free => "Free",
+        }
+    }
+
+    /// Top left position of a window of the given size docked in this corner, None if it isn't docked
+    pub fn position(&self, screen: Rect, size: Vec2) -> Option<Pos2> {
+        let left = screen.left() + DOCK_MARGIN;
+        let right = screen.right() - DOCK_MARGIN - size.x;
+        let top = screen.top() + DOCK_MARGIN;
+        let bottom = screen.bottom() - DOCK_MARGIN - size.y;
+        match self {
+            Dock::TopLeft => Some(Pos2::new(left, top)),
+            Dock::TopRight => Some(Pos2::new(right, top)),
+            Dock::BottomLeft => Some(Pos2::new(left, bottom)),
+            Dock::BottomRight => Some(Pos2::new(right, bottom)),
+            Dock::Free => None,
+        }
+    }
+
+    /// The corner a window dropped at `rect` snaps to, or Free if it isn't near one
+    pub fn snap(rect: Rect, screen: Rect) -> Self {
+        DOCKS.into_iter()
+            .find(|dock| dock.position(screen, rect.size()).is_some_and(|pos| pos.distance(rect.min) < DOCK_SNAP_DISTANCE))
+            .unwrap_or(Dock::Free)
+    }
+}
+
+/// How the zoom box is drawn while a selection is dragged out
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelectionStyle {
+    pub colour: [u8; 3],
+    /// Darken the frame outside the selection
+    pub dim_outside: bool,
+    /// Show the width and height of the selection in the complex plane
+    pub show_size: bool,
+    /// Show the width and height of the selection in pixels of the window
+    pub show_pixels: bool,
+}
+
+impl Default for SelectionStyle {
+    fn default() -> Self {
+        Self { colour: [255, 255, 255], dim_outside: true, show_size: true, show_pixels: false }
+    }
+}
+
+/// The values the zoom box settings can take, loaded configs are brought back into them
+pub const BOX_THRESHOLDS: RangeInclusive<f32> = 1.0..=100.0;
+pub const BOX_MIN_AREAS: RangeInclusive<f32> = 1.0..=10_000.0;
+pub const BOX_SENSITIVITIES: RangeInclusive<f64> = 1.0..=100.0;
+pub const BOX_LEAST_ZOOMS: RangeInclusive<f64> = 1.0..=4.0;
+pub const BOX_MOST_ZOOMS: RangeInclusive<f64> = 10.0..=1_000_000.0;
+
+/// How far the pan keys and the mouse wheel move the view
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Navigation {
+    /// Distance a press of a pan key moves, as a share of the window height
+    pub pan_step: f64,
+    /// Keep panning for as long as a pan key is held instead of a step each press
+    pub continuous_pan: bool,
+    /// Window heights a second that held pan keys move
+    pub pan_speed: f64,
+    /// How many times each notch of the mouse wheel zooms in or out
+    pub zoom_factor: f64,
+    /// Window pixels the mouse has to move both across and down before a drag becomes a zoom box
+    pub box_threshold: f32,
+    /// Smallest area in window pixels a zoom box can have, anything smaller is taken as a click that centres on the point
+    pub box_min_area: f32,
+    /// How far a zoom box zooms for its size, the new view is this many times the share of the window the box covers
+    pub box_sensitivity: f64,
+    /// Least and most times a zoom box zooms in, however big or small it is
+    pub box_least_zoom: f64,
+    pub box_most_zoom: f64,
+}
+
+impl Default for Navigation {
+    fn default() -> Self {
+        Self {
+            pan_step: 0.15, continuous_pan: false, pan_speed: 1.0, zoom_factor: 1.5,
+            box_threshold: 10.0, box_min_area: 100.0, box_sensitivity: 10.0, box_least_zoom: 1.25, box_most_zoom: 100_000.0,
+        }
+    }
+}
+
+impl Navigation {
+    /// Whether a drag between two corners is big enough to be a zoom box rather than a click
+    pub fn is_box(&self, (x1, y1): (f32, f32), (x2, y2): (f32, f32)) -> bool {
+        let (width, height) = ((x1 - x2).abs(), (y1 - y2).abs());
+        width > self.box_threshold && height > self.box_threshold && width * height >= self.box_min_area
+    }
+
+    /// How many times the size of a pixel changes zooming into a box covering `share` of the window's area
+    pub fn box_scale(&self, share: f64) -> f64 {
+        (share * self.box_sensitivity).clamp(1.0 / self.box_most_zoom, 1.0 / self.box_least_zoom)
+    }
+
+    /// Bring the zoom box settings of a hand edited config into the ranges the settings window allows,
+    /// `box_scale` panics if the least zoom is above the most or either isn't a number
+    fn sanitise(&mut self) {
+        let default = Self::default();
+        let within = |value: f64, range: RangeInclusive<f64>, default: f64| if value.is_nan() { default } else { value.clamp(*range.start(), *range.end()) };
+        let within_f32 = |value: f32, range: RangeInclusive<f32>, default: f32| if value.is_nan() { default } else { value.clamp(*range.start(), *range.end()) };
+        self.box_threshold = within_f32(self.box_threshold, BOX_THRESHOLDS, default.box_threshold);
+        self.box_min_area = within_f32(self.box_min_area, BOX_MIN_AREAS, default.box_min_area);
+        self.box_sensitivity = within(self.box_sensitivity, BOX_SENSITIVITIES, default.box_sensitivity);
+        self.box_least_zoom = within(self.box_least_zoom, BOX_LEAST_ZOOMS, default.box_least_zoom);
+        self.box_most_zoom = within(self.box_most_zoom, BOX_MOST_ZOOMS, default.box_most_zoom);
+    }
+}
+
+/// Preferences and startup defaults that are kept between runs, stored as toml in the config directory
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Fractal shown at startup and in new tabs
+    pub default_fractal: String,
+    pub default_palette: String,
+    /// Maps iteration values to positions on the palette, applied to every palette
+    pub colour_curve: TransferCurve,
+    /// Size of the window in pixels at startup, maximised if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_size: Option<(u32, u32)>,
+    /// Threads used for rendering, 0 uses one per core
+    pub threads: usize,
+    pub renderer: Backend,
+    /// Pixels generated along each side for every pixel of the window, from 0.5 to 2
+    pub render_scale: f32,
+    /// How much work goes into each pixel while exploring and in final renders
+    pub quality: QualityProfiles,
+    /// Multiplier on top of the window's scale factor
+    pub ui_scale: f32,
+    pub font_size: f32,
+    pub language: Language,
+    pub theme: Theme,
+    /// Replaces the theme's selection and link colour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent: Option<[u8; 3]>,
+    pub settings_dock: Dock,
+    /// Top left of the settings window when it isn't docked
+    pub settings_position: (f32, f32),
+    pub selection: SelectionStyle,
+    pub navigation: Navigation,
+    /// The graphics card and present mode the window is drawn with
+    pub graphics: GraphicsSettings,
+    /// Name of the monitor to go fullscreen on, the one the window is on if not set or it isn't connected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fullscreen_monitor: Option<String>,
+    /// Folder whose parameter files are loaded as soon as they're added or changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watch_folder: Option<PathBuf>,
+    /// Caption, logo and border drawn onto saved images
+    pub watermark: Watermark,
+    /// Parameters and view each type of fractal starts with in place of its built in defaults
+    pub profiles: Profiles,
+    pub keybindings: KeyBindings,
+    /// Set when the file couldn't be parsed or moved out of the way, so saving doesn't overwrite the user's edits
+    #[serde(skip)]
+    read_only: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_fractal: "Mandelbrot".into(),
+            default_palette: "Sinebow".into(),
+            colour_curve: TransferCurve::default(),
+            window_size: None,
+            threads: 0,
+            renderer: Backend::Cpu,
+            render_scale: 1.0,
+            quality: QualityProfiles::default(),
+            ui_scale: 1.2,
+            font_size: 15.0,
+            language: Language::English,
+            theme: Theme::Dark,
+            accent: None,
+            settings_dock: Dock::TopLeft,
+            settings_position: (10.0, 10.0),
+            selection: SelectionStyle::default(),
+            navigation: Navigation::default(),
+            graphics: GraphicsSettings::default(),
+            fullscreen_monitor: None,
+            watch_folder: None,
+            watermark: Watermark::default(),
+            profiles: Profiles::default(),
+            keybindings: KeyBindings::default(),
+            read_only: false,
+        }
+    }
+}
+
+impl Config {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("fractal-viz").join("config.toml"))
+    }
+
+    /// Load the saved config, using the defaults if it can't be read.
+    /// Settings saved as json by older versions are read if there is no toml config yet.
+    /// A config that doesn't parse is moved to `config.toml.bak` before the defaults are used, and the error is returned to be shown.
+    pub fn load() -> (Self, Option<String>) {
+        let path = Self::path();
+        let toml = path.as_ref().and_then(|path| Some((path, fs::read_to_string(path).ok()?)));
+        if let Some((path, toml)) = toml {
+            return match toml::from_str::<Self>(&toml) {
+                Ok(config) => (config.sanitised(), None),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), "Couldn't parse the config: {}", e);
+                    let backup = path.with_extension("toml.bak");
+                    match fs::rename(path, &backup) {
+                        Ok(()) => (Self::default(), Some(format!("{}, it was moved to {}", e, backup.display()))),
+                        Err(rename) => {
+                            let error = format!("{}, settings won't be saved as it couldn't be moved to {}: {}", e, backup.display(), rename);
+                            (Self { read_only: true, ..Self::default() }, Some(error))
+                        }
+                    }
+                }
+            };
+        }
+        let config = path
+            .and_then(|path| fs::read_to_string(path.with_extension("json")).ok())
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok())
+            .unwrap_or_default();
+        (config.sanitised(), None)
+    }
+
+    fn sanitised(mut self) -> Self {
+        self.navigation.sanitise();
+        self
+    }
+
+    /// The egui visuals for the theme with the accent colour applied
+    pub fn visuals(&self) -> Visuals {
+        let mut visuals = self.theme.visuals();
+        if let Some([r, g, b]) = self.accent {
+            let accent = Color32::from_rgb(r, g, b);
+            visuals.selection.bg_fill = accent;
+            visuals.hyperlink_color = accent;
+            visuals.widgets.hovered.bg_stroke.color = accent;
+        }
+        visuals
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = Self::path() else {
+            return Err("No config directory to save settings in".into());
+        };
+        if self.read_only {
+            return Err(format!("{} couldn't be read, it is left as it is", path.display()));
+        }
+        path.parent().map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| toml::to_string(self).map_err(std::io::Error::other))
+            .and_then(|toml| fs::write(path, toml))
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::mpsc;
+
+use fractal_viz_core::fractals::{Fractal, MAX_ITERATIONS};
+use fractal_viz_core::transfer::Levels;
+use fractal_viz_core::viewport::Viewport;
+use rayon::prelude::*;
+
+
+/// Share of the pixels along the edge of the set the suggested max iterations should be enough for
+const SUGGESTION_SHARE: f64 = 0.9;
+/// Pixels on the inside of the edge are iterated again up to this many times the max iterations, to see if they were cut off
+const SUGGESTION_HEADROOM: u32 = 8;
+
+/// Summary of the iteration counts inside part of the frame
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RegionStats {
+    pub mean: f64,
+    pub max: u32,
+    /// Share of the pixels that reached max iterations, which are taken to be inside the set
+    pub interior: f64,
+}
+
+/// Iteration count of every pixel of the frame, for the tools that look at the counts rather than the colours.
+/// They're worked out on a background thread whenever the fractal is generated again.
+#[derive(Default)]
+pub struct IterationCounts {
+    counts: Option<Vec<u32>>,
+    /// The max iterations the counts were made with
+    max_iterations: u32,
+    /// Width of the frame the counts are for
+    width: u32,
+    /// The range of counts the frame's escaping pixels cover, found once the counts arrive
+    levels: Option<Levels>,
+    /// Dropped to stop counting
+    receiver: Option<mpsc::Receiver<Vec<u32>>>,
+    /// Max iterations enough for most of the edge of the set, None if there's no edge in the frame
+    suggestion: Option<Option<u32>>,
+    suggestion_receiver: Option<mpsc::Receiver<Option<u32>>>,
+}
+
+impl IterationCounts {
+    /// Forget the counts, for when the fractal or view has changed
+    pub fn clear(&mut self) {
+        self.counts = None;
+        self.receiver = None;
+        self.suggestion = None;
+        self.suggestion_receiver = None;
+    }
+
+    /// True while the counts are being worked out
+    pub fn counting(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Start counting the iterations of the frame if they aren't known, and pick them up once they're done
+    pub fn update(&mut self, fractal: &dyn Fractal, viewport: Viewport) {
+        if self.counts.is_none() && self.receiver.is_none() {
+            let fractal = fractal.clone_box();
+            let (sender, receiver) = mpsc::channel();
+            self.max_iterations = fractal.max_iterations();
+            self.width = viewport.width as u32;
+            crate::background(move || {
+                let counts = (0..(viewport.width * viewport.height) as usize).into_par_iter()
+                    .map(|i| fractal.sample_pixel(&viewport, i).iterations)
+                    .collect();
+                let _ = sender.send(counts);
+            });
+            self.receiver = Some(receiver);
+        }
+        if let Some(counts) = self.receiver.as_ref().and_then(|receiver| receiver.try_recv().ok()) {
+            self.levels = Some(levels(&counts, self.max_iterations));
+            self.counts = Some(counts);
+            self.receiver = None;
+        }
+    }
+
+    /// True while the counts or the suggested max iterations are being worked out
+    pub fn suggesting(&self) -> bool {
+        self.counting() || self.suggestion_receiver.is_some()
+    }
+
+    /// Work out the max iterations to suggest once the counts are known, and pick it up once it's done
+    pub fn update_suggestion(&mut self, fractal: &dyn Fractal, viewport: Viewport) {
+        self.update(fractal, viewport);
+        if let (Some(counts), None, None) = (&self.counts, self.suggestion, &self.suggestion_receiver) {
+            let (fractal, counts, max_iterations) = (fractal.clone_box(), counts.clone(), self.max_iterations);
+            let (sender, receiver) = mpsc::channel();
+            crate::background(move || {
+                let _ = sender.send(suggested_iterations(fractal.as_ref(), viewport, &counts, max_iterations));
+            });
+            self.suggestion_receiver = Some(receiver);
+        }
+        if let Some(suggestion) = self.suggestion_receiver.as_ref().and_then(|receiver| receiver.try_recv().ok()) {
+            self.suggestion = Some(suggestion);
+            self.suggestion_receiver = None;
+        }
+    }
+
+    /// The suggested max iterations, the outer None until it's known and the inner None when the frame has no edge to go by
+    pub fn suggestion(&self) -> Option<Option<u32>> {
+        self.suggestion
+    }
+
+    /// The counts if they're known for a frame of this many pixels
+    pub fn get(&self, pixels: usize) -> Option<&[u32]> {
+        self.counts.as_deref().filter(|counts| counts.len() == pixels)
+    }
+
+    /// The counts shrunk down to `width` pixels across by taking every so many, with the size they come out at
+    pub fn thumbnail(&self, width: u32) -> Option<(Vec<u32>, (u32, u32))> {
+        let counts = self.counts.as_ref().filter(|_| self.width > 0)?;
+        let frame_height = (counts.len() / self.width as usize) as u32;
+        let height = (width * frame_height / self.width).max(1);
+        let thumbnail = (0..width * height).map(|i| {
+            let (x, y) = (i % width * self.width / width, i / width * frame_height / height);
+            counts[(y * self.width + x) as usize]
+        }).collect();
+        Some((thumbnail, (width, height)))
+    }
+
+    /// Statistics of the rectangle between two corners, given as shares of the frame's width and height.
+    /// None until the counts are known
+    pub fn region(&self, from: (f32, f32), to: (f32, f32)) -> Option<RegionStats> {
+        let counts = self.counts.as_ref().filter(|_| self.width > 0)?;
+        let (width, height) = (self.width as usize, counts.len() / self.width as usize);
+        let to_pixels = |share: f32, size: usize| ((share.clamp(0.0, 1.0) * size as f32) as usize).min(size.saturating_sub(1));
+        let (x0, x1) = (to_pixels(from.0.min(to.0), width), to_pixels(from.0.max(to.0), width));
+        let (y0, y1) = (to_pixels(from.1.min(to.1), height), to_pixels(from.1.max(to.1), height));
+        let region: Vec<u32> = (y0..=y1).flat_map(|y| counts[y * width + x0..=y * width + x1].iter().copied()).collect();
+        let pixels = region.len() as f64;
+        Some(RegionStats {
+            mean: region.iter().map(|&count| count as f64).sum::<f64>() / pixels,
+            max: region.iter().copied().max().unwrap_or(0),
+            interior: region.iter().filter(|&&count| count >= self.max_iterations).count() as f64 / pixels,
+        })
+    }
+
+    /// The levels that spread the palette from the lowest count in the frame to the highest that escaped, None until the counts are known
+    pub fn levels(&self) -> Option<Levels> {
+        self.levels.filter(|_| self.counts.is_some())
+    }
+
+    pub fn max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+}
+
+/// The max iterations that 90% of the pixels along the edge of the set escape within.
+/// The edge is the pixels with a neighbour on the other side of max iterations, and the ones on the inside
+/// are iterated again with a higher limit to see how many more they needed, those that still don't escape are in the set.
+/// None if there's no edge in the frame or the fractal has no max iterations to change
+fn suggested_iterations(fractal: &dyn Fractal, viewport: Viewport, counts: &[u32], max_iterations: u32) -> Option<u32> {
+    fractal.parameter("Max Iterations")?;
+    let limit = max_iterations.saturating_mul(SUGGESTION_HEADROOM).min(MAX_ITERATIONS);
+    let mut deeper = fractal.clone_box();
+    deeper.set_parameter("Max Iterations", limit as f64);
+
+    let width = viewport.width as usize;
+    let inside = |i: usize| counts[i] >= max_iterations;
+    let mut needed: Vec<u32> = (0..counts.len()).into_par_iter().filter_map(|i| {
+        let neighbours = [
+            (i % width > 0).then(|| i - 1),
+            (i % width + 1 < width).then_some(i + 1),
+            i.checked_sub(width),
+            (i + width < counts.len()).then_some(i + width),
+        ];
+        if !neighbours.into_iter().flatten().any(|j| inside(j) != inside(i)) {
+            return None;
+        }
+        if !inside(i) {
+            return Some(counts[i]);
+        }
+        let count = deeper.sample_pixel(&viewport, i).iterations;
+        (count < limit).then_some(count)
+    }).collect();
+    if needed.is_empty() {
+        return None;
+    }
+    let index = ((needed.len() - 1) as f64 * SUGGESTION_SHARE) as usize;
+    Some(*needed.select_nth_unstable(index).1)
+}
+
+/// Levels from the lowest count to the highest below max iterations, the whole range if nothing escaped.
+/// Pixels inside the set are left out as they'd always stretch the range to the top
+fn levels(counts: &[u32], max_iterations: u32) -> Levels {
+    let max_iterations = max_iterations.max(1);
+    let escaped = counts.par_iter().copied().filter(|&count| count < max_iterations);
+    let (low, high) = escaped.fold(|| (u32::MAX, 0), |(low, high), count| (low.min(count), high.max(count)))
+        .reduce(|| (u32::MAX, 0), |a, b| (a.0.min(b.0), a.1.max(b.1)));
+    if low >= high {
+        return Levels::FULL;
+    }
+    Levels { low: low as f64 / max_iterations as f64, high: high as f64 / max_iterations as f64 }
+}
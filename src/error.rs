@@ -0,0 +1,122 @@
+use std::sync::Mutex;
+
+use egui::{Align2, Color32, Context, FontId, Id, LayerId, Order, RichText, Sense};
+use pixels::wgpu;
+
+use crate::i18n::tr;
+
+const OUT_OF_MEMORY_HINT: &str = "Closing other tabs or making the window smaller may help.";
+
+/// Errors from the gpu that wgpu would otherwise panic on, waiting to be shown
+static GPU_ERROR: Mutex<Option<ErrorDialog>> = Mutex::new(None);
+
+/// What the user chose in the error dialog
+pub enum ErrorResponse {
+    Continue,
+    Quit,
+}
+
+/// An error the user needs to know about, shown in a modal over everything else until it is dismissed
+pub struct ErrorDialog {
+    title: &'static str,
+    /// The error itself, usually from wgpu so it isn't translated
+    message: String,
+    /// What the user can do about it
+    hint: Option<&'static str>,
+}
+
+impl ErrorDialog {
+    pub fn new(title: &'static str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        tracing::error!("{}: {}", title, message);
+        Self { title, message, hint: None }
+    }
+
+    pub fn with_hint(self, hint: &'static str) -> Self {
+        Self { hint: Some(hint), ..self }
+    }
+
+    /// Draw the dialog and dim everything behind it so it can't be clicked, returns the button that was pressed
+    pub fn show(&self, ctx: &Context, font: &FontId) -> Option<ErrorResponse> {
+        let screen = ctx.screen_rect();
+        egui::Area::new("Error backdrop")
+        .order(Order::Foreground)
+        .fixed_pos(screen.min)
+        .show(ctx, |ui| {
+            ui.allocate_rect(screen, Sense::click_and_drag());
+            ui.painter().rect_filled(screen, 0.0, Color32::from_black_alpha(160));
+        });
+
+        let mut response = None;
+        let id = Id::new("Error dialog");
+        egui::Area::new(id)
+        .order(Order::Foreground)
+        .anchor(Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(ctx, |ui| {
+            egui::Frame::window(ui.style()).show(ui, |ui| {
+                ui.set_max_width(400.0);
+                ui.label(RichText::new(tr(self.title)).font(font.clone()).color(ui.visuals().error_fg_color).strong());
+                ui.separator();
+                ui.label(RichText::new(&self.message).font(font.clone()));
+                if let Some(hint) = self.hint {
+                    ui.label(RichText::new(tr(hint)).font(font.clone()));
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button(RichText::new(tr("Continue")).font(font.clone())).clicked() {
+                        response = Some(ErrorResponse::Continue);
+                    }
+                    if ui.button(RichText::new(tr("Quit")).font(font.clone())).clicked() {
+                        response = Some(ErrorResponse::Quit);
+                    }
+                });
+            });
+        });
+        // keep the dialog above the backdrop
+        ctx.move_to_top(LayerId::new(Order::Foreground, id));
+        response
+    }
+}
+
+/// Report gpu errors in a dialog instead of panicking, which is what wgpu does by default
+pub fn catch_gpu_errors(device: &wgpu::Device) {
+    device.on_uncaptured_error(Box::new(|error| {
+        let dialog = match error {
+            wgpu::Error::OutOfMemory { source } => ErrorDialog::new("Out of GPU memory", source.to_string()).with_hint(OUT_OF_MEMORY_HINT),
+            e => ErrorDialog::new("Graphics error", e.to_string()),
+        };
+        GPU_ERROR.lock().unwrap().get_or_insert(dialog);
+    }));
+}
+
+/// Take the first gpu error since the last call, if there was one
+pub fn take_gpu_error() -> Option<ErrorDialog> {
+    GPU_ERROR.lock().unwrap().take()
+}
+
+/// Recover from a frame that couldn't be rendered where possible,
+/// returns a dialog for errors the user should know about
+pub fn render_error(error: pixels::Error, pixels: &mut pixels::Pixels, width: u32, height: u32) -> Option<ErrorDialog> {
+    match error {
+        // pixels already reconfigured the surface once, resizing recreates it for the next frame
+        pixels::Error::Surface(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+            pixels.resize_surface(width, height).err().map(|e| ErrorDialog::new("Couldn't recreate the window surface", e.to_string()))
+        }
+        // the frame is dropped, the next one usually makes it
+        pixels::Error::Surface(wgpu::SurfaceError::Timeout) => None,
+        pixels::Error::Surface(e @ wgpu::SurfaceError::OutOfMemory) => {
+            Some(ErrorDialog::new("Out of GPU memory", e.to_string()).with_hint(OUT_OF_MEMORY_HINT))
+        }
+        e => Some(ErrorDialog::new("Rendering failed", e.to_string())),
+    }
+}
+
+/// Log an error that can't be shown in a window, and exit
+pub fn fatal(message: &str) -> ! {
+    tracing::error!("{}", message);
+    // a page can't exit, the panic shows the message in the browser console instead
+    #[cfg(target_arch = "wasm32")]
+    panic!("{}", message);
+    #[cfg(not(target_arch = "wasm32"))]
+    std::process::exit(1)
+}
@@ -0,0 +1,372 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::view::View;
+use fractal_viz_core::viewport::Viewport;
+use pixels::{wgpu, PixelsContext};
+use rayon::prelude::*;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::watermark::Watermark;
+
+/// Zoom covered by one keyframe of a zoom video, the frames in between are scaled down from it
+const KEYFRAME_ZOOM: f64 = 2.0;
+/// Largest keyframe as a multiple of the frame size along each side, limits the memory used when the view pans as well
+const MAX_KEYFRAME_SIZE: f64 = 4.0;
+
+fn timestamp() -> u128 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|t| t.as_millis()).unwrap_or(0)
+}
+
+/// A path in the current directory named after the current time, so previous files are not overwritten
+pub fn timestamped_path(prefix: &str, extension: &str) -> PathBuf {
+    PathBuf::from(format!("{}_{}.{}", prefix, timestamp(), extension))
+}
+
+/// A folder in the current directory named after the current time
+pub fn timestamped_dir(prefix: &str) -> PathBuf {
+    PathBuf::from(format!("{}_{}", prefix, timestamp()))
+}
+
+/// Save an rgba frame as a png in the current directory.
+/// Returns the path the image was saved to.
+pub fn save_image(frame: &[u8], width: u32, height: u32) -> image::ImageResult<PathBuf> {
+    let path = timestamped_path("fractal", "png");
+    image::save_buffer(&path, frame, width, height, image::ColorType::Rgba8)?;
+    Ok(path)
+}
+
+/// Render a png of any size covering the same area as a window `window_width` pixels wide,
+/// for automation that renders at a higher resolution than the window
+pub fn render_image(fractal: &dyn Fractal, view: View, window_width: u32, (width, height): (u32, u32), path: &Path) -> image::ImageResult<()> {
+    let zoom = view.zoom * window_width as f64 / width as f64;
+    let mut image = vec![0; (width * height * 4) as usize];
+    fractal.draw(&mut image, View { zoom, ..view }.viewport(width, height));
+    image::save_buffer(path, &image, width, height, image::ColorType::Rgba8)
+}
+
+/// Samples along each side of a pixel that are averaged for anti-aliased masks
+const MASK_SAMPLES: i32 = 4;
+
+/// Saves which points of the view are in the set, black inside and white outside, on a background thread.
+/// Designers use it for stencils and laser cutting. It is a 1-bit pbm, which potrace and cutter software read,
+/// or an anti-aliased 8-bit png with the share of each pixel inside the set
+pub struct MaskExport {
+    receiver: mpsc::Receiver<image::ImageResult<PathBuf>>,
+}
+
+impl MaskExport {
+    pub fn start(fractal: Box<dyn Fractal>, viewport: Viewport, antialiased: bool) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        crate::background(move || {
+            let (width, height) = (viewport.width as u32, viewport.height as u32);
+            let samples = if antialiased { MASK_SAMPLES } else { 1 };
+            let mask = membership(fractal.as_ref(), viewport, samples);
+            let result = if antialiased {
+                let path = timestamped_path("mask", "png");
+                image::save_buffer(&path, &mask, width, height, image::ColorType::L8).map(|_| path)
+            } else {
+                save_pbm(&mask, width, height).map_err(image::ImageError::IoError)
+            };
+            let _ = sender.send(result);
+        });
+        Self { receiver }
+    }
+
+    /// The path of the mask once it has been saved
+    pub fn poll(&self) -> Option<image::ImageResult<PathBuf>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// Grey level of each pixel from the share of its samples that reach max iterations, 0 for entirely inside the set
+fn membership(fractal: &dyn Fractal, viewport: Viewport, samples: i32) -> Vec<u8> {
+    let (width, height) = (viewport.width, viewport.height);
+    let max_iterations = fractal.max_iterations();
+    // the samples of a pixel are spread around its centre rather than starting at its corner
+    let sample_viewport = viewport.resized(width * samples, height * samples);
+    let sample_viewport = sample_viewport.moved(sample_viewport.delta_to_complex(-(samples as f64 - 1.0) / 2.0, -(samples as f64 - 1.0) / 2.0));
+    let (samples, sample_width) = (samples as usize, sample_viewport.width as usize);
+    (0..(width * height) as usize).into_par_iter().map(|i| {
+        let (x, y) = (i % width as usize, i / width as usize);
+        let mut inside = 0;
+        for sample_y in y * samples..(y + 1) * samples {
+            for sample_x in x * samples..(x + 1) * samples {
+                if fractal.sample_pixel(&sample_viewport, sample_y * sample_width + sample_x).iterations >= max_iterations {
+                    inside += 1;
+                }
+            }
+        }
+        (255 - inside * 255 / (samples * samples)) as u8
+    }).collect()
+}
+
+/// Save a mask as a binary pbm in the current directory, any pixel at least half inside the set is black
+fn save_pbm(mask: &[u8], width: u32, height: u32) -> std::io::Result<PathBuf> {
+    let path = timestamped_path("mask", "pbm");
+    let mut pbm = format!("P4\n{} {}\n", width, height).into_bytes();
+    // each row is packed into whole bytes, most significant bit first with 1 for black
+    for row in mask.chunks(width as usize) {
+        for byte in row.chunks(8) {
+            pbm.push(byte.iter().enumerate().fold(0, |bits, (i, &grey)| bits | (u8::from(grey < 128) << (7 - i))));
+        }
+    }
+    std::fs::write(&path, pbm)?;
+    Ok(path)
+}
+
+/// A copy of everything drawn to the window (the fractal and the gui) that is waiting to be read back from the gpu.
+/// The frame buffer only holds the fractal, so this is needed for screenshots that include the gui.
+pub struct WindowCapture {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    format: wgpu::TextureFormat,
+}
+
+impl WindowCapture {
+    /// Create a texture the same size and format as the window, for the fractal and gui to be rendered into
+    pub fn create_texture(context: &PixelsContext, format: wgpu::TextureFormat, width: u32, height: u32) -> wgpu::Texture {
+        context.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("window_capture"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    /// Copy the texture into a buffer that can be read once the encoder has been submitted
+    pub fn copy(encoder: &mut wgpu::CommandEncoder, context: &PixelsContext, texture: &wgpu::Texture) -> Self {
+        let size = texture.size();
+        // rows in the buffer have to be aligned
+        let bytes_per_row = size.width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = bytes_per_row.div_ceil(align) * align;
+
+        let buffer = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("window_capture_buffer"),
+            size: (padded_bytes_per_row * size.height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            size,
+        );
+
+        Self { buffer, width: size.width, height: size.height, padded_bytes_per_row, format: texture.format() }
+    }
+
+    /// Wait for the copy to finish and return the captured rgba pixels
+    pub fn read(self, device: &wgpu::Device) -> Option<Vec<u8>> {
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver.recv().ok()?.ok()?;
+
+        let data = slice.get_mapped_range();
+        let mut frame = Vec::with_capacity((self.width * self.height * 4) as usize);
+        // remove the row padding
+        for row in data.chunks(self.padded_bytes_per_row as usize) {
+            frame.extend_from_slice(&row[..(self.width * 4) as usize]);
+        }
+        drop(data);
+        self.buffer.unmap();
+
+        // the window is usually bgra
+        if matches!(self.format, wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb) {
+            for pixel in frame.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        Some(frame)
+    }
+
+    /// Wait for the copy to finish and save it as a png with the watermark for the view
+    pub fn save(self, device: &wgpu::Device, watermark: &Watermark, view: View) -> image::ImageResult<PathBuf> {
+        let (width, height) = (self.width, self.height);
+        match self.read(device) {
+            Some(frame) => watermark.apply(&frame, width, height, view).and_then(|(frame, width, height)| save_image(&frame, width, height)),
+            None => Err(image::ImageError::IoError(std::io::Error::other("failed to read the window from the gpu"))),
+        }
+    }
+}
+
+enum VideoProgress {
+    Frame(usize),
+    Done(Result<PathBuf, String>),
+}
+
+/// Renders the frames of an animation on a background thread.
+/// The frames are saved as a png sequence and then encoded into a video with ffmpeg if it is installed.
+pub struct VideoExport {
+    receiver: mpsc::Receiver<VideoProgress>,
+    /// Frames rendered so far
+    pub rendered: usize,
+    pub total: usize,
+}
+
+impl VideoExport {
+    /// With `reuse_frames`, runs of frames that only zoom or pan are scaled from a larger keyframe rather than each being rendered
+    pub fn start(frames: Vec<(Box<dyn Fractal>, View)>, width: u32, height: u32, fps: u32, reuse_frames: bool) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let total = frames.len();
+        crate::background(move || {
+            let result = export_video(frames, width, height, fps, reuse_frames, |i| {
+                let _ = sender.send(VideoProgress::Frame(i));
+            });
+            let _ = sender.send(VideoProgress::Done(result));
+        });
+        Self { receiver, rendered: 0, total }
+    }
+
+    /// Update the progress, returning the result once the export has finished
+    pub fn poll(&mut self) -> Option<Result<PathBuf, String>> {
+        for progress in self.receiver.try_iter() {
+            match progress {
+                VideoProgress::Frame(i) => self.rendered = i + 1,
+                VideoProgress::Done(result) => return Some(result),
+            }
+        }
+        None
+    }
+}
+
+/// Returns the path of the video, or of the directory of frames if ffmpeg couldn't be run
+fn export_video(frames: Vec<(Box<dyn Fractal>, View)>, width: u32, height: u32, fps: u32, reuse_frames: bool, progress: impl Fn(usize)) -> Result<PathBuf, String> {
+    let dir = timestamped_dir("animation");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let mut frame = vec![0; (width * height * 4) as usize];
+    let mut keyframe = Vec::new();
+    let mut i = 0;
+    while i < frames.len() {
+        let (count, keyframe_viewport) = if reuse_frames { keyframe_group(&frames[i..], width, height) } else { (1, None) };
+        if let Some(keyframe_viewport) = keyframe_viewport {
+            keyframe.resize((keyframe_viewport.width * keyframe_viewport.height * 4) as usize, 0);
+            frames[i].0.draw(&mut keyframe, keyframe_viewport);
+        }
+        for (j, (fractal, view)) in frames[i..i + count].iter().enumerate() {
+            match keyframe_viewport {
+                Some(keyframe_viewport) => resample(&keyframe, keyframe_viewport, &mut frame, view.viewport(width, height)),
+                None => fractal.draw(&mut frame, view.viewport(width, height)),
+            }
+            image::save_buffer(dir.join(format!("frame_{:05}.png", i + j)), &frame, width, height, image::ColorType::Rgba8)
+                .map_err(|e| e.to_string())?;
+            progress(i + j);
+        }
+        i += count;
+    }
+
+    let video = dir.with_extension("mp4");
+    let encoded = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-framerate", &fps.to_string()])
+        .arg("-i").arg(dir.join("frame_%05d.png"))
+        // yuv420p needs even dimensions
+        .args(["-vf", "crop=trunc(iw/2)*2:trunc(ih/2)*2", "-c:v", "libx264", "-pix_fmt", "yuv420p"])
+        .arg(&video)
+        .status()
+        .is_ok_and(|status| status.success());
+    Ok(if encoded { video } else { dir })
+}
+
+/// How many of the frames, from the first, can be scaled from one keyframe and the viewport of the keyframe.
+/// The keyframe has the pixel size of the most zoomed in frame and covers all of them, so no frame is scaled up.
+/// None if only the first frame can use it, as rendering the frame itself is quicker and sharper.
+fn keyframe_group(frames: &[(Box<dyn Fractal>, View)], width: u32, height: u32) -> (usize, Option<Viewport>) {
+    let (first_fractal, first) = &frames[0];
+    let first_viewport = first.viewport(width, height);
+    // the frames are measured in pixels of the first one, they line up as they all have the same rotation
+    let (mut min, mut max): ((f64, f64), (f64, f64)) = ((0.0, 0.0), (width as f64, height as f64));
+    let (mut finest, mut coarsest) = (first.zoom, first.zoom);
+    let mut count = 1;
+    for (fractal, view) in &frames[1..] {
+        // any setting can change the image, not only the ones that animate, so all of them have to match
+        if view.rotation != first.rotation || fractal != first_fractal {
+            break;
+        }
+        let (x, y) = first_viewport.complex_to_pixel_dd(view.viewport(width, height).centre_dd());
+        let scale = view.zoom / first.zoom;
+        let (half_width, half_height) = (width as f64 * scale / 2.0, height as f64 * scale / 2.0);
+        let new_min = (min.0.min(x - half_width), min.1.min(y - half_height));
+        let new_max = (max.0.max(x + half_width), max.1.max(y + half_height));
+        let (new_finest, new_coarsest) = (finest.min(view.zoom), coarsest.max(view.zoom));
+        let pixel = new_finest / first.zoom;
+        let too_big = (new_max.0 - new_min.0) / pixel > width as f64 * MAX_KEYFRAME_SIZE
+            || (new_max.1 - new_min.1) / pixel > height as f64 * MAX_KEYFRAME_SIZE;
+        if new_coarsest / new_finest > KEYFRAME_ZOOM || too_big {
+            break;
+        }
+        (min, max, finest, coarsest) = (new_min, new_max, new_finest, new_coarsest);
+        count += 1;
+    }
+    if count == 1 {
+        return (1, None);
+    }
+
+    let pixel = finest / first.zoom;
+    // a pixel of margin so samples at the far edge have a neighbour to blend with
+    let keyframe_width = ((max.0 - min.0) / pixel).ceil() as i32 + 1;
+    let keyframe_height = ((max.1 - min.1) / pixel).ceil() as i32 + 1;
+    let mut keyframe = first_viewport.centred_on((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0);
+    (keyframe.width, keyframe.height, keyframe.scale) = (keyframe_width, keyframe_height, finest);
+    (count, Some(keyframe))
+}
+
+/// Fill `frame` with the part of the keyframe the viewport covers.
+/// Each pixel averages enough bilinear samples across it that the keyframe's finer detail doesn't shimmer
+fn resample(keyframe: &[u8], keyframe_viewport: Viewport, frame: &mut [u8], viewport: Viewport) {
+    let step = viewport.scale / keyframe_viewport.scale;
+    let origin = keyframe_viewport.complex_to_pixel_dd(viewport.point_dd(0));
+    let taps = step.ceil().max(1.0) as usize;
+    let (keyframe_width, keyframe_height) = (keyframe_viewport.width as usize, keyframe_viewport.height as usize);
+
+    frame.par_chunks_mut(viewport.width as usize * 4).enumerate().for_each(|(y, row)| {
+        for (x, pixel) in row.chunks_mut(4).enumerate() {
+            let mut sum = [0.0; 4];
+            for tap_y in 0..taps {
+                for tap_x in 0..taps {
+                    let sample_x = origin.0 + (x as f64 + (tap_x as f64 + 0.5) / taps as f64 - 0.5) * step;
+                    let sample_y = origin.1 + (y as f64 + (tap_y as f64 + 0.5) / taps as f64 - 0.5) * step;
+                    let sample = bilinear(keyframe, keyframe_width, keyframe_height, sample_x, sample_y);
+                    sum.iter_mut().zip(sample).for_each(|(sum, value)| *sum += value);
+                }
+            }
+            for (value, sum) in pixel.iter_mut().zip(sum) {
+                *value = (sum / (taps * taps) as f64).round() as u8;
+            }
+        }
+    });
+}
+
+/// The colour between the pixels around a point of an rgba image, the point is clamped to the image
+fn bilinear(image: &[u8], width: usize, height: usize, x: f64, y: f64) -> [f64; 4] {
+    let (x, y) = (x.clamp(0.0, (width - 1) as f64), y.clamp(0.0, (height - 1) as f64));
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+    let at = |x: usize, y: usize, channel: usize| image[(y * width + x) * 4 + channel] as f64;
+    std::array::from_fn(|channel| {
+        let top = at(x0, y0, channel) * (1.0 - fx) + at(x1, y0, channel) * fx;
+        let bottom = at(x0, y1, channel) * (1.0 - fx) + at(x1, y1, channel) * fx;
+        top * (1.0 - fy) + bottom * fy
+    })
+}
@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::transfer::Levels;
+use fractal_viz_core::view::View;
+use web_time::{Duration, Instant};
+
+use crate::quality::{self, Quality};
+use crate::schedule::time_left;
+
+enum GalleryProgress {
+    Image(usize),
+    Done(Result<PathBuf, String>),
+}
+
+/// Renders bookmarks one after another at the final render quality into a folder on a background thread,
+/// with an index.html showing them all if asked for
+pub struct GalleryExport {
+    receiver: mpsc::Receiver<GalleryProgress>,
+    /// Images rendered so far
+    pub rendered: usize,
+    pub total: usize,
+    started: Instant,
+}
+
+impl GalleryExport {
+    /// Bookmarks are given as (name, fractal, view) and cover the same area as they would in a window `window_width` pixels wide
+    pub fn start(bookmarks: Vec<(String, Box<dyn Fractal>, View)>, window_width: u32, size: (u32, u32), quality: Quality, index: bool, dir: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let total = bookmarks.len();
+        crate::background(move || {
+            let result = export_gallery(&bookmarks, window_width, size, quality, index, dir, |i| {
+                let _ = sender.send(GalleryProgress::Image(i));
+            });
+            let _ = sender.send(GalleryProgress::Done(result));
+        });
+        Self { receiver, rendered: 0, total, started: Instant::now() }
+    }
+
+    /// Update the progress, returning the folder once the gallery has been rendered
+    pub fn poll(&mut self) -> Option<Result<PathBuf, String>> {
+        for progress in self.receiver.try_iter() {
+            match progress {
+                GalleryProgress::Image(i) => self.rendered = i + 1,
+                GalleryProgress::Done(result) => return Some(result),
+            }
+        }
+        None
+    }
+
+    /// How long the rest of the images are likely to take going by the ones rendered so far
+    pub fn time_left(&self) -> Option<Duration> {
+        time_left(self.started, self.rendered as f32 / self.total.max(1) as f32)
+    }
+}
+
+fn export_gallery(bookmarks: &[(String, Box<dyn Fractal>, View)], window_width: u32, (width, height): (u32, u32), quality: Quality,
+    index: bool, dir: PathBuf, progress: impl Fn(usize)) -> Result<PathBuf, String> {
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let mut entries = Vec::with_capacity(bookmarks.len());
+    let mut image = vec![0; (width * height * 4) as usize];
+    for (i, (name, fractal, view)) in bookmarks.iter().enumerate() {
+        // scale the zoom so the image covers the same area as the window
+        let zoom = view.zoom * window_width as f64 / width as f64;
+        quality::draw(fractal.as_ref(), &mut image, View { zoom, ..*view }.viewport(width, height), quality, Levels::FULL, |_, _| true);
+        let file = format!("{:03}_{}.png", i + 1, file_name(name));
+        image::save_buffer(dir.join(&file), &image, width, height, image::ColorType::Rgba8).map_err(|e| e.to_string())?;
+        entries.push((name.as_str(), fractal.name(), file));
+        progress(i);
+    }
+    if index {
+        fs::write(dir.join("index.html"), index_page(&entries, (width, height))).map_err(|e| e.to_string())?;
+    }
+    Ok(dir)
+}
+
+/// The bookmark's name with anything that isn't safe in a file name replaced
+fn file_name(name: &str) -> String {
+    let name: String = name.trim().chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if name.is_empty() { "bookmark".into() } else { name }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// A page showing every image with the bookmark's name and fractal under it, given as (name, fractal, file)
+fn index_page(entries: &[(&str, &str, String)], (width, height): (u32, u32)) -> String {
+    let figures: String = entries.iter().map(|(name, fractal, file)| format!(
+        "<figure><a href=\"{file}\"><img src=\"{file}\" width=\"{width}\" height=\"{height}\" alt=\"{name}\" loading=\"lazy\"></a>\
+        <figcaption>{name} <small>{fractal}</small></figcaption></figure>\n",
+        file = escape_html(file), name = escape_html(name), fractal = escape_html(fractal),
+    )).collect();
+    format!("<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Fractal gallery</title>
+<style>
+body {{ background: #111; color: #ddd; font-family: sans-serif; margin: 2em; }}
+main {{ display: grid; grid-template-columns: repeat(auto-fill, minmax(320px, 1fr)); gap: 1.5em; }}
+figure {{ margin: 0; }}
+img {{ width: 100%; height: auto; display: block; }}
+small {{ color: #888; }}
+</style>
+</head>
+<body>
+<h1>Fractal gallery</h1>
+<main>
+{}</main>
+</body>
+</html>
+", figures)
+}
@@ -0,0 +1,166 @@
+use pixels::{wgpu, Pixels, PixelsBuilder, SurfaceTexture};
+use serde::{Deserialize, Serialize};
+use winit::window::Window;
+
+// only offered natively, the browser picks the graphics card and present mode itself
+#[cfg(not(target_arch = "wasm32"))]
+pub const POWER_PREFERENCES: [PowerPreference; 3] = [PowerPreference::Default, PowerPreference::LowPower, PowerPreference::HighPerformance];
+#[cfg(not(target_arch = "wasm32"))]
+pub const PRESENT_MODES: [PresentMode; 3] = [PresentMode::Vsync, PresentMode::Immediate, PresentMode::Mailbox];
+
+/// Which kind of graphics card to ask for when no adapter has been picked
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum PowerPreference {
+    #[default]
+    Default,
+    LowPower,
+    HighPerformance,
+}
+
+impl PowerPreference {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerPreference::Default => "Default",
+            PowerPreference::LowPower => "Low power",
+            PowerPreference::HighPerformance => "High performance",
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn description(&self) -> &'static str {
+        match self {
+            PowerPreference::Default => "Let the system choose, or the WGPU_POWER_PREF environment variable",
+            PowerPreference::LowPower => "Prefer the integrated graphics, which uses less battery",
+            PowerPreference::HighPerformance => "Prefer the discrete graphics card",
+        }
+    }
+
+    fn wgpu(self) -> wgpu::PowerPreference {
+        match self {
+            PowerPreference::Default => wgpu::util::power_preference_from_env().unwrap_or_default(),
+            PowerPreference::LowPower => wgpu::PowerPreference::LowPower,
+            PowerPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        }
+    }
+}
+
+/// How finished frames are shown
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum PresentMode {
+    #[default]
+    Vsync,
+    Immediate,
+    Mailbox,
+}
+
+impl PresentMode {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn name(&self) -> &'static str {
+        match self {
+            PresentMode::Vsync => "Vsync",
+            PresentMode::Immediate => "Immediate",
+            PresentMode::Mailbox => "Mailbox",
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn description(&self) -> &'static str {
+        match self {
+            PresentMode::Vsync => "Wait for the display to refresh, supported everywhere",
+            PresentMode::Immediate => "Show frames straight away, with the lowest latency but possible tearing",
+            PresentMode::Mailbox => "Show the newest frame at each refresh, low latency without tearing",
+        }
+    }
+
+    fn wgpu(self) -> wgpu::PresentMode {
+        match self {
+            PresentMode::Vsync => wgpu::PresentMode::AutoVsync,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+        }
+    }
+}
+
+/// The graphics card and present mode the window is drawn with, set in the settings
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    /// Label of the adapter to use, from `adapter_label`. The power preference picks one if it isn't set or isn't found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adapter: Option<String>,
+    pub power_preference: PowerPreference,
+    pub present_mode: PresentMode,
+}
+
+/// Name and api of an adapter, as shown in the settings and saved in the config
+pub fn adapter_label(info: &wgpu::AdapterInfo) -> String {
+    format!("{} ({:?})", info.name, info.backend)
+}
+
+/// Every adapter wgpu can find. The browser only offers the one it picks, so there are none to choose from there
+pub fn adapters() -> Vec<wgpu::AdapterInfo> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return wgpu::Instance::default().enumerate_adapters(wgpu::Backends::all()).map(|adapter| adapter.get_info()).collect();
+    #[cfg(target_arch = "wasm32")]
+    Vec::new()
+}
+
+/// Create the pixel buffer and surface for the window with the settings.
+/// `fallback` asks for a software renderer, for when no graphics card could be used.
+pub async fn build(window: &Window, settings: &GraphicsSettings, fallback: bool) -> Result<Pixels, pixels::Error> {
+    let size = window.inner_size();
+    let (backends, options) = adapter_options(settings, fallback);
+    let present_mode = supported_present_mode(window, backends, &options, settings.present_mode.wgpu()).await;
+    PixelsBuilder::new(size.width, size.height, SurfaceTexture::new(size.width, size.height, window))
+        .wgpu_backend(backends)
+        .request_adapter_options(options)
+        .present_mode(present_mode)
+        .build_async()
+        .await
+}
+
+/// Which apis to look at and what to ask them for, to get the picked adapter or one matching the power preference
+fn adapter_options(settings: &GraphicsSettings, fallback: bool) -> (wgpu::Backends, wgpu::RequestAdapterOptions<'static>) {
+    let mut backends = wgpu::util::backend_bits_from_env().unwrap_or(wgpu::Backends::PRIMARY);
+    let mut options = wgpu::RequestAdapterOptions {
+        power_preference: settings.power_preference.wgpu(),
+        force_fallback_adapter: fallback,
+        compatible_surface: None,
+    };
+    let picked = settings.adapter.as_ref().filter(|_| !fallback)
+        .and_then(|label| adapters().into_iter().find(|info| adapter_label(info) == *label));
+    // wgpu can't be asked for an adapter by name, but only looking at its api and asking for its type finds it
+    if let Some(info) = picked {
+        backends = info.backend.into();
+        match info.device_type {
+            wgpu::DeviceType::IntegratedGpu => options.power_preference = wgpu::PowerPreference::LowPower,
+            wgpu::DeviceType::DiscreteGpu => options.power_preference = wgpu::PowerPreference::HighPerformance,
+            wgpu::DeviceType::Cpu => options.force_fallback_adapter = true,
+            wgpu::DeviceType::VirtualGpu | wgpu::DeviceType::Other => {}
+        }
+    }
+    (backends, options)
+}
+
+/// The present mode if the adapter supports it for the window, otherwise vsync.
+/// wgpu panics when a surface is set up with a mode it doesn't support.
+async fn supported_present_mode(window: &Window, backends: wgpu::Backends, options: &wgpu::RequestAdapterOptions<'_>, mode: wgpu::PresentMode) -> wgpu::PresentMode {
+    // vsync is supported everywhere and the browser doesn't offer anything else
+    if mode == wgpu::PresentMode::AutoVsync || cfg!(target_arch = "wasm32") {
+        return wgpu::PresentMode::AutoVsync;
+    }
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor { backends, ..Default::default() });
+    // only used to check what the adapter can do with the window, it is never configured
+    let Ok(surface) = (unsafe { instance.create_surface(window) }) else {
+        return wgpu::PresentMode::AutoVsync;
+    };
+    let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions { compatible_surface: Some(&surface), ..*options }).await;
+    let supported = adapter.is_some_and(|adapter| surface.get_capabilities(&adapter).present_modes.contains(&mode));
+    if supported {
+        mode
+    } else {
+        tracing::warn!(?mode, "The graphics card doesn't support the present mode, using vsync");
+        wgpu::PresentMode::AutoVsync
+    }
+}
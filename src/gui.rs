@@ -1,11 +1,47 @@
-use egui::{ClippedPrimitive, Context, TexturesDelta, RichText, FontFamily, FontId, Align, Stroke};
+use egui::{ClippedPrimitive, Context, TexturesDelta, RichText, FontFamily, FontId, Align};
+use egui::plot::{Line, Plot, PlotPoints, Points};
 use egui_wgpu::renderer::{Renderer, ScreenDescriptor};
 use egui_winit::EventResponse;
+use fractal_viz_core::fractals::{self, colour_gradient_names, string_to_colour_gradient, Fractal, Julia, Bailout, Mandelbrot, Newton, NewtonFunction, SettingsUi, COLOUR_BLIND_SAFE, MAX_ITERATIONS};
+use fractal_viz_core::random::Random;
+use fractal_viz_core::transfer::{self, Levels, TransferCurve};
+use fractal_viz_core::view::{Precision, View, INIT_ZOOM};
+use fractal_viz_core::viewport::Viewport;
 use pixels::{wgpu, PixelsContext};
 use winit::event_loop::EventLoopWindowTarget;
+use tracing::Level;
 use winit::window::Window;
 
-use crate::{fractals::{Fractals, COLOUR_GRADIENTS}, Flags};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use web_time::Instant;
+
+use crate::{animation::{Timeline, EASINGS}, replay::Recorder, adjust::Adjustments, colour_blind::SIMULATIONS, stereo::{StereoMode, STEREO_MODES}, compare::Compare, rays::{parse_angles, RayOverlay}, area::{AreaEstimate, Region, REGIONS}, locator::{nearest_misiurewicz, nearest_nucleus, Feature, FeatureKind}, bookmarks::{thumbnail, Bookmarks}, breadcrumbs::{magnification, Breadcrumbs}, counts::{IterationCounts, RegionStats}, session::{Session, Sessions}, commands::{self, Command, Overlay}, keybindings::{key_name, Action, KeyBindings, MOUSE_BINDINGS, FIXED_BINDINGS}, config::{Config, Dock, Navigation, BOX_LEAST_ZOOMS, BOX_MIN_AREAS, BOX_MOST_ZOOMS, BOX_SENSITIVITIES, BOX_THRESHOLDS, DOCKS, THEMES}, i18n::{set_language, tr, LANGUAGES}, error::ErrorResponse, graphics::adapter_label, logging, profiles::Profile, renderer::BACKENDS, Flags};
+
+/// Size in pixels of the Julia set drawn for the point under the mouse
+const JULIA_PREVIEW_SIZE: (u32, u32) = (160, 120);
+/// Shortest time between redrawing the Julia preview while the mouse moves
+const JULIA_PREVIEW_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Width in pixels of the view drawn in each palette in the colour section
+const PALETTE_PREVIEW_WIDTH: u32 = 64;
+
+/// The view drawn in every palette, with the counts, max iterations and curve it was drawn from
+struct PalettePreviews {
+    textures: Vec<(String, egui::TextureHandle)>,
+    counts: Vec<u32>,
+    max_iterations: u32,
+    curve: TransferCurve,
+    levels: Levels,
+}
+
+/// The texture of the Julia preview and the set it shows
+struct JuliaPreview {
+    texture: egui::TextureHandle,
+    julia: Julia,
+    drawn: Instant,
+}
 
 /// Manages all state required for rendering egui over `Pixels`.
 pub(crate) struct Framework {
@@ -16,31 +52,222 @@ pub(crate) struct Framework {
     renderer: Renderer,
     paint_jobs: Vec<ClippedPrimitive>,
     textures: TexturesDelta,
+    /// How long egui can wait before the next frame, zero while it is animating
+    repaint_after: Duration,
+
+    /// Scale factor of the window, the ui scale from the config is applied on top of this
+    window_scale_factor: f32,
 
     // State for the GUI
     gui: Gui,
 }
 
+/// A context menu opened by right clicking on the fractal
+pub struct ContextMenu {
+    /// Position of the click in physical pixels
+    pub position: (f32, f32),
+    /// The pixel of the frame that was clicked, moving relative to it keeps full precision on deep zooms
+    pub pixel: (f64, f64),
+    /// The point in the complex plane that was clicked
+    pub point: (f64, f64),
+}
+
+/// Actions from the context menu that have to be handled outside of the gui
+pub enum ContextAction {
+    /// Centre on a pixel of the frame
+    CentreHere((f64, f64)),
+    /// Centre on a point in the complex plane
+    CentreOn((f64, f64)),
+    /// Zoom in keeping a pixel of the frame where it is
+    ZoomIn((f64, f64)),
+    /// Centre on a point and zoom so something of the given radius fills the view
+    ZoomTo((f64, f64), f64),
+    SetJuliaSeed((f64, f64)),
+    AddNewtonRoot((f64, f64)),
+    SaveImage,
+    /// Save which points are in the set, anti-aliased or 1-bit
+    SaveMask { antialiased: bool },
+    SaveSession,
+}
+
+/// The commands the palette was opened with, what's been typed and which match is highlighted
+struct CommandPalette {
+    commands: Vec<Command>,
+    query: String,
+    selected: usize,
+}
+
+/// Iterating a point one step at a time to show how escape time fractals work
+struct StepThrough {
+    point: (f64, f64),
+    /// Index of the z being shown
+    step: usize,
+    playing: bool,
+    /// Time of the last automatic step in seconds
+    last_step: f64,
+}
+
+/// Seconds between steps while playing
+const STEP_INTERVAL: f64 = 0.6;
+
+/// A point's orbit moving out step by step until it crosses the escape boundary, over and over
+struct EscapeDemo {
+    point: (f64, f64),
+    /// When it started in seconds, the point's position comes from the time since
+    started: f64,
+}
+
+/// Seconds the escape demonstration takes to move the point one iteration
+const ESCAPE_STEP: f64 = 0.8;
+/// Seconds the escape demonstration waits at the end before starting again
+const ESCAPE_PAUSE: f64 = 2.5;
+
+/// Radius in points of the markers on the roots of the Newton polynomial
+const ROOT_MARKER_RADIUS: f32 = 8.0;
+
+/// Size of the square the colour curve is drawn in
+const CURVE_EDITOR_SIZE: f32 = 240.0;
+/// Closest two points of the colour curve can be across
+const CURVE_POINT_GAP: f64 = 0.01;
+
+/// Most tabs of the last session and recent bookmarks shown on the start panel
+const START_TABS: usize = 4;
+const START_BOOKMARKS: usize = 6;
+/// Size of the thumbnails on the start panel compared to the bookmarks gallery
+const START_THUMBNAIL_SCALE: f32 = 0.75;
+
+/// Actions from the bookmarks gallery that have to be handled outside of the gui
+pub enum BookmarkAction {
+    Save(String),
+    GoTo(usize),
+    Remove(usize),
+    /// Render every bookmark at this size into a folder, a new one named after the time if there's none
+    RenderGallery { size: (u32, u32), index: bool, folder: Option<PathBuf> },
+}
+
+/// How the bookmarks are rendered into a gallery
+struct GallerySettings {
+    width: u32,
+    height: u32,
+    /// Write an html page showing all the images
+    index: bool,
+    folder: String,
+}
+
+impl Default for GallerySettings {
+    fn default() -> Self {
+        Self { width: 1920, height: 1080, index: true, folder: String::new() }
+    }
+}
+
+/// Actions from the timeline that need the session's fractal and view
+pub enum TimelineAction {
+    AddKeyframe,
+    Remove(usize),
+    /// Jump to a time in seconds
+    Seek(f32),
+    Export,
+    ExportSweep,
+    StartRecording,
+    StopRecording,
+}
+
+/// Actions from the session tabs that have to be handled outside of the gui
+pub enum SessionAction {
+    New,
+    Switch(usize),
+    Close(usize),
+}
+
 /// Application state.
 pub struct Gui {
     /// Only show the egui window when true.
     window_open: bool,
-    window_position: (f32, f32),
     // Track the position and size of the egui window.
     window_open_size: (f32, f32),
     window_closed_size: (f32, f32),
     font: FontId,
+    /// Name typed in for the next bookmark
+    bookmark_name: String,
+    gallery: GallerySettings,
+    /// Textures of the bookmark thumbnails, keyed by the thumbnail file name
+    thumbnails: HashMap<String, egui::TextureHandle>,
+    /// Textures of the tabs open at the last exit, drawn when the start panel is first shown
+    last_session_thumbnails: Vec<egui::TextureHandle>,
+    /// Precision of the view last frame, to notify when the limit is first reached
+    precision: Precision,
+    config: Config,
+    /// Ui scale being edited, only applied once the slider is released so it doesn't move under the mouse
+    ui_scale: f32,
+    show_appearance: bool,
+    show_quality: bool,
+    show_adjustments: bool,
+    show_colour_curve: bool,
+    show_watermark: bool,
+    show_defaults: bool,
+    show_navigation: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    show_graphics: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    show_watch_folder: bool,
+    /// Device the virtual camera publishes frames to
+    camera_device: String,
+    /// Path typed in for the watermark logo
+    watermark_logo: String,
+    /// Folder typed in to watch for parameter files
+    #[cfg(not(target_arch = "wasm32"))]
+    watch_folder: String,
+    /// Labels of the adapters that can be picked, found when the graphics window is first opened
+    #[cfg(not(target_arch = "wasm32"))]
+    adapters: Option<Vec<String>>,
+    /// The adapter the window is drawn with
+    adapter_in_use: String,
+    /// Names of the connected monitors, looked up every frame the graphics window is open so plugging one in shows up
+    #[cfg(not(target_arch = "wasm32"))]
+    monitors: Vec<String>,
+    /// Plot orbits in the complex plane rather than |z| against the iteration
+    orbit_plane: bool,
+    /// Draw external rays and equipotentials over the Mandelbrot set
+    show_rays: bool,
+    /// Draw the boundary a point has to cross to escape over the fractal
+    show_escape_radius: bool,
+    /// How long frames take to generate and how long the one being drawn has left
+    show_render_stats: bool,
+    /// Seed the last random parameters came from, written as "seed:" and the text it was made from
+    random_seed: String,
+    escape_demo: Option<EscapeDemo>,
+    /// The Julia set of the point under the mouse, shown in a corner while browsing the Mandelbrot set
+    julia_preview: Option<JuliaPreview>,
+    palette_previews: Option<PalettePreviews>,
+    /// Show the roots of the Newton polynomial on the fractal so they can be moved, added and removed
+    edit_roots: bool,
+    /// Comma separated angles of the rays in turns
+    ray_angles: String,
+    equipotentials: u32,
+    /// The computed curves, or the angle that couldn't be read. None when the settings changed
+    ray_overlay: Option<Result<RayOverlay, String>>,
+    show_area: bool,
+    area_region: Region,
+    area_estimate: Option<AreaEstimate>,
+    show_locator: bool,
+    /// Show the recent log lines
+    show_log: bool,
+    step_through: Option<StepThrough>,
+    command_palette: Option<CommandPalette>,
+    /// The result of the last search, None if nothing was found
+    located: Option<Option<Feature>>,
 }
 
 impl Framework {
     /// Create egui.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new<T>(
         event_loop: &EventLoopWindowTarget<T>,
         width: u32,
         height: u32,
         scale_factor: f32,
         pixels: &pixels::Pixels,
-        window_position: (f32, f32),
+        config: Config,
         window_open_size: (f32, f32),
         window_closed_size: (f32, f32),
     ) -> Self {
@@ -48,27 +275,24 @@ impl Framework {
 
         // egui handle
         let egui_ctx = Context::default();
-        // Change font colour to white
-        let mut style = egui::Style::default();
-        style.visuals.override_text_color = Some(egui::Color32::from_rgb(255, 255, 255)); // set text to white
+        let mut style = egui::Style { visuals: config.visuals(), ..Default::default() };
         style.spacing.item_spacing = egui::Vec2::new(6.0, 6.0); // increase spacing between items
         egui_ctx.set_style(style);
 
-        let visual = egui::Visuals::dark();
-        visual.gray_out(egui::Color32::from_rgb(255, 255,255));
-        egui_ctx.set_visuals(visual);
-
+        let pixels_per_point = scale_factor * config.ui_scale;
         let mut egui_state = egui_winit::State::new(event_loop);
         egui_state.set_max_texture_side(max_texture_size);
-        egui_state.set_pixels_per_point(scale_factor);
+        egui_state.set_pixels_per_point(pixels_per_point);
         let screen_descriptor = ScreenDescriptor {
             size_in_pixels: [width, height],
-            pixels_per_point: scale_factor,
+            pixels_per_point,
         };
         let renderer = Renderer::new(pixels.device(), pixels.render_texture_format(), None, 1);
         let textures = TexturesDelta::default();
         
-        let gui = Gui::new(window_position, window_open_size, window_closed_size);
+        set_language(config.language);
+        let mut gui = Gui::new(config, window_open_size, window_closed_size);
+        gui.adapter_in_use = adapter_label(&pixels.adapter().get_info());
 
         Self {
             egui_ctx,
@@ -77,10 +301,29 @@ impl Framework {
             renderer,
             paint_jobs: Vec::new(),
             textures,
+            repaint_after: Duration::ZERO,
+            window_scale_factor: scale_factor,
             gui,
         }
     }
 
+    /// Switch to the device of a `Pixels` recreated with new graphics settings.
+    /// egui starts afresh so its textures are uploaded to the new device, keeping its style and the state of the windows.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn recreate(&mut self, pixels: &pixels::Pixels) {
+        let egui_ctx = Context::default();
+        egui_ctx.set_style(self.egui_ctx.style());
+        egui_ctx.memory_mut(|memory| *memory = self.egui_ctx.memory(|old| old.clone()));
+        self.egui_ctx = egui_ctx;
+        self.egui_state.set_max_texture_side(pixels.device().limits().max_texture_dimension_2d as usize);
+        self.renderer = Renderer::new(pixels.device(), pixels.render_texture_format(), None, 1);
+        self.textures = TexturesDelta::default();
+        self.paint_jobs.clear();
+        self.gui.thumbnails.clear();
+        self.gui.last_session_thumbnails.clear();
+        self.gui.adapter_in_use = adapter_label(&pixels.adapter().get_info());
+    }
+
     /// Handle input events from the window manager.
     pub(crate) fn handle_event(&mut self, event: &winit::event::WindowEvent) -> EventResponse {
         self.egui_state.on_event(&self.egui_ctx, event)
@@ -95,24 +338,47 @@ impl Framework {
 
     /// Update scaling factor.
     pub(crate) fn scale_factor(&mut self, scale_factor: f64) {
-        self.screen_descriptor.pixels_per_point = scale_factor as f32;
+        self.window_scale_factor = scale_factor as f32;
     }
 
     /// Prepare egui.
-    pub(crate) fn prepare(&mut self, window: &Window, current_fractal: &mut Fractals, flags: &mut Flags) {
+    pub(crate) fn prepare(&mut self, window: &Window, sessions: &mut Sessions, bookmarks: &Bookmarks, keybindings: &KeyBindings, flags: &mut Flags) {
+        // apply the ui scale every frame as egui resets it when the window's scale factor changes
+        let pixels_per_point = self.window_scale_factor * self.gui.config.ui_scale;
+        self.egui_state.set_pixels_per_point(pixels_per_point);
+        self.screen_descriptor.pixels_per_point = pixels_per_point;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.gui.show_graphics {
+            self.gui.monitors = window.available_monitors().filter_map(|monitor| monitor.name()).collect();
+        }
+
         // Run the egui frame and create all paint jobs to prepare for rendering.
         let raw_input = self.egui_state.take_egui_input(window);
         let output = self.egui_ctx.run(raw_input, |egui_ctx| {
             // Draw the demo application.
-            self.gui.ui(egui_ctx, current_fractal, flags);
+            // Still run egui when the gui is hidden so input doesn't build up and get replayed when it is shown again
+            if !flags.hide_ui {
+                self.gui.ui(egui_ctx, sessions, bookmarks, keybindings, flags);
+            }
+            // the zoom box is part of exploring rather than the gui so it's drawn when the gui is hidden too
+            if let Some(zoom_box) = flags.zoom_box {
+                self.gui.zoom_box(egui_ctx, zoom_box, sessions.current_mut().view, &flags.navigation);
+            }
         });
 
         self.textures.append(output.textures_delta);
+        self.repaint_after = output.repaint_after;
         self.egui_state
             .handle_platform_output(window, &self.egui_ctx, output.platform_output);
         self.paint_jobs = self.egui_ctx.tessellate(output.shapes);
     }
 
+    /// How long the gui can go without being drawn again, unless there's input
+    pub(crate) fn repaint_after(&self) -> Duration {
+        self.repaint_after
+    }
+
     /// Render egui.
     pub(crate) fn render(
         &mut self,
@@ -160,42 +426,266 @@ impl Framework {
     }
 }
 
-/// Adds a label and a method to adjust the variable e.g. a slider
-/// If a setting is changed then fractal_change is set to true to redraw the fractal
-macro_rules! create_fractal_setting {
-    ($ui:ident, $fractal_change:ident, $font:ident, $(($label:expr, $setting_type:ident)),+) => {
-        $(
-            $ui.horizontal(|ui| {
-                ui.label(RichText::new($label).font($font.clone()));
-                ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
-                    ui.add_space(10.0);
-                    let change = ui.add($setting_type).changed();
-                    *$fractal_change |= change;
-                });
-            });
-        )*
-    };
+/// Lays out a fractal's settings as labelled rows in the settings window
+struct FractalSettings<'a> {
+    ui: &'a mut egui::Ui,
+    font: &'a FontId,
+    /// Set when any of the settings are changed
+    changed: bool,
+}
+
+impl FractalSettings<'_> {
+    fn row(&mut self, label: &str, widget: impl egui::Widget) {
+        let font = self.font.clone();
+        let changed = self.ui.horizontal(|ui| {
+            ui.label(RichText::new(tr(label)).font(font));
+            ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                ui.add_space(10.0);
+                ui.add(widget).changed()
+            }).inner
+        }).inner;
+        self.changed |= changed;
+    }
+}
+
+impl SettingsUi for FractalSettings<'_> {
+    fn iterations(&mut self, max_iterations: &mut u32) {
+        self.row("Max Iterations", iterations_slider(max_iterations));
+    }
+
+    fn slider(&mut self, label: &str, value: &mut f64, range: std::ops::RangeInclusive<f64>) {
+        self.row(label, egui::Slider::new(value, range).clamp_to_range(true));
+    }
+
+    fn checkbox(&mut self, label: &str, value: &mut bool) {
+        self.changed |= self.ui.checkbox(value, RichText::new(tr(label)).font(self.font.clone())).changed();
+    }
+
+    fn choice(&mut self, label: &str, selected: &mut usize, options: &[&str]) {
+        let font = self.font.clone();
+        self.row(label, move |ui: &mut egui::Ui| {
+            let mut changed = false;
+            let mut response = egui::ComboBox::from_id_source(label)
+            .selected_text(tr(options.get(*selected).copied().unwrap_or_default()))
+            .show_ui(ui, |ui| {
+                for (i, option) in options.iter().enumerate() {
+                    changed |= ui.selectable_value(selected, i, RichText::new(tr(option)).font(font.clone())).changed();
+                }
+            }).response;
+            if changed {
+                response.mark_changed();
+            }
+            response
+        });
+    }
+
+    fn label(&mut self, text: &str) {
+        self.ui.label(RichText::new(text).font(self.font.clone()));
+    }
+}
+
+/// Logarithmic slider for the maximum number of iterations with an editable field next to it,
+/// so there is fine control at low values and large jumps at high values
+fn iterations_slider(max_iterations: &mut u32) -> impl egui::Widget + '_ {
+    move |ui: &mut egui::Ui| {
+        // added right to left, so the field ends up on the right of the slider
+        let field = ui.add(egui::DragValue::new(max_iterations).clamp_range(1..=MAX_ITERATIONS).speed(10.0));
+        let slider = ui.add(egui::Slider::new(max_iterations, 1..=MAX_ITERATIONS).logarithmic(true).show_value(false).clamp_to_range(true));
+        field | slider
+    }
+}
+
+/// The viewport the fractal is drawn with, from the size of the screen, so overlays line up with the pixels under them
+fn screen_viewport(ctx: &Context, view: View) -> Viewport {
+    let size = ctx.screen_rect().size() * ctx.pixels_per_point();
+    view.viewport(size.x.round() as u32, size.y.round() as u32)
+}
+
+/// Where a point in the complex plane is on the screen, in egui's points rather than pixels
+fn complex_to_pos(ctx: &Context, viewport: &Viewport, point: (f64, f64)) -> egui::Pos2 {
+    let (x, y) = viewport.complex_to_pixel(point.0, point.1);
+    let pixels_per_point = ctx.pixels_per_point();
+    egui::pos2(x as f32 / pixels_per_point, y as f32 / pixels_per_point)
+}
+
+/// Outline of the region points escape from, a circle, square or pair of lines around 0 depending on the bailout
+fn escape_boundary(ctx: &Context, painter: &egui::Painter, viewport: &Viewport, escape_radius: f64, bailout: Bailout, stroke: egui::Stroke) {
+    let r = escape_radius;
+    match bailout {
+        Bailout::Circle => {
+            let radius = (r / viewport.scale) as f32 / ctx.pixels_per_point();
+            painter.circle_stroke(complex_to_pos(ctx, viewport, (0.0, 0.0)), radius, stroke);
+        }
+        // the corners go through the viewport so the square turns with the view
+        Bailout::Square => {
+            let corners = [(r, r), (-r, r), (-r, -r), (r, -r)].map(|corner| complex_to_pos(ctx, viewport, corner));
+            painter.add(egui::Shape::closed_line(corners.to_vec(), stroke));
+        }
+        // lines long enough to cross the whole view whichever way it's turned
+        Bailout::Strip => {
+            let reach = viewport.centre.1.abs() + viewport.scale * (viewport.width as f64).hypot(viewport.height as f64);
+            for re in [r, -r] {
+                painter.line_segment([complex_to_pos(ctx, viewport, (re, -reach)), complex_to_pos(ctx, viewport, (re, reach))], stroke);
+            }
+        }
+    }
 }
 
 /// Adds a selectable value to a combo box for a colour gradient
+/// An editable curve with the palette it produces along the bottom.
+/// Returns whether the curve changed and whether it should be saved, which isn't until a drag ends.
+fn curve_editor(ui: &mut egui::Ui, curve: &mut TransferCurve, colour_gradient: &str) -> (bool, bool) {
+    let (mut changed, mut save) = (false, false);
+    let (response, painter) = ui.allocate_painter(egui::vec2(CURVE_EDITOR_SIZE, CURVE_EDITOR_SIZE), egui::Sense::click());
+    let rect = response.rect;
+    let to_screen = |(x, y): (f64, f64)| egui::pos2(rect.left() + x as f32 * rect.width(), rect.bottom() - y as f32 * rect.height());
+    let from_screen = |pos: egui::Pos2| (((pos.x - rect.left()) / rect.width()) as f64, ((rect.bottom() - pos.y) / rect.height()) as f64);
+
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 0.0, visuals.extreme_bg_color);
+    // the palette as it comes out of the curve, left to right by iteration value
+    let gradient = string_to_colour_gradient(colour_gradient);
+    let strip = 64;
+    for i in 0..strip {
+        let [r, g, b, _] = gradient.at((i as f64 + 0.5) / strip as f64).to_rgba8();
+        let left = rect.left() + rect.width() * i as f32 / strip as f32;
+        let right = rect.left() + rect.width() * (i + 1) as f32 / strip as f32;
+        painter.rect_filled(egui::Rect::from_min_max(egui::pos2(left, rect.bottom() - 12.0), egui::pos2(right, rect.bottom())), 0.0, egui::Color32::from_rgb(r, g, b));
+    }
+    let grid = egui::Stroke::new(1.0, visuals.widgets.noninteractive.bg_stroke.color);
+    for i in 1..4 {
+        let t = i as f64 / 4.0;
+        painter.line_segment([to_screen((t, 0.0)), to_screen((t, 1.0))], grid);
+        painter.line_segment([to_screen((0.0, t)), to_screen((1.0, t))], grid);
+    }
+    painter.line_segment([to_screen((0.0, 0.0)), to_screen((1.0, 1.0))], grid);
+    let line: Vec<egui::Pos2> = (0..=100).map(|i| i as f64 / 100.0).map(|x| to_screen((x, curve.at(x)))).collect();
+    painter.add(egui::Shape::line(line, egui::Stroke::new(2.0, visuals.selection.bg_fill)));
+
+    let mut remove = None;
+    let mut over_point = false;
+    let last = curve.points.len() - 1;
+    for i in 0..=last {
+        let centre = to_screen(curve.points[i]);
+        let point = ui.interact(egui::Rect::from_center_size(centre, egui::vec2(12.0, 12.0)), response.id.with(i), egui::Sense::click_and_drag());
+        over_point |= point.hovered();
+        if point.dragged() {
+            if let Some(pos) = point.interact_pointer_pos() {
+                let (x, y) = from_screen(pos);
+                // the ends stay at the ends and the rest can't pass their neighbours
+                let x = match i {
+                    0 => 0.0,
+                    i if i == last => 1.0,
+                    i => x.clamp(curve.points[i - 1].0 + CURVE_POINT_GAP, curve.points[i + 1].0 - CURVE_POINT_GAP),
+                };
+                curve.points[i] = (x, y.clamp(0.0, 1.0));
+                changed = true;
+            }
+        }
+        save |= point.drag_released();
+        if point.secondary_clicked() && i != 0 && i != last {
+            remove = Some(i);
+        }
+        let colour = if point.hovered() || point.dragged() { visuals.widgets.hovered.fg_stroke.color } else { visuals.widgets.inactive.fg_stroke.color };
+        painter.circle_filled(centre, 5.0, colour);
+    }
+    if let Some(i) = remove {
+        curve.points.remove(i);
+        (changed, save) = (true, true);
+    }
+    if response.double_clicked() && !over_point {
+        if let Some(pos) = response.interact_pointer_pos() {
+            let (x, y) = from_screen(pos);
+            let i = curve.points.partition_point(|(point_x, _)| *point_x < x);
+            let fits = i > 0 && i <= last && x - curve.points[i - 1].0 >= CURVE_POINT_GAP && curve.points[i].0 - x >= CURVE_POINT_GAP;
+            if fits {
+                curve.points.insert(i, (x, y.clamp(0.0, 1.0)));
+                (changed, save) = (true, true);
+            }
+        }
+    }
+    (changed, save)
+}
+
+/// A length in the complex plane, in scientific notation once it gets small
+fn format_length(length: f64) -> String {
+    if length >= 0.01 { format!("{:.4}", length) } else { format!("{:.3e}", length) }
+}
+
+/// A duration to the nearest unit worth showing, for render times and what's left of a render
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64();
+    match seconds {
+        s if s < 1.0 => format!("{:.0} ms", s * 1000.0),
+        s if s < 60.0 => format!("{:.1} s", s),
+        s => format!("{} min {} s", s as u64 / 60, s as u64 % 60),
+    }
+}
+
 macro_rules! create_colour_gradient_option {
     ($ui:ident, $current_colour_gradient:ident, $font:ident, $colour_gradient:ident) => {
-        $ui.selectable_value($current_colour_gradient, String::from($colour_gradient), RichText::new($colour_gradient).font($font.clone()));
+        $ui.selectable_value($current_colour_gradient, String::from($colour_gradient), RichText::new($colour_gradient).font($font.clone()))
     };
 }
 
 impl Gui {
     /// Create a `Gui`.
-    fn new(window_position: (f32, f32), window_open_size: (f32,f32), window_closed_size: (f32,f32)) -> Self {
+    fn new(config: Config, window_open_size: (f32,f32), window_closed_size: (f32,f32)) -> Self {
         Self { 
             window_open: true,
-            window_position,
             window_open_size,
             window_closed_size,
             font: FontId {
-                size: 15.0,
+                size: config.font_size,
                 family: FontFamily::default(),
             },
+            bookmark_name: String::new(),
+            gallery: GallerySettings::default(),
+            thumbnails: HashMap::new(),
+            last_session_thumbnails: Vec::new(),
+            precision: Precision::Ok,
+            ui_scale: config.ui_scale,
+            camera_device: crate::camera::DEFAULT_DEVICE.to_string(),
+            watermark_logo: config.watermark.logo.as_ref().map(|logo| logo.display().to_string()).unwrap_or_default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            watch_folder: config.watch_folder.as_ref().map(|folder| folder.display().to_string()).unwrap_or_default(),
+            config,
+            show_appearance: false,
+            show_quality: false,
+            show_adjustments: false,
+            show_colour_curve: false,
+            show_watermark: false,
+            show_defaults: false,
+            show_navigation: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            show_graphics: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            show_watch_folder: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            adapters: None,
+            adapter_in_use: String::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            monitors: Vec::new(),
+            orbit_plane: false,
+            show_rays: false,
+            show_escape_radius: false,
+            show_render_stats: false,
+            random_seed: String::new(),
+            escape_demo: None,
+            julia_preview: None,
+            palette_previews: None,
+            edit_roots: false,
+            ray_angles: "0, 1/3, 2/3, 1/2".into(),
+            equipotentials: 6,
+            ray_overlay: None,
+            show_area: false,
+            area_region: Region::Visible,
+            area_estimate: None,
+            show_locator: false,
+            show_log: false,
+            step_through: None,
+            command_palette: None,
+            located: None,
         }
     }
 
@@ -208,11 +698,147 @@ impl Gui {
     }
 
     /// Create the UI using egui.
-    fn ui(&mut self, ctx: &Context, current_fractal: &mut Fractals, flags: &mut Flags) {
+    fn ui(&mut self, ctx: &Context, sessions: &mut Sessions, bookmarks: &Bookmarks, keybindings: &KeyBindings, flags: &mut Flags) {
+        self.session_tabs(ctx, sessions, flags);
+        flags.toasts.show(ctx, &self.font);
+        match flags.error.as_ref().and_then(|error| error.show(ctx, &self.font)) {
+            Some(ErrorResponse::Continue) => flags.error = None,
+            Some(ErrorResponse::Quit) => flags.quit = true,
+            None => {}
+        }
+        if flags.recovered.is_some() {
+            self.restore_prompt(ctx, flags);
+        }
+        if flags.show_start {
+            self.start_panel(ctx, bookmarks, flags);
+        }
+        let session = sessions.current_mut();
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::P)) {
+            self.command_palette = match self.command_palette {
+                Some(_) => None,
+                None => Some(CommandPalette { commands: commands::all(bookmarks), query: String::new(), selected: 0 }),
+            };
+        }
+        if self.command_palette.is_some() {
+            self.command_palette(ctx, session, keybindings, flags);
+        }
+        self.precision_badge(ctx, session.view.precision(session.fractal.double_double()), flags);
+        if session.breadcrumbs.list().len() > 1 {
+            self.breadcrumbs(ctx, &session.breadcrumbs, session.view, flags);
+        }
+        if flags.show_timeline {
+            self.timeline(ctx, &mut session.timeline, session.fractal.as_ref(), session.recorder.as_ref(), flags);
+        }
+        if self.step_through.is_some() {
+            self.step_through(ctx, session.fractal.as_ref(), session.view);
+        }
+        if self.show_escape_radius {
+            self.escape_radius(ctx, session.fractal.as_ref(), session.view);
+        }
+        if self.show_locator {
+            self.locator(ctx, session.fractal.as_ref(), session.view, flags);
+        }
+        if self.show_area {
+            self.area(ctx, session.fractal.as_ref(), session.view);
+        }
+        if self.show_watermark {
+            self.watermark(ctx, session.view, flags);
+        }
+        if self.show_navigation {
+            self.navigation(ctx, flags);
+        }
+        if self.show_defaults {
+            self.defaults(ctx, session.fractal.as_mut(), session.view, flags);
+        }
+        if self.show_rays && session.fractal.is::<Mandelbrot>() {
+            self.rays(ctx, session.view);
+        }
+        if self.edit_roots {
+            if let Some(newton) = session.fractal.downcast_mut::<Newton>() {
+                if newton.function == NewtonFunction::Polynomial {
+                    self.newton_roots(ctx, newton, session.view, flags);
+                } else {
+                    self.found_roots(ctx, newton, session.view);
+                }
+            }
+        }
+        let current_fractal = &mut session.fractal;
+        let compare = &mut session.compare;
+        let rotation = &mut session.view.rotation;
+
+        if compare.enabled {
+            self.compare_labels(ctx, compare);
+        }
+        self.context_menu(ctx, current_fractal.downcast_ref::<Newton>().map(|newton| newton.function), flags);
+        if flags.show_bookmarks {
+            self.bookmarks(ctx, bookmarks, flags);
+        }
+        if flags.show_console {
+            self.console(ctx, flags);
+        }
+        if self.show_log {
+            self.log(ctx);
+        }
+        if flags.show_help {
+            self.help(ctx, keybindings, flags);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.show_graphics {
+            self.graphics(ctx, flags);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.show_watch_folder {
+            self.watch_folder(ctx, flags);
+        }
+        if self.show_appearance {
+            self.appearance(ctx, current_fractal.as_mut(), flags);
+        }
+        if self.show_quality {
+            self.quality(ctx, keybindings, flags);
+        }
+        if self.show_adjustments {
+            self.adjustments(ctx, flags);
+        }
+        if flags.highlight.enabled {
+            self.iteration_bands(ctx, current_fractal.max_iterations(), flags);
+        }
+        if self.show_colour_curve {
+            self.colour_curve(ctx, current_fractal.colour_gradient_mut(), flags);
+        }
+        if let Some(render) = &flags.final_render {
+            self.final_render_progress(ctx, render.progress, render.time_left(), render.is_region(), flags);
+        }
+        if self.show_render_stats {
+            self.render_stats(ctx, flags);
+        }
+        if flags.orbit_point.is_some() {
+            self.orbit(ctx, current_fractal.as_ref(), flags);
+        }
+        if flags.julia_preview {
+            if let Some(mandelbrot) = current_fractal.downcast_ref::<Mandelbrot>() {
+                self.julia_preview(ctx, mandelbrot, flags);
+            }
+        }
+        if let Some(hover) = flags.hover.filter(|_| flags.inspector) {
+            // in compare mode inspect whichever parameter set is under the mouse
+            let divider_x = ctx.screen_rect().width() * compare.divider;
+            let fractal = if compare.enabled && hover.0.0 / ctx.pixels_per_point() > divider_x { &compare.fractal } else { &*current_fractal };
+            // statistics of the box being dragged out, from the counts of the frame it's drawn over
+            let selection = flags.zoom_box.filter(|(start, end)| flags.navigation.is_box(*start, *end)).map(|((x1, y1), (x2, y2))| {
+                let window = ctx.screen_rect().size() * ctx.pixels_per_point();
+                let stats = flags.iteration_counts.region((x1 / window.x, y1 / window.y), (x2 / window.x, y2 / window.y));
+                let size = ((x1 - x2).abs() as f64 * session.view.zoom, (y1 - y2).abs() as f64 * session.view.zoom);
+                (size, stats)
+            });
+            self.inspector(ctx, fractal.as_ref(), hover.1, selection);
+        }
+
         let size = self.get_window_size();
-        egui::Area::new("Settings")
-        .fixed_pos(self.window_position)
-        .movable(false)
+        let position = self.config.settings_dock.position(ctx.screen_rect(), size.into())
+            .unwrap_or(self.config.settings_position.into());
+        let area = egui::Area::new("Settings")
+        .current_pos(position)
+        .movable(true)
         .show(ctx, |ui| {
             // Change size depending on if the window is open or closed
             ui.set_width(size.0);
@@ -226,114 +852,2472 @@ impl Gui {
             }
             
             // Add background to area
-            ui.painter().rect_stroke(ui.max_rect(), 2.0, Stroke::new(1.0, egui::Color32::from_rgba_unmultiplied(150, 150, 150, 200)));
-            ui.painter().rect_filled(ui.max_rect(), 2.0, egui::Color32::from_rgba_premultiplied(0, 0, 0, 255));
+            let visuals = ui.visuals();
+            ui.painter().rect_filled(ui.max_rect(), 2.0, visuals.window_fill());
+            ui.painter().rect_stroke(ui.max_rect(), 2.0, visuals.window_stroke());
 
-            let drop_down_title = RichText::new("Settings").color(egui::Color32::WHITE).font(self.font.clone());
-            let collapse_button = ui.collapsing(drop_down_title, |ui| {
+            let drop_down_title = RichText::new(tr("Settings")).color(ui.visuals().strong_text_color()).font(self.font.clone());
+            let collapse_button = egui::CollapsingHeader::new(drop_down_title).id_source("Settings").show(ui, |ui| {
                 ui.separator();
 
-                let display_name = match current_fractal {
-                    Fractals::Mandelbrot {..} => "Mandelbrot",
-                    Fractals::Julia {..} => "Julia",
-                    Fractals::Newton {..} => "Newton"
-                };
+                // A/B compare mode
+                ui.horizontal(|ui| {
+                    let checkbox = ui.checkbox(&mut compare.enabled, RichText::new(tr("A/B Compare")).font(self.font.clone()));
+                    if checkbox.changed() {
+                        // start B off as a copy of the current parameters
+                        compare.fractal = current_fractal.clone();
+                        compare.editing_b = false;
+                        flags.generate_fractal = true;
+                    }
+                    if compare.enabled {
+                        ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                            ui.add_space(10.0);
+                            ui.selectable_value(&mut compare.editing_b, true, RichText::new("B").font(self.font.clone()));
+                            ui.selectable_value(&mut compare.editing_b, false, RichText::new("A").font(self.font.clone()));
+                            ui.label(RichText::new(tr("Editing:")).font(self.font.clone()));
+                        });
+                    }
+                });
+                // the rest of the settings edit whichever parameter set is selected
+                let current_fractal = if compare.enabled && compare.editing_b { &mut compare.fractal } else { current_fractal };
+
+                let display_name = current_fractal.name();
                 
                 // Fractal selection
                 ui.horizontal(|ui| {
-                    ui.label(RichText::new("Fractal:").font(self.font.clone()));
+                    ui.label(RichText::new(tr("Fractal:")).font(self.font.clone()));
                     ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
                         ui.add_space(10.0); // add space to right side of combo box
                         egui::ComboBox::from_label("")
                         .selected_text(display_name)
                         .show_ui(ui, |ui| {
-                            ui.selectable_value(current_fractal, Fractals::Mandelbrot {max_iterations: 100, escape_radius: 2.0, colour_gradient: "Magma".into()},
-                                RichText::new("Mandelbrot").font(self.font.clone()));
-                            ui.selectable_value(current_fractal, Fractals::Julia {max_iterations: 100, escape_radius: 2.0, c: (-0.7,0.27015), colour_gradient: "Magma".into()}, 
-                                RichText::new("Julia").font(self.font.clone()));
-                            ui.selectable_value(current_fractal, Fractals::Newton {max_iterations: 100, colour_gradient: "Magma".into()},
-                                RichText::new("Newton").font(self.font.clone()));
+                            for name in fractals::names() {
+                                // switching is done outside of the gui so the previous settings and view can be restored
+                                if ui.selectable_label(display_name == name, RichText::new(name).font(self.font.clone())).clicked() && display_name != name {
+                                    flags.switch_fractal = Some(name);
+                                }
+                            }
                         })
                     });
                 });
 
                 // Colour gradient selection
-                let current_colour_gradient = match current_fractal {
-                    Fractals::Mandelbrot {ref mut colour_gradient, ..} => colour_gradient,
-                    Fractals::Julia {ref mut colour_gradient, ..} => colour_gradient,
-                    Fractals::Newton {ref mut colour_gradient, ..} => colour_gradient
-                };
+                let current_colour_gradient = current_fractal.colour_gradient_mut();
                 let old_colour = current_colour_gradient.clone();
                 ui.horizontal(|ui| {
-                    ui.label(RichText::new("Colour:").font(self.font.clone()));
+                    ui.label(RichText::new(tr("Colour:")).font(self.font.clone()));
                     ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
                         ui.add_space(10.0); // add space to right side of combo box
+                        ui.toggle_value(&mut self.show_colour_curve, RichText::new(tr("Curve")).font(self.font.clone()))
+                            .on_hover_text(tr("Edit how iteration counts map onto the palette"));
+                        ui.toggle_value(&mut flags.palette_previews, RichText::new(tr("Previews")).font(self.font.clone()))
+                            .on_hover_text(tr("Show the view in every palette"));
+                        ui.toggle_value(&mut flags.auto_levels, RichText::new(tr("Auto levels")).font(self.font.clone()))
+                            .on_hover_text(tr("Spread the palette over the iterations in view rather than up to max iterations"));
                         egui::ComboBox::from_label(" ")
                         .selected_text(current_colour_gradient.clone())
                         .show_ui(ui, |ui| {
                             let font = self.font.clone();
-                            for colour_gradient in COLOUR_GRADIENTS.iter() {
-                                let colour_gradient = *colour_gradient;
-                                create_colour_gradient_option!(ui, current_colour_gradient, font, colour_gradient);
+                            for colour_gradient in colour_gradient_names() {
+                                let colour_gradient = colour_gradient.as_str();
+                                let option = create_colour_gradient_option!(ui, current_colour_gradient, font, colour_gradient);
+                                if COLOUR_BLIND_SAFE.contains(&colour_gradient) {
+                                    option.on_hover_text(tr("Colour blind friendly"));
+                                }
+                            }
+                        });
+                    });
+                });
+                if flags.palette_previews {
+                    self.palette_previews(ui, current_colour_gradient, &flags.iteration_counts, flags.auto_levels);
+                } else {
+                    self.palette_previews = None;
+                }
+
+                // Preview the image as seen with a colour vision deficiency
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(tr("Simulate:")).font(self.font.clone()));
+                    ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                        ui.add_space(10.0);
+                        let selected = flags.colour_blind_simulation.map_or(tr("Normal vision"), |simulation| tr(simulation.name()));
+                        egui::ComboBox::from_id_source("Simulation")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut flags.colour_blind_simulation, None, RichText::new(tr("Normal vision")).font(self.font.clone()));
+                            for simulation in SIMULATIONS {
+                                ui.selectable_value(&mut flags.colour_blind_simulation, Some(simulation), RichText::new(tr(simulation.name())).font(self.font.clone()));
+                            }
+                        });
+                    });
+                });
+
+                // Where the fractal is drawn, anything the backend can't draw falls back to the CPU
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(tr("Renderer:")).font(self.font.clone()));
+                    ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                        ui.add_space(10.0);
+                        let mut changed = false;
+                        egui::ComboBox::from_id_source("Renderer")
+                        .selected_text(flags.renderer.name())
+                        .show_ui(ui, |ui| {
+                            for backend in BACKENDS {
+                                changed |= ui.selectable_value(&mut flags.renderer, backend, RichText::new(backend.name()).font(self.font.clone()))
+                                    .on_hover_text(tr(backend.description()))
+                                    .changed();
                             }
                         });
+                        if changed {
+                            flags.generate_fractal = true;
+                            self.config.renderer = flags.renderer;
+                            if let Err(e) = self.config.save() {
+                                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+                            }
+                        }
+                    });
+                });
+
+                // Generating fewer pixels than the window has is faster, more is sharper
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(tr("Render scale:")).font(self.font.clone()));
+                    ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                        ui.add_space(10.0);
+                        let mut percent = (flags.render_scale * 100.0).round();
+                        let slider = egui::Slider::new(&mut percent, 50.0..=200.0).suffix("%").step_by(5.0).clamp_to_range(true);
+                        let response = ui.add(slider).on_hover_text(tr("Pixels generated for each pixel of the window, lower is faster and higher is sharper"));
+                        if response.changed() {
+                            // the buffer is resized to the new scale and regenerated on the next redraw
+                            flags.render_scale = percent / 100.0;
+                        }
+                        // saved once the slider is let go rather than on every step of a drag
+                        if (response.changed() && !response.dragged()) || response.drag_released() {
+                            self.config.render_scale = flags.render_scale;
+                            if let Err(e) = self.config.save() {
+                                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+                            }
+                        }
+                    });
+                });
+
+                // View rotation
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(tr("Rotation:")).font(self.font.clone()));
+                    ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                        ui.add_space(10.0);
+                        let slider = egui::Slider::new(rotation, -180.0..=180.0).suffix("°").clamp_to_range(true);
+                        flags.generate_fractal |= ui.add(slider).changed();
                     });
                 });
 
+                ui.horizontal(|ui| {
+                    flags.generate_fractal |= ui.checkbox(&mut flags.edges, RichText::new(tr("Boundary only")).font(self.font.clone())).changed();
+                    if flags.edges {
+                        ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                            ui.add_space(10.0);
+                            flags.generate_fractal |= ui.color_edit_button_srgb(&mut flags.edge_style.background).on_hover_text(tr("Background")).changed();
+                            flags.generate_fractal |= ui.color_edit_button_srgb(&mut flags.edge_style.line).on_hover_text(tr("Line")).changed();
+                        });
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut flags.inspector, RichText::new(tr("Inspector")).font(self.font.clone()));
+                    ui.checkbox(&mut flags.screenshot_ui, RichText::new(tr("Screenshots include UI")).font(self.font.clone()));
+                    ui.checkbox(&mut self.show_render_stats, RichText::new(tr("Render stats")).font(self.font.clone()));
+                });
+                ui.horizontal_wrapped(|ui| {
+                    ui.toggle_value(&mut flags.show_bookmarks, RichText::new(tr("Bookmarks")).font(self.font.clone()));
+                    ui.toggle_value(&mut self.show_appearance, RichText::new(tr("Appearance")).font(self.font.clone()));
+                    ui.toggle_value(&mut self.show_quality, RichText::new(tr("Quality")).font(self.font.clone()));
+                    ui.toggle_value(&mut self.show_adjustments, RichText::new(tr("Adjustments")).font(self.font.clone()));
+                    ui.toggle_value(&mut flags.highlight.enabled, RichText::new(tr("Iteration bands")).font(self.font.clone()));
+                    ui.toggle_value(&mut self.show_watermark, RichText::new(tr("Watermark")).font(self.font.clone()));
+                    ui.toggle_value(&mut self.show_defaults, RichText::new(tr("Defaults")).font(self.font.clone()))
+                        .on_hover_text(tr("What this fractal starts with and is reset to"));
+                    ui.toggle_value(&mut self.show_navigation, RichText::new(tr("Navigation")).font(self.font.clone()))
+                        .on_hover_text(tr("How far the pan keys and mouse wheel move"));
+                    // the browser picks the graphics card and present mode itself
+                    #[cfg(not(target_arch = "wasm32"))]
+                    ui.toggle_value(&mut self.show_graphics, RichText::new(tr("Graphics")).font(self.font.clone()));
+                    #[cfg(not(target_arch = "wasm32"))]
+                    ui.toggle_value(&mut self.show_watch_folder, RichText::new(tr("Watch folder")).font(self.font.clone()));
+                    ui.toggle_value(&mut flags.show_timeline, RichText::new(tr("Timeline")).font(self.font.clone()));
+                    ui.toggle_value(&mut self.show_area, RichText::new(tr("Area")).font(self.font.clone()));
+                    ui.toggle_value(&mut flags.show_console, RichText::new(tr("Console")).font(self.font.clone()));
+                    ui.toggle_value(&mut self.show_log, RichText::new(tr("Log")).font(self.font.clone()));
+                });
+
                 ui.separator();
                 
-                let font = &self.font;
-                // create a mutable reference to fractal_change as "." cant be used inside a macro call
-                let generate_fractal = &mut flags.generate_fractal;
                 // Display the correct settings for the selected fractal
-                match current_fractal {
-                    Fractals::Mandelbrot { ref mut max_iterations, ref mut escape_radius, ref colour_gradient,.. } => {
-                        let slider1 = egui::Slider::new(max_iterations, 1..=10000).text("").clamp_to_range(true);
-                        let slider2 = egui::Slider::new(escape_radius, 1.0..=10.0).text("").clamp_to_range(true);
-                        create_fractal_setting!(ui, generate_fractal, font, ("Max Iterations", slider1), ("Escape Radius", slider2));
-                        
-                        flags.reset |= display_name != "Mandelbrot";
-                        flags.generate_fractal |= flags.reset || old_colour != *colour_gradient
-                    },
-                    Fractals::Julia { ref mut max_iterations, ref mut escape_radius, ref mut c, ref mut colour_gradient, ..} => {
-                        let slider1 = egui::Slider::new(max_iterations, 1..=10000).text("").clamp_to_range(true);
-                        let slider2 = egui::Slider::new(escape_radius, 1.0..=10.0).text("").clamp_to_range(true);
-                        let slider3 = egui::Slider::new(&mut c.0, -1.5..=1.5).clamp_to_range(true);
-                        let slider4 = egui::Slider::new(&mut c.1, -1.5..=1.5).clamp_to_range(true);
-                        create_fractal_setting!(ui, generate_fractal, font, ("Max Iterations", slider1), ("Escape Radius", slider2), ("Real", slider3), ("Imaginary", slider4));
-                        
-                        flags.reset |= display_name != "Julia";
-                        flags.generate_fractal |= flags.reset || old_colour != *colour_gradient
-                    },
-                    Fractals::Newton { ref mut max_iterations, ref mut colour_gradient,.. } => {
-                        let slider1 = egui::Slider::new(max_iterations, 1..=10000).text("").clamp_to_range(true);
-                        create_fractal_setting!(ui, generate_fractal, font, ("Max Iterations", slider1));
-
-                        flags.reset |= display_name != "Newton";
-                        flags.generate_fractal |= flags.reset || old_colour != *colour_gradient
+                let mut settings = FractalSettings { ui, font: &self.font, changed: false };
+                current_fractal.settings(&mut settings);
+                flags.generate_fractal |= settings.changed || old_colour != *current_fractal.colour_gradient_mut();
+                if current_fractal.is::<Mandelbrot>() {
+                    ui.checkbox(&mut self.show_rays, RichText::new(tr("External rays and equipotentials")).font(self.font.clone()));
+                    ui.checkbox(&mut flags.julia_preview, RichText::new(tr("Julia preview")).font(self.font.clone()))
+                        .on_hover_text(tr("Show the Julia set of the point under the mouse in the corner, click it to explore that set"));
+                }
+                if current_fractal.escape_radius().is_some() {
+                    ui.checkbox(&mut self.show_escape_radius, RichText::new(tr("Show escape radius")).font(self.font.clone()))
+                        .on_hover_text(tr("Draw the boundary points have to cross to escape, right click a point to watch it escape"));
+                    if !self.show_escape_radius {
+                        self.escape_demo = None;
                     }
-                };
+                }
+                match current_fractal.downcast_ref::<Newton>().map(|newton| newton.function) {
+                    Some(NewtonFunction::Polynomial) => {
+                        ui.checkbox(&mut self.edit_roots, RichText::new(tr("Edit roots")).font(self.font.clone()))
+                            .on_hover_text(tr("Drag the roots to move them, right click one to remove it or right click the fractal to add one"));
+                    }
+                    Some(_) => {
+                        ui.checkbox(&mut self.edit_roots, RichText::new(tr("Show roots")).font(self.font.clone()))
+                            .on_hover_text(tr("Mark the roots found in the view"));
+                    }
+                    None => {}
+                }
+                if current_fractal.randomizable() {
+                    ui.horizontal(|ui| {
+                        let randomize = ui.button(RichText::new(tr("Randomize")).font(self.font.clone()))
+                            .on_hover_text(tr("Pick random parameters that tend to give interesting results")).clicked();
+                        if randomize {
+                            self.random_seed = format!("seed:{}", Random::text_seed());
+                        }
+                        let input = ui.add(egui::TextEdit::singleline(&mut self.random_seed).hint_text("seed:").desired_width(120.0))
+                            .on_hover_text(tr("Share the seed to give someone the same parameters, or enter one you were given"));
+                        let entered = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        let seed = self.random_seed.trim();
+                        let seed = seed.strip_prefix("seed:").unwrap_or(seed).trim();
+                        if (randomize || entered) && !seed.is_empty() {
+                            current_fractal.randomize(&mut Random::from_text(seed));
+                            flags.generate_fractal = true;
+                        }
+                    });
+                }
+                if let Some(max_iterations) = current_fractal.parameter("Max Iterations") {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut flags.suggest_iterations, RichText::new(tr("Suggest iterations")).font(self.font.clone()))
+                            .on_hover_text(tr("Work out how many iterations the edge of the set needs each time the view is drawn"));
+                        if !flags.suggest_iterations {
+                            return;
+                        }
+                        match flags.iteration_counts.suggestion() {
+                            None => {
+                                ui.spinner();
+                            }
+                            Some(None) => {
+                                ui.label(RichText::new(tr("No edge in view")).font(self.font.clone()).weak());
+                            }
+                            Some(Some(suggested)) => {
+                                ui.label(RichText::new(format!("{} {}", tr("90% of boundary pixels need ≤"), suggested)).font(self.font.clone()));
+                                if ui.add_enabled(suggested != max_iterations as u32, egui::Button::new(RichText::new(tr("Apply")).font(self.font.clone()))).clicked() {
+                                    current_fractal.set_parameter("Max Iterations", suggested as f64);
+                                    flags.generate_fractal = true;
+                                }
+                            }
+                        }
+                    });
+                }
 
-                // Reset button in bottom right
+                // Reset buttons in bottom right
                 if self.window_open {
                     ui.with_layout(egui::Layout::right_to_left(Align::BOTTOM), |ui| {
-                        ui.add_space(10.0); // add space to the right of the button
+                        ui.add_space(10.0); // add space to the right of the buttons
                         ui.with_layout(egui::Layout::bottom_up(Align::RIGHT), |ui| {
-                            ui.add_space(10.0); // add space below the button
-                            if ui.button("Reset").clicked() {
-                                *current_fractal = match current_fractal{
-                                    Fractals::Mandelbrot {colour_gradient, ..} => Fractals::Mandelbrot {max_iterations: 100, escape_radius: 2.0, colour_gradient: colour_gradient.to_string()},
-                                    Fractals::Julia {colour_gradient,..} => Fractals::Julia {max_iterations: 100, escape_radius: 2.0, c: (-0.7,0.27015), colour_gradient: colour_gradient.to_string()},
-                                    Fractals::Newton {colour_gradient,..} => Fractals::Newton {max_iterations: 100, colour_gradient: colour_gradient.to_string()}
-                                };
-                                flags.reset = true;
-                                flags.generate_fractal = true;
-                            }
+                            ui.add_space(10.0); // add space below the buttons
+                            ui.with_layout(egui::Layout::right_to_left(Align::BOTTOM), |ui| {
+                                // Reset the zoom/offset and the sliders
+                                if ui.button(tr("Reset all")).clicked() {
+                                    *current_fractal = flags.profiles.reset(current_fractal.as_ref());
+                                    flags.reset = true;
+                                    flags.generate_fractal = true;
+                                }
+                                // Reset the sliders but keep the current view
+                                if ui.button(tr("Reset parameters")).clicked() {
+                                    *current_fractal = flags.profiles.reset(current_fractal.as_ref());
+                                    flags.generate_fractal = true;
+                                }
+                                // Reset the zoom/offset but keep the sliders
+                                if ui.button(tr("Reset view")).clicked() {
+                                    flags.reset = true;
+                                    flags.generate_fractal = true;
+                                }
+                            });
                         });
                     });
                 }
             });
             self.window_open = collapse_button.fully_open();
-        });   
+        });
+
+        // dragging undocks the window, dropping it near a corner docks it again
+        let response = area.response;
+        if response.dragged() {
+            let rect = response.rect.translate(response.drag_delta());
+            self.config.settings_dock = Dock::Free;
+            self.config.settings_position = rect.min.into();
+        }
+        if response.drag_released() {
+            self.config.settings_dock = Dock::snap(response.rect, ctx.screen_rect());
+            if let Err(e) = self.config.save() {
+                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+            }
+        }
+    }
+
+    /// Warn when the view is zoomed in too far for f64
+    fn precision_badge(&mut self, ctx: &Context, precision: Precision, flags: &mut Flags) {
+        if precision == Precision::Exhausted && self.precision != Precision::Exhausted {
+            flags.toasts.warning(tr("Precision limit reached"));
+        }
+        self.precision = precision;
+
+        let (text, colour, explanation) = match precision {
+            Precision::Ok => return,
+            Precision::Extended => ("Double-double precision", ctx.style().visuals.weak_text_color(),
+                "The view is past the limit of 64 bit floating point numbers, \
+                so it is drawn in double-double precision which is a few times slower."),
+            Precision::Approaching => ("⚠ Near precision limit", ctx.style().visuals.warn_fg_color,
+                "The view is close to the limit of the floating point numbers it is drawn in. \
+                Zooming much further will make the image blocky."),
+            Precision::Exhausted => ("⚠ Precision limit", ctx.style().visuals.error_fg_color,
+                "Neighbouring pixels are closer together than the floating point numbers the view is drawn in can represent, \
+                so groups of pixels get the same value and the image goes blocky. \
+                The Mandelbrot and Julia sets switch to double-double precision by themselves, \
+                zooming deeper than that needs a perturbation renderer."),
+        };
+        egui::Area::new("Precision")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(RichText::new(tr(text)).font(self.font.clone()).color(colour))
+                    .on_hover_text(tr(explanation));
+            });
+        });
+    }
+
+    /// Tabs along the top of the window for switching between sessions
+    fn session_tabs(&self, ctx: &Context, sessions: &Sessions, flags: &mut Flags) {
+        egui::Area::new("Sessions")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 10.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for (i, session) in sessions.list.iter().enumerate() {
+                        let title = format!("{}: {}", i + 1, session.title());
+                        if ui.selectable_label(i == sessions.current, RichText::new(title).font(self.font.clone())).clicked() {
+                            flags.session_action = Some(SessionAction::Switch(i));
+                        }
+                        if sessions.list.len() > 1 && ui.small_button("x").on_hover_text(tr("Close")).clicked() {
+                            flags.session_action = Some(SessionAction::Close(i));
+                        }
+                        ui.separator();
+                    }
+                    if ui.button(RichText::new("+").font(self.font.clone())).on_hover_text(tr("New session")).clicked() {
+                        flags.session_action = Some(SessionAction::New);
+                    }
+                    ui.toggle_value(&mut flags.link_views, RichText::new(tr("Link views")).font(self.font.clone()))
+                        .on_hover_text(tr("Panning and zooming in one tab moves the others to the same region"));
+                });
+            });
+        });
+    }
+
+    /// The magnifications passed on the way down to the view along the bottom, clicking one jumps back to it
+    fn breadcrumbs(&self, ctx: &Context, breadcrumbs: &Breadcrumbs, view: View, flags: &mut Flags) {
+        // above the final render progress while it's shown
+        let offset = if flags.final_render.is_some() { -50.0 } else { -10.0 };
+        egui::Area::new("Breadcrumbs")
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, offset))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for (i, waypoint) in breadcrumbs.list().iter().enumerate() {
+                        if i > 0 {
+                            ui.label(RichText::new("›").font(self.font.clone()).weak());
+                        }
+                        let label = RichText::new(magnification(waypoint.level)).font(self.font.clone());
+                        if ui.selectable_label(waypoint.view == view, label).on_hover_text(tr("Jump back to this depth")).clicked() {
+                            flags.go_to_waypoint = Some(i);
+                        }
+                    }
+                });
+            });
+        });
+    }
+
+    /// Every command searchable by name, Enter or a click runs the highlighted one
+    fn command_palette(&mut self, ctx: &Context, session: &mut Session, keybindings: &KeyBindings, flags: &mut Flags) {
+        let Some(palette) = self.command_palette.as_mut() else {
+            return;
+        };
+        let matching: Vec<(String, Command)> = palette.commands.iter()
+            .map(|command| (command.label(), command))
+            .filter(|(label, _)| commands::matches(label, &palette.query))
+            .map(|(label, command)| (label, command.clone()))
+            .collect();
+        let (up, down, enter, escape) = ctx.input(|i| {
+            (i.key_pressed(egui::Key::ArrowUp), i.key_pressed(egui::Key::ArrowDown), i.key_pressed(egui::Key::Enter), i.key_pressed(egui::Key::Escape))
+        });
+        if down {
+            palette.selected = (palette.selected + 1).min(matching.len().saturating_sub(1));
+        }
+        if up {
+            palette.selected = palette.selected.saturating_sub(1);
+        }
+        let mut chosen = None;
+        egui::Window::new(RichText::new(tr("Commands")).font(self.font.clone()))
+        .id(egui::Id::new("Command palette"))
+        .title_bar(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            let input = ui.add(egui::TextEdit::singleline(&mut palette.query).hint_text(tr("Type a command")).desired_width(360.0));
+            input.request_focus();
+            if input.changed() {
+                palette.selected = 0;
+            }
+            egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                for (i, (label, command)) in matching.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let response = ui.selectable_label(i == palette.selected, RichText::new(label).font(self.font.clone()));
+                        if i == palette.selected && (up || down) {
+                            response.scroll_to_me(None);
+                        }
+                        if response.clicked() {
+                            chosen = Some(command.clone());
+                        }
+                        // the keys that do the same, so they can be learnt
+                        if let Command::Action(action) = command {
+                            let keys: Vec<String> = keybindings.list.iter()
+                                .filter(|binding| binding.action == *action)
+                                .flat_map(|binding| binding.keys.iter().map(|key| key_name(*key)))
+                                .collect();
+                            ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                                ui.label(RichText::new(keys.join(" / ")).font(self.font.clone()).weak());
+                            });
+                        }
+                    });
+                }
+                if matching.is_empty() {
+                    ui.label(RichText::new(tr("No matching commands")).font(self.font.clone()).weak());
+                }
+            });
+        });
+        if enter {
+            chosen = matching.get(palette.selected).map(|(_, command)| command.clone());
+        }
+        if escape || chosen.is_some() {
+            self.command_palette = None;
+        }
+        if let Some(command) = chosen {
+            self.run_command(command, session, flags);
+        }
+    }
+
+    /// Do what a command from the palette says, the keyboard actions are done by main as if the key was pressed
+    fn run_command(&mut self, command: Command, session: &mut Session, flags: &mut Flags) {
+        match command {
+            Command::Action(action) => flags.run_action = Some(action),
+            Command::History(step) => flags.history_step = Some(step),
+            Command::SwitchFractal(name) => {
+                if name != session.fractal.name() {
+                    flags.switch_fractal = Some(name);
+                }
+            }
+            Command::SetPalette(name) => {
+                *session.fractal.colour_gradient_mut() = name;
+                flags.generate_fractal = true;
+            }
+            Command::GoToBookmark { index, .. } => flags.bookmark_action = Some(BookmarkAction::GoTo(index)),
+            Command::Toggle(overlay) => match overlay {
+                Overlay::Inspector => flags.inspector = !flags.inspector,
+                Overlay::Compare => {
+                    // start B off as a copy of the current parameters, as the checkbox does
+                    let compare = &mut session.compare;
+                    compare.enabled = !compare.enabled;
+                    compare.fractal = session.fractal.clone();
+                    compare.editing_b = false;
+                    flags.generate_fractal = true;
+                }
+                Overlay::Edges => {
+                    flags.edges = !flags.edges;
+                    flags.generate_fractal = true;
+                }
+                Overlay::IterationBands => flags.highlight.enabled = !flags.highlight.enabled,
+                Overlay::JuliaPreview => flags.julia_preview = !flags.julia_preview,
+                Overlay::EscapeRadius => {
+                    self.show_escape_radius = !self.show_escape_radius;
+                    self.escape_demo = None;
+                }
+                Overlay::ExternalRays => self.show_rays = !self.show_rays,
+                Overlay::PalettePreviews => flags.palette_previews = !flags.palette_previews,
+                Overlay::RenderStats => self.show_render_stats = !self.show_render_stats,
+            },
+            Command::SaveSession => flags.context_action = Some(ContextAction::SaveSession),
+            Command::SaveMask => flags.context_action = Some(ContextAction::SaveMask { antialiased: false }),
+            Command::ExportVideo => flags.timeline_action = Some(TimelineAction::Export),
+        }
+    }
+
+    /// Gallery of saved views, clicking a thumbnail goes to that view
+    fn bookmarks(&mut self, ctx: &Context, bookmarks: &Bookmarks, flags: &mut Flags) {
+        self.upload_thumbnails(ctx, bookmarks);
+        let mut open = flags.show_bookmarks;
+        egui::Window::new(RichText::new(tr("Bookmarks")).font(self.font.clone()))
+        .id(egui::Id::new("Bookmarks"))
+        .open(&mut open)
+        .default_pos((ctx.screen_rect().width() - 220.0, 10.0))
+        .default_width(200.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.bookmark_name).hint_text(tr("Name")).desired_width(110.0));
+                if ui.button(tr("Save view")).clicked() {
+                    let name = if self.bookmark_name.is_empty() {
+                        format!("{} {}", tr("Bookmark"), bookmarks.list.len() + 1)
+                    } else {
+                        std::mem::take(&mut self.bookmark_name)
+                    };
+                    flags.bookmark_action = Some(BookmarkAction::Save(name));
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (i, bookmark) in bookmarks.list.iter().enumerate() {
+                    if let Some(texture) = self.thumbnails.get(&bookmark.thumbnail) {
+                        let (width, height) = bookmark.image_size;
+                        if ui.add(egui::ImageButton::new(texture.id(), egui::vec2(width as f32, height as f32))).clicked() {
+                            flags.bookmark_action = Some(BookmarkAction::GoTo(i));
+                        }
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.link(RichText::new(&bookmark.name).font(self.font.clone())).clicked() {
+                            flags.bookmark_action = Some(BookmarkAction::GoTo(i));
+                        }
+                        if ui.small_button("x").on_hover_text(tr("Delete")).clicked() {
+                            flags.bookmark_action = Some(BookmarkAction::Remove(i));
+                        }
+                    });
+                }
+            });
+
+            if !bookmarks.list.is_empty() {
+                ui.separator();
+                egui::CollapsingHeader::new(RichText::new(tr("Render gallery")).font(self.font.clone())).id_source("Render gallery").show(ui, |ui| {
+                    self.gallery(ui, flags);
+                });
+            }
+        });
+        flags.show_bookmarks = open;
+    }
+
+    /// Size and folder the bookmarks are rendered into at the final render quality, with the progress while they're rendering
+    fn gallery(&mut self, ui: &mut egui::Ui, flags: &mut Flags) {
+        let gallery = &mut self.gallery;
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut gallery.width).clamp_range(16..=16384).suffix(" px"));
+            ui.label("x");
+            ui.add(egui::DragValue::new(&mut gallery.height).clamp_range(16..=16384).suffix(" px"));
+        });
+        ui.add(egui::TextEdit::singleline(&mut gallery.folder).hint_text(tr("Folder, a new one if empty")).desired_width(180.0));
+        ui.checkbox(&mut gallery.index, RichText::new(tr("HTML index page")).font(self.font.clone()));
+        match &flags.gallery_export {
+            Some(export) => {
+                ui.add(egui::ProgressBar::new(export.rendered as f32 / export.total.max(1) as f32)
+                    .text(format!("{} / {}", export.rendered, export.total)));
+                if let Some(time_left) = export.time_left() {
+                    ui.label(RichText::new(format!("{} {}", format_duration(time_left), tr("left"))).font(self.font.clone()));
+                }
+            }
+            None => {
+                if ui.button(RichText::new(tr("Render gallery")).font(self.font.clone()))
+                    .on_hover_text(tr("Render every bookmark at the final render quality")).clicked() {
+                    let folder = gallery.folder.trim();
+                    flags.bookmark_action = Some(BookmarkAction::RenderGallery {
+                        size: (gallery.width, gallery.height),
+                        index: gallery.index,
+                        folder: (!folder.is_empty()).then(|| PathBuf::from(folder)),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Upload any new bookmark thumbnails and drop the textures of removed bookmarks
+    fn upload_thumbnails(&mut self, ctx: &Context, bookmarks: &Bookmarks) {
+        self.thumbnails.retain(|name, _| bookmarks.list.iter().any(|b| &b.thumbnail == name));
+        for bookmark in bookmarks.list.iter().filter(|b| !b.image.is_empty()) {
+            self.thumbnails.entry(bookmark.thumbnail.clone()).or_insert_with(|| {
+                let (width, height) = bookmark.image_size;
+                let image = egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &bookmark.image);
+                ctx.load_texture(&bookmark.thumbnail, image, egui::TextureOptions::LINEAR)
+            });
+        }
+    }
+
+    /// Shown at startup to carry on from the tabs open at the last exit or go to a recent bookmark
+    fn start_panel(&mut self, ctx: &Context, bookmarks: &Bookmarks, flags: &mut Flags) {
+        self.upload_thumbnails(ctx, bookmarks);
+        if let Some(last) = flags.last_session.as_ref().filter(|_| self.last_session_thumbnails.is_empty()) {
+            let window_size = ctx.screen_rect().size() * ctx.pixels_per_point();
+            self.last_session_thumbnails = last.sessions.iter().take(START_TABS).enumerate().map(|(i, session)| {
+                let (image, (width, height)) = thumbnail(session.fractal.as_ref(), session.view, (window_size.x as u32, window_size.y as u32));
+                let image = egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &image);
+                ctx.load_texture(format!("Last session {}", i), image, egui::TextureOptions::LINEAR)
+            }).collect();
+        }
+
+        let mut open = flags.show_start;
+        let mut start_fresh = false;
+        egui::Window::new(RichText::new(tr("Welcome back")).font(self.font.clone()))
+        .id(egui::Id::new("Start"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(ctx, |ui| {
+            if let Some(last) = &flags.last_session {
+                ui.label(RichText::new(tr("Last session")).font(self.font.clone()).strong());
+                ui.horizontal_wrapped(|ui| {
+                    for (i, texture) in self.last_session_thumbnails.iter().enumerate() {
+                        if ui.add(egui::ImageButton::new(texture.id(), texture.size_vec2() * START_THUMBNAIL_SCALE))
+                            .on_hover_text(tr("Continue from this tab"))
+                            .clicked() {
+                            flags.continue_last = Some(i);
+                        }
+                    }
+                });
+                if last.sessions.len() > START_TABS {
+                    ui.label(RichText::new(format!("+{} {}", last.sessions.len() - START_TABS, tr("more tabs"))).font(self.font.clone()).weak());
+                }
+                if ui.button(RichText::new(tr("Continue where I left off")).font(self.font.clone())).clicked() {
+                    flags.continue_last = Some(last.current);
+                }
+                ui.separator();
+            }
+            if !bookmarks.list.is_empty() {
+                ui.label(RichText::new(tr("Recent bookmarks")).font(self.font.clone()).strong());
+                ui.horizontal_wrapped(|ui| {
+                    // newer bookmarks are added at the end
+                    for (i, bookmark) in bookmarks.list.iter().enumerate().rev().take(START_BOOKMARKS) {
+                        let Some(texture) = self.thumbnails.get(&bookmark.thumbnail) else {
+                            continue;
+                        };
+                        if ui.add(egui::ImageButton::new(texture.id(), texture.size_vec2() * START_THUMBNAIL_SCALE))
+                            .on_hover_text(&bookmark.name)
+                            .clicked() {
+                            flags.bookmark_action = Some(BookmarkAction::GoTo(i));
+                        }
+                    }
+                });
+                ui.separator();
+            }
+            start_fresh = ui.button(RichText::new(tr("Start fresh")).font(self.font.clone())).clicked();
+        });
+        if !open || start_fresh || flags.continue_last.is_some() || flags.bookmark_action.is_some() {
+            flags.show_start = false;
+            self.last_session_thumbnails.clear();
+        }
+    }
+
+    /// Offer to bring back the sessions from a run that didn't exit normally
+    fn restore_prompt(&mut self, ctx: &Context, flags: &mut Flags) {
+        let Some(recovered) = &flags.recovered else {
+            return;
+        };
+        let tabs = recovered.sessions.len();
+        egui::Window::new(RichText::new(tr("Restore previous session?")).font(self.font.clone()))
+        .id(egui::Id::new("Restore"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(ctx, |ui| {
+            ui.label(RichText::new(tr("The app didn't close properly last time.")).font(self.font.clone()));
+            ui.label(RichText::new(format!("{}: {}", tr("Tabs that were open"), tabs)).font(self.font.clone()));
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button(RichText::new(tr("Restore")).font(self.font.clone())).clicked() {
+                    flags.restore = Some(true);
+                }
+                if ui.button(RichText::new(tr("Discard")).font(self.font.clone())).clicked() {
+                    flags.restore = Some(false);
+                }
+            });
+        });
+    }
+
+    /// Output and errors from scripts, and a line to type automation commands into
+    fn console(&mut self, ctx: &Context, flags: &mut Flags) {
+        let mut open = flags.show_console;
+        egui::Window::new(RichText::new(tr("Console")).font(self.font.clone()))
+        .id(egui::Id::new("Console"))
+        .open(&mut open)
+        .default_pos((10.0, ctx.screen_rect().height() - 260.0))
+        .default_width(400.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().max_height(180.0).stick_to_bottom(true).auto_shrink([false, true]).show(ui, |ui| {
+                for line in &flags.console.lines {
+                    let text = RichText::new(&line.text).monospace();
+                    if line.error {
+                        ui.colored_label(ui.visuals().error_fg_color, text);
+                    } else {
+                        ui.label(text);
+                    }
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                let input = ui.add(egui::TextEdit::singleline(&mut flags.console.input).code_editor().hint_text(tr("Script")).desired_width(220.0));
+                let submitted = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if let Some(automation) = &flags.console.automation {
+                    if ui.button(tr("Stop")).clicked() {
+                        automation.stop();
+                    }
+                } else if (ui.button(tr("Run")).clicked() || submitted) && !flags.console.input.trim().is_empty() {
+                    let source = std::mem::take(&mut flags.console.input);
+                    flags.console.output(format!("> {}", source));
+                    flags.run_script = Some(source);
+                    input.request_focus();
+                }
+                if ui.button(tr("Clear")).clicked() {
+                    flags.console.lines.clear();
+                }
+            });
+        });
+        flags.show_console = open;
+    }
+
+    /// Ui scale, font size, language and theme, saved to the config when changed
+    fn appearance(&mut self, ctx: &Context, fractal: &mut dyn Fractal, flags: &mut Flags) {
+        let mut open = self.show_appearance;
+        let mut changed = false;
+        egui::Window::new(RichText::new(tr("Appearance")).font(self.font.clone()))
+        .id(egui::Id::new("Appearance"))
+        .open(&mut open)
+        .default_pos((330.0, 10.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("Appearance").num_columns(2).show(ui, |ui| {
+                ui.label(RichText::new(tr("UI scale:")).font(self.font.clone()));
+                let slider = ui.add(egui::Slider::new(&mut self.ui_scale, 0.5..=3.0).step_by(0.05).clamp_to_range(true));
+                if slider.drag_released() || (slider.changed() && !slider.dragged()) {
+                    self.config.ui_scale = self.ui_scale;
+                    changed = true;
+                }
+                ui.end_row();
+
+                ui.label(RichText::new(tr("Font size:")).font(self.font.clone()));
+                let slider = ui.add(egui::Slider::new(&mut self.config.font_size, 8.0..=32.0).step_by(1.0).clamp_to_range(true));
+                self.font.size = self.config.font_size;
+                changed |= slider.drag_released() || (slider.changed() && !slider.dragged());
+                ui.end_row();
+            });
+            egui::Grid::new("Theme").num_columns(2).show(ui, |ui| {
+                ui.label(RichText::new(tr("Language:")).font(self.font.clone()));
+                egui::ComboBox::from_id_source("Language")
+                .selected_text(self.config.language.name())
+                .show_ui(ui, |ui| {
+                    for language in LANGUAGES {
+                        changed |= ui.selectable_value(&mut self.config.language, language, RichText::new(language.name()).font(self.font.clone())).changed();
+                    }
+                });
+                ui.end_row();
+
+                ui.label(RichText::new(tr("Theme:")).font(self.font.clone()));
+                egui::ComboBox::from_id_source("Theme")
+                .selected_text(tr(self.config.theme.name()))
+                .show_ui(ui, |ui| {
+                    for theme in THEMES {
+                        changed |= ui.selectable_value(&mut self.config.theme, theme, RichText::new(tr(theme.name())).font(self.font.clone())).changed();
+                    }
+                });
+                ui.end_row();
+
+                let mut custom_accent = self.config.accent.is_some();
+                if ui.checkbox(&mut custom_accent, RichText::new(tr("Accent:")).font(self.font.clone())).changed() {
+                    self.config.accent = custom_accent.then_some([255, 140, 0]);
+                    changed = true;
+                }
+                if let Some(accent) = &mut self.config.accent {
+                    changed |= ui.color_edit_button_srgb(accent).changed();
+                }
+                ui.end_row();
+
+                ui.label(RichText::new(tr("Settings position:")).font(self.font.clone()));
+                egui::ComboBox::from_id_source("Dock")
+                .selected_text(tr(self.config.settings_dock.name()))
+                .show_ui(ui, |ui| {
+                    for dock in DOCKS {
+                        changed |= ui.selectable_value(&mut self.config.settings_dock, dock, RichText::new(tr(dock.name())).font(self.font.clone())).changed();
+                    }
+                });
+                ui.end_row();
+
+                // how the zoom box looks while it's dragged out
+                let selection = &mut self.config.selection;
+                ui.label(RichText::new(tr("Selection:")).font(self.font.clone()));
+                ui.horizontal(|ui| {
+                    changed |= ui.color_edit_button_srgb(&mut selection.colour).changed();
+                    changed |= ui.checkbox(&mut selection.dim_outside, RichText::new(tr("Dim outside")).font(self.font.clone())).changed();
+                    changed |= ui.checkbox(&mut selection.show_size, RichText::new(tr("Show size")).font(self.font.clone()))
+                        .on_hover_text(tr("Show the width and height of the selection in the complex plane"))
+                        .changed();
+                    changed |= ui.checkbox(&mut selection.show_pixels, RichText::new(tr("Show pixels")).font(self.font.clone()))
+                        .on_hover_text(tr("Show the width and height of the selection in pixels of the window"))
+                        .changed();
+                });
+                ui.end_row();
+            });
+            ui.horizontal(|ui| {
+                if ui.button(tr("Reset")).clicked() {
+                    // only the appearance is reset, the startup defaults are kept
+                    let config = std::mem::take(&mut self.config);
+                    self.config.default_fractal = config.default_fractal;
+                    self.config.default_palette = config.default_palette;
+                    self.config.window_size = config.window_size;
+                    self.config.threads = config.threads;
+                    self.config.keybindings = config.keybindings;
+                    self.config.profiles = config.profiles;
+                    self.config.navigation = config.navigation;
+                    self.ui_scale = self.config.ui_scale;
+                    self.font.size = self.config.font_size;
+                    changed = true;
+                }
+                if ui.button(tr("Save current as defaults")).on_hover_text(tr("Start with this fractal, palette and window size")).clicked() {
+                    let size = ctx.screen_rect().size() * ctx.pixels_per_point();
+                    self.config.default_fractal = fractal.name().into();
+                    self.config.default_palette = fractal.colour_gradient_mut().clone();
+                    self.config.window_size = Some((size.x.round() as u32, size.y.round() as u32));
+                    match self.config.save() {
+                        Ok(()) => flags.toasts.success(tr("Saved defaults")),
+                        Err(e) => flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e)),
+                    }
+                }
+            });
+        });
+        self.show_appearance = open;
+
+        if changed {
+            ctx.set_visuals(self.config.visuals());
+            set_language(self.config.language);
+            if let Err(e) = self.config.save() {
+                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+            }
+        }
+    }
+
+    /// The settings used while exploring and for final renders side by side
+    fn quality(&mut self, ctx: &Context, keybindings: &KeyBindings, flags: &mut Flags) {
+        let mut open = self.show_quality;
+        let mut changed = false;
+        let mut save = false;
+        let exploring = flags.quality.exploring;
+        egui::Window::new(RichText::new(tr("Quality")).font(self.font.clone()))
+        .id(egui::Id::new("Quality"))
+        .open(&mut open)
+        .default_pos((330.0, 200.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("Quality").num_columns(3).show(ui, |ui| {
+                ui.label("");
+                ui.label(RichText::new(tr("Exploring")).font(self.font.clone()).strong());
+                ui.label(RichText::new(tr("Final")).font(self.font.clone()).strong());
+                ui.end_row();
+
+                let (exploring, final_render) = (&mut flags.quality.exploring, &mut flags.quality.final_render);
+                ui.label(RichText::new(tr("Supersampling:")).font(self.font.clone()))
+                    .on_hover_text(tr("Samples averaged for each pixel, smoothing the edges"));
+                for (id, quality) in [("Exploring supersampling", &mut *exploring), ("Final supersampling", &mut *final_render)] {
+                    egui::ComboBox::from_id_source(id)
+                    .selected_text(format!("{}x", quality.supersampling * quality.supersampling))
+                    .show_ui(ui, |ui| {
+                        for side in 1..=4 {
+                            if ui.selectable_value(&mut quality.supersampling, side, format!("{}x", side * side)).changed() {
+                                changed = true;
+                                save = true;
+                            }
+                        }
+                    });
+                }
+                ui.end_row();
+
+                ui.label(RichText::new(tr("Iterations:")).font(self.font.clone()))
+                    .on_hover_text(tr("The max iterations are multiplied by this"));
+                for quality in [&mut *exploring, &mut *final_render] {
+                    let response = ui.add(egui::DragValue::new(&mut quality.iteration_factor).clamp_range(1..=64).prefix("×"));
+                    changed |= response.changed();
+                    save |= response.drag_released() || (response.changed() && !response.dragged());
+                }
+                ui.end_row();
+
+                ui.label(RichText::new(tr("Smooth colouring")).font(self.font.clone()))
+                    .on_hover_text(tr("Colour by the continuous iteration count, removing the bands between counts"));
+                for quality in [&mut *exploring, &mut *final_render] {
+                    if ui.checkbox(&mut quality.smooth, "").changed() {
+                        changed = true;
+                        save = true;
+                    }
+                }
+                ui.end_row();
+
+                ui.label(RichText::new(tr("Adaptive sampling")).font(self.font.clone()))
+                    .on_hover_text(tr("Supersample only the parts of the image with detail, found from a first pass with one sample per pixel"));
+                for quality in [&mut *exploring, &mut *final_render] {
+                    if ui.add_enabled(quality.supersampling > 1, egui::Checkbox::new(&mut quality.adaptive, "")).changed() {
+                        changed = true;
+                        save = true;
+                    }
+                }
+                ui.end_row();
+
+                ui.label(RichText::new(tr("Tolerance:")).font(self.font.clone()))
+                    .on_hover_text(tr("How much the colours in a part of the image can vary before it's supersampled, lower is better quality and slower"));
+                for quality in [exploring, final_render] {
+                    let response = ui.add_enabled(quality.supersampling > 1 && quality.adaptive,
+                        egui::DragValue::new(&mut quality.tolerance).clamp_range(0.0..=50.0).speed(0.1).max_decimals(1));
+                    changed |= response.changed();
+                    save |= response.drag_released() || (response.changed() && !response.dragged());
+                }
+                ui.end_row();
+            });
+            if ui.checkbox(&mut flags.quality.refine_boundary, RichText::new(tr("Refine the boundary")).font(self.font.clone()))
+                .on_hover_text(tr("Draw the points at max iterations next to the boundary again with more iterations, for root, period and atom domain colouring"))
+                .changed() {
+                changed = true;
+                save = true;
+            }
+            ui.horizontal(|ui| {
+                let time_lapse = &mut flags.quality.time_lapse;
+                save |= ui.checkbox(&mut time_lapse.enabled, RichText::new(tr("Time-lapse every")).font(self.font.clone()))
+                    .on_hover_text(tr("Save final renders as numbered pngs while they're drawn, to watch long renders fill in"))
+                    .changed();
+                let response = ui.add_enabled(time_lapse.enabled, egui::DragValue::new(&mut time_lapse.interval).clamp_range(0.1..=600.0).speed(0.1).suffix("s"));
+                save |= response.drag_released() || (response.changed() && !response.dragged());
+            });
+            ui.separator();
+            let button = |label, action| {
+                let keys: Vec<String> = keybindings.list.iter()
+                    .filter(|binding| binding.action == action)
+                    .flat_map(|binding| binding.keys.iter().map(|key| key_name(*key)))
+                    .collect();
+                egui::Button::new(RichText::new(format!("{} ({})", tr(label), keys.join(", "))).font(self.font.clone()))
+            };
+            ui.horizontal(|ui| {
+                if ui.add_enabled(flags.final_render.is_none(), button("Render final quality", Action::FinalRender)).clicked() {
+                    flags.start_final_render = true;
+                }
+                if ui.add_enabled(flags.final_render.is_none(), button("Render region", Action::RenderRegion))
+                    .on_hover_text(tr("Draw a box around the region to render again"))
+                    .clicked() {
+                    flags.select_region = true;
+                }
+            });
+        });
+        self.show_quality = open;
+
+        if changed && flags.quality.exploring != exploring {
+            flags.generate_fractal = true;
+        }
+        // the iterations are saved once they're let go rather than on every step of the drag
+        if save {
+            self.config.quality = flags.quality;
+            if let Err(e) = self.config.save() {
+                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+            }
+        }
+    }
+
+    /// The selection being dragged out to zoom into, with the frame outside it dimmed and its size in the complex plane
+    fn zoom_box(&self, ctx: &Context, ((x1, y1), (x2, y2)): ((f32, f32), (f32, f32)), view: View, navigation: &Navigation) {
+        // nothing is shown until the mouse has moved far enough to make a box
+        if !navigation.is_box((x1, y1), (x2, y2)) {
+            return;
+        }
+        let style = self.config.selection;
+        let [r, g, b] = style.colour;
+        let colour = egui::Color32::from_rgb(r, g, b);
+        let pixels_per_point = ctx.pixels_per_point();
+        let rect = egui::Rect::from_two_pos(egui::pos2(x1 / pixels_per_point, y1 / pixels_per_point), egui::pos2(x2 / pixels_per_point, y2 / pixels_per_point));
+        // under the windows so it doesn't cover them
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("Zoom box")));
+
+        if style.dim_outside {
+            let screen = ctx.screen_rect();
+            let dim = egui::Color32::from_black_alpha(120);
+            for outside in [
+                egui::Rect::from_min_max(screen.min, egui::pos2(screen.max.x, rect.min.y)),
+                egui::Rect::from_min_max(egui::pos2(screen.min.x, rect.max.y), screen.max),
+                egui::Rect::from_min_max(egui::pos2(screen.min.x, rect.min.y), egui::pos2(rect.min.x, rect.max.y)),
+                egui::Rect::from_min_max(egui::pos2(rect.max.x, rect.min.y), egui::pos2(screen.max.x, rect.max.y)),
+            ] {
+                painter.rect_filled(outside, 0.0, dim);
+            }
+        }
+        painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.5, colour));
+        for corner in [rect.left_top(), rect.right_top(), rect.left_bottom(), rect.right_bottom()] {
+            painter.rect_filled(egui::Rect::from_center_size(corner, egui::vec2(7.0, 7.0)), 1.0, colour);
+        }
+
+        let (width, height) = ((x1 - x2).abs(), (y1 - y2).abs());
+        let mut sizes = Vec::new();
+        if style.show_size {
+            // a pixel of the window is the view's zoom across in the complex plane
+            sizes.push(format!("{} × {}", format_length(width as f64 * view.zoom), format_length(height as f64 * view.zoom)));
+        }
+        if style.show_pixels {
+            sizes.push(format!("{} × {} px", width.round(), height.round()));
+        }
+        if !sizes.is_empty() {
+            let galley = painter.layout_no_wrap(sizes.join("   "), self.font.clone(), colour);
+            let text = egui::Align2::RIGHT_BOTTOM.anchor_rect(egui::Rect::from_min_size(rect.right_bottom() - egui::vec2(8.0, 8.0), galley.size()));
+            painter.rect_filled(text.expand(3.0), 3.0, egui::Color32::from_black_alpha(160));
+            painter.galley(text.min, galley);
+        }
+    }
+
+    /// Brightness, contrast, gamma and saturation sliders, which change the displayed frame without generating it again
+    fn adjustments(&mut self, ctx: &Context, flags: &mut Flags) {
+        let mut open = self.show_adjustments;
+        let adjustments = &mut flags.adjustments;
+        egui::Window::new(RichText::new(tr("Adjustments")).font(self.font.clone()))
+        .id(egui::Id::new("Adjustments"))
+        .open(&mut open)
+        .default_pos((330.0, 300.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("Adjustments").num_columns(2).show(ui, |ui| {
+                for (label, value, range) in [
+                    ("Brightness:", &mut adjustments.brightness, -1.0..=1.0),
+                    ("Contrast:", &mut adjustments.contrast, 0.0..=3.0),
+                    ("Gamma:", &mut adjustments.gamma, 0.2..=5.0),
+                    ("Saturation:", &mut adjustments.saturation, 0.0..=3.0),
+                ] {
+                    ui.label(RichText::new(tr(label)).font(self.font.clone()));
+                    ui.add(egui::Slider::new(value, range).clamp_to_range(true));
+                    ui.end_row();
+                }
+            });
+            if ui.add_enabled(!adjustments.is_neutral(), egui::Button::new(RichText::new(tr("Reset")).font(self.font.clone()))).clicked() {
+                *adjustments = Adjustments::default();
+            }
+
+            // drawn on the gpu as the frame goes to the window, so it doesn't need the fractal generating again either
+            ui.separator();
+            let bloom = &mut flags.bloom;
+            ui.checkbox(&mut bloom.enabled, RichText::new(tr("Bloom")).font(self.font.clone()))
+                .on_hover_text(tr("Make the bright parts glow"));
+            ui.add_enabled_ui(bloom.enabled, |ui| {
+                egui::Grid::new("Bloom").num_columns(2).show(ui, |ui| {
+                    ui.label(RichText::new(tr("Threshold:")).font(self.font.clone()))
+                        .on_hover_text(tr("How bright a colour has to be to glow"));
+                    ui.add(egui::Slider::new(&mut bloom.threshold, 0.0..=1.0).clamp_to_range(true));
+                    ui.end_row();
+                    ui.label(RichText::new(tr("Intensity:")).font(self.font.clone()));
+                    ui.add(egui::Slider::new(&mut bloom.intensity, 0.0..=3.0).clamp_to_range(true));
+                    ui.end_row();
+                    ui.label(RichText::new(tr("Radius:")).font(self.font.clone()));
+                    ui.add(egui::Slider::new(&mut bloom.radius, 1.0..=64.0).suffix("px").clamp_to_range(true));
+                    ui.end_row();
+                });
+            });
+
+            // depth comes from the iteration counts so any of the fractals can be seen in 3D
+            ui.separator();
+            let stereo = &mut flags.stereo;
+            egui::Grid::new("Stereo").num_columns(2).show(ui, |ui| {
+                ui.label(RichText::new(tr("3D:")).font(self.font.clone()))
+                    .on_hover_text(tr("Points that take longer to escape stand out towards you"));
+                egui::ComboBox::from_id_source("Stereo")
+                .selected_text(tr(stereo.mode.name()))
+                .show_ui(ui, |ui| {
+                    for mode in STEREO_MODES {
+                        ui.selectable_value(&mut stereo.mode, mode, RichText::new(tr(mode.name())).font(self.font.clone()));
+                    }
+                });
+                ui.end_row();
+                ui.label(RichText::new(tr("Eye separation:")).font(self.font.clone()))
+                    .on_hover_text(tr("How far apart the nearest and furthest points are for the two eyes"));
+                ui.add_enabled(stereo.mode != StereoMode::Off, egui::Slider::new(&mut stereo.separation, 0.0..=50.0).suffix("px").clamp_to_range(true));
+                ui.end_row();
+            });
+        });
+        self.show_adjustments = open;
+    }
+
+    /// Pick the iteration counts to highlight over the dimmed frame, open while the highlight is on
+    fn iteration_bands(&mut self, ctx: &Context, max_iterations: u32, flags: &mut Flags) {
+        let counting = flags.iteration_counts.counting();
+        let highlight = &mut flags.highlight;
+        let mut open = highlight.enabled;
+        egui::Window::new(RichText::new(tr("Iteration bands")).font(self.font.clone()))
+        .id(egui::Id::new("Iteration bands"))
+        .open(&mut open)
+        .default_pos((330.0, 420.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            let mut from_changed = false;
+            egui::Grid::new("Iteration bands").num_columns(2).show(ui, |ui| {
+                ui.label(RichText::new(tr("From:")).font(self.font.clone()));
+                from_changed = ui.add(egui::Slider::new(&mut highlight.from, 0..=max_iterations).logarithmic(true).clamp_to_range(true)).changed();
+                ui.end_row();
+                ui.label(RichText::new(tr("To:")).font(self.font.clone()));
+                ui.add(egui::Slider::new(&mut highlight.to, 0..=max_iterations).logarithmic(true).clamp_to_range(true));
+                ui.end_row();
+                ui.label(RichText::new(tr("Highlight colour:")).font(self.font.clone()));
+                ui.color_edit_button_srgb(&mut highlight.colour);
+                ui.end_row();
+            });
+            // dragging either end past the other pushes it along
+            if highlight.from > highlight.to {
+                if from_changed {
+                    highlight.to = highlight.from;
+                } else {
+                    highlight.from = highlight.to;
+                }
+            }
+            if counting {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(RichText::new(tr("Counting iterations...")).font(self.font.clone()));
+                });
+            } else {
+                ui.label(RichText::new(format!("{:.1}% {}", highlight.coverage * 100.0, tr("of the pixels are in the band"))).font(self.font.clone()));
+            }
+        });
+        highlight.enabled = open;
+    }
+
+    /// The view in each palette side by side, click one to use it.
+    /// They're coloured from the frame's iteration counts so they keep up without drawing the fractal again,
+    /// with auto levels they're stretched across the levels of those counts
+    fn palette_previews(&mut self, ui: &mut egui::Ui, colour_gradient: &mut String, counts: &IterationCounts, auto_levels: bool) {
+        let Some((thumbnail, (width, height))) = counts.thumbnail(PALETTE_PREVIEW_WIDTH) else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(RichText::new(tr("Counting iterations...")).font(self.font.clone()));
+            });
+            return;
+        };
+        let names = colour_gradient_names();
+        let max_iterations = counts.max_iterations().max(1);
+        let curve = transfer::current();
+        let levels = if auto_levels { counts.levels().unwrap_or_default() } else { Levels::FULL };
+        let stale = self.palette_previews.as_ref().is_none_or(|previews| {
+            previews.counts != thumbnail || previews.max_iterations != max_iterations || previews.curve != curve || previews.levels != levels
+                || !previews.textures.iter().map(|(name, _)| name).eq(names.iter())
+        });
+        if stale {
+            let textures = names.into_iter().map(|name| {
+                let gradient = string_to_colour_gradient(&name);
+                let pixels: Vec<u8> = thumbnail.iter()
+                    .flat_map(|&count| gradient.at(levels.stretch(count as f64 / max_iterations as f64)).to_rgba8())
+                    .collect();
+                let image = egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &pixels);
+                let texture = ui.ctx().load_texture(format!("Palette preview {}", name), image, egui::TextureOptions::LINEAR);
+                (name, texture)
+            }).collect();
+            self.palette_previews = Some(PalettePreviews { textures, counts: thumbnail, max_iterations, curve, levels });
+        }
+        let Some(previews) = &self.palette_previews else {
+            return;
+        };
+        egui::ScrollArea::horizontal().id_source("Palette previews").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for (name, texture) in &previews.textures {
+                    let button = egui::ImageButton::new(texture.id(), texture.size_vec2()).selected(name == colour_gradient);
+                    if ui.add(button).on_hover_text(name).clicked() {
+                        name.clone_into(colour_gradient);
+                    }
+                }
+            });
+        });
+    }
+
+    /// The caption, logo and border put on screenshots, with the caption for the current view
+    fn watermark(&mut self, ctx: &Context, view: View, flags: &mut Flags) {
+        let mut open = self.show_watermark;
+        let watermark = &mut flags.watermark;
+        let mut save = false;
+        egui::Window::new(RichText::new(tr("Watermark")).font(self.font.clone()))
+        .id(egui::Id::new("Watermark"))
+        .open(&mut open)
+        .default_pos((330.0, 300.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("Watermark").num_columns(2).show(ui, |ui| {
+                ui.label(RichText::new(tr("Text:")).font(self.font.clone()));
+                save |= ui.add(egui::TextEdit::singleline(&mut watermark.text).hint_text(tr("Title or name")).desired_width(220.0)).lost_focus();
+                ui.end_row();
+                ui.label(RichText::new(tr("Caption:")).font(self.font.clone()));
+                ui.horizontal(|ui| {
+                    save |= ui.checkbox(&mut watermark.coordinates, RichText::new(tr("Coordinates")).font(self.font.clone())).changed();
+                    save |= ui.checkbox(&mut watermark.zoom, RichText::new(tr("Zoom")).font(self.font.clone())).changed();
+                    save |= ui.checkbox(&mut watermark.date, RichText::new(tr("Date")).font(self.font.clone())).changed();
+                });
+                ui.end_row();
+                ui.label(RichText::new(tr("Caption colour:")).font(self.font.clone()));
+                save |= ui.color_edit_button_srgb(&mut watermark.caption_colour).changed();
+                ui.end_row();
+                ui.label(RichText::new(tr("Logo:")).font(self.font.clone()))
+                    .on_hover_text(tr("An image drawn in the bottom right corner"));
+                ui.horizontal(|ui| {
+                    if ui.add(egui::TextEdit::singleline(&mut self.watermark_logo).hint_text(tr("Image file")).desired_width(160.0)).lost_focus() {
+                        let logo = self.watermark_logo.trim();
+                        watermark.logo = (!logo.is_empty()).then(|| std::path::PathBuf::from(logo));
+                        save = true;
+                    }
+                    if ui.add_enabled(watermark.logo.is_some(), egui::Button::new(RichText::new(tr("Clear")).font(self.font.clone()))).clicked() {
+                        self.watermark_logo.clear();
+                        watermark.logo = None;
+                        save = true;
+                    }
+                });
+                ui.end_row();
+                ui.label(RichText::new(tr("Border:")).font(self.font.clone()));
+                let response = ui.add(egui::Slider::new(&mut watermark.border, 0..=200).suffix("px").clamp_to_range(true));
+                save |= response.drag_released() || (response.changed() && !response.dragged());
+                ui.end_row();
+                ui.label(RichText::new(tr("Border colour:")).font(self.font.clone()));
+                save |= ui.color_edit_button_srgb(&mut watermark.border_colour).changed();
+                ui.end_row();
+            });
+            let caption = watermark.caption(view);
+            if !caption.is_empty() {
+                ui.separator();
+                ui.label(RichText::new(caption).font(self.font.clone()).weak());
+            }
+        });
+        self.show_watermark = open;
+
+        // the text is saved once it's finished with and the border once it's let go
+        if save {
+            self.config.watermark.clone_from(&flags.watermark);
+            if let Err(e) = self.config.save() {
+                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+            }
+        }
+    }
+
+    /// Pan step, held key panning and the mouse wheel's zoom factor
+    fn navigation(&mut self, ctx: &Context, flags: &mut Flags) {
+        let mut open = self.show_navigation;
+        let navigation = &mut flags.navigation;
+        let mut save = false;
+        egui::Window::new(RichText::new(tr("Navigation")).font(self.font.clone()))
+        .id(egui::Id::new("Navigation"))
+        .open(&mut open)
+        .default_pos((330.0, 200.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("Navigation").num_columns(2).show(ui, |ui| {
+                ui.label(RichText::new(tr("Zoom factor:")).font(self.font.clone()))
+                    .on_hover_text(tr("How many times each notch of the mouse wheel zooms in or out"));
+                let response = ui.add(egui::Slider::new(&mut navigation.zoom_factor, 1.05..=4.0).suffix("x").logarithmic(true).clamp_to_range(true));
+                save |= response.drag_released() || (response.changed() && !response.dragged());
+                ui.end_row();
+                ui.label(RichText::new(tr("Pan keys:")).font(self.font.clone()));
+                ui.horizontal(|ui| {
+                    save |= ui.radio_value(&mut navigation.continuous_pan, false, RichText::new(tr("Step each press")).font(self.font.clone())).changed();
+                    save |= ui.radio_value(&mut navigation.continuous_pan, true, RichText::new(tr("Move while held")).font(self.font.clone())).changed();
+                });
+                ui.end_row();
+                if navigation.continuous_pan {
+                    ui.label(RichText::new(tr("Pan speed:")).font(self.font.clone()))
+                        .on_hover_text(tr("Window heights a second"));
+                    let response = ui.add(egui::Slider::new(&mut navigation.pan_speed, 0.1..=5.0).logarithmic(true).clamp_to_range(true));
+                    save |= response.drag_released() || (response.changed() && !response.dragged());
+                } else {
+                    ui.label(RichText::new(tr("Pan step:")).font(self.font.clone()))
+                        .on_hover_text(tr("Share of the window height each press moves"));
+                    let mut percent = navigation.pan_step * 100.0;
+                    let response = ui.add(egui::Slider::new(&mut percent, 1.0..=100.0).suffix("%").clamp_to_range(true));
+                    navigation.pan_step = percent / 100.0;
+                    save |= response.drag_released() || (response.changed() && !response.dragged());
+                }
+                ui.end_row();
+            });
+            // high DPI screens and tablets move further between the press and release of what's meant as a click
+            ui.collapsing(RichText::new(tr("Zoom box")).font(self.font.clone()), |ui| {
+                egui::Grid::new("Zoom box").num_columns(2).show(ui, |ui| {
+                    ui.label(RichText::new(tr("Drag threshold:")).font(self.font.clone()))
+                        .on_hover_text(tr("Pixels the mouse has to move both across and down before a drag becomes a zoom box"));
+                    let response = ui.add(egui::Slider::new(&mut navigation.box_threshold, BOX_THRESHOLDS).suffix("px").logarithmic(true).clamp_to_range(true));
+                    save |= response.drag_released() || (response.changed() && !response.dragged());
+                    ui.end_row();
+                    ui.label(RichText::new(tr("Minimum area:")).font(self.font.clone()))
+                        .on_hover_text(tr("Smaller boxes are taken as a click, which centres on the point"));
+                    let response = ui.add(egui::Slider::new(&mut navigation.box_min_area, BOX_MIN_AREAS).suffix("px²").logarithmic(true).clamp_to_range(true));
+                    save |= response.drag_released() || (response.changed() && !response.dragged());
+                    ui.end_row();
+                    ui.label(RichText::new(tr("Sensitivity:")).font(self.font.clone()))
+                        .on_hover_text(tr("Lower zooms further into a box of the same size"));
+                    let response = ui.add(egui::Slider::new(&mut navigation.box_sensitivity, BOX_SENSITIVITIES).logarithmic(true).clamp_to_range(true));
+                    save |= response.drag_released() || (response.changed() && !response.dragged());
+                    ui.end_row();
+                    ui.label(RichText::new(tr("Least zoom:")).font(self.font.clone()))
+                        .on_hover_text(tr("How far even a box covering the whole window zooms in"));
+                    let response = ui.add(egui::Slider::new(&mut navigation.box_least_zoom, BOX_LEAST_ZOOMS).suffix("x").clamp_to_range(true));
+                    save |= response.drag_released() || (response.changed() && !response.dragged());
+                    ui.end_row();
+                    ui.label(RichText::new(tr("Most zoom:")).font(self.font.clone()))
+                        .on_hover_text(tr("How far even the smallest box zooms in"));
+                    let response = ui.add(egui::Slider::new(&mut navigation.box_most_zoom, BOX_MOST_ZOOMS).suffix("x").logarithmic(true).clamp_to_range(true));
+                    save |= response.drag_released() || (response.changed() && !response.dragged());
+                    ui.end_row();
+                });
+            });
+            if ui.button(tr("Reset")).clicked() {
+                *navigation = Navigation::default();
+                save = true;
+            }
+        });
+        self.show_navigation = open;
+
+        if save {
+            self.config.navigation = flags.navigation;
+            if let Err(e) = self.config.save() {
+                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+            }
+        }
+    }
+
+    /// The profile the current type of fractal starts from when it's switched to, in new tabs and when it's reset
+    fn defaults(&mut self, ctx: &Context, fractal: &mut dyn Fractal, view: View, flags: &mut Flags) {
+        let mut open = self.show_defaults;
+        let name = fractal.name();
+        let mut save = false;
+        egui::Window::new(RichText::new(tr("Defaults")).font(self.font.clone()))
+        .id(egui::Id::new("Defaults"))
+        .open(&mut open)
+        .default_pos((330.0, 250.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(RichText::new(name).font(self.font.clone()).strong());
+            let has_profile = flags.profiles.get(name).is_some();
+            match flags.profiles.get_mut(name) {
+                Some(profile) => {
+                    egui::Grid::new("Defaults").num_columns(2).show(ui, |ui| {
+                        ui.label(RichText::new(tr("Max iterations:")).font(self.font.clone()));
+                        let response = ui.add(egui::DragValue::new(&mut profile.max_iterations).clamp_range(1..=MAX_ITERATIONS).speed(10.0));
+                        save |= response.drag_released() || (response.changed() && !response.dragged());
+                        ui.end_row();
+                        if let Some(escape_radius) = &mut profile.escape_radius {
+                            ui.label(RichText::new(tr("Escape radius:")).font(self.font.clone()));
+                            let response = ui.add(egui::DragValue::new(escape_radius).clamp_range(1.0..=10.0).speed(0.05));
+                            save |= response.drag_released() || (response.changed() && !response.dragged());
+                            ui.end_row();
+                        }
+                        ui.label(RichText::new(tr("Colour:")).font(self.font.clone()));
+                        egui::ComboBox::from_id_source("Default palette")
+                        .selected_text(profile.palette.clone())
+                        .show_ui(ui, |ui| {
+                            for palette in colour_gradient_names() {
+                                save |= ui.selectable_value(&mut profile.palette, palette.clone(), RichText::new(palette).font(self.font.clone())).changed();
+                            }
+                        });
+                        ui.end_row();
+                        ui.label(RichText::new(tr("Starting view:")).font(self.font.clone()));
+                        ui.horizontal(|ui| {
+                            let start = profile.view;
+                            ui.label(RichText::new(format!("{} {:+}i  {:.3e}x", start.offset_x, start.offset_y, INIT_ZOOM / start.zoom)).font(self.font.clone()).weak());
+                            if ui.button(tr("Use current view")).clicked() {
+                                profile.view = view;
+                                save = true;
+                            }
+                        });
+                        ui.end_row();
+                    });
+                }
+                None => {
+                    ui.label(RichText::new(tr("Starts with the built in defaults")).font(self.font.clone()).weak());
+                }
+            }
+            ui.horizontal(|ui| {
+                if ui.button(tr("Use current settings")).on_hover_text(tr("Start with the current iterations, escape radius, palette and view")).clicked() {
+                    flags.profiles.set(name, Profile::of(fractal, view));
+                    save = true;
+                }
+                if ui.add_enabled(has_profile, egui::Button::new(tr("Forget"))).on_hover_text(tr("Go back to the built in defaults")).clicked() {
+                    flags.profiles.remove(name);
+                    save = true;
+                }
+            });
+        });
+        self.show_defaults = open;
+
+        if save {
+            self.config.profiles.clone_from(&flags.profiles);
+            if let Err(e) = self.config.save() {
+                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+            }
+        }
+    }
+
+    /// The transfer curve from iteration values to palette positions, drawn over the palette it's applied to
+    fn colour_curve(&mut self, ctx: &Context, colour_gradient: &str, flags: &mut Flags) {
+        let mut open = self.show_colour_curve;
+        let curve = &mut self.config.colour_curve;
+        if curve.points.len() < 2 {
+            *curve = TransferCurve::default();
+        }
+        let (mut changed, mut save) = (false, false);
+        egui::Window::new(RichText::new(tr("Colour curve")).font(self.font.clone()))
+        .id(egui::Id::new("Colour curve"))
+        .open(&mut open)
+        .default_pos((330.0, 150.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(tr("Drag the points, double click to add one and right click to remove it"));
+            (changed, save) = curve_editor(ui, curve, colour_gradient);
+            if ui.add_enabled(!curve.is_identity(), egui::Button::new(RichText::new(tr("Reset")).font(self.font.clone()))).clicked() {
+                *curve = TransferCurve::default();
+                (changed, save) = (true, true);
+            }
+        });
+        self.show_colour_curve = open;
+
+        if changed {
+            transfer::set(self.config.colour_curve.clone());
+            flags.generate_fractal = true;
+        }
+        // saved once a point is let go rather than on every step of a drag
+        if save {
+            if let Err(e) = self.config.save() {
+                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+            }
+        }
+    }
+
+    /// Progress of the final quality render at the bottom of the screen
+    fn final_render_progress(&self, ctx: &Context, progress: f32, time_left: Option<Duration>, region: bool, flags: &mut Flags) {
+        egui::Area::new("Final render")
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -10.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(egui::ProgressBar::new(progress).desired_width(200.0)
+                        .text(format!("{} {:.0}%", tr(if region { "Region" } else { "Final quality" }), progress * 100.0)));
+                    if let Some(time_left) = time_left {
+                        ui.label(RichText::new(format!("{} {}", format_duration(time_left), tr("left"))).font(self.font.clone()));
+                    }
+                    if ui.button(RichText::new(tr("Cancel")).font(self.font.clone())).clicked() {
+                        flags.final_render = None;
+                    }
+                });
+            });
+        });
+    }
+
+    /// How long the last frame took to generate, and how far through the one being drawn a band at a time is
+    fn render_stats(&self, ctx: &Context, flags: &Flags) {
+        let stats = &flags.render_stats;
+        egui::Area::new("Render stats")
+        .anchor(egui::Align2::RIGHT_CENTER, egui::vec2(-10.0, 0.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let font = self.font.clone();
+                let last_frame = stats.last_frame.map_or_else(|| "-".to_string(), format_duration);
+                ui.label(RichText::new(format!("{}: {}", tr("Last frame"), last_frame)).font(font.clone()));
+                if let Some((fraction, time_left)) = stats.drawing {
+                    ui.add(egui::ProgressBar::new(fraction).desired_width(160.0).show_percentage());
+                    let time_left = time_left.map_or_else(|| "-".to_string(), format_duration);
+                    ui.label(RichText::new(format!("{}: {}", tr("Time left"), time_left)).font(font.clone()));
+                }
+                if let Some(pass) = &flags.cpu_pass {
+                    ui.label(RichText::new(tr("Drawing again on the CPU")).font(font));
+                    ui.add(egui::ProgressBar::new(pass.progress()).desired_width(160.0).show_percentage());
+                }
+            });
+        });
+    }
+
+    /// Pick a folder that parameter files from other programs are loaded from as soon as they're written there
+    #[cfg(not(target_arch = "wasm32"))]
+    fn watch_folder(&mut self, ctx: &Context, flags: &mut Flags) {
+        let mut open = self.show_watch_folder;
+        let mut folder = None;
+        egui::Window::new(RichText::new(tr("Watch folder")).font(self.font.clone()))
+        .id(egui::Id::new("Watch folder"))
+        .open(&mut open)
+        .default_pos((330.0, 250.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.set_max_width(320.0);
+            ui.label(RichText::new(tr("Session (.json) and Kalles Fraktaler (.kfr) files added to the folder or changed are loaded into the current tab")).font(self.font.clone()));
+            ui.add_space(4.0);
+            match &flags.watch_folder {
+                Some(watching) => ui.label(RichText::new(format!("{}: {}", tr("Watching"), watching.display())).font(self.font.clone()).strong()),
+                None => ui.label(RichText::new(tr("Not watching a folder")).font(self.font.clone()).weak()),
+            };
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.watch_folder).hint_text(tr("Folder")).desired_width(220.0));
+                if ui.add_enabled(!self.watch_folder.trim().is_empty(), egui::Button::new(RichText::new(tr("Watch")).font(self.font.clone()))).clicked() {
+                    folder = Some(Some(std::path::PathBuf::from(self.watch_folder.trim())));
+                }
+                if ui.add_enabled(flags.watch_folder.is_some(), egui::Button::new(RichText::new(tr("Stop")).font(self.font.clone()))).clicked() {
+                    folder = Some(None);
+                }
+            });
+        });
+        self.show_watch_folder = open;
+
+        // remembered for next time, the command line folder is only used for the run it was given to
+        if let Some(folder) = folder {
+            self.config.watch_folder.clone_from(&folder);
+            flags.change_watch_folder = Some(folder);
+            if let Err(e) = self.config.save() {
+                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+            }
+        }
+    }
+
+    /// The graphics card, power preference and present mode, the window's surface is recreated when they change
+    #[cfg(not(target_arch = "wasm32"))]
+    fn graphics(&mut self, ctx: &Context, flags: &mut Flags) {
+        use crate::graphics::{self, POWER_PREFERENCES, PRESENT_MODES};
+        let adapters = self.adapters.get_or_insert_with(|| graphics::adapters().iter().map(adapter_label).collect());
+        let settings = &mut self.config.graphics;
+        let mut open = self.show_graphics;
+        let mut changed = false;
+        let mut monitor_changed = false;
+        egui::Window::new(RichText::new(tr("Graphics")).font(self.font.clone()))
+        .id(egui::Id::new("Graphics"))
+        .open(&mut open)
+        .default_pos((330.0, 250.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(RichText::new(format!("{}: {}", tr("In use"), self.adapter_in_use)).font(self.font.clone()));
+            egui::Grid::new("Graphics").num_columns(2).show(ui, |ui| {
+                ui.label(RichText::new(tr("Adapter:")).font(self.font.clone()));
+                egui::ComboBox::from_id_source("Adapter")
+                .selected_text(settings.adapter.clone().unwrap_or_else(|| tr("Automatic").to_string()))
+                .show_ui(ui, |ui| {
+                    changed |= ui.selectable_value(&mut settings.adapter, None, RichText::new(tr("Automatic")).font(self.font.clone()))
+                        .on_hover_text(tr("Pick one by the power preference"))
+                        .changed();
+                    for adapter in adapters.iter() {
+                        changed |= ui.selectable_value(&mut settings.adapter, Some(adapter.clone()), RichText::new(adapter).font(self.font.clone())).changed();
+                    }
+                });
+                ui.end_row();
+
+                ui.label(RichText::new(tr("Power preference:")).font(self.font.clone()));
+                ui.add_enabled_ui(settings.adapter.is_none(), |ui| {
+                    egui::ComboBox::from_id_source("Power preference")
+                    .selected_text(tr(settings.power_preference.name()))
+                    .show_ui(ui, |ui| {
+                        for preference in POWER_PREFERENCES {
+                            changed |= ui.selectable_value(&mut settings.power_preference, preference, RichText::new(tr(preference.name())).font(self.font.clone()))
+                                .on_hover_text(tr(preference.description()))
+                                .changed();
+                        }
+                    });
+                });
+                ui.end_row();
+
+                ui.label(RichText::new(tr("Present mode:")).font(self.font.clone()));
+                egui::ComboBox::from_id_source("Present mode")
+                .selected_text(tr(settings.present_mode.name()))
+                .show_ui(ui, |ui| {
+                    for mode in PRESENT_MODES {
+                        changed |= ui.selectable_value(&mut settings.present_mode, mode, RichText::new(tr(mode.name())).font(self.font.clone()))
+                            .on_hover_text(tr(mode.description()))
+                            .changed();
+                    }
+                });
+                ui.end_row();
+
+                ui.label(RichText::new(tr("Fullscreen on:")).font(self.font.clone()));
+                let monitor = &mut self.config.fullscreen_monitor;
+                let before = monitor.clone();
+                egui::ComboBox::from_id_source("Fullscreen monitor")
+                .selected_text(monitor.clone().unwrap_or_else(|| tr("Current monitor").to_string()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(monitor, None, RichText::new(tr("Current monitor")).font(self.font.clone()))
+                        .on_hover_text(tr("The monitor the window is on"));
+                    for name in &self.monitors {
+                        ui.selectable_value(monitor, Some(name.clone()), RichText::new(name).font(self.font.clone()));
+                    }
+                });
+                if *monitor != before {
+                    flags.fullscreen_monitor.clone_from(monitor);
+                    // move over straight away if already fullscreen
+                    if flags.fullscreen {
+                        flags.set_fullscreen = Some(true);
+                    }
+                    monitor_changed = true;
+                }
+                ui.end_row();
+            });
+            let mut fullscreen = flags.fullscreen;
+            if ui.toggle_value(&mut fullscreen, RichText::new(tr("Fullscreen")).font(self.font.clone())).changed() {
+                flags.set_fullscreen = Some(fullscreen);
+            }
+        });
+        self.show_graphics = open;
+
+        if changed {
+            flags.graphics = Some(self.config.graphics.clone());
+        }
+        if changed || monitor_changed {
+            if let Err(e) = self.config.save() {
+                flags.toasts.error(format!("{}: {}", tr("Failed to save settings"), e));
+            }
+        }
+    }
+
+    /// Keyframes of the current session's animation with playback and export controls
+    fn timeline(&mut self, ctx: &Context, timeline: &mut Timeline, fractal: &dyn Fractal, recorder: Option<&Recorder>, flags: &mut Flags) {
+        let mut open = flags.show_timeline;
+        egui::Window::new(RichText::new(tr("Timeline")).font(self.font.clone()))
+        .id(egui::Id::new("Timeline"))
+        .open(&mut open)
+        .default_pos((ctx.screen_rect().width() / 2.0 - 200.0, ctx.screen_rect().height() - 300.0))
+        .default_width(400.0)
+        .show(ctx, |ui| {
+            let can_play = timeline.keyframes.len() > 1;
+            ui.horizontal(|ui| {
+                if ui.button(tr("Add keyframe")).clicked() {
+                    flags.timeline_action = Some(TimelineAction::AddKeyframe);
+                }
+                if timeline.playing {
+                    if ui.button(tr("Pause")).clicked() {
+                        timeline.pause();
+                    }
+                } else if ui.add_enabled(can_play, egui::Button::new(tr("Play"))).clicked() {
+                    timeline.play();
+                }
+                let can_export = can_play || timeline.motion.active();
+                if ui.add_enabled(can_export && flags.video_export.is_none(), egui::Button::new(tr("Export video"))).clicked() {
+                    flags.timeline_action = Some(TimelineAction::Export);
+                }
+                ui.label(tr("FPS:"));
+                ui.add(egui::DragValue::new(&mut timeline.fps).clamp_range(1..=120));
+                ui.checkbox(&mut timeline.reuse_frames, tr("Reuse frames"))
+                    .on_hover_text(tr("Frames that only zoom or pan are scaled from a keyframe rendered every 2x of zoom, much faster for long zooms but a little softer"));
+            });
+            // recording navigation replaces the keyframes with a replay of it when stopped
+            ui.horizontal(|ui| {
+                if let Some(recorder) = recorder {
+                    if ui.button(tr("Stop recording")).clicked() {
+                        flags.timeline_action = Some(TimelineAction::StopRecording);
+                    }
+                    ui.colored_label(ui.visuals().error_fg_color, format!("⏺ {} {}", recorder.event_count(), tr("changes recorded")));
+                } else if ui.button(tr("Record navigation")).clicked() {
+                    flags.timeline_action = Some(TimelineAction::StartRecording);
+                }
+            });
+            if let Some(export) = &flags.video_export {
+                ui.add(egui::ProgressBar::new(export.rendered as f32 / export.total.max(1) as f32)
+                    .text(format!("{} {} / {}", tr("Exporting"), export.rendered, export.total)));
+            }
+
+            egui::CollapsingHeader::new(RichText::new(tr("Motion")).font(self.font.clone())).id_source("Motion").show(ui, |ui| {
+                let motion = &mut timeline.motion;
+                egui::Grid::new("Motion").num_columns(3).show(ui, |ui| {
+                    ui.checkbox(&mut motion.rotate, tr("Rotate"));
+                    ui.add(egui::DragValue::new(&mut motion.rotation_speed).clamp_range(-360.0..=360.0).speed(0.5).suffix("°/s"))
+                        .on_hover_text(tr("Speed"));
+                    ui.end_row();
+
+                    ui.checkbox(&mut motion.wobble, tr("Wobble"));
+                    ui.add(egui::DragValue::new(&mut motion.wobble_speed).clamp_range(0.01..=5.0).speed(0.01).suffix("/s"))
+                        .on_hover_text(tr("Orbits per second"));
+                    ui.add(egui::DragValue::new(&mut motion.wobble_radius).clamp_range(1.0..=500.0).speed(0.5).suffix("px"))
+                        .on_hover_text(tr("Radius"));
+                    ui.end_row();
+
+                    // without keyframes the export is a clip of just the motion
+                    ui.label(tr("Clip length:"));
+                    ui.add(egui::DragValue::new(&mut motion.clip_length).clamp_range(0.5..=600.0).speed(0.1).suffix("s"));
+                    ui.end_row();
+                });
+            });
+
+            egui::CollapsingHeader::new(RichText::new(tr("Parameter sweep")).font(self.font.clone())).id_source("Sweep").show(ui, |ui| {
+                let sweep = &mut timeline.sweep;
+                let parameters = fractal.animatable_parameters();
+                if !parameters.contains(&sweep.parameter) {
+                    sweep.parameter = parameters[0];
+                }
+                egui::Grid::new("Sweep").num_columns(2).show(ui, |ui| {
+                    ui.label(tr("Parameter:"));
+                    egui::ComboBox::from_id_source("Sweep parameter")
+                    .selected_text(tr(sweep.parameter))
+                    .show_ui(ui, |ui| {
+                        for parameter in parameters {
+                            if ui.selectable_value(&mut sweep.parameter, parameter, tr(parameter)).changed() {
+                                // start from the current value
+                                let value = fractal.parameter(parameter).unwrap_or(0.0);
+                                (sweep.from, sweep.to) = (value, value);
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label(tr("From:"));
+                    ui.add(egui::DragValue::new(&mut sweep.from).speed(0.01));
+                    ui.end_row();
+                    ui.label(tr("To:"));
+                    ui.add(egui::DragValue::new(&mut sweep.to).speed(0.01));
+                    ui.end_row();
+                    ui.label(tr("Frames:"));
+                    ui.add(egui::DragValue::new(&mut sweep.frames).clamp_range(2..=10_000));
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    if sweep.playing() {
+                        if ui.button(tr("Stop")).clicked() {
+                            sweep.stop();
+                        }
+                    } else if ui.button(tr("Preview")).clicked() {
+                        sweep.play();
+                    }
+                    if ui.add_enabled(flags.video_export.is_none(), egui::Button::new(tr("Export sweep"))).clicked() {
+                        flags.timeline_action = Some(TimelineAction::ExportSweep);
+                    }
+                });
+            });
+
+            egui::CollapsingHeader::new(RichText::new(tr("Audio reactive")).font(self.font.clone())).id_source("Audio").show(ui, |ui| {
+                let button = if flags.audio.is_some() { tr("Stop listening") } else { tr("Listen to audio input") };
+                if ui.button(button).clicked() {
+                    flags.toggle_audio = true;
+                }
+                if let Some(audio) = &mut flags.audio {
+                    egui::Grid::new("Audio").num_columns(2).show(ui, |ui| {
+                        for (level, (name, enabled)) in audio.levels.into_iter().zip([
+                            ("Bass: zoom pulse", &mut audio.zoom_pulse),
+                            ("Mids: Julia seed", &mut audio.julia_c),
+                            ("Treble: rotation", &mut audio.rotation),
+                        ]) {
+                            ui.checkbox(enabled, tr(name));
+                            ui.add(egui::ProgressBar::new(level.clamp(0.0, 1.0)).desired_width(120.0));
+                            ui.end_row();
+                        }
+                        ui.label(tr("Sensitivity:"));
+                        ui.add(egui::Slider::new(&mut audio.sensitivity, 0.0..=3.0));
+                        ui.end_row();
+                    });
+                }
+            });
+
+            egui::CollapsingHeader::new(RichText::new(tr("Virtual camera")).font(self.font.clone())).id_source("Virtual camera").show(ui, |ui| {
+                match &flags.virtual_camera {
+                    Some(camera) => {
+                        let (width, height) = camera.size();
+                        ui.label(format!("{} {}x{} {} {}", tr("Publishing"), width, height, tr("to"), self.camera_device));
+                        if ui.button(tr("Stop publishing")).clicked() {
+                            flags.virtual_camera = None;
+                        }
+                    }
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.camera_device).desired_width(120.0))
+                                .on_hover_text(tr("A v4l2loopback device, OBS and VJ software see it as a webcam"));
+                            if ui.button(tr("Publish frames")).clicked() {
+                                flags.start_camera = Some(self.camera_device.clone());
+                            }
+                        });
+                    }
+                }
+            });
+
+            if timeline.keyframes.is_empty() {
+                ui.label(RichText::new(tr("Add keyframes to animate between them")).font(self.font.clone()));
+                return;
+            }
+            // scrubbing previews that point of the animation
+            let mut time = timeline.time;
+            let scrubber = egui::Slider::new(&mut time, 0.0..=timeline.duration()).suffix("s").clamp_to_range(true);
+            if ui.add_enabled(can_play, scrubber).changed() {
+                timeline.pause();
+                flags.timeline_action = Some(TimelineAction::Seek(time));
+            }
+            ui.separator();
+
+            let last = timeline.keyframes.len() - 1;
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                egui::Grid::new("Keyframes").num_columns(4).striped(true).show(ui, |ui| {
+                    for i in 0..timeline.keyframes.len() {
+                        if ui.link(RichText::new(format!("{} {}", tr("Keyframe"), i + 1)).font(self.font.clone())).on_hover_text(tr("Go to")).clicked() {
+                            timeline.pause();
+                            flags.timeline_action = Some(TimelineAction::Seek(timeline.keyframe_time(i)));
+                        }
+                        // the last keyframe is where the animation ends so it has no segment to edit
+                        let keyframe = &mut timeline.keyframes[i];
+                        if i < last {
+                            ui.add(egui::DragValue::new(&mut keyframe.duration).clamp_range(0.1..=600.0).speed(0.1).suffix("s"))
+                                .on_hover_text(tr("Duration"));
+                            egui::ComboBox::from_id_source(("Easing", i))
+                            .selected_text(tr(keyframe.easing.name()))
+                            .show_ui(ui, |ui| {
+                                for easing in EASINGS {
+                                    ui.selectable_value(&mut keyframe.easing, easing, tr(easing.name()));
+                                }
+                            });
+                        } else {
+                            ui.label("");
+                            ui.label("");
+                        }
+                        if ui.small_button("x").on_hover_text(tr("Delete")).clicked() {
+                            flags.timeline_action = Some(TimelineAction::Remove(i));
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+        flags.show_timeline = open;
+    }
+
+    /// Recent log lines for diagnosing problems without a terminal, run with --verbose for more detail
+    fn log(&mut self, ctx: &Context) {
+        let mut open = self.show_log;
+        egui::Window::new(RichText::new(tr("Log")).font(self.font.clone()))
+        .id(egui::Id::new("Log"))
+        .open(&mut open)
+        .default_pos((10.0, ctx.screen_rect().height() - 320.0))
+        .default_width(520.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::both().max_height(240.0).stick_to_bottom(true).auto_shrink([false, true]).show(ui, |ui| {
+                for line in logging::recent() {
+                    let colour = match line.level {
+                        Level::ERROR => ui.visuals().error_fg_color,
+                        Level::WARN => ui.visuals().warn_fg_color,
+                        Level::INFO => ui.visuals().text_color(),
+                        _ => ui.visuals().weak_text_color(),
+                    };
+                    let spans = if line.spans.is_empty() { String::new() } else { format!("{}: ", line.spans) };
+                    let text = format!("{:>8.3} {:<5} {}{}", line.time.as_secs_f64(), line.level, spans, line.text);
+                    ui.label(RichText::new(text).monospace().color(colour));
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button(tr("Clear")).clicked() {
+                    logging::clear();
+                }
+                ui.label(RichText::new(tr("Start with --verbose for more detail")).small());
+            });
+        });
+        self.show_log = open;
+    }
+
+    /// List every shortcut and mouse interaction
+    fn help(&self, ctx: &Context, keybindings: &KeyBindings, flags: &mut Flags) {
+        let mut open = flags.show_help;
+        egui::Window::new(RichText::new(tr("Controls")).font(self.font.clone()))
+        .id(egui::Id::new("Controls"))
+        .open(&mut open)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("Help").num_columns(2).spacing([20.0, 4.0]).striped(true).show(ui, |ui| {
+                for binding in keybindings.list.iter() {
+                    let keys: Vec<String> = binding.keys.iter().map(|key| key_name(*key)).collect();
+                    ui.label(RichText::new(keys.join(" / ")).font(self.font.clone()).strong());
+                    ui.label(RichText::new(tr(binding.action.description())).font(self.font.clone()));
+                    ui.end_row();
+                }
+                for (input, description) in MOUSE_BINDINGS.iter().chain(FIXED_BINDINGS) {
+                    ui.label(RichText::new(tr(input)).font(self.font.clone()).strong());
+                    ui.label(RichText::new(tr(description)).font(self.font.clone()));
+                    ui.end_row();
+                }
+            });
+        });
+        flags.show_help = open;
+    }
+
+    /// Show the iteration details of the point under the mouse in the bottom left
+    /// With the size in the complex plane and iteration statistics of the zoom box while one is being dragged out
+    fn inspector(&self, ctx: &Context, fractal: &dyn Fractal, point: (f64, f64), selection: Option<((f64, f64), Option<RegionStats>)>) {
+        let sample = fractal.sample(point);
+        egui::Area::new("Inspector")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(10.0, -10.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let font = self.font.clone();
+                ui.label(RichText::new(format!("{}: {:.10} {:+.10}i", tr("Point"), point.0, point.1)).font(font.clone()));
+                ui.label(RichText::new(format!("{}: {} / {}", tr("Iterations"), sample.iterations, fractal.max_iterations())).font(font.clone()));
+                ui.label(RichText::new(format!("{}: {:.4}", tr("Smooth"), sample.smooth)).font(font.clone()));
+                ui.label(RichText::new(format!("|z|: {:.6}", sample.z.norm())).font(font.clone()));
+                if let Some(atom_domain) = sample.atom_domain {
+                    ui.label(RichText::new(format!("{}: {}", tr("Atom domain"), atom_domain)).font(font.clone()));
+                }
+                if let Some(period) = sample.period {
+                    ui.label(RichText::new(format!("{}: {}", tr("Period"), period)).font(font.clone()));
+                }
+                if fractal.is::<Newton>() {
+                    let root = match sample.root {
+                        Some(root) => format!("{:.4} {:+.4}i", root.re, root.im),
+                        None => tr("none").into(),
+                    };
+                    ui.label(RichText::new(format!("{}: {}", tr("Root"), root)).font(font.clone()));
+                }
+                if let Some(((width, height), stats)) = selection {
+                    ui.separator();
+                    ui.label(RichText::new(format!("{}: {} × {}", tr("Selection"), format_length(width), format_length(height))).font(font.clone()));
+                    match stats {
+                        Some(stats) => {
+                            ui.label(RichText::new(format!("{}: {:.1}", tr("Mean iterations"), stats.mean)).font(font.clone()));
+                            ui.label(RichText::new(format!("{}: {}", tr("Max iterations"), stats.max)).font(font.clone()));
+                            ui.label(RichText::new(format!("{}: {:.1}%", tr("Interior"), stats.interior * 100.0)).font(font));
+                        }
+                        None => {
+                            ui.label(RichText::new(tr("Counting iterations...")).font(font).weak());
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    /// The Julia set for the point under the mouse in the top right, clicking it switches to that set.
+    /// It is redrawn at most every `JULIA_PREVIEW_INTERVAL` so it keeps up with the mouse
+    fn julia_preview(&mut self, ctx: &Context, mandelbrot: &Mandelbrot, flags: &mut Flags) {
+        // the seed stays put while the mouse is over the gui, so the inset can be reached and clicked
+        if let Some((_, c)) = flags.hover.filter(|_| !ctx.is_pointer_over_area()) {
+            let julia = Julia {
+                c,
+                max_iterations: mandelbrot.max_iterations,
+                escape_radius: mandelbrot.escape_radius,
+                colour_gradient: mandelbrot.colour_gradient.clone(),
+                ..Julia::default()
+            };
+            let wait = match &self.julia_preview {
+                Some(preview) if preview.julia == julia => None,
+                Some(preview) => Some(JULIA_PREVIEW_INTERVAL.saturating_sub(preview.drawn.elapsed())),
+                None => Some(Duration::ZERO),
+            };
+            match wait {
+                Some(wait) if wait.is_zero() => {
+                    let (width, height) = JULIA_PREVIEW_SIZE;
+                    let mut frame = vec![0; (width * height * 4) as usize];
+                    // wide enough to fit any connected Julia set
+                    julia.draw(&mut frame, Viewport::new(width as i32, height as i32, (0.0, 0.0), 3.6 / width as f64, 0.0));
+                    let image = egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &frame);
+                    match &mut self.julia_preview {
+                        Some(preview) => {
+                            preview.texture.set(image, egui::TextureOptions::LINEAR);
+                            preview.julia = julia;
+                            preview.drawn = Instant::now();
+                        }
+                        None => {
+                            let texture = ctx.load_texture("Julia preview", image, egui::TextureOptions::LINEAR);
+                            self.julia_preview = Some(JuliaPreview { texture, julia, drawn: Instant::now() });
+                        }
+                    }
+                }
+                // draw the latest point once it's been long enough, even if the mouse has stopped by then
+                Some(wait) => ctx.request_repaint_after(wait),
+                None => {}
+            }
+        }
+
+        let Some(preview) = &self.julia_preview else {
+            return;
+        };
+        egui::Area::new("Julia preview")
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 10.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let (width, height) = JULIA_PREVIEW_SIZE;
+                let button = egui::ImageButton::new(preview.texture.id(), egui::vec2(width as f32, height as f32)).frame(false);
+                if ui.add(button).on_hover_text(tr("Click to explore this Julia set")).clicked() {
+                    flags.context_action = Some(ContextAction::SetJuliaSeed(preview.julia.c));
+                }
+                ui.label(RichText::new(format!("c = {:.6} {:+.6}i", preview.julia.c.0, preview.julia.c.1)).font(self.font.clone()));
+            });
+        });
+    }
+
+    /// Plot the values of z a point goes through, either |z| against the iteration or in the complex plane
+    fn orbit(&mut self, ctx: &Context, fractal: &dyn Fractal, flags: &mut Flags) {
+        if flags.orbit_follow {
+            if let Some((_, point)) = flags.hover {
+                flags.orbit_point = Some(point);
+            }
+        }
+        let Some(point) = flags.orbit_point else {
+            return;
+        };
+        let orbit = fractal.orbit(point);
+
+        let mut open = true;
+        egui::Window::new(RichText::new(tr("Orbit")).font(self.font.clone()))
+        .id(egui::Id::new("Orbit"))
+        .open(&mut open)
+        .default_size([320.0, 300.0])
+        .show(ctx, |ui| {
+            ui.label(RichText::new(format!("{}: {:.10} {:+.10}i", tr("Point"), point.0, point.1)).font(self.font.clone()));
+            ui.label(RichText::new(format!("{}: {}", tr("Iterations"), orbit.len() - 1)).font(self.font.clone()));
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.orbit_plane, false, RichText::new("|z| / n").font(self.font.clone()));
+                ui.selectable_value(&mut self.orbit_plane, true, RichText::new(tr("Complex plane")).font(self.font.clone()));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.checkbox(&mut flags.orbit_follow, RichText::new(tr("Follow mouse")).font(self.font.clone()));
+                });
+            });
+
+            if self.orbit_plane {
+                let points: Vec<[f64; 2]> = orbit.iter().map(|z| [z.re, z.im]).collect();
+                Plot::new("Orbit plane")
+                .data_aspect(1.0)
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(PlotPoints::new(points.clone())).width(1.0));
+                    plot_ui.points(Points::new(PlotPoints::new(points)).radius(2.0));
+                });
+            } else {
+                let points: Vec<[f64; 2]> = orbit.iter().enumerate().map(|(n, z)| [n as f64, z.norm()]).collect();
+                Plot::new("Orbit magnitude")
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(PlotPoints::new(points)));
+                });
+            }
+        });
+        if !open {
+            flags.orbit_point = None;
+            flags.orbit_follow = false;
+        }
+    }
+
+    /// Walk through the iterations of a point with the rule, the current value and the escape test explained,
+    /// and draw the orbit so far over the fractal
+    fn step_through(&mut self, ctx: &Context, fractal: &dyn Fractal, view: View) {
+        let Some(stepping) = self.step_through.as_mut() else {
+            return;
+        };
+        let orbit = fractal.orbit(stepping.point);
+        let last = orbit.len() - 1;
+        let now = ctx.input(|i| i.time);
+        if stepping.playing && now - stepping.last_step > STEP_INTERVAL {
+            stepping.step = (stepping.step + 1).min(last);
+            stepping.last_step = now;
+            stepping.playing = stepping.step < last;
+        }
+        if stepping.playing {
+            // the app sleeps without input, wake it for the next step
+            ctx.request_repaint_after(Duration::from_secs_f64((stepping.last_step + STEP_INTERVAL - now).max(0.0)));
+        }
+        stepping.step = stepping.step.min(last);
+        let (point, step) = (stepping.point, stepping.step);
+        let z = orbit[step];
+
+        let mut open = true;
+        let font = self.font.clone();
+        egui::Window::new(RichText::new(tr("Step through")).font(font.clone()))
+        .id(egui::Id::new("Step through"))
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let rule = fractal.rule(point);
+            ui.label(RichText::new(rule).font(font.clone()));
+            ui.separator();
+            ui.label(RichText::new(format!("n = {}", step)).font(font.clone()).strong());
+            ui.label(RichText::new(format!("z = {:.6} {:+.6}i", z.re, z.im)).font(font.clone()));
+            ui.label(RichText::new(format!("|z| = {:.6}", z.norm())).font(font.clone()));
+
+            let test = match fractal.escape_radius() {
+                Some(escape_radius) => {
+                    if z.norm() > escape_radius {
+                        format!("|z| > {} {}", escape_radius, tr("so the point escaped, it's outside the set"))
+                    } else if step == last {
+                        tr("Still inside after the max iterations, it's counted as in the set").to_string()
+                    } else {
+                        format!("|z| ≤ {} {}", escape_radius, tr("so keep iterating"))
+                    }
+                }
+                None => {
+                    if step == last && last < fractal.max_iterations() as usize {
+                        tr("Converged on a root").to_string()
+                    } else {
+                        tr("Not at a root yet, keep iterating").to_string()
+                    }
+                }
+            };
+            ui.label(RichText::new(test).font(font.clone()));
+
+            ui.horizontal(|ui| {
+                if ui.add_enabled(step > 0, egui::Button::new(RichText::new("◀").font(font.clone()))).on_hover_text(tr("Step back")).clicked() {
+                    stepping.step -= 1;
+                    stepping.playing = false;
+                }
+                if ui.add_enabled(step < last, egui::Button::new(RichText::new("▶").font(font.clone()))).on_hover_text(tr("Step forward")).clicked() {
+                    stepping.step += 1;
+                    stepping.playing = false;
+                }
+                let play = if stepping.playing { tr("Pause") } else { tr("Play") };
+                if ui.add_enabled(step < last, egui::Button::new(RichText::new(play).font(font.clone()))).clicked() {
+                    stepping.playing = !stepping.playing;
+                    stepping.last_step = now;
+                }
+                if ui.button(RichText::new(tr("Reset")).font(font.clone())).clicked() {
+                    stepping.step = 0;
+                    stepping.playing = false;
+                }
+            });
+        });
+        if !open {
+            self.step_through = None;
+            return;
+        }
+
+        // the orbit so far, with the escape circle for escape time fractals
+        let viewport = screen_viewport(ctx, view);
+        let to_screen = |z: num::complex::Complex64| complex_to_pos(ctx, &viewport, (z.re, z.im));
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("step_through")));
+        let colour = ctx.style().visuals.selection.bg_fill;
+        if let Some(escape_radius) = fractal.escape_radius() {
+            escape_boundary(ctx, &painter, &viewport, escape_radius, fractal.bailout(), egui::Stroke::new(1.0, egui::Color32::from_white_alpha(120)));
+        }
+        let points: Vec<egui::Pos2> = orbit[..=step].iter().map(|z| to_screen(*z)).collect();
+        painter.add(egui::Shape::line(points.clone(), egui::Stroke::new(1.5, colour)));
+        for position in points.iter() {
+            painter.circle_filled(*position, 3.0, colour);
+        }
+        if let Some(current) = points.last() {
+            painter.circle_stroke(*current, 7.0, egui::Stroke::new(2.0, egui::Color32::WHITE));
+            painter.text(*current + egui::vec2(10.0, -10.0), egui::Align2::LEFT_BOTTOM, format!("z{}", step), font, egui::Color32::WHITE);
+        }
+    }
+
+    /// The escape boundary over the fractal, and the demonstration of a point moving out past it if one was started
+    fn escape_radius(&mut self, ctx: &Context, fractal: &dyn Fractal, view: View) {
+        let Some(escape_radius) = fractal.escape_radius() else {
+            return;
+        };
+        let viewport = screen_viewport(ctx, view);
+        let to_screen = |z: num::complex::Complex64| complex_to_pos(ctx, &viewport, (z.re, z.im));
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("Escape radius")));
+        let colour = ctx.style().visuals.selection.bg_fill;
+        let bailout = fractal.bailout();
+        escape_boundary(ctx, &painter, &viewport, escape_radius, bailout, egui::Stroke::new(2.0, egui::Color32::from_white_alpha(180)));
+        painter.text(complex_to_pos(ctx, &viewport, (escape_radius, 0.0)) + egui::vec2(4.0, -4.0), egui::Align2::LEFT_BOTTOM,
+            format!("R = {}", escape_radius), self.font.clone(), egui::Color32::WHITE);
+
+        let Some(demo) = &self.escape_demo else {
+            return;
+        };
+        let orbit = fractal.orbit(demo.point);
+        let escaped = |z: &num::complex::Complex64| bailout.norm(z.re * z.re, z.im * z.im) > escape_radius * escape_radius;
+        let escaped_at = orbit.iter().position(escaped);
+        let end = escaped_at.unwrap_or(orbit.len() - 1);
+        // the point glides from each z to the next, then waits at the end before going round again
+        let elapsed = ctx.input(|i| i.time) - demo.started;
+        let steps = (elapsed % (end as f64 * ESCAPE_STEP + ESCAPE_PAUSE)) / ESCAPE_STEP;
+        let step = (steps as usize).min(end);
+        let moving = if step < end { orbit[step] + (orbit[step + 1] - orbit[step]) * steps.fract() } else { orbit[end] };
+
+        let mut points: Vec<egui::Pos2> = orbit[..=step].iter().map(|z| to_screen(*z)).collect();
+        for position in points.iter() {
+            painter.circle_filled(*position, 3.0, colour);
+        }
+        points.push(to_screen(moving));
+        painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, colour)));
+        painter.circle_filled(to_screen(moving), 6.0, egui::Color32::WHITE);
+        if step == end {
+            let (text, ring) = match escaped_at {
+                Some(n) => (format!("{} {} {}", tr("Escaped after"), n, tr("iterations")), egui::Color32::from_rgb(255, 90, 90)),
+                None => (tr("Still inside after the max iterations, it's counted as in the set").to_string(), egui::Color32::WHITE),
+            };
+            painter.circle_stroke(to_screen(moving), 12.0, egui::Stroke::new(2.0, ring));
+            painter.text(to_screen(moving) + egui::vec2(14.0, -14.0), egui::Align2::LEFT_BOTTOM, text, self.font.clone(), egui::Color32::WHITE);
+        }
+        ctx.request_repaint();
+    }
+
+    /// Search near the view for the centre of a mini Mandelbrot or a Misiurewicz point and offer to go to it
+    fn locator(&mut self, ctx: &Context, fractal: &dyn Fractal, view: View, flags: &mut Flags) {
+        let viewport = screen_viewport(ctx, view);
+        let radius = viewport.scale * viewport.width.min(viewport.height) as f64 / 2.0;
+        let centre = viewport.centre;
+
+        let mut open = true;
+        egui::Window::new(RichText::new(tr("Locate")).font(self.font.clone()))
+        .id(egui::Id::new("Locate"))
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let Some(Mandelbrot {max_iterations, ..}) = fractal.downcast_ref::<Mandelbrot>() else {
+                ui.label(RichText::new(tr("Only available for the Mandelbrot set")).font(self.font.clone()));
+                return;
+            };
+            ui.horizontal(|ui| {
+                if ui.button(RichText::new(tr("Mini Mandelbrot")).font(self.font.clone())).clicked() {
+                    self.located = Some(nearest_nucleus(centre, radius, *max_iterations));
+                }
+                if ui.button(RichText::new(tr("Misiurewicz point")).font(self.font.clone())).clicked() {
+                    self.located = Some(nearest_misiurewicz(centre, radius));
+                }
+            });
+
+            match self.located {
+                Some(Some(feature)) => {
+                    ui.separator();
+                    let description = match feature.kind {
+                        FeatureKind::Nucleus { period } => format!("{} {}", tr("Nucleus of period"), period),
+                        FeatureKind::Misiurewicz { preperiod, period } => format!("{} M({},{})", tr("Misiurewicz point"), preperiod, period),
+                    };
+                    ui.label(RichText::new(description).font(self.font.clone()).strong());
+                    ui.label(RichText::new(format!("{:.15} {:+.15}i", feature.point.0, feature.point.1)).font(self.font.clone()));
+                    ui.horizontal(|ui| {
+                        if ui.button(RichText::new(tr("Centre here")).font(self.font.clone())).clicked() {
+                            flags.context_action = Some(ContextAction::CentreOn(feature.point));
+                        }
+                        if ui.button(RichText::new(tr("Zoom to it")).font(self.font.clone())).clicked() {
+                            flags.context_action = Some(ContextAction::ZoomTo(feature.point, feature.size));
+                        }
+                    });
+                }
+                Some(None) => {
+                    ui.label(RichText::new(tr("Nothing found near the view")).font(self.font.clone()));
+                }
+                None => {}
+            }
+        });
+        self.show_locator = open;
+
+        // mark where the feature is
+        if let Some(Some(feature)) = self.located.filter(|_| open) {
+            let position = complex_to_pos(ctx, &viewport, feature.point);
+            let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("located")));
+            painter.circle_stroke(position, 8.0, egui::Stroke::new(2.0, egui::Color32::WHITE));
+        }
+    }
+
+    /// Estimate the area of the set with random sampling, the estimate runs in the background
+    fn area(&mut self, ctx: &Context, fractal: &dyn Fractal, view: View) {
+        let mut open = true;
+        egui::Window::new(RichText::new(tr("Area estimate")).font(self.font.clone()))
+        .id(egui::Id::new("Area estimate"))
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for region in REGIONS {
+                    ui.selectable_value(&mut self.area_region, region, RichText::new(tr(region.name())).font(self.font.clone()));
+                }
+            });
+            ui.horizontal(|ui| {
+                if let Some(estimate) = self.area_estimate.as_mut().filter(|estimate| estimate.running()) {
+                    if ui.button(RichText::new(tr("Stop")).font(self.font.clone())).clicked() {
+                        estimate.stop();
+                    }
+                } else if ui.button(RichText::new(tr("Start")).font(self.font.clone())).clicked() {
+                    self.area_estimate = AreaEstimate::start(fractal.clone_box(), self.area_region, screen_viewport(ctx, view));
+                }
+            });
+            ui.label(RichText::new(tr("Points that don't escape within the max iterations count as inside")).font(self.font.clone()).small());
+
+            if self.area_region == Region::WholeSet && fractal.bounds().is_none() {
+                ui.label(RichText::new(tr("This fractal has no bounded set to measure")).font(self.font.clone()));
+            }
+            if let Some(estimate) = self.area_estimate.as_mut() {
+                estimate.poll();
+                ui.separator();
+                ui.label(RichText::new(format!("{}: {:.6} ± {:.6}", tr("Area"), estimate.area(), estimate.error())).font(self.font.clone()).strong());
+                ui.label(RichText::new(format!("{}, {} {}", tr("95% confidence"), estimate.samples, tr("samples"))).font(self.font.clone()));
+                if estimate.running() {
+                    ui.add(egui::ProgressBar::new(estimate.progress()).show_percentage());
+                    // keep polling while the samples come in
+                    ctx.request_repaint();
+                }
+            }
+        });
+        if !open {
+            self.show_area = false;
+            self.area_estimate = None;
+        }
+    }
+
+    /// Settings for the external rays and equipotentials, and the curves drawn over the fractal
+    fn rays(&mut self, ctx: &Context, view: View) {
+        let mut open = true;
+        egui::Window::new(RichText::new(tr("External rays")).font(self.font.clone()))
+        .id(egui::Id::new("External rays"))
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(tr("Angles:")).font(self.font.clone()));
+                let edit = egui::TextEdit::singleline(&mut self.ray_angles).hint_text("1/3, 2/3").font(self.font.clone());
+                if ui.add(edit).on_hover_text(tr("Fractions of a turn separated by commas")).changed() {
+                    self.ray_overlay = None;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(tr("Equipotentials:")).font(self.font.clone()));
+                if ui.add(egui::DragValue::new(&mut self.equipotentials).clamp_range(0..=12)).changed() {
+                    self.ray_overlay = None;
+                }
+            });
+            if let Some(Err(angle)) = &self.ray_overlay {
+                ui.colored_label(ui.visuals().error_fg_color, format!("{} \"{}\"", tr("Couldn't read angle"), angle));
+            }
+        });
+        self.show_rays = open;
+
+        let overlay = self.ray_overlay.get_or_insert_with(|| {
+            parse_angles(&self.ray_angles).map(|angles| RayOverlay::compute(&angles, self.equipotentials))
+        });
+        let Ok(overlay) = overlay else {
+            return;
+        };
+        let viewport = screen_viewport(ctx, view);
+        let to_screen = |curve: &Vec<num::complex::Complex64>| -> Vec<egui::Pos2> {
+            curve.iter().map(|c| complex_to_pos(ctx, &viewport, (c.re, c.im))).collect()
+        };
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("rays")));
+        for curve in overlay.equipotentials.iter() {
+            painter.add(egui::Shape::line(to_screen(curve), egui::Stroke::new(1.0, egui::Color32::from_white_alpha(140))));
+        }
+        for ray in overlay.rays.iter() {
+            painter.add(egui::Shape::line(to_screen(ray), egui::Stroke::new(1.5, egui::Color32::WHITE)));
+        }
+    }
+
+    /// Markers on the roots of the Newton polynomial, dragging one moves the root and regenerates the fractal as it goes.
+    /// Right clicking one removes it, as long as there's another left
+    fn newton_roots(&mut self, ctx: &Context, newton: &mut Newton, view: View, flags: &mut Flags) {
+        let viewport = screen_viewport(ctx, view);
+        let pixels_per_point = ctx.pixels_per_point();
+        let highlight = ctx.style().visuals.selection.bg_fill;
+        let removable = newton.roots.len() > 1;
+        let mut remove = None;
+        for (index, root) in newton.roots.iter_mut().enumerate() {
+            let position = complex_to_pos(ctx, &viewport, *root);
+            egui::Area::new(egui::Id::new(("Newton root", index)))
+            .fixed_pos(position - egui::vec2(ROOT_MARKER_RADIUS, ROOT_MARKER_RADIUS))
+            .show(ctx, |ui| {
+                let (rect, response) = ui.allocate_exact_size(egui::Vec2::splat(ROOT_MARKER_RADIUS * 2.0), egui::Sense::click_and_drag());
+                let colour = if response.hovered() || response.dragged() { highlight } else { egui::Color32::WHITE };
+                ui.painter().circle(rect.center(), ROOT_MARKER_RADIUS - 2.0, egui::Color32::from_black_alpha(120), egui::Stroke::new(2.0, colour));
+                let delta = response.drag_delta() * pixels_per_point;
+                if delta != egui::Vec2::ZERO {
+                    let (dx, dy) = viewport.delta_to_complex(delta.x as f64, delta.y as f64);
+                    root.0 += dx;
+                    root.1 += dy;
+                    flags.generate_fractal = true;
+                }
+                if response.secondary_clicked() && removable {
+                    remove = Some(index);
+                }
+                response.on_hover_text(format!("{:.6} {:+.6}i", root.0, root.1));
+            });
+        }
+        if let Some(index) = remove {
+            newton.roots.remove(index);
+            flags.generate_fractal = true;
+        }
+    }
+
+    /// Markers on the roots of a Newton function found in the view, they can't be moved as the function decides where they are
+    fn found_roots(&mut self, ctx: &Context, newton: &Newton, view: View) {
+        let viewport = screen_viewport(ctx, view);
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("Newton roots")));
+        for root in newton.roots_in(viewport) {
+            let position = complex_to_pos(ctx, &viewport, root);
+            painter.circle(position, ROOT_MARKER_RADIUS - 2.0, egui::Color32::from_black_alpha(120), egui::Stroke::new(2.0, egui::Color32::WHITE));
+        }
+    }
+
+    /// Show the right click menu at the cursor
+    /// `newton` is the function of a Newton fractal
+    fn context_menu(&mut self, ctx: &Context, newton: Option<NewtonFunction>, flags: &mut Flags) {
+        let Some(menu) = &flags.context_menu else {
+            return;
+        };
+        let (x, y) = menu.point;
+        let pixel = menu.pixel;
+        let position = egui::pos2(menu.position.0 / ctx.pixels_per_point(), menu.position.1 / ctx.pixels_per_point());
+        let mut close = false;
+
+        let area = egui::Area::new("Context Menu")
+        .fixed_pos(position)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::menu(ui.style()).show(ui, |ui| {
+                if ui.button(RichText::new(tr("Copy coordinates")).font(self.font.clone())).clicked() {
+                    ui.output_mut(|o| o.copied_text = format!("{} {:+}i", x, y));
+                    close = true;
+                }
+                if ui.button(RichText::new(tr("Centre here")).font(self.font.clone())).clicked() {
+                    flags.context_action = Some(ContextAction::CentreHere(pixel));
+                    close = true;
+                }
+                if ui.button(RichText::new(tr("Zoom in 2x")).font(self.font.clone())).clicked() {
+                    flags.context_action = Some(ContextAction::ZoomIn(pixel));
+                    close = true;
+                }
+                if ui.button(RichText::new(tr("Set Julia seed here")).font(self.font.clone())).clicked() {
+                    flags.context_action = Some(ContextAction::SetJuliaSeed((x, y)));
+                    close = true;
+                }
+                if newton == Some(NewtonFunction::Polynomial) && self.edit_roots && ui.button(RichText::new(tr("Add root here")).font(self.font.clone())).clicked() {
+                    flags.context_action = Some(ContextAction::AddNewtonRoot((x, y)));
+                    close = true;
+                }
+                if ui.button(RichText::new(tr("Plot orbit here")).font(self.font.clone())).clicked() {
+                    flags.orbit_point = Some((x, y));
+                    flags.orbit_follow = false;
+                    close = true;
+                }
+                if ui.button(RichText::new(tr("Step through iterations")).font(self.font.clone())).clicked() {
+                    self.step_through = Some(StepThrough { point: (x, y), step: 0, playing: false, last_step: 0.0 });
+                    close = true;
+                }
+                // Newton fractals have no escape radius
+                if newton.is_none() && ui.button(RichText::new(tr("Watch it escape")).font(self.font.clone())).clicked() {
+                    self.escape_demo = Some(EscapeDemo { point: (x, y), started: ctx.input(|i| i.time) });
+                    self.show_escape_radius = true;
+                    close = true;
+                }
+                if ui.button(RichText::new(tr("Locate mini Mandelbrots")).font(self.font.clone())).clicked() {
+                    self.show_locator = true;
+                    close = true;
+                }
+                if ui.button(RichText::new(tr("Save image")).font(self.font.clone())).clicked() {
+                    flags.context_action = Some(ContextAction::SaveImage);
+                    close = true;
+                }
+                // Newton fractals have basins rather than a set to be in
+                if newton.is_none() {
+                    if ui.add_enabled(flags.mask_export.is_none(), egui::Button::new(RichText::new(tr("Save mask")).font(self.font.clone())))
+                        .on_hover_text(tr("Black inside the set and white outside, as a 1-bit pbm for stencils and laser cutting"))
+                        .clicked() {
+                        flags.context_action = Some(ContextAction::SaveMask { antialiased: false });
+                        close = true;
+                    }
+                    if ui.add_enabled(flags.mask_export.is_none(), egui::Button::new(RichText::new(tr("Save anti-aliased mask")).font(self.font.clone())))
+                        .on_hover_text(tr("An 8-bit png with the share of each pixel inside the set"))
+                        .clicked() {
+                        flags.context_action = Some(ContextAction::SaveMask { antialiased: true });
+                        close = true;
+                    }
+                }
+                if ui.button(RichText::new(tr("Save session")).font(self.font.clone())).clicked() {
+                    flags.context_action = Some(ContextAction::SaveSession);
+                    close = true;
+                }
+            });
+        });
+
+        // close the menu when clicking anywhere else
+        if close || (ctx.input(|i| i.pointer.any_pressed()) && !area.response.hovered()) {
+            flags.context_menu = None;
+        }
+    }
+
+    /// Label each side of the compare divider
+    fn compare_labels(&self, ctx: &Context, compare: &Compare) {
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("compare_labels")));
+        let screen = ctx.screen_rect();
+        let divider_x = screen.left() + screen.width() * compare.divider;
+        let y = screen.bottom() - 20.0;
+        painter.text(egui::pos2(divider_x - 10.0, y), egui::Align2::RIGHT_CENTER, "A", self.font.clone(), egui::Color32::WHITE);
+        painter.text(egui::pos2(divider_x + 10.0, y), egui::Align2::LEFT_CENTER, "B", self.font.clone(), egui::Color32::WHITE);
     }
 }
\ No newline at end of file
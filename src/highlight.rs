@@ -0,0 +1,40 @@
+use rayon::prelude::*;
+
+/// How much of its brightness the image outside the band keeps
+const DIM: f32 = 0.25;
+
+/// Picks out the pixels whose iteration count is in a band, drawn in one colour over a dimmed image
+pub struct BandHighlight {
+    pub enabled: bool,
+    /// First and last iteration counts in the band, both included
+    pub from: u32,
+    pub to: u32,
+    pub colour: [u8; 3],
+    /// Share of the pixels in the band, updated each time it's drawn
+    pub coverage: f32,
+}
+
+impl Default for BandHighlight {
+    fn default() -> Self {
+        Self { enabled: false, from: 0, to: 10, colour: [255, 0, 255], coverage: 0.0 }
+    }
+}
+
+impl BandHighlight {
+    /// Draw the band over an rgba frame with an iteration count for each pixel
+    pub fn apply(&mut self, pixels: &mut [u8], counts: &[u32]) {
+        let (from, to, colour) = (self.from, self.to, self.colour);
+        let inside = pixels.par_chunks_mut(4).zip(counts.par_iter()).filter_map(|(pixel, &count)| {
+            if (from..=to).contains(&count) {
+                pixel[..3].copy_from_slice(&colour);
+                Some(())
+            } else {
+                for channel in &mut pixel[..3] {
+                    *channel = (*channel as f32 * DIM) as u8;
+                }
+                None
+            }
+        }).count();
+        self.coverage = inside as f32 / counts.len().max(1) as f32;
+    }
+}
@@ -0,0 +1,84 @@
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::view::View;
+use web_time::{Duration, Instant};
+
+/// Most steps kept to undo, the oldest are forgotten
+const MAX_STEPS: usize = 100;
+/// Changes closer together than this are undone in one step, so a slider drag or a few notches of the wheel is one step
+const MERGE_WITHIN: Duration = Duration::from_millis(600);
+
+/// Which way to go through the history
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Step {
+    Undo,
+    Redo,
+}
+
+/// The fractal with its parameters and the view at one point in the history
+#[derive(Clone)]
+pub struct State {
+    pub fractal: Box<dyn Fractal>,
+    pub view: View,
+}
+
+impl State {
+    /// Two states are the same if the fractal saves the same and the view is the same
+    fn key(&self) -> String {
+        format!("{} {} {:?}", self.fractal.name(), self.fractal.to_json(), self.view)
+    }
+}
+
+/// The changes made to the view and the fractal's settings in a session, to step back and forward through
+#[derive(Clone, Default)]
+pub struct History {
+    undo: Vec<State>,
+    redo: Vec<State>,
+    /// What the next change is measured against, with its key
+    current: Option<(State, String)>,
+    /// When the last change was made
+    changed: Option<Instant>,
+}
+
+impl History {
+    /// Note the fractal and view as they are now, making a step if they've changed since the last step
+    pub fn track(&mut self, fractal: &dyn Fractal, view: View) {
+        let state = State { fractal: fractal.clone_box(), view };
+        let key = state.key();
+        let Some((current, current_key)) = self.current.take() else {
+            self.current = Some((state, key));
+            return;
+        };
+        if key == current_key {
+            self.current = Some((current, current_key));
+            return;
+        }
+        let now = Instant::now();
+        // a change soon after the last one carries on the same step
+        if self.changed.is_none_or(|changed| now - changed > MERGE_WITHIN) {
+            self.undo.push(current);
+            if self.undo.len() > MAX_STEPS {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.changed = Some(now);
+        self.current = Some((state, key));
+    }
+
+    /// The state to go back or forward to, None if there's nothing that way
+    pub fn step(&mut self, step: Step) -> Option<State> {
+        let (from, to) = match step {
+            Step::Undo => (&mut self.undo, &mut self.redo),
+            Step::Redo => (&mut self.redo, &mut self.undo),
+        };
+        let state = from.pop()?;
+        if let Some((current, _)) = self.current.take() {
+            to.push(current);
+        }
+        let key = state.key();
+        self.current = Some((state.clone(), key));
+        // the next change starts a new step rather than joining the one before
+        self.changed = None;
+        Some(state)
+    }
+}
@@ -0,0 +1,564 @@
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+pub const LANGUAGES: [Language; 2] = [Language::English, Language::French];
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Language {
+    English,
+    French,
+}
+
+impl Language {
+    /// The name of the language in that language
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::French => "Français",
+        }
+    }
+
+    /// Pairs of english text and its translation
+    fn translations(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::French => FRENCH,
+        }
+    }
+}
+
+/// Global like the custom palettes so any label can be translated without passing the language around
+static LANGUAGE: RwLock<Language> = RwLock::new(Language::English);
+
+pub fn set_language(language: Language) {
+    if let Ok(mut current) = LANGUAGE.write() {
+        *current = language;
+    }
+}
+
+/// Translate english gui text into the current language, falling back to the english text if there is no translation
+pub fn tr(text: &str) -> &str {
+    let language = LANGUAGE.read().map(|language| *language).unwrap_or(Language::English);
+    language.translations().iter()
+        .find(|(english, _)| *english == text)
+        .map_or(text, |(_, translation)| translation)
+}
+
+const FRENCH: &[(&str, &str)] = &[
+    // settings window
+    ("Settings", "Paramètres"),
+    ("A/B Compare", "Comparaison A/B"),
+    ("Editing:", "Modifier :"),
+    ("Fractal:", "Fractale :"),
+    ("Colour:", "Couleurs :"),
+    ("Curve", "Courbe"),
+    ("Edit how iteration counts map onto the palette", "Modifier la correspondance entre itérations et palette"),
+    ("Previews", "Aperçus"),
+    ("Show the view in every palette", "Afficher la vue dans chaque palette"),
+    ("Auto levels", "Niveaux auto"),
+    ("Spread the palette over the iterations in view rather than up to max iterations", "Étaler la palette sur les itérations visibles plutôt que jusqu'au maximum d'itérations"),
+    ("Rotation:", "Rotation :"),
+    ("Colour blind friendly", "Adapté aux daltoniens"),
+    ("Simulate:", "Simuler :"),
+    ("Normal vision", "Vision normale"),
+    ("Protanopia", "Protanopie"),
+    ("Deuteranopia", "Deutéranopie"),
+    ("Tritanopia", "Tritanopie"),
+    ("Renderer:", "Rendu :"),
+    ("Render scale:", "Échelle de rendu :"),
+    ("Pixels generated for each pixel of the window, lower is faster and higher is sharper", "Pixels générés pour chaque pixel de la fenêtre, plus bas est plus rapide et plus haut est plus net"),
+    ("Draws every fractal and colouring in 64 bit floats, using every core", "Dessine toutes les fractales et colorations en flottants 64 bits, sur tous les cœurs"),
+    ("Draws the Mandelbrot and Julia sets with escape time colouring in 32 bit floats. \
+        Other fractals and colourings, and zooms past the limit of 32 bit floats, are drawn on the CPU",
+        "Dessine les ensembles de Mandelbrot et de Julia colorés par temps d'échappement en flottants 32 bits. \
+        Les autres fractales et colorations, et les zooms au-delà de la limite des flottants 32 bits, sont dessinés sur le CPU"),
+    ("Draws on the GPU while exploring, even past the limit of 32 bit floats where it goes blocky. \
+        Views past the limit are then drawn again on the CPU in the background and shown band by band as they finish",
+        "Dessine sur le GPU pendant l'exploration, même au-delà de la limite des flottants 32 bits où l'image devient pixelisée. \
+        Les vues au-delà de la limite sont ensuite redessinées sur le CPU en arrière-plan et affichées bande par bande"),
+    ("Rendering failed, switched to the CPU", "Échec du rendu, passage au CPU"),
+    ("Couldn't start remote control", "Impossible de démarrer le contrôle à distance"),
+    ("Couldn't read the settings", "Impossible de lire les paramètres"),
+    ("Boundary only", "Bord uniquement"),
+    ("Background", "Arrière-plan"),
+    ("Line", "Ligne"),
+    ("Inspector", "Inspecteur"),
+    ("Screenshots include UI", "Captures avec l'interface"),
+    ("Render stats", "Statistiques de rendu"),
+    ("Last frame", "Dernière image"),
+    ("Time left", "Temps restant"),
+    ("Drawing again on the CPU", "Redessin sur le CPU"),
+    ("Bookmarks", "Favoris"),
+    ("Appearance", "Apparence"),
+    ("Max Iterations", "Itérations max"),
+    ("Escape Radius", "Rayon d'échappement"),
+    ("Bailout", "Test d'échappement"),
+    ("Circle", "Cercle"),
+    ("Square", "Carré"),
+    ("Strip", "Bande"),
+    ("Real", "Réel"),
+    ("Imaginary", "Imaginaire"),
+    ("Tint interior by period", "Teinter l'intérieur selon la période"),
+    ("Colour by atom domain", "Colorer par domaine atomique"),
+    ("Colour by convergence speed", "Colorer selon la vitesse de convergence"),
+    ("Boundary by inverse iteration", "Bord par itération inverse"),
+    ("External rays and equipotentials", "Rayons externes et équipotentielles"),
+    ("Julia preview", "Aperçu de Julia"),
+    ("Show the Julia set of the point under the mouse in the corner, click it to explore that set", "Afficher dans le coin l'ensemble de Julia du point sous la souris, cliquer dessus pour explorer cet ensemble"),
+    ("Click to explore this Julia set", "Cliquer pour explorer cet ensemble de Julia"),
+    ("Edit roots", "Modifier les racines"),
+    ("Drag the roots to move them, right click one to remove it or right click the fractal to add one", "Faire glisser les racines pour les déplacer, clic droit sur une racine pour la supprimer ou sur la fractale pour en ajouter une"),
+    ("Show roots", "Afficher les racines"),
+    ("Mark the roots found in the view", "Marquer les racines trouvées dans la vue"),
+    ("Function", "Fonction"),
+    ("Polynomial", "Polynôme"),
+    ("Reset all", "Tout réinitialiser"),
+    ("Reset parameters", "Réinitialiser les paramètres"),
+    ("Reset view", "Réinitialiser la vue"),
+    ("Randomize", "Aléatoire"),
+    ("Pick random parameters that tend to give interesting results", "Choisir des paramètres aléatoires qui donnent souvent des résultats intéressants"),
+    ("Share the seed to give someone the same parameters, or enter one you were given", "Partagez la graine pour donner les mêmes paramètres à quelqu'un, ou saisissez-en une reçue"),
+    ("Suggest iterations", "Suggérer les itérations"),
+    ("Work out how many iterations the edge of the set needs each time the view is drawn", "Calculer le nombre d'itérations dont le bord de l'ensemble a besoin à chaque dessin de la vue"),
+    ("No edge in view", "Aucun bord visible"),
+    ("Apply", "Appliquer"),
+    ("90% of boundary pixels need ≤", "90 % des pixels du bord demandent ≤"),
+    // appearance
+    ("UI scale:", "Échelle de l'interface :"),
+    ("Font size:", "Taille du texte :"),
+    ("Language:", "Langue :"),
+    ("Theme:", "Thème :"),
+    ("Accent:", "Accent :"),
+    ("Settings position:", "Position des paramètres :"),
+    ("Reset", "Réinitialiser"),
+    ("Failed to save settings", "Échec de l'enregistrement des paramètres"),
+    ("Selection:", "Sélection :"),
+    ("Dim outside", "Assombrir l'extérieur"),
+    ("Show size", "Afficher la taille"),
+    ("Show the width and height of the selection in the complex plane", "Afficher la largeur et la hauteur de la sélection dans le plan complexe"),
+    ("Show pixels", "Afficher les pixels"),
+    ("Show the width and height of the selection in pixels of the window", "Afficher la largeur et la hauteur de la sélection en pixels de la fenêtre"),
+    ("Save current as defaults", "Enregistrer comme valeurs par défaut"),
+    ("Start with this fractal, palette and window size", "Démarrer avec cette fractale, cette palette et cette taille de fenêtre"),
+    ("Saved defaults", "Valeurs par défaut enregistrées"),
+    ("Dark", "Sombre"),
+    ("Light", "Clair"),
+    ("High contrast", "Contraste élevé"),
+    ("Top left", "En haut à gauche"),
+    ("Top right", "En haut à droite"),
+    ("Bottom left", "En bas à gauche"),
+    ("Bottom right", "En bas à droite"),
+    ("Free", "Libre"),
+    // precision
+    ("Precision limit reached", "Limite de précision atteinte"),
+    ("⚠ Near precision limit", "⚠ Proche de la limite de précision"),
+    ("⚠ Precision limit", "⚠ Limite de précision"),
+    ("Double-double precision", "Précision double-double"),
+    ("The view is past the limit of 64 bit floating point numbers, so it is drawn in double-double precision which is a few times slower.",
+        "La vue dépasse la limite des nombres à virgule flottante 64 bits, elle est donc dessinée en précision double-double, quelques fois plus lente."),
+    ("The view is close to the limit of the floating point numbers it is drawn in. Zooming much further will make the image blocky.",
+        "La vue approche la limite des nombres à virgule flottante avec lesquels elle est dessinée. Zoomer davantage rendra l'image pixelisée."),
+    ("Neighbouring pixels are closer together than the floating point numbers the view is drawn in can represent, so groups of pixels get the same value and the image goes blocky. The Mandelbrot and Julia sets switch to double-double precision by themselves, zooming deeper than that needs a perturbation renderer.",
+        "Les pixels voisins sont plus proches que ce que les nombres à virgule flottante avec lesquels la vue est dessinée peuvent représenter, des groupes de pixels ont donc la même valeur et l'image devient pixelisée. Les ensembles de Mandelbrot et de Julia passent d'eux-mêmes en précision double-double, zoomer plus loin nécessite un rendu par perturbation."),
+    // sessions and bookmarks
+    ("Close", "Fermer"),
+    ("Link views", "Lier les vues"),
+    ("Panning and zooming in one tab moves the others to the same region", "Déplacer et zoomer dans un onglet amène les autres sur la même région"),
+    ("New session", "Nouvelle session"),
+    ("Name", "Nom"),
+    ("Save view", "Enregistrer la vue"),
+    ("Render gallery", "Rendre la galerie"),
+    ("Folder, a new one if empty", "Dossier, un nouveau si vide"),
+    ("HTML index page", "Page d'index HTML"),
+    ("Render every bookmark at the final render quality", "Rendre chaque favori en qualité finale"),
+    ("Bookmark", "Favori"),
+    ("Delete", "Supprimer"),
+    // inspector
+    ("Point", "Point"),
+    ("Iterations", "Itérations"),
+    ("Smooth", "Lissé"),
+    ("Root", "Racine"),
+    ("Period", "Période"),
+    ("Atom domain", "Domaine atomique"),
+    ("none", "aucune"),
+    ("Selection", "Sélection"),
+    ("Mean iterations", "Itérations moyennes"),
+    ("Max iterations", "Itérations max"),
+    ("Interior", "Intérieur"),
+    // external rays
+    ("External rays", "Rayons externes"),
+    ("Angles:", "Angles :"),
+    ("Fractions of a turn separated by commas", "Fractions de tour séparées par des virgules"),
+    ("Equipotentials:", "Équipotentielles :"),
+    ("Couldn't read angle", "Impossible de lire l'angle"),
+    // area estimate
+    ("Area", "Aire"),
+    ("Area estimate", "Estimation de l'aire"),
+    ("Visible area", "Zone visible"),
+    ("Whole set", "Ensemble entier"),
+    ("Start", "Démarrer"),
+    ("Points that don't escape within the max iterations count as inside", "Les points qui ne s'échappent pas avant le nombre max d'itérations comptent comme intérieurs"),
+    ("This fractal has no bounded set to measure", "Cette fractale n'a pas d'ensemble borné à mesurer"),
+    ("95% confidence", "Confiance à 95 %"),
+    ("samples", "échantillons"),
+    // locator
+    ("Locate", "Localiser"),
+    ("Locate mini Mandelbrots", "Localiser les mini Mandelbrot"),
+    ("Only available for the Mandelbrot set", "Disponible uniquement pour l'ensemble de Mandelbrot"),
+    ("Mini Mandelbrot", "Mini Mandelbrot"),
+    ("Misiurewicz point", "Point de Misiurewicz"),
+    ("Nucleus of period", "Noyau de période"),
+    ("Zoom to it", "Zoomer dessus"),
+    ("Nothing found near the view", "Rien trouvé près de la vue"),
+    // step through
+    ("Step through iterations", "Parcourir les itérations"),
+    ("Step through", "Pas à pas"),
+    ("so the point escaped, it's outside the set", "donc le point s'est échappé, il est hors de l'ensemble"),
+    ("Still inside after the max iterations, it's counted as in the set", "Toujours à l'intérieur après le nombre max d'itérations, il compte comme dans l'ensemble"),
+    ("so keep iterating", "donc on continue d'itérer"),
+    ("Converged on a root", "A convergé vers une racine"),
+    ("Not at a root yet, keep iterating", "Pas encore sur une racine, on continue d'itérer"),
+    ("Step back", "Pas en arrière"),
+    ("Step forward", "Pas en avant"),
+    // escape radius
+    ("Show escape radius", "Afficher le rayon d'échappement"),
+    ("Draw the boundary points have to cross to escape, right click a point to watch it escape", "Dessiner la limite que les points doivent franchir pour s'échapper, clic droit sur un point pour le voir s'échapper"),
+    ("Watch it escape", "Le voir s'échapper"),
+    ("Escaped after", "Échappé après"),
+    ("iterations", "itérations"),
+    // scripting
+    ("Console", "Console"),
+    ("Script", "Script"),
+    ("Run", "Exécuter"),
+    ("Clear", "Effacer"),
+    ("Script error", "Erreur de script"),
+    ("Reloaded", "Rechargé"),
+    ("The custom shader didn't compile, see the console", "Le shader personnalisé n'a pas compilé, voir la console"),
+    ("A script is already running", "Un script est déjà en cours d'exécution"),
+    // log
+    ("Log", "Journal"),
+    ("Start with --verbose for more detail", "Lancer avec --verbose pour plus de détails"),
+    // orbit
+    ("Orbit", "Orbite"),
+    ("Complex plane", "Plan complexe"),
+    ("Follow mouse", "Suivre la souris"),
+    // context menu
+    ("Copy coordinates", "Copier les coordonnées"),
+    ("Centre here", "Centrer ici"),
+    ("Zoom in 2x", "Zoomer 2x"),
+    ("Set Julia seed here", "Graine de Julia ici"),
+    ("Add root here", "Ajouter une racine ici"),
+    ("Plot orbit here", "Tracer l'orbite ici"),
+    ("Save image", "Enregistrer l'image"),
+    ("Save mask", "Enregistrer le masque"),
+    ("Black inside the set and white outside, as a 1-bit pbm for stencils and laser cutting", "Noir dans l'ensemble et blanc à l'extérieur, en pbm 1 bit pour les pochoirs et la découpe laser"),
+    ("Save anti-aliased mask", "Enregistrer le masque anticrénelé"),
+    ("An 8-bit png with the share of each pixel inside the set", "Un png 8 bits avec la part de chaque pixel dans l'ensemble"),
+    ("Save session", "Enregistrer la session"),
+    // controls
+    ("Controls", "Commandes"),
+    ("Cancel the zoom box, divider drag or context menu", "Annuler le zoom, le déplacement du séparateur ou le menu"),
+    ("Pan up", "Déplacer vers le haut"),
+    ("Pan down", "Déplacer vers le bas"),
+    ("Pan left", "Déplacer vers la gauche"),
+    ("Pan right", "Déplacer vers la droite"),
+    ("Rotate anticlockwise", "Tourner dans le sens antihoraire"),
+    ("Rotate clockwise", "Tourner dans le sens horaire"),
+    ("Hide or show the UI", "Masquer ou afficher l'interface"),
+    ("Toggle the pixel inspector", "Afficher l'inspecteur de pixels"),
+    ("Open the bookmarks gallery", "Ouvrir la galerie des favoris"),
+    ("Save a screenshot", "Enregistrer une capture d'écran"),
+    ("Render the view at final quality", "Rendre la vue en qualité finale"),
+    ("Draw a box to render again at final quality", "Dessiner une zone à rendre de nouveau en qualité finale"),
+    ("Open the animation timeline", "Ouvrir la chronologie d'animation"),
+    ("Toggle fullscreen", "Basculer en plein écran"),
+    ("Show this help", "Afficher cette aide"),
+    ("Scroll", "Molette"),
+    ("Zoom in and out", "Zoomer et dézoomer"),
+    ("Left drag", "Glisser clic gauche"),
+    ("Draw a box to zoom into", "Dessiner une zone à agrandir"),
+    ("Shift + left drag", "Maj + glisser clic gauche"),
+    ("Draw a box to fit the view into, zooming out", "Dessiner une zone où faire tenir la vue, pour dézoomer"),
+    ("Left click", "Clic gauche"),
+    ("Centre on the point", "Centrer sur le point"),
+    ("Left drag divider", "Glisser le séparateur"),
+    ("Wipe between A and B in compare mode", "Basculer entre A et B en mode comparaison"),
+    ("Right click", "Clic droit"),
+    ("Open the context menu", "Ouvrir le menu contextuel"),
+    ("Ctrl + P", "Ctrl + P"),
+    ("Open the command palette", "Ouvrir la palette de commandes"),
+    ("Ctrl + Z", "Ctrl + Z"),
+    ("Undo the last change to the view or settings", "Annuler la dernière modification de la vue ou des réglages"),
+    ("Ctrl + Y", "Ctrl + Y"),
+    ("Redo the change that was undone", "Rétablir la modification annulée"),
+    ("Ctrl + 1-9", "Ctrl + 1-9"),
+    ("Save a snapshot of the settings and view to the slot", "Enregistrer un instantané des réglages et de la vue dans l'emplacement"),
+    ("1-9", "1-9"),
+    ("Go back to the snapshot in the slot", "Revenir à l'instantané de l'emplacement"),
+    // history
+    ("Undo", "Annuler"),
+    ("Redo", "Rétablir"),
+    ("Nothing to undo", "Rien à annuler"),
+    ("Nothing to redo", "Rien à rétablir"),
+    // command palette
+    ("Commands", "Commandes"),
+    ("Type a command", "Saisir une commande"),
+    ("No matching commands", "Aucune commande correspondante"),
+    ("Toggle", "Basculer"),
+    ("Go to bookmark", "Aller au favori"),
+    ("Palette previews", "Aperçus des palettes"),
+    // navigation
+    ("Navigation", "Navigation"),
+    ("How far the pan keys and mouse wheel move", "Distance parcourue avec les touches de déplacement et la molette"),
+    ("Zoom factor:", "Facteur de zoom :"),
+    ("How many times each notch of the mouse wheel zooms in or out", "Facteur de zoom avant ou arrière à chaque cran de la molette"),
+    ("Pan keys:", "Touches de déplacement :"),
+    ("Step each press", "Un pas par appui"),
+    ("Move while held", "Déplacer tant qu'elles sont enfoncées"),
+    ("Pan speed:", "Vitesse de déplacement :"),
+    ("Window heights a second", "Hauteurs de fenêtre par seconde"),
+    ("Pan step:", "Pas de déplacement :"),
+    ("Share of the window height each press moves", "Part de la hauteur de la fenêtre parcourue à chaque appui"),
+    ("Zoom box", "Cadre de zoom"),
+    ("Drag threshold:", "Seuil de glissement :"),
+    ("Pixels the mouse has to move both across and down before a drag becomes a zoom box", "Pixels que la souris doit parcourir en largeur et en hauteur avant qu'un glissement devienne un cadre de zoom"),
+    ("Minimum area:", "Aire minimale :"),
+    ("Smaller boxes are taken as a click, which centres on the point", "Les cadres plus petits comptent comme un clic, qui centre la vue sur le point"),
+    ("Lower zooms further into a box of the same size", "Plus bas zoome davantage dans un cadre de même taille"),
+    ("Least zoom:", "Zoom minimal :"),
+    ("How far even a box covering the whole window zooms in", "Zoom appliqué même par un cadre couvrant toute la fenêtre"),
+    ("Most zoom:", "Zoom maximal :"),
+    ("How far even the smallest box zooms in", "Zoom appliqué même par le plus petit cadre"),
+    // breadcrumbs
+    ("Jump back to this depth", "Revenir à cette profondeur"),
+    // snapshots
+    ("Saved snapshot", "Instantané enregistré"),
+    ("Snapshot", "Instantané"),
+    ("Nothing saved yet, Ctrl + the number saves to slot", "Rien d'enregistré, Ctrl + le chiffre enregistre dans l'emplacement"),
+    // error dialog
+    ("Continue", "Continuer"),
+    ("Quit", "Quitter"),
+    ("Out of GPU memory", "Mémoire graphique épuisée"),
+    ("Closing other tabs or making the window smaller may help.", "Fermer d'autres onglets ou réduire la fenêtre peut aider."),
+    ("Graphics error", "Erreur graphique"),
+    ("Rendering failed", "Échec du rendu"),
+    ("Couldn't recreate the window surface", "Impossible de recréer la surface de la fenêtre"),
+    ("Couldn't resize the window", "Impossible de redimensionner la fenêtre"),
+    ("No suitable graphics card", "Aucune carte graphique compatible"),
+    ("Falling back to software rendering, which will be slow.", "Passage au rendu logiciel, qui sera lent."),
+    // graphics settings
+    ("Graphics", "Graphismes"),
+    ("In use", "Utilisé"),
+    ("Adapter:", "Adaptateur :"),
+    ("Automatic", "Automatique"),
+    ("Pick one by the power preference", "En choisir un selon la préférence d'énergie"),
+    ("Power preference:", "Préférence d'énergie :"),
+    ("Default", "Par défaut"),
+    ("Low power", "Économie d'énergie"),
+    ("High performance", "Hautes performances"),
+    ("Let the system choose, or the WGPU_POWER_PREF environment variable", "Laisser le système choisir, ou la variable d'environnement WGPU_POWER_PREF"),
+    ("Prefer the integrated graphics, which uses less battery", "Préférer la puce graphique intégrée, qui consomme moins de batterie"),
+    ("Prefer the discrete graphics card", "Préférer la carte graphique dédiée"),
+    ("Present mode:", "Mode de présentation :"),
+    ("Fullscreen on:", "Plein écran sur :"),
+    ("Current monitor", "Écran actuel"),
+    ("The monitor the window is on", "L'écran où se trouve la fenêtre"),
+    ("Fullscreen", "Plein écran"),
+    ("Monitor", "L'écran"),
+    ("isn't connected, going fullscreen on this one", "n'est pas connecté, plein écran sur celui-ci"),
+    ("Vsync", "Synchro verticale"),
+    ("Immediate", "Immédiat"),
+    ("Mailbox", "Boîte aux lettres"),
+    ("Wait for the display to refresh, supported everywhere", "Attendre le rafraîchissement de l'écran, pris en charge partout"),
+    ("Show frames straight away, with the lowest latency but possible tearing", "Afficher les images immédiatement, avec la latence la plus faible mais un possible déchirement"),
+    ("Show the newest frame at each refresh, low latency without tearing", "Afficher l'image la plus récente à chaque rafraîchissement, faible latence sans déchirement"),
+    ("Graphics settings applied", "Paramètres graphiques appliqués"),
+    ("Couldn't apply the graphics settings", "Impossible d'appliquer les paramètres graphiques"),
+    // virtual camera
+    ("Virtual camera", "Caméra virtuelle"),
+    ("Publishing", "Publication"),
+    ("to", "vers"),
+    ("Stop publishing", "Arrêter la publication"),
+    ("A v4l2loopback device, OBS and VJ software see it as a webcam", "Un périphérique v4l2loopback, OBS et les logiciels de VJ le voient comme une webcam"),
+    ("Publish frames", "Publier les images"),
+    ("Publishing frames to", "Publication des images vers"),
+    ("Couldn't start the virtual camera", "Impossible de démarrer la caméra virtuelle"),
+    ("Virtual camera stopped", "Caméra virtuelle arrêtée"),
+    // watch folder
+    ("Watch folder", "Dossier surveillé"),
+    ("Session (.json) and Kalles Fraktaler (.kfr) files added to the folder or changed are loaded into the current tab",
+        "Les fichiers de session (.json) et Kalles Fraktaler (.kfr) ajoutés ou modifiés dans le dossier sont chargés dans l'onglet actuel"),
+    ("Watching", "Surveillé"),
+    ("Not watching a folder", "Aucun dossier surveillé"),
+    ("Folder", "Dossier"),
+    ("Watch", "Surveiller"),
+    ("Couldn't watch the folder", "Impossible de surveiller le dossier"),
+    ("Files can't be watched for changes", "Les fichiers ne peuvent pas être surveillés"),
+    // adjustments
+    ("Adjustments", "Réglages"),
+    ("Brightness:", "Luminosité :"),
+    ("Contrast:", "Contraste :"),
+    ("Gamma:", "Gamma :"),
+    ("Saturation:", "Saturation :"),
+    ("Bloom", "Halo lumineux"),
+    ("Make the bright parts glow", "Faire briller les parties lumineuses"),
+    ("Threshold:", "Seuil :"),
+    ("How bright a colour has to be to glow", "Luminosité à partir de laquelle une couleur brille"),
+    ("Intensity:", "Intensité :"),
+    ("Radius:", "Rayon :"),
+    // stereo
+    ("3D:", "3D :"),
+    ("Points that take longer to escape stand out towards you", "Les points qui mettent plus longtemps à s'échapper ressortent vers vous"),
+    ("Off", "Désactivé"),
+    ("Red-cyan anaglyph", "Anaglyphe rouge-cyan"),
+    ("Side by side", "Côte à côte"),
+    ("Eye separation:", "Écart des yeux :"),
+    ("How far apart the nearest and furthest points are for the two eyes", "Écart entre les points les plus proches et les plus éloignés pour les deux yeux"),
+    // iteration bands
+    ("Iteration bands", "Bandes d'itérations"),
+    ("Highlight colour:", "Couleur de surbrillance :"),
+    ("Counting iterations...", "Comptage des itérations..."),
+    ("of the pixels are in the band", "des pixels sont dans la bande"),
+    // watermark
+    ("Watermark", "Filigrane"),
+    ("Text:", "Texte :"),
+    ("Title or name", "Titre ou nom"),
+    ("Caption:", "Légende :"),
+    ("Coordinates", "Coordonnées"),
+    ("Zoom", "Zoom"),
+    ("Date", "Date"),
+    ("Caption colour:", "Couleur de la légende :"),
+    ("Logo:", "Logo :"),
+    ("An image drawn in the bottom right corner", "Une image dessinée dans le coin inférieur droit"),
+    ("Image file", "Fichier image"),
+    ("Border:", "Bordure :"),
+    ("Border colour:", "Couleur de la bordure :"),
+    // defaults
+    ("Defaults", "Valeurs par défaut"),
+    ("What this fractal starts with and is reset to", "Ce avec quoi cette fractale démarre et est réinitialisée"),
+    ("Max iterations:", "Itérations max :"),
+    ("Escape radius:", "Rayon d'échappement :"),
+    ("Starting view:", "Vue de départ :"),
+    ("Use current view", "Utiliser la vue actuelle"),
+    ("Starts with the built in defaults", "Démarre avec les valeurs par défaut intégrées"),
+    ("Use current settings", "Utiliser les réglages actuels"),
+    ("Start with the current iterations, escape radius, palette and view", "Démarrer avec les itérations, le rayon d'échappement, la palette et la vue actuels"),
+    ("Forget", "Oublier"),
+    ("Go back to the built in defaults", "Revenir aux valeurs par défaut intégrées"),
+    // colour curve
+    ("Colour curve", "Courbe de couleurs"),
+    ("Drag the points, double click to add one and right click to remove it", "Faites glisser les points, double-cliquez pour en ajouter un et clic droit pour le supprimer"),
+    // quality
+    ("Quality", "Qualité"),
+    ("Exploring", "Exploration"),
+    ("Final", "Final"),
+    ("Supersampling:", "Suréchantillonnage :"),
+    ("Samples averaged for each pixel, smoothing the edges", "Échantillons moyennés pour chaque pixel, adoucissant les bords"),
+    ("Iterations:", "Itérations :"),
+    ("The max iterations are multiplied by this", "Le nombre maximal d'itérations est multiplié par ceci"),
+    ("Time-lapse every", "Time-lapse toutes les"),
+    ("Save final renders as numbered pngs while they're drawn, to watch long renders fill in", "Enregistrer les rendus finaux en png numérotés pendant leur dessin, pour voir les longs rendus se remplir"),
+    ("Saved a time-lapse of", "Time-lapse enregistré de"),
+    ("frames to", "images dans"),
+    ("Failed to save the time-lapse", "Échec de l'enregistrement du time-lapse"),
+    ("Refine the boundary", "Affiner la frontière"),
+    ("Adaptive sampling", "Échantillonnage adaptatif"),
+    ("Supersample only the parts of the image with detail, found from a first pass with one sample per pixel", "Suréchantillonner seulement les parties de l'image avec des détails, trouvées par une première passe à un échantillon par pixel"),
+    ("Tolerance:", "Tolérance :"),
+    ("How much the colours in a part of the image can vary before it's supersampled, lower is better quality and slower", "De combien les couleurs d'une partie de l'image peuvent varier avant qu'elle soit suréchantillonnée, plus bas donne une meilleure qualité mais plus lente"),
+    ("Draw the points at max iterations next to the boundary again with more iterations, for root, period and atom domain colouring", "Redessiner avec plus d'itérations les points au maximum d'itérations près de la frontière, pour la coloration par racine, période et domaine atomique"),
+    ("Smooth colouring", "Coloration lissée"),
+    ("Colour by the continuous iteration count, removing the bands between counts", "Colorer selon le nombre d'itérations continu, sans bandes entre les valeurs"),
+    ("Render final quality", "Rendu en qualité finale"),
+    ("Final quality", "Qualité finale"),
+    ("left", "restant"),
+    ("Cancel", "Annuler"),
+    ("Final quality render finished", "Rendu en qualité finale terminé"),
+    ("Render region", "Rendre une zone"),
+    ("Region", "Zone"),
+    ("Draw a box around the region to render again", "Dessinez une zone autour de la région à rendre de nouveau"),
+    ("Region render finished", "Rendu de la zone terminé"),
+    ("Region render cancelled", "Rendu de la zone annulé"),
+    // start panel
+    ("Welcome back", "Bon retour"),
+    ("Last session", "Dernière session"),
+    ("Continue from this tab", "Reprendre depuis cet onglet"),
+    ("more tabs", "onglets de plus"),
+    ("Continue where I left off", "Reprendre là où je m'étais arrêté"),
+    ("Recent bookmarks", "Favoris récents"),
+    ("Start fresh", "Repartir de zéro"),
+    // restoring after a crash
+    ("Restore previous session?", "Restaurer la session précédente ?"),
+    ("The app didn't close properly last time.", "L'application ne s'est pas fermée correctement la dernière fois."),
+    ("Tabs that were open", "Onglets ouverts"),
+    ("Restore", "Restaurer"),
+    ("Discard", "Ignorer"),
+    // notifications
+    ("Opened", "Ouvert :"),
+    ("Loaded palette", "Palette chargée"),
+    ("Couldn't open", "Impossible d'ouvrir"),
+    ("only sessions and bookmarks can be opened at startup", "seuls les sessions et les favoris peuvent être ouverts au démarrage"),
+    ("there's no file or bookmark with that name", "aucun fichier ou favori ne porte ce nom"),
+    ("Bookmark saved", "Favori enregistré"),
+    ("Failed to save bookmark", "Échec de l'enregistrement du favori"),
+    ("Failed to remove bookmark", "Échec de la suppression du favori"),
+    ("Saved session to", "Session enregistrée dans"),
+    ("Failed to save session", "Échec de l'enregistrement de la session"),
+    ("The UI is hidden so it won't be in the screenshot", "L'interface est masquée, elle ne sera pas dans la capture"),
+    ("Saved image to", "Image enregistrée dans"),
+    ("Failed to save image", "Échec de l'enregistrement de l'image"),
+    ("Zoom cancelled", "Zoom annulé"),
+    ("Saved animation to", "Animation enregistrée dans"),
+    ("Failed to export animation", "Échec de l'export de l'animation"),
+    ("Saved mask to", "Masque enregistré dans"),
+    ("Failed to save mask", "Échec de l'enregistrement du masque"),
+    ("Saved gallery to", "Galerie enregistrée dans"),
+    ("Failed to render gallery", "Échec du rendu de la galerie"),
+    // timeline
+    ("Timeline", "Chronologie"),
+    ("Add keyframe", "Ajouter une image clé"),
+    ("Play", "Lecture"),
+    ("Pause", "Pause"),
+    ("Export video", "Exporter la vidéo"),
+    ("Exporting", "Export"),
+    ("Reuse frames", "Réutiliser les images"),
+    ("Frames that only zoom or pan are scaled from a keyframe rendered every 2x of zoom, much faster for long zooms but a little softer", "Les images qui ne font que zoomer ou se déplacer sont agrandies depuis une image clé rendue à chaque zoom x2, bien plus rapide pour les longs zooms mais un peu plus douce"),
+    ("Go to", "Aller à"),
+    ("Keyframe", "Image clé"),
+    ("Duration", "Durée"),
+    ("FPS:", "Images/s :"),
+    ("Add keyframes to animate between them", "Ajoutez des images clés pour animer entre elles"),
+    ("Motion", "Mouvement"),
+    ("Rotate", "Rotation"),
+    ("Wobble", "Oscillation"),
+    ("Speed", "Vitesse"),
+    ("Orbits per second", "Orbites par seconde"),
+    ("Radius", "Rayon"),
+    ("Clip length:", "Durée du clip :"),
+    ("Parameter sweep", "Balayage de paramètre"),
+    ("Parameter:", "Paramètre :"),
+    ("From:", "De :"),
+    ("To:", "À :"),
+    ("Frames:", "Images :"),
+    ("Stop", "Arrêter"),
+    ("Preview", "Aperçu"),
+    ("Export sweep", "Exporter le balayage"),
+    ("Record navigation", "Enregistrer la navigation"),
+    ("Stop recording", "Arrêter l'enregistrement"),
+    ("changes recorded", "changements enregistrés"),
+    ("Saved replay to", "Relecture enregistrée dans"),
+    ("Failed to save replay", "Échec de l'enregistrement de la relecture"),
+    ("Audio reactive", "Réaction au son"),
+    ("Listen to audio input", "Écouter l'entrée audio"),
+    ("Stop listening", "Arrêter l'écoute"),
+    ("Bass: zoom pulse", "Basses : pulsation du zoom"),
+    ("Mids: Julia seed", "Médiums : graine de Julia"),
+    ("Treble: rotation", "Aigus : rotation"),
+    ("Sensitivity:", "Sensibilité :"),
+    ("Couldn't start audio", "Impossible de démarrer l'audio"),
+    ("Linear", "Linéaire"),
+    ("Ease in", "Accélération"),
+    ("Ease out", "Décélération"),
+    ("Ease in/out", "Accélération/décélération"),
+];
@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use fractal_viz_core::double_double::DoubleDouble;
+use fractal_viz_core::fractals::Mandelbrot;
+use fractal_viz_core::palette;
+use fractal_viz_core::view::View;
+
+use crate::replay::Replay;
+use crate::session::SessionFile;
+
+/// Something loaded from a file dropped onto the window
+pub enum Imported {
+    Session(SessionFile),
+    Palette(String),
+    Replay(Replay),
+    /// A Rhai script, either a custom fractal or automation to run
+    Script { name: String, source: String },
+}
+
+/// A file or bookmark to start at, given on the command line as `fractal open <file or bookmark>`.
+/// A file on its own works too, which is how the system passes on files opened with the app
+pub fn target_from_args() -> Option<String> {
+    let mut args = std::env::args().skip(1).filter(|arg| !arg.starts_with('-'));
+    match args.next()? {
+        command if command == "open" => args.next(),
+        target => Some(target),
+    }
+}
+
+/// Load a session (.json or .fvz), replay (.replay), Kalles Fraktaler location (.kfr), palette (.gpl, .txt, .pal) or script (.rhai) file.
+/// `window_height` is needed to convert the zoom of Kalles Fraktaler files.
+pub fn import_file(path: &Path, window_height: u32) -> Result<Imported, String> {
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        // sessions can be given their own extension so the system opens them with the app
+        "json" | "fvz" => SessionFile::load(path).map(Imported::Session),
+        "replay" => Replay::load(path).map(Imported::Replay),
+        "kfr" => load_kfr(path, window_height).map(Imported::Session),
+        "gpl" | "txt" | "pal" => {
+            let (name, colours) = palette::load(path)?;
+            palette::register(name.clone(), colours);
+            Ok(Imported::Palette(name))
+        }
+        "rhai" => {
+            let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            Ok(Imported::Script { name, source })
+        }
+        _ => Err(format!("Can't open .{} files", extension)),
+    }
+}
+
+/// Read the location of a Kalles Fraktaler parameter file as a Mandelbrot session.
+/// The palette in the file is loaded too.
+fn load_kfr(path: &Path, window_height: u32) -> Result<SessionFile, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let field = |name: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .map(str::trim)
+    };
+    let number = |name: &str| field(name).and_then(|value| value.parse::<f64>().ok());
+
+    // the location is often given to more digits than f64 holds
+    let location = |name: &str| field(name).and_then(|value| value.parse::<DoubleDouble>().ok());
+    let (Some(re), Some(im)) = (location("Re"), location("Im")) else {
+        return Err("The file has no Re/Im location".into());
+    };
+    // at zoom 1 Kalles Fraktaler shows a height of 4 in the complex plane
+    let kf_zoom = number("Zoom").unwrap_or(1.0);
+    let zoom = 4.0 / (kf_zoom * window_height.max(1) as f64);
+    let max_iterations = number("Iterations").map(|i| i as u32).unwrap_or(100).max(1);
+
+    let mut colour_gradient = "Sinebow".to_string();
+    if let Some(colours) = field("Colors") {
+        let channels: Vec<u8> = colours.split(',').filter_map(|c| c.trim().parse().ok()).collect();
+        let colours: Vec<[u8; 3]> = channels.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+        if colours.len() >= 2 {
+            colour_gradient = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| "Kalles Fraktaler".into());
+            palette::register(colour_gradient.clone(), colours);
+        }
+    }
+
+    // the imaginary axis points down the screen here but up in Kalles Fraktaler
+    let view = View { zoom, offset_x: re.hi, offset_y: -im.hi, offset_lo: (re.lo, -im.lo), rotation: -number("Rotate").unwrap_or(0.0) };
+    let fractal = Mandelbrot { max_iterations, colour_gradient, ..Mandelbrot::default() };
+    Ok(SessionFile { fractal: Box::new(fractal), view })
+}
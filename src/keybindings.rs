@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use winit::event::VirtualKeyCode;
+use winit_input_helper::WinitInputHelper;
+
+/// Something the user can do from the keyboard
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Action {
+    Cancel,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    RotateLeft,
+    RotateRight,
+    HideUi,
+    Inspector,
+    Bookmarks,
+    Screenshot,
+    FinalRender,
+    RenderRegion,
+    Timeline,
+    Fullscreen,
+    Help,
+}
+
+impl Action {
+    /// True for actions whose keys still work while something in the gui is being typed into
+    pub fn while_typing(&self) -> bool {
+        matches!(self, Action::Cancel | Action::Screenshot)
+    }
+
+    /// The way a pan action moves the view in screen space, None for the other actions
+    pub fn pan_direction(&self) -> Option<(f64, f64)> {
+        match self {
+            Action::PanUp => Some((0.0, -1.0)),
+            Action::PanDown => Some((0.0, 1.0)),
+            Action::PanLeft => Some((-1.0, 0.0)),
+            Action::PanRight => Some((1.0, 0.0)),
+            _ => None,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Cancel => "Cancel the zoom box, divider drag or context menu",
+            Action::PanUp => "Pan up",
+            Action::PanDown => "Pan down",
+            Action::PanLeft => "Pan left",
+            Action::PanRight => "Pan right",
+            Action::RotateLeft => "Rotate anticlockwise",
+            Action::RotateRight => "Rotate clockwise",
+            Action::HideUi => "Hide or show the UI",
+            Action::Inspector => "Toggle the pixel inspector",
+            Action::Bookmarks => "Open the bookmarks gallery",
+            Action::Screenshot => "Save a screenshot",
+            Action::FinalRender => "Render the view at final quality",
+            Action::RenderRegion => "Draw a box to render again at final quality",
+            Action::Timeline => "Open the animation timeline",
+            Action::Fullscreen => "Toggle fullscreen",
+            Action::Help => "Show this help",
+        }
+    }
+}
+
+/// Mouse interactions, these can't be rebound so they're only listed for the help overlay
+pub const MOUSE_BINDINGS: &[(&str, &str)] = &[
+    ("Scroll", "Zoom in and out"),
+    ("Left drag", "Draw a box to zoom into"),
+    ("Shift + left drag", "Draw a box to fit the view into, zooming out"),
+    ("Left click", "Centre on the point"),
+    ("Left drag divider", "Wipe between A and B in compare mode"),
+    ("Right click", "Open the context menu"),
+];
+
+/// Keys with a modifier and the number keys for the snapshot slots, these can't be rebound either
+pub const FIXED_BINDINGS: &[(&str, &str)] = &[
+    ("Ctrl + P", "Open the command palette"),
+    ("Ctrl + Z", "Undo the last change to the view or settings"),
+    ("Ctrl + Y", "Redo the change that was undone"),
+    ("Ctrl + 1-9", "Save a snapshot of the settings and view to the slot"),
+    ("1-9", "Go back to the snapshot in the slot"),
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub action: Action,
+    pub keys: Vec<VirtualKeyCode>,
+}
+
+/// The keys bound to each action, the input handling and the help overlay both read from this.
+/// Actions missing from the config keep their default keys.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "Vec<KeyBinding>", into = "Vec<KeyBinding>")]
+pub struct KeyBindings {
+    pub list: Vec<KeyBinding>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        use VirtualKeyCode::*;
+        let bind = |action, keys: &[VirtualKeyCode]| KeyBinding { action, keys: keys.to_vec() };
+        Self {
+            list: vec![
+                bind(Action::Cancel, &[Escape]),
+                bind(Action::PanUp, &[W, Up]),
+                bind(Action::PanDown, &[S, Down]),
+                bind(Action::PanLeft, &[A, Left]),
+                bind(Action::PanRight, &[D, Right]),
+                bind(Action::RotateLeft, &[Q]),
+                bind(Action::RotateRight, &[E]),
+                bind(Action::HideUi, &[Tab, H]),
+                bind(Action::Inspector, &[I]),
+                bind(Action::Bookmarks, &[B]),
+                bind(Action::Screenshot, &[F12]),
+                bind(Action::FinalRender, &[F5]),
+                bind(Action::RenderRegion, &[F6]),
+                bind(Action::Timeline, &[T]),
+                bind(Action::Fullscreen, &[F11]),
+                bind(Action::Help, &[Slash, F1]),
+            ],
+        }
+    }
+}
+
+impl From<Vec<KeyBinding>> for KeyBindings {
+    fn from(bindings: Vec<KeyBinding>) -> Self {
+        let mut keybindings = Self::default();
+        for binding in bindings {
+            if let Some(default) = keybindings.list.iter_mut().find(|default| default.action == binding.action) {
+                default.keys = binding.keys;
+            }
+        }
+        keybindings
+    }
+}
+
+impl From<KeyBindings> for Vec<KeyBinding> {
+    fn from(keybindings: KeyBindings) -> Self {
+        keybindings.list
+    }
+}
+
+impl KeyBindings {
+    /// The first action with a key that was pressed this frame
+    pub fn pressed_action(&self, input: &WinitInputHelper) -> Option<Action> {
+        self.list.iter()
+            .find(|binding| binding.keys.iter().any(|key| input.key_pressed(*key)))
+            .map(|binding| binding.action)
+    }
+
+    /// Returns true if any key bound to the action is down
+    pub fn held(&self, input: &WinitInputHelper, action: Action) -> bool {
+        self.list.iter()
+            .filter(|binding| binding.action == action)
+            .any(|binding| binding.keys.iter().any(|key| input.key_held(*key)))
+    }
+
+    /// Returns true if any key bound to the action was pressed this frame
+    pub fn pressed(&self, input: &WinitInputHelper, action: Action) -> bool {
+        self.list.iter()
+            .filter(|binding| binding.action == action)
+            .any(|binding| binding.keys.iter().any(|key| input.key_pressed(*key)))
+    }
+}
+
+/// A readable name for a key
+pub fn key_name(key: VirtualKeyCode) -> String {
+    use VirtualKeyCode::*;
+    match key {
+        Slash => "?".into(),
+        Up => "↑".into(),
+        Down => "↓".into(),
+        Left => "←".into(),
+        Right => "→".into(),
+        _ => format!("{:?}", key),
+    }
+}
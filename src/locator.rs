@@ -0,0 +1,135 @@
+use num::complex::Complex64;
+
+const NEWTON_STEPS: usize = 64;
+/// Largest preperiod and period tried when searching for Misiurewicz points
+const MAX_PREPERIOD: u32 = 12;
+const MAX_MISIUREWICZ_PERIOD: u32 = 12;
+/// How close values have to be to count as equal
+const TOLERANCE: f64 = 1e-9;
+/// Newton's method only creeps towards nuclei when looking for Misiurewicz points, so they're rejected more loosely
+const NUCLEUS_TOLERANCE: f64 = 1e-6;
+
+/// A point of interest in the Mandelbrot set found near the view
+#[derive(Clone, Copy, Debug)]
+pub struct Feature {
+    pub point: (f64, f64),
+    pub kind: FeatureKind,
+    /// Rough radius of the feature in the complex plane, used to zoom in on it
+    pub size: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FeatureKind {
+    /// The centre of a mini Mandelbrot or bulb, where 0 is periodic
+    Nucleus { period: u32 },
+    /// A point where 0 lands on a repelling cycle after a number of iterations
+    Misiurewicz { preperiod: u32, period: u32 },
+}
+
+/// The nucleus of the lowest period component in the disk, usually the biggest mini Mandelbrot in view.
+/// The period is the first iteration where the disk's image surrounds 0, found by tracking the disk with the derivative.
+pub fn nearest_nucleus(centre: (f64, f64), radius: f64, max_period: u32) -> Option<Feature> {
+    let c = Complex64::new(centre.0, centre.1);
+    let mut z = Complex64::new(0.0, 0.0);
+    let mut dc = Complex64::new(0.0, 0.0);
+    let period = (1..=max_period).find(|_| {
+        dc = 2.0 * z * dc + 1.0;
+        z = z * z + c;
+        z.norm() < dc.norm() * radius
+    })?;
+
+    let nucleus = newton(c, |c| iterate(c, period))?;
+    // converging to a nucleus of a smaller period that divides this one isn't what was asked for
+    let lower_period = (1..period).any(|n| period.is_multiple_of(n) && iterate(nucleus, n).0.norm() < TOLERANCE);
+    if lower_period || iterate(nucleus, period).0.norm() > TOLERANCE {
+        return None;
+    }
+    Some(Feature { point: (nucleus.re, nucleus.im), kind: FeatureKind::Nucleus { period }, size: atom_size(nucleus, period) })
+}
+
+/// The simplest Misiurewicz point in the disk, or the closest one if there are none in it.
+/// They're dense on the boundary so the closest is usually a complicated one with a long preperiod.
+pub fn nearest_misiurewicz(centre: (f64, f64), radius: f64) -> Option<Feature> {
+    let start = Complex64::new(centre.0, centre.1);
+    let mut best: Option<((bool, u32, f64), Feature)> = None;
+    for preperiod in 1..=MAX_PREPERIOD {
+        for period in 1..=MAX_MISIUREWICZ_PERIOD {
+            // solve z_(preperiod + period)(c) = z_preperiod(c)
+            let Some(c) = newton(start, |c| {
+                let (z1, dc1) = iterate(c, preperiod);
+                let (z2, dc2) = iterate(c, preperiod + period);
+                (z2 - z1, dc2 - dc1)
+            }) else {
+                continue;
+            };
+            if !is_misiurewicz(c, preperiod, period) {
+                continue;
+            }
+            let distance = (c - start).norm();
+            let outside = distance > radius;
+            let rank = (outside, if outside { 0 } else { preperiod + period }, distance);
+            if best.as_ref().is_none_or(|(best_rank, _)| rank < *best_rank) {
+                // the spirals around a Misiurewicz point shrink at a similar rate to the view, so keep the zoom
+                let feature = Feature { point: (c.re, c.im), kind: FeatureKind::Misiurewicz { preperiod, period }, size: radius };
+                best = Some((rank, feature));
+            }
+        }
+    }
+    best.map(|(_, feature)| feature)
+}
+
+/// Check the preperiod and period are exact, and that it isn't a nucleus where 0 itself is periodic
+fn is_misiurewicz(c: Complex64, preperiod: u32, period: u32) -> bool {
+    let orbit: Vec<Complex64> = std::iter::successors(Some(Complex64::new(0.0, 0.0)), |z| Some(z * z + c))
+        .take((preperiod + period + 1) as usize)
+        .collect();
+    let equal = |a: Complex64, b: Complex64| (a - b).norm() < TOLERANCE;
+    let k = preperiod as usize;
+    let p = period as usize;
+    equal(orbit[k + p], orbit[k])
+        && !equal(orbit[k - 1 + p], orbit[k - 1])
+        && !(1..p).any(|q| p.is_multiple_of(q) && equal(orbit[k + q], orbit[k]))
+        && !orbit[1..].iter().any(|z| z.norm() < NUCLEUS_TOLERANCE)
+}
+
+/// z_n(c) and its derivative with respect to c, starting from z_0 = 0
+fn iterate(c: Complex64, n: u32) -> (Complex64, Complex64) {
+    let mut z = Complex64::new(0.0, 0.0);
+    let mut dc = Complex64::new(0.0, 0.0);
+    for _ in 0..n {
+        dc = 2.0 * z * dc + 1.0;
+        z = z * z + c;
+    }
+    (z, dc)
+}
+
+/// Find a root of a function given its value and derivative, None if it doesn't converge.
+/// Repeated roots, like nuclei when looking for Misiurewicz points, converge too slowly and are skipped.
+fn newton(mut c: Complex64, f: impl Fn(Complex64) -> (Complex64, Complex64)) -> Option<Complex64> {
+    for _ in 0..NEWTON_STEPS {
+        let (value, derivative) = f(c);
+        let next = c - value / derivative;
+        if !next.is_finite() {
+            return None;
+        }
+        if (next - c).norm() <= next.norm().max(1.0) * 1e-15 {
+            return Some(next);
+        }
+        c = next;
+    }
+    None
+}
+
+/// Estimate of the size of the component at a nucleus.
+/// From the linear approximation of the period p map near the nucleus.
+fn atom_size(nucleus: Complex64, period: u32) -> f64 {
+    let mut z = Complex64::new(0.0, 0.0);
+    let mut l = Complex64::new(1.0, 0.0);
+    let mut b = Complex64::new(1.0, 0.0);
+    for _ in 1..period {
+        z = z * z + nucleus;
+        l = 2.0 * z * l;
+        b += 1.0 / l;
+    }
+    1.0 / (b * l * l).norm()
+}
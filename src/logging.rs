@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::sync::{Mutex, OnceLock};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt as format, EnvFilter, Layer};
+use web_time::{Duration, Instant};
+
+/// How many lines the debug panel keeps
+const MAX_LINES: usize = 500;
+
+static RECENT: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// A log event kept for the debug panel
+#[derive(Clone)]
+pub struct LogLine {
+    /// Time since the app started
+    pub time: Duration,
+    pub level: Level,
+    /// The spans the event happened in, outermost first, separated by colons
+    pub spans: String,
+    pub text: String,
+}
+
+/// Log to stderr and the debug panel. `RUST_LOG` overrides the levels, otherwise
+/// only warnings are shown unless `verbose` is set.
+pub fn init(verbose: bool) {
+    START.get_or_init(Instant::now);
+    let default = if verbose { "info,fractal=debug,fractal_viz_core=debug" } else { "warn,fractal=info" };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(format::layer().with_writer(std::io::stderr))
+        .with(RecentLines)
+        .init();
+}
+
+/// The most recent log lines, oldest first. They're copied so nothing that logs can run while the lock is held
+pub fn recent() -> Vec<LogLine> {
+    RECENT.lock().unwrap().iter().cloned().collect()
+}
+
+pub fn clear() {
+    RECENT.lock().unwrap().clear();
+}
+
+/// Keeps the last lines in memory for the debug panel
+struct RecentLines;
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for RecentLines {
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut message = Message::default();
+        event.record(&mut message);
+        let spans = ctx.event_scope(event)
+            .map(|scope| scope.from_root().map(|span| span.name()).collect::<Vec<_>>().join(":"))
+            .unwrap_or_default();
+        let line = LogLine {
+            time: START.get().map(Instant::elapsed).unwrap_or_default(),
+            level: *event.metadata().level(),
+            spans,
+            text: format!("{}{}", message.text, message.fields),
+        };
+        let mut recent = RECENT.lock().unwrap();
+        if recent.len() == MAX_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+}
+
+/// The message of an event followed by its other fields as `name=value`
+#[derive(Default)]
+struct Message {
+    text: String,
+    fields: String,
+}
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.text, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.text.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+}
@@ -1,152 +1,1269 @@
 #![windows_subsystem = "windows"]
 
 mod gui;
-mod fractals;
+mod compare;
+mod export;
+mod memory;
+mod bookmarks;
+mod session;
+mod import;
+mod toast;
+mod keybindings;
+mod config;
+mod i18n;
+mod colour_blind;
+mod adjust;
+mod highlight;
+mod counts;
+mod stereo;
+mod animation;
+mod replay;
+mod audio;
+mod camera;
+mod rays;
+mod area;
+mod locator;
+mod script;
+mod error;
+mod logging;
+mod renderer;
+mod remote;
+mod autosave;
+mod graphics;
+mod scaler;
+mod bloom;
+mod cache;
+mod watermark;
+mod quality;
+mod refine;
+mod schedule;
+mod profiles;
+mod snapshots;
+mod breadcrumbs;
+mod commands;
+mod history;
+mod gallery;
+#[cfg(not(target_arch = "wasm32"))]
+mod watch;
+#[cfg(target_arch = "wasm32")]
+mod web;
 
-use pixels::{Pixels, SurfaceTexture};
+use pixels::wgpu;
 use winit::{
-    dpi::LogicalSize,
-    event::{Event, WindowEvent},
-    event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    dpi::{LogicalSize, PhysicalSize},
+    event::{Event, VirtualKeyCode, WindowEvent},
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    monitor::MonitorHandle,
+    window::{Fullscreen, Window, WindowBuilder},
 };
 use winit_input_helper::WinitInputHelper;
-use gui::Framework;
-use fractals::Fractals;
+use tracing::{debug, debug_span, info, trace_span, warn};
+use web_time::{Duration, Instant};
+use gui::{Framework, ContextMenu, ContextAction, BookmarkAction, SessionAction, TimelineAction};
+use bookmarks::Bookmarks;
+use fractal_viz_core::fractals::{self, Julia, Newton};
+use export::{MaskExport, VideoExport, WindowCapture};
+use gallery::GalleryExport;
+use replay::Recorder;
+use audio::AudioReactive;
+use camera::VirtualCamera;
+use fractal_viz_core::transfer::{self, Levels};
+use session::{Session, SessionFile, Sessions};
+use compare::Compare;
+use fractal_viz_core::view::View;
+use snapshots::Snapshot;
+use history::Step;
+use import::Imported;
+use toast::Toasts;
+use keybindings::{Action, KeyBindings};
+use config::{Config, Navigation};
+use i18n::tr;
+use colour_blind::Simulation;
+use adjust::Adjustments;
+use highlight::BandHighlight;
+use counts::IterationCounts;
+use stereo::{Stereo, StereoMode};
+use fractal_viz_core::edges::{self, EdgeStyle};
+use error::ErrorDialog;
+use script::{Automation, Console, ScriptEvent, ScriptFractal};
+use renderer::{Backend, Renderers};
+use autosave::{Autosave, Autosaver};
+use scaler::Scaler;
+use bloom::{Bloom, BloomSettings};
+use watermark::Watermark;
+use cache::FrameCache;
+use quality::{FinalRender, QualityProfiles};
+use refine::Refinement;
+use schedule::{CpuPass, RenderQueue, RenderStats};
+use profiles::Profiles;
 
 
 const MIN_WIDTH: i32 = 400;
 const MIN_HEIGHT: i32 = 300;
-const INIT_ZOOM: f64 = 0.003;
+/// Longest the app sleeps for when idle, it still wakes up now and then for the autosave
+const IDLE_WAKE: Duration = Duration::from_secs(30);
+/// How often to check on video exports and automation scripts running in the background while idle
+const BACKGROUND_POLL: Duration = Duration::from_millis(100);
+/// Most a shift dragged zoom box can zoom out by, the same as the most a zoom box can zoom in by
+const MAX_ZOOM_OUT: f64 = 100_000.0;
+
+/// Consecutive frames that can fail before giving up, by then not even the error dialog can be shown
+const MAX_RENDER_FAILURES: u32 = 120;
+/// Degrees the view turns for each press of a rotate key
+const ROTATION_STEP: f64 = 5.0;
 
 /// Control what to render through flags as generating fractals is expensive
 struct Flags { 
-    render_zoom_box: bool,
+    /// Where the zoom box being dragged out starts and ends, in pixels of the window
+    zoom_box: Option<((f32, f32), (f32, f32))>,
     generate_fractal: bool,
     reset: bool,
     window_event: bool,
+    /// Set when the user right clicks to open the context menu
+    context_menu: Option<ContextMenu>,
+    /// An action chosen from the context menu that still needs to be applied
+    context_action: Option<ContextAction>,
+    /// Set when a different type of fractal is chosen in the gui
+    switch_fractal: Option<&'static str>,
+    /// Hide the gui for distraction free viewing
+    hide_ui: bool,
+    /// Save a screenshot after the next render
+    screenshot: bool,
+    /// Include the gui in screenshots
+    screenshot_ui: bool,
+    /// Show the pixel inspector
+    inspector: bool,
+    /// The mouse position in pixels and the point in the complex plane under it
+    hover: Option<((f32, f32), (f64, f64))>,
+    /// The point whose orbit is plotted, the orbit panel is shown while this is set
+    orbit_point: Option<(f64, f64)>,
+    /// Plot the orbit of the point under the mouse instead of a fixed point
+    orbit_follow: bool,
+    /// Show the Julia set of the point under the mouse while browsing the Mandelbrot set
+    julia_preview: bool,
+    /// Show the bookmarks gallery
+    show_bookmarks: bool,
+    /// An action from the bookmarks gallery that still needs to be applied
+    bookmark_action: Option<BookmarkAction>,
+    /// Undo or redo from the keys or the command palette
+    history_step: Option<Step>,
+    /// An action picked from the command palette, done as if its key was pressed
+    run_action: Option<Action>,
+    /// Waypoint picked from the zoom breadcrumbs, by its place in the list
+    go_to_waypoint: Option<usize>,
+    /// An action from the session tabs that still needs to be applied
+    session_action: Option<SessionAction>,
+    /// Tabs share the view, so switching tabs compares fractals or palettes over the same region
+    link_views: bool,
+    /// Notifications to show in the gui
+    toasts: Toasts,
+    /// Show the keyboard help overlay
+    show_help: bool,
+    /// Which renderer draws the fractal
+    renderer: Backend,
+    /// Pixels generated along each side for every pixel of the window, trading sharpness for speed
+    render_scale: f32,
+    quality: QualityProfiles,
+    /// Set when a final quality render of the view is asked for
+    start_final_render: bool,
+    /// The next zoom box picks a region to render again at final quality instead of zooming
+    select_region: bool,
+    /// Corners of a region to render again at final quality, in pixels of the window
+    start_region_render: Option<((f32, f32), (f32, f32))>,
+    /// The final quality render in progress, its frame replaces the freeze frame when it's done
+    final_render: Option<FinalRender>,
+    /// Set when a frame is generated whose boundary should be drawn again once its iteration counts are known
+    refine_pending: bool,
+    /// The boundary of the frame being drawn again with more iterations
+    refinement: Option<Refinement>,
+    /// The hybrid backend drawing the GPU's preview again on the CPU
+    cpu_pass: Option<CpuPass>,
+    /// Draw only the boundary of the set as lines
+    edges: bool,
+    edge_style: EdgeStyle,
+    /// Preview the frame as seen with a colour vision deficiency
+    colour_blind_simulation: Option<Simulation>,
+    /// Brightness, contrast, gamma and saturation of the displayed frame
+    adjustments: Adjustments,
+    /// Iteration band picked out over the displayed frame
+    highlight: BandHighlight,
+    /// Depth from the iteration counts for fake 3D
+    stereo: Stereo,
+    /// Iteration counts of the frame, for the highlight, stereo, palette previews and the inspector's selection statistics
+    iteration_counts: IterationCounts,
+    /// The colour section is showing the view in each palette, which needs the counts
+    palette_previews: bool,
+    /// Spread the palette over the range of iterations in the frame, which is drawn again once its counts are in
+    auto_levels: bool,
+    /// Suggest a max iterations from the edge of the set in each frame's counts
+    suggest_iterations: bool,
+    bloom: BloomSettings,
+    /// Caption, logo and border drawn onto screenshots
+    watermark: Watermark,
+    navigation: Navigation,
+    /// When the view was last moved by held pan keys, while they're held with continuous panning
+    panning: Option<Instant>,
+    /// How long frames take to generate, for the render stats overlay
+    render_stats: RenderStats,
+    /// True while the window is fullscreen
+    fullscreen: bool,
+    /// Go fullscreen or back to a window
+    set_fullscreen: Option<bool>,
+    /// Name of the monitor picked to go fullscreen on
+    fullscreen_monitor: Option<String>,
+    /// Parameters and view each type of fractal starts with and is reset to
+    profiles: Profiles,
+    /// Show the animation timeline
+    show_timeline: bool,
+    /// An action from the timeline that still needs to be applied
+    timeline_action: Option<TimelineAction>,
+    /// The animation currently being exported
+    video_export: Option<VideoExport>,
+    /// The set membership mask being saved
+    mask_export: Option<MaskExport>,
+    gallery_export: Option<GalleryExport>,
+    /// Set when audio reactive mode is turned on or off in the gui
+    toggle_audio: bool,
+    audio: Option<AudioReactive>,
+    /// Set to start publishing the frames to the virtual camera device
+    start_camera: Option<String>,
+    virtual_camera: Option<VirtualCamera>,
+    /// Output from scripts and the automation script that is running
+    console: Console,
+    show_console: bool,
+    /// An automation script to start, from a dropped file or typed into the console
+    run_script: Option<String>,
+    /// An error shown in a modal until the user continues or quits
+    error: Option<ErrorDialog>,
+    /// Set when the user chooses to quit from the error dialog
+    quit: bool,
+    /// Sessions autosaved by a run that didn't exit normally, waiting for the user to choose whether to restore them
+    recovered: Option<Autosave>,
+    /// Set when the user chooses whether to restore the recovered sessions
+    restore: Option<bool>,
+    /// Sessions that were open when the app last exited, offered on the start panel
+    last_session: Option<Autosave>,
+    /// Show the start panel with the last sessions and recent bookmarks
+    show_start: bool,
+    /// Set when the user continues from the last sessions, with the tab to show
+    continue_last: Option<usize>,
+    /// Graphics settings changed in the gui, the window's surface is recreated with them
+    #[cfg(not(target_arch = "wasm32"))]
+    graphics: Option<graphics::GraphicsSettings>,
+    /// The folder parameter files are loaded from as soon as they're added or changed
+    #[cfg(not(target_arch = "wasm32"))]
+    watch_folder: Option<std::path::PathBuf>,
+    /// Set to start watching another folder, or stop with None
+    #[cfg(not(target_arch = "wasm32"))]
+    change_watch_folder: Option<Option<std::path::PathBuf>>,
 }
 
 
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    logging::init(std::env::args().skip(1).any(|arg| arg == "--verbose" || arg == "-v"));
+    pollster::block_on(run());
+}
+
+#[cfg(target_arch = "wasm32")]
 fn main() {
+    std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+    logging::init(false);
+    wasm_bindgen_futures::spawn_local(run());
+}
+
+/// Creating the renderer is async as the browser can't block waiting for the gpu
+async fn run() {
     let event_loop = EventLoop::new();
     let mut input = WinitInputHelper::new();
     
-    let monitor_size = event_loop.primary_monitor().unwrap().size(); 
+    let (config, config_error) = Config::load();
+    // 0 threads lets rayon use one per core, the browser has no threads so rayon runs everything on the page's thread
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(e) = rayon::ThreadPoolBuilder::new().num_threads(config.threads).build_global() {
+        warn!("Couldn't set the number of render threads: {}", e);
+    }
+
     // Set up window
     let window = { 
         let size = LogicalSize::new(MIN_WIDTH as f64, MIN_HEIGHT as f64); // minimum window size
-        let scaled_size = LogicalSize::new(monitor_size.width as f64, monitor_size.height as f64); // initial window size
-        WindowBuilder::new()
+        let builder = WindowBuilder::new()
             .with_title("Fractals")
-            .with_inner_size(scaled_size)
-            .with_min_inner_size(size)
-            .with_maximized(true)
-            .build(&event_loop)
-            .unwrap()
+            .with_min_inner_size(size);
+        // start maximised unless the config has a window size
+        let builder = match config.window_size {
+            Some((width, height)) => builder.with_inner_size(PhysicalSize::new(width, height)),
+            None => {
+                // some platforms don't report a primary monitor
+                let monitor = event_loop.primary_monitor().or_else(|| event_loop.available_monitors().next());
+                let builder = match monitor {
+                    Some(monitor) => builder.with_inner_size(LogicalSize::new(monitor.size().width as f64, monitor.size().height as f64)), // initial window size
+                    None => builder,
+                };
+                builder.with_maximized(true)
+            }
+        };
+        builder.build(&event_loop).unwrap_or_else(|e| error::fatal(&format!("Couldn't create the window: {}", e)))
     };
+    #[cfg(target_arch = "wasm32")]
+    web::attach_canvas(&window);
     let window_size = window.inner_size();
     // Set up pixels, we change the pixel rgba in the pixel buffer
-    let mut pixels = {
-        let window_size = window.inner_size();
-        let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, &window);
-        Pixels::new(window_size.width as u32, window_size.height as u32, surface_texture).unwrap()
+    let mut startup_error = None;
+    let pixels = match graphics::build(&window, &config.graphics, false).await {
+        Ok(pixels) => pixels,
+        Err(e) => {
+            // a software renderer is slow but better than not starting at all
+            startup_error = Some(ErrorDialog::new("No suitable graphics card", e.to_string()).with_hint("Falling back to software rendering, which will be slow."));
+            graphics::build(&window, &config.graphics, true).await
+                .unwrap_or_else(|e| error::fatal(&format!("Couldn't start the renderer: {}", e)))
+        }
     };
+    error::catch_gpu_errors(pixels.device());
+    let adapter = pixels.adapter().get_info();
+    info!(adapter = adapter.name, backend = ?adapter.backend, device_type = ?adapter.device_type, "Renderer ready");
 
-    let scale_factor = window.scale_factor() as f32 * 1.2; // increase the scale factor to make the ui/text bigger
+    let scale_factor = window.scale_factor() as f32;
     
     // Set up the gui
-    let window_open_size: (f32, f32) = (300.0, 235.0);
+    let window_open_size: (f32, f32) = (300.0, 435.0);
     let window_closed_size: (f32, f32) = (85.0, 30.0);
-    let window_position: (f32, f32) = (10.0, 10.0);
+    script::register();
+    // Set the fractal new sessions start with, the Mandelbrot set unless the config says otherwise
+    let mut default_fractal = fractals::create(&config.default_fractal);
+    *default_fractal.colour_gradient_mut() = config.default_palette.clone();
+    transfer::set(config.colour_curve.clone());
+    let keybindings = config.keybindings.clone();
+    let navigation = config.navigation;
+    let backend = config.renderer;
+    let render_scale = config.render_scale.clamp(0.5, 2.0);
+    let quality = config.quality;
+    let watermark = config.watermark.clone();
+    let fullscreen_monitor = config.fullscreen_monitor.clone();
+    let profiles = config.profiles.clone();
+    // the folder given on the command line is only watched for this run
+    #[cfg(not(target_arch = "wasm32"))]
+    let watch_folder = watch::folder_from_args().or_else(|| config.watch_folder.clone());
+    // what the surface was last built with, to go back to if new settings don't work
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut graphics_settings = config.graphics.clone();
     let mut framework = Framework::new(
         &event_loop,
         window_size.width,
         window_size.height,
         scale_factor,
         &pixels,
-        window_position,
+        config,
         window_open_size,
         window_closed_size
     );
 
-    // Set the default fractal to render the Mandelbrot set
-    let mut fractal = Fractals::Mandelbrot {max_iterations: 100, escape_radius: 2.0, colour_gradient: "Sinebow".into() };
-    // Set the default zoom to zero, changes when scrolling mouse wheel
-    let mut zoom: f64 = INIT_ZOOM;
-    // Set the default offset to zero, changes when moving the camera around
-    let mut offset_x: f64 = 0.0;
-    let mut offset_y: f64 = 0.0;
-    let zoom_amount = 5.0; // how much to zoom in/out when scrolling the mouse wheel
+    // Each session holds its own fractal, view and last rendered frame
+    let mut sessions = Sessions::new(Session::with_profile(default_fractal.as_ref(), &profiles));
+    let mut bookmarks = Bookmarks::load();
+    let mut autosaver = Autosaver::default();
     // the start and end points of the zoom box
-    let mut zoom_start: (f32, f32) = (0.0,0.0); 
-    let mut zoom_end: (f32, f32) = (0.0,0.0);
+    let mut render_failures = 0;
+    let mut renderers = Renderers::default();
+    let mut frame_cache = FrameCache::new();
+    // the custom shader and script fractals loaded from files are reloaded when they're saved
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut watcher = match watch::FileWatcher::new(event_loop.create_proxy()) {
+        Ok(mut watcher) => {
+            if let Some(path) = renderer::shader_path().filter(|path| path.parent().is_some_and(|dir| dir.exists())) {
+                if let Err(e) = watcher.watch(&path) {
+                    warn!(error = %e, "Couldn't watch the custom shader");
+                }
+            }
+            Some(watcher)
+        }
+        Err(e) => {
+            warn!(error = %e, "Couldn't watch files for changes");
+            None
+        }
+    };
+    // frames that take longer than a frame's time budget are spread over several
+    let mut render_queue = RenderQueue::default();
 
     let mut flags = Flags {
-        render_zoom_box: false,
+        zoom_box: None,
         generate_fractal: true,
         reset: false,
         window_event: false,
+        context_menu: None,
+        context_action: None,
+        switch_fractal: None,
+        hide_ui: false,
+        screenshot: false,
+        screenshot_ui: false,
+        inspector: false,
+        hover: None,
+        orbit_point: None,
+        orbit_follow: false,
+        julia_preview: false,
+        show_bookmarks: false,
+        bookmark_action: None,
+        go_to_waypoint: None,
+        run_action: None,
+        history_step: None,
+        session_action: None,
+        link_views: false,
+        toasts: Toasts::default(),
+        show_help: false,
+        renderer: backend,
+        render_scale,
+        quality,
+        start_final_render: false,
+        select_region: false,
+        start_region_render: None,
+        final_render: None,
+        refine_pending: false,
+        refinement: None,
+        cpu_pass: None,
+        edges: false,
+        edge_style: EdgeStyle::default(),
+        colour_blind_simulation: None,
+        adjustments: Adjustments::default(),
+        highlight: BandHighlight::default(),
+        stereo: Stereo::default(),
+        iteration_counts: IterationCounts::default(),
+        palette_previews: false,
+        auto_levels: false,
+        suggest_iterations: false,
+        bloom: BloomSettings::default(),
+        watermark,
+        navigation,
+        panning: None,
+        render_stats: RenderStats::default(),
+        fullscreen: false,
+        set_fullscreen: None,
+        fullscreen_monitor,
+        profiles,
+        show_timeline: false,
+        timeline_action: None,
+        video_export: None,
+        mask_export: None,
+        gallery_export: None,
+        toggle_audio: false,
+        audio: None,
+        start_camera: None,
+        virtual_camera: None,
+        console: Console::default(),
+        show_console: false,
+        run_script: None,
+        error: startup_error,
+        quit: false,
+        recovered: autosaver.recover(),
+        restore: None,
+        last_session: autosaver.last(),
+        show_start: false,
+        continue_last: None,
+        #[cfg(not(target_arch = "wasm32"))]
+        graphics: None,
+        #[cfg(not(target_arch = "wasm32"))]
+        watch_folder: None,
+        #[cfg(not(target_arch = "wasm32"))]
+        change_watch_folder: watch_folder.map(Some),
+    };
+    // taken out while the surface is recreated, the old one has to be gone before the window can get a new one.
+    // The scaler and bloom belong to the same device so they're recreated along with it
+    let scaler = Scaler::new(&pixels);
+    let bloom = Bloom::new(&pixels);
+    let mut window_pixels = Some((pixels, scaler, bloom));
+    // returning users get a way back to where they were, unless they're already being offered the sessions from a crash
+    flags.show_start = flags.recovered.is_none() && (flags.last_session.is_some() || !bookmarks.list.is_empty());
+    if let Some(target) = import::target_from_args() {
+        open_at_startup(&target, &mut sessions, &bookmarks, window.inner_size().height, &mut flags);
+    }
+    if let Some(e) = config_error {
+        flags.toasts.error(format!("{}: {}", tr("Couldn't read the settings"), e));
+    }
+    // other programs can control the app when it's started with --remote
+    let remote = match remote::from_args(event_loop.create_proxy()) {
+        Some(Ok(server)) => Some(server),
+        Some(Err(e)) => {
+            flags.toasts.error(format!("{}: {}", tr("Couldn't start remote control"), e));
+            None
+        }
+        None => None,
     };
 
-    // store the frame when the user starts dragging the mouse to select an area to zoom in on
-    // this is so that the previous frames select box is removed and we dont have to re-render the fractal
-    let mut freeze_frame: Vec<u8> = pixels.frame().to_vec(); 
-
-    event_loop.run(move |event, _, control_flow| {
+    let event_handler = move |event: Event<'_, ()>, _: &EventLoopWindowTarget<()>, control_flow: &mut ControlFlow| {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(settings) = flags.graphics.take() {
+            window_pixels = None;
+            let pixels = match pollster::block_on(graphics::build(&window, &settings, false)) {
+                Ok(pixels) => {
+                    graphics_settings = settings;
+                    flags.toasts.success(tr("Graphics settings applied"));
+                    pixels
+                }
+                // go back to what worked before
+                Err(e) => {
+                    flags.toasts.error(format!("{}: {}", tr("Couldn't apply the graphics settings"), e));
+                    pollster::block_on(graphics::build(&window, &graphics_settings, false))
+                        .or_else(|_| pollster::block_on(graphics::build(&window, &graphics_settings, true)))
+                        .unwrap_or_else(|e| error::fatal(&format!("Couldn't start the renderer: {}", e)))
+                }
+            };
+            error::catch_gpu_errors(pixels.device());
+            let adapter = pixels.adapter().get_info();
+            info!(adapter = adapter.name, backend = ?adapter.backend, device_type = ?adapter.device_type, "Renderer recreated");
+            framework.recreate(&pixels);
+            let scaler = Scaler::new(&pixels);
+            let bloom = Bloom::new(&pixels);
+            window_pixels = Some((pixels, scaler, bloom));
+            flags.generate_fractal = true;
+        }
+        let Some((pixels, scaler, bloom)) = window_pixels.as_mut() else {
+            return;
+        };
         match event {
             Event::WindowEvent {
                 event: WindowEvent::CloseRequested,
                 ..
             } => *control_flow = ControlFlow::Exit,
 
+            // the autosave is only kept when the app crashes or is quit after an error,
+            // or if the user hasn't said what to do with the last one yet
+            Event::LoopDestroyed => {
+                // the sessions on offer are kept if the user quits before choosing
+                if flags.recovered.is_none() && !flags.show_start {
+                    if let Err(e) = autosaver.save_last(&sessions) {
+                        warn!(error = %e, "Couldn't save the sessions for next time");
+                    }
+                }
+                if flags.recovered.is_none() && !flags.quit {
+                    autosaver.clear();
+                }
+            }
+
+            // Load session, Kalles Fraktaler and palette files dropped onto the window
+            Event::WindowEvent { event: WindowEvent::DroppedFile(ref path), .. } => {
+                // a palette loaded under a name already in use changes how frames are coloured
+                frame_cache.clear();
+                let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                match import::import_file(path, window.inner_size().height) {
+                    Ok(Imported::Session(file)) => {
+                        sessions.add(file.into());
+                        flags.generate_fractal = true;
+                        flags.toasts.success(format!("{} {}", tr("Opened"), file_name));
+                    }
+                    Ok(Imported::Replay(replay)) => {
+                        let timeline = &mut sessions.current_mut().timeline;
+                        timeline.keyframes = replay.keyframes();
+                        timeline.time = 0.0;
+                        flags.show_timeline = true;
+                        flags.toasts.success(format!("{} {}", tr("Opened"), file_name));
+                    }
+                    Ok(Imported::Palette(name)) => {
+                        *sessions.current_mut().fractal.colour_gradient_mut() = name.clone();
+                        flags.generate_fractal = true;
+                        flags.toasts.success(format!("{} \"{}\"", tr("Loaded palette"), name));
+                    }
+                    // scripts that define iterate(z, c) are fractals, anything else is run as automation
+                    Ok(Imported::Script { name, source }) if script::defines_fractal(&source) => {
+                        let session = sessions.current_mut();
+                        let mut fractal = ScriptFractal::new(name, source);
+                        fractal.colour_gradient = session.fractal.colour_gradient_mut().clone();
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if let Some(watcher) = &mut watcher {
+                            match watcher.watch(path) {
+                                Ok(path) => fractal.path = Some(path),
+                                Err(e) => warn!(error = %e, "Couldn't watch the script"),
+                            }
+                        }
+                        session.fractal = Box::new(fractal);
+                        flags.reset = true;
+                        flags.generate_fractal = true;
+                        flags.toasts.success(format!("{} {}", tr("Opened"), file_name));
+                    }
+                    Ok(Imported::Script { source, .. }) => {
+                        flags.run_script = Some(source);
+                        flags.show_console = true;
+                    }
+                    Err(e) => flags.toasts.error(format!("{} {}: {}", tr("Couldn't open"), file_name, e)),
+                }
+            }
+
             Event::WindowEvent { ref event, .. } => {
-                let response = framework.handle_event(&event);
-                flags.window_event = response.consumed;
+                let _span = trace_span!("window_event").entered();
+                let response = framework.handle_event(event);
+                flags.window_event = response.consumed && !flags.hide_ui;
             }
 
             Event::RedrawRequested(_) => {
+                let _span = trace_span!("redraw").entered();
                 let (width, height) = (window.inner_size().width, window.inner_size().height);
-                framework.prepare(&window, &mut fractal, &mut flags);
+                // nothing to draw while minimised
+                if width == 0 || height == 0 {
+                    return;
+                }
+                // the fractal is generated at the render scale and stretched over the window,
+                // everything else works in window pixels
+                let (buffer_width, buffer_height) = buffer_size(width, height, flags.render_scale, pixels.device().limits().max_texture_dimension_2d);
+                if pixels.frame().len() != (buffer_width * buffer_height * 4) as usize {
+                    if let Err(e) = pixels.resize_buffer(buffer_width, buffer_height) {
+                        warn!(error = %e, "Couldn't resize the pixel buffer");
+                        flags.render_scale = 1.0;
+                        flags.error.get_or_insert(ErrorDialog::new("Couldn't resize the window", e.to_string()));
+                        return;
+                    }
+                }
+                framework.prepare(&window, &mut sessions, &bookmarks, &keybindings, &mut flags);
+
+                if let Some(fullscreen) = flags.set_fullscreen.take() {
+                    // borderless rather than exclusive so the monitor keeps its mode and switching is instant
+                    let monitor = if fullscreen { chosen_monitor(&window, &mut flags) } else { None };
+                    window.set_fullscreen(fullscreen.then_some(Fullscreen::Borderless(monitor)));
+                }
+                // the window manager can also take the window out of fullscreen
+                flags.fullscreen = window.fullscreen().is_some();
 
-                if flags.render_zoom_box {
-                    // reset the pixel buffer to the freeze frame so that the previous frames select box is removed
-                    pixels.frame_mut().copy_from_slice(&freeze_frame);
-                    // don't render the select box if the mouse hasn't moved enough
-                    if (zoom_start.0 - zoom_end.0).abs() > 10.0 && (zoom_start.1 - zoom_end.1).abs() > 10.0 {
-                        draw_zoom_box(pixels.frame_mut(), zoom_start, zoom_end, width as u32);
+                if let Some(action) = flags.session_action.take() {
+                    // audio only drives the session it was started in
+                    if let Some(audio) = flags.audio.take() {
+                        let session = sessions.current_mut();
+                        audio.stop(session.fractal.as_mut(), &mut session.view);
                     }
-                } 
-                else if flags.generate_fractal || flags.reset {
+                    // a frame left half drawn is generated again when its session is switched back to
+                    if render_queue.busy() {
+                        render_queue.cancel();
+                        sessions.current_mut().freeze_frame.clear();
+                    }
+                    let linked_view = flags.link_views.then(|| sessions.current_mut().view);
+                    match action {
+                        SessionAction::New => sessions.add(Session::with_profile(default_fractal.as_ref(), &flags.profiles)),
+                        SessionAction::Switch(index) => sessions.current = index.min(sessions.list.len() - 1),
+                        SessionAction::Close(index) => sessions.close(index),
+                    }
+                    // only the shown tab is drawn, so the others pick up the linked view as they're switched to
+                    let session = sessions.current_mut();
+                    if let Some(view) = linked_view.filter(|view| *view != session.view) {
+                        session.view = view;
+                        session.progressive = None;
+                        session.freeze_frame.clear();
+                    }
+                    flags.zoom_box = None;
+                    flags.context_menu = None;
+                    flags.final_render = None;
+                    flags.refine_pending = false;
+                    flags.refinement = None;
+                    flags.cpu_pass = None;
+                    flags.iteration_counts.clear();
+                }
+                if let Some(restore) = flags.restore.take() {
+                    match flags.recovered.take() {
+                        Some(recovered) if restore => {
+                            recovered.restore(&mut sessions);
+                            flags.generate_fractal = true;
+                        }
+                        _ => autosaver.clear(),
+                    }
+                }
+                if let Some(tab) = flags.continue_last.take() {
+                    if let Some(last) = flags.last_session.take() {
+                        last.restore(&mut sessions);
+                        sessions.current = tab.min(sessions.list.len() - 1);
+                        flags.generate_fractal = true;
+                    }
+                }
+                // saving before the user has chosen would overwrite the sessions they're being offered
+                if flags.recovered.is_none() {
+                    autosaver.tick(&sessions);
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(folder) = flags.change_watch_folder.take() {
+                    match watcher.as_mut().map(|watcher| watcher.watch_folder(folder.as_deref())) {
+                        Some(Ok(folder)) => flags.watch_folder = folder,
+                        Some(Err(e)) => flags.toasts.error(format!("{}: {}", tr("Couldn't watch the folder"), e)),
+                        None => flags.toasts.error(tr("Files can't be watched for changes")),
+                    }
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                for path in watcher.as_ref().map(watch::FileWatcher::poll).unwrap_or_default() {
+                    frame_cache.clear();
+                    if watcher.as_ref().is_some_and(|watcher| watcher.in_folder(&path)) {
+                        load_watched(&path, &mut sessions, window.inner_size().height, &mut flags);
+                    } else {
+                        reload_file(&path, &mut sessions, &mut renderers, &mut flags);
+                    }
+                }
+                let session = sessions.current_mut();
+                let Session { fractal, view, compare, memory, freeze_frame, timeline, recorder, progressive, breadcrumbs, history, levels, .. } = session;
+                // store the frame when the user starts dragging the mouse to select an area to zoom in on
+                // this is so that the previous frames select box is removed and we dont have to re-render the fractal.
+                // A session that hasn't been rendered at this window size yet needs generating
+                if freeze_frame.len() != (buffer_width * buffer_height * 4) as usize {
+                    freeze_frame.resize((buffer_width * buffer_height * 4) as usize, 0);
+                    flags.generate_fractal = true;
+                }
+
+                if let Some(name) = flags.switch_fractal.take() {
+                    if compare.enabled && compare.editing_b {
+                        // B shares the view with A so only its parameters are restored
+                        compare.fractal = memory.parameters(name, &flags.profiles);
+                    } else {
+                        (*fractal, *view) = memory.switch(fractal.as_ref(), *view, name, &flags.profiles);
+                    }
+                    flags.generate_fractal = true;
+                }
+
+                if let Some(step) = flags.history_step.take() {
+                    match history.step(step) {
+                        Some(state) => {
+                            *fractal = state.fractal;
+                            *view = state.view;
+                            flags.generate_fractal = true;
+                        }
+                        None => flags.toasts.info(tr(if step == Step::Undo { "Nothing to undo" } else { "Nothing to redo" })),
+                    }
+                }
+                if let Some(action) = flags.run_action.take() {
+                    perform(action, &mut flags, view, compare, (width, height));
+                }
+                if let Some(waypoint) = flags.go_to_waypoint.take().and_then(|index| breadcrumbs.list().get(index)) {
+                    *view = waypoint.view;
+                    flags.generate_fractal = true;
+                }
+
+                if let Some(action) = flags.bookmark_action.take() {
+                    match action {
+                        BookmarkAction::Save(name) => {
+                            match bookmarks.add(name.clone(), fractal.as_ref(), *view, (width, height)) {
+                                Ok(()) => flags.toasts.success(format!("{}: \"{}\"", tr("Bookmark saved"), name)),
+                                Err(e) => flags.toasts.error(format!("{}: {}", tr("Failed to save bookmark"), e)),
+                            }
+                        }
+                        BookmarkAction::GoTo(index) => {
+                            if let Some(bookmark) = bookmarks.list.get(index) {
+                                *fractal = bookmark.fractal.clone();
+                                *view = bookmark.view;
+                                flags.generate_fractal = true;
+                            }
+                        }
+                        BookmarkAction::Remove(index) => {
+                            if let Err(e) = bookmarks.remove(index) {
+                                flags.toasts.error(format!("{}: {}", tr("Failed to remove bookmark"), e));
+                            }
+                        }
+                        BookmarkAction::RenderGallery { size, index, folder } => {
+                            let list = bookmarks.list.iter().map(|b| (b.name.clone(), b.fractal.clone(), b.view)).collect();
+                            let dir = folder.unwrap_or_else(|| export::timestamped_dir("gallery"));
+                            flags.gallery_export = Some(GalleryExport::start(list, width, size, flags.quality.final_render, index, dir));
+                        }
+                    }
+                }
+
+                if let Some(action) = flags.timeline_action.take() {
+                    match action {
+                        TimelineAction::AddKeyframe => timeline.add(fractal.clone(), *view),
+                        TimelineAction::Remove(index) => timeline.remove(index),
+                        TimelineAction::Seek(time) => {
+                            timeline.time = time;
+                            if let Some((keyframe_fractal, keyframe_view)) = timeline.sample(time) {
+                                *fractal = keyframe_fractal;
+                                *view = keyframe_view;
+                                flags.generate_fractal = true;
+                            }
+                        }
+                        TimelineAction::StartRecording => *recorder = Some(Recorder::new(fractal.as_ref(), *view)),
+                        TimelineAction::StopRecording => {
+                            if let Some(replay) = recorder.take().map(Recorder::finish) {
+                                match replay.save() {
+                                    Ok(path) => flags.toasts.success(format!("{} {}", tr("Saved replay to"), path.display())),
+                                    Err(e) => flags.toasts.error(format!("{}: {}", tr("Failed to save replay"), e)),
+                                }
+                                // load it into the timeline so it can be played back and exported
+                                timeline.keyframes = replay.keyframes();
+                                timeline.time = 0.0;
+                            }
+                        }
+                        TimelineAction::ExportSweep => {
+                            if flags.video_export.is_none() {
+                                flags.video_export = Some(VideoExport::start(timeline.sweep_frames(fractal.as_ref(), *view), width, height, timeline.fps, timeline.reuse_frames));
+                            }
+                        }
+                        TimelineAction::Export => {
+                            if flags.video_export.is_none() && (timeline.keyframes.len() > 1 || timeline.motion.active()) {
+                                flags.video_export = Some(VideoExport::start(timeline.frames(fractal.as_ref(), *view), width, height, timeline.fps, timeline.reuse_frames));
+                            }
+                        }
+                    }
+                }
+                // preview the animation in the window, the motion is a per frame tick unless it is layered on the playback
+                if let Some((keyframe_fractal, keyframe_view)) = timeline.advance() {
+                    *fractal = keyframe_fractal;
+                    *view = timeline.motion.apply(keyframe_view, timeline.time as f64);
+                    flags.generate_fractal = true;
+                }
+                else if timeline.motion.tick(view) {
+                    flags.generate_fractal = true;
+                }
+                if timeline.sweep.advance(fractal.as_mut()) {
+                    flags.generate_fractal = true;
+                }
+                if std::mem::take(&mut flags.toggle_audio) {
+                    match flags.audio.take() {
+                        Some(audio) => audio.stop(fractal.as_mut(), view),
+                        None => match AudioReactive::start() {
+                            Ok(audio) => flags.audio = Some(audio),
+                            Err(e) => flags.toasts.error(format!("{}: {}", tr("Couldn't start audio"), e)),
+                        },
+                    }
+                    flags.generate_fractal = true;
+                }
+                if let Some(audio) = &mut flags.audio {
+                    flags.generate_fractal |= audio.update(fractal.as_mut(), view);
+                }
+                if let Some(result) = flags.video_export.as_mut().and_then(|export| export.poll()) {
+                    match result {
+                        Ok(path) => flags.toasts.success(format!("{} {}", tr("Saved animation to"), path.display())),
+                        Err(e) => flags.toasts.error(format!("{}: {}", tr("Failed to export animation"), e)),
+                    }
+                    flags.video_export = None;
+                }
+                if let Some(result) = flags.mask_export.as_ref().and_then(MaskExport::poll) {
+                    match result {
+                        Ok(path) => flags.toasts.success(format!("{} {}", tr("Saved mask to"), path.display())),
+                        Err(e) => flags.toasts.error(format!("{}: {}", tr("Failed to save mask"), e)),
+                    }
+                    flags.mask_export = None;
+                }
+                if let Some(result) = flags.gallery_export.as_mut().and_then(GalleryExport::poll) {
+                    match result {
+                        Ok(path) => flags.toasts.success(format!("{} {}", tr("Saved gallery to"), path.display())),
+                        Err(e) => flags.toasts.error(format!("{}: {}", tr("Failed to render gallery"), e)),
+                    }
+                    flags.gallery_export = None;
+                }
+
+                if let Some(source) = flags.run_script.take() {
+                    if flags.console.automation.is_some() {
+                        flags.console.error(tr("A script is already running"));
+                    } else {
+                        let bookmarks = bookmarks.list.iter().map(|b| (b.name.clone(), b.fractal.clone(), b.view)).collect();
+                        flags.console.automation = Some(Automation::start(source, fractal.clone(), *view, bookmarks, (width, height)));
+                    }
+                }
+                if let Some(automation) = &flags.console.automation {
+                    match automation.poll() {
+                        Some(events) => for event in events {
+                            match event {
+                                ScriptEvent::Output(text) => flags.console.output(text),
+                                ScriptEvent::Error(e) => {
+                                    flags.toasts.error(format!("{}: {}", tr("Script error"), e));
+                                    flags.console.error(e);
+                                }
+                                ScriptEvent::Show(script_fractal, script_view) => {
+                                    (*fractal, *view) = (script_fractal, script_view);
+                                    flags.generate_fractal = true;
+                                }
+                            }
+                        },
+                        None => flags.console.automation = None,
+                    }
+                }
+                if let Some(server) = &remote {
+                    for request in server.poll() {
+                        flags.generate_fractal |= request.handle(fractal, view, &bookmarks, (width, height));
+                    }
+                }
+
+                if let Some(action) = flags.context_action.take() {
+                    match action {
+                        ContextAction::CentreHere((x, y)) => {
+                            view.set_viewport(view.viewport(width, height).centred_on(x, y));
+                            flags.generate_fractal = true;
+                        }
+                        ContextAction::CentreOn(point) => {
+                            view.set_centre(point);
+                            flags.generate_fractal = true;
+                        }
+                        ContextAction::ZoomIn((x, y)) => {
+                            // keep the clicked point under the cursor
+                            view.set_viewport(view.viewport(width, height).zoom_about(x, y, 0.5));
+                            flags.generate_fractal = true;
+                        }
+                        ContextAction::ZoomTo(point, size) => {
+                            // the feature fills about half the smaller side of the window
+                            view.set_centre(point);
+                            view.zoom = size * 4.0 / width.min(height) as f64;
+                            flags.generate_fractal = true;
+                        }
+                        ContextAction::SetJuliaSeed(seed) => {
+                            let (mut julia, _) = memory.switch(fractal.as_ref(), *view, "Julia", &flags.profiles);
+                            if let Some(julia) = julia.downcast_mut::<Julia>() {
+                                julia.c = seed;
+                            }
+                            *fractal = julia;
+                            // start from the default view as the new seed gives a completely different set
+                            flags.reset = true;
+                            flags.generate_fractal = true;
+                        }
+                        ContextAction::AddNewtonRoot(root) => {
+                            if let Some(newton) = fractal.downcast_mut::<Newton>() {
+                                newton.roots.push(root);
+                                flags.generate_fractal = true;
+                            }
+                        }
+                        ContextAction::SaveImage => flags.screenshot = true,
+                        ContextAction::SaveMask { antialiased } => {
+                            flags.mask_export = Some(MaskExport::start(fractal.clone(), view.viewport(width, height), antialiased));
+                        }
+                        ContextAction::SaveSession => {
+                            match (SessionFile { fractal: fractal.clone(), view: *view }).save() {
+                                Ok(path) => flags.toasts.success(format!("{} {}", tr("Saved session to"), path.display())),
+                                Err(e) => flags.toasts.error(format!("{}: {}", tr("Failed to save session"), e)),
+                            }
+                        }
+                    }
+                }
+
+                if let Some(recorder) = recorder {
+                    recorder.record(fractal.as_ref(), *view);
+                }
+
+                // the final render is better than the refinement, which would paint over it
+                if flags.start_final_render || flags.start_region_render.is_some() {
+                    flags.refine_pending = false;
+                    flags.refinement = None;
+                    flags.cpu_pass = None;
+                }
+                if std::mem::take(&mut flags.start_final_render) {
+                    let viewport = view.viewport(width, height).resized(buffer_width as i32, buffer_height as i32);
+                    flags.final_render = Some(FinalRender::start(fractal.clone(), viewport, flags.quality.final_render, *levels, flags.quality.time_lapse.interval()));
+                }
+                if let Some(((start_x, start_y), (end_x, end_y))) = flags.start_region_render.take() {
+                    let viewport = view.viewport(width, height).resized(buffer_width as i32, buffer_height as i32);
+                    // the box is in window pixels and the frame may be drawn at a different size
+                    let (scale_x, scale_y) = (buffer_width as f32 / width as f32, buffer_height as f32 / height as f32);
+                    let left = (start_x.min(end_x) * scale_x).floor().max(0.0) as i32;
+                    let top = (start_y.min(end_y) * scale_y).floor().max(0.0) as i32;
+                    let right = ((start_x.max(end_x) + 1.0) * scale_x).ceil().min(buffer_width as f32) as i32;
+                    let bottom = ((start_y.max(end_y) + 1.0) * scale_y).ceil().min(buffer_height as f32) as i32;
+                    if right > left && bottom > top {
+                        let region = (left, top, right - left, bottom - top);
+                        flags.final_render = Some(FinalRender::start_region(fractal.clone(), viewport, flags.quality.final_render, *levels, region, flags.quality.time_lapse.interval()));
+                    }
+                }
+                if let Some(render) = flags.final_render.as_mut() {
+                    let frame = render.poll();
+                    match render.time_lapse.take() {
+                        Some(Ok((dir, frames))) => flags.toasts.success(format!("{} {} {} {}", tr("Saved a time-lapse of"), frames, tr("frames to"), dir.display())),
+                        Some(Err(e)) => flags.toasts.error(format!("{}: {}", tr("Failed to save the time-lapse"), e)),
+                        None => {}
+                    }
+                    if let Some(frame) = frame {
+                        // shown from the freeze frame until the view changes
+                        if render.composite(&frame, freeze_frame) {
+                            // anything still drawing the view would paint over it
+                            *progressive = None;
+                            render_queue.cancel();
+                            flags.toasts.success(tr(if render.is_region() { "Region render finished" } else { "Final quality render finished" }));
+                        }
+                        flags.final_render = None;
+                    }
+                }
+                // the GPU's preview is drawn a band at a time too, it would paint over the CPU's bands
+                if let Some(pass) = flags.cpu_pass.as_mut() {
+                    if !render_queue.busy() && pass.composite(freeze_frame, &mut frame_cache) {
+                        debug!("Drawn again on the CPU");
+                        flags.cpu_pass = None;
+                    }
+                }
+                // the boundary is drawn again once the whole frame is there, it would be painted over otherwise
+                if flags.refine_pending && !render_queue.busy() {
+                    let viewport = view.viewport(width, height).resized(buffer_width as i32, buffer_height as i32);
+                    flags.iteration_counts.update(fractal.as_ref(), viewport);
+                    if let Some(counts) = flags.iteration_counts.get((buffer_width * buffer_height) as usize) {
+                        flags.refinement = Some(Refinement::start(fractal.clone(), viewport, *levels, counts.to_vec()));
+                        flags.refine_pending = false;
+                    }
+                }
+                if let Some(refinement) = flags.refinement.as_ref() {
+                    if let Some(refined) = refinement.poll() {
+                        // shown from the freeze frame until the view changes
+                        if refinement.merge(&refined, freeze_frame) {
+                            debug!(pixels = refined.len(), "Refined boundary");
+                        }
+                        flags.refinement = None;
+                    }
+                }
+
+                // the frame is coloured again with the levels of its own counts, or with the whole range once auto levels is off
+                if flags.auto_levels && !flags.generate_fractal && !flags.reset {
+                    flags.iteration_counts.update(fractal.as_ref(), view.viewport(width, height).resized(buffer_width as i32, buffer_height as i32));
+                    if let Some(counted) = flags.iteration_counts.levels().filter(|counted| counted != levels) {
+                        *levels = counted;
+                        flags.generate_fractal = true;
+                    }
+                } else if !flags.auto_levels && *levels != Levels::FULL {
+                    *levels = Levels::FULL;
+                    flags.generate_fractal = true;
+                }
+                if flags.suggest_iterations && !flags.generate_fractal && !flags.reset {
+                    flags.iteration_counts.update_suggestion(fractal.as_ref(), view.viewport(width, height).resized(buffer_width as i32, buffer_height as i32));
+                }
+                if flags.generate_fractal || flags.reset {
+                    let _span = debug_span!("generate", fractal = fractal.name(), buffer_width, buffer_height).entered();
+                    let started = Instant::now();
+                    flags.render_stats.start();
                     if flags.reset { // reset the fractal to default position/zoom
-                        zoom = INIT_ZOOM;
-                        offset_x = 0.0;
-                        offset_y = 0.0;
+                        *view = flags.profiles.view(fractal.name());
                         flags.reset = false;
                     }
                     // Generate and render the fractal here
-                    // Cloning a fractal is cheap, so we can clone it here and pass it to the draw function
-                    fractal.clone().draw(pixels.frame_mut(), width as i32, height as i32, zoom, offset_x, offset_y);
+                    let viewport = view.viewport(width, height).resized(buffer_width as i32, buffer_height as i32);
+                    breadcrumbs.record(fractal.name(), *view, (width, height));
+                    // animations change the view every frame, they aren't steps to undo
+                    if !timeline.playing && !timeline.motion.active() && !timeline.sweep.playing() && flags.audio.is_none() {
+                        history.track(fractal.as_ref(), *view);
+                    }
+                    *progressive = None;
+                    // a final render of the old view is no use any more
+                    flags.final_render = None;
+                    flags.refinement = None;
+                    flags.cpu_pass = None;
+                    flags.iteration_counts.clear();
+                    // frames that change every step of an animation aren't worth refining
+                    flags.refine_pending = flags.quality.refine_boundary && Refinement::applies(fractal.as_ref())
+                        && flags.quality.exploring.is_plain() && !compare.enabled && !flags.edges
+                        && !timeline.playing && !timeline.sweep.playing() && flags.audio.is_none();
+                    render_queue.cancel();
+                    // going back to a view that was just shown reuses its frame, animations rarely come back to the same one
+                    let mut cache_key = (!compare.enabled && !timeline.playing && !timeline.sweep.playing() && flags.audio.is_none())
+                        .then(|| FrameCache::key(fractal.as_ref(), *view, (buffer_width, buffer_height), flags.renderer, flags.quality.exploring, flags.edges.then_some(flags.edge_style), *levels));
+                    if let Some(frame) = cache_key.as_deref().and_then(|key| frame_cache.get(key)) {
+                        pixels.frame_mut().copy_from_slice(frame);
+                        cache_key = None;
+                    } else if compare.enabled {
+                        // render both parameter sets and split them at the divider
+                        compare.draw(fractal.as_ref(), viewport, *levels);
+                        compare.composite(pixels.frame_mut(), buffer_width);
+                    } else if flags.edges {
+                        edges::draw(fractal.as_ref(), pixels.frame_mut(), viewport, flags.edge_style);
+                    } else if let Some(mut renderer) = fractal.progressive(viewport) {
+                        // only the first points are drawn now, the rest are added over the next frames
+                        renderer.accumulate();
+                        renderer.draw(pixels.frame_mut());
+                        *progressive = Some(renderer);
+                        // frames that are still being filled in aren't cached
+                        cache_key = None;
+                    } else if !flags.quality.exploring.is_plain() {
+                        // supersampling and smooth colouring are only done on the CPU
+                        quality::draw(fractal.as_ref(), pixels.frame_mut(), viewport, flags.quality.exploring, *levels, |_, _| true);
+                    } else {
+                        // the first bands are drawn over the last frame now and the rest over the next frames,
+                        // animations and screenshots need the whole frame at once
+                        let whole = flags.screenshot || timeline.playing || timeline.motion.active() || timeline.sweep.playing() || flags.audio.is_some();
+                        // the hybrid backend's preview is drawn again on the CPU, which animations don't wait for
+                        // and screenshots do straight away
+                        let refines = renderers.refines_on_cpu(flags.renderer, fractal.as_ref(), viewport);
+                        let cpu_pass = refines && !whole;
+                        let backend = if refines && flags.screenshot { Backend::Cpu } else { flags.renderer };
+                        render_queue.start(viewport, *levels, if cpu_pass { None } else { cache_key.take() });
+                        let result = if whole {
+                            render_queue.finish(&mut renderers, backend, fractal.as_ref(), freeze_frame, &mut frame_cache)
+                        } else {
+                            render_queue.run(&mut renderers, backend, fractal.as_ref(), freeze_frame, &mut frame_cache)
+                        };
+                        if let Err(e) = result {
+                            renderer_failed(&mut flags, e);
+                        }
+                        if cpu_pass {
+                            flags.cpu_pass = Some(CpuPass::start(fractal.clone(), viewport, *levels, cache_key.take()));
+                        }
+                        pixels.frame_mut().copy_from_slice(freeze_frame);
+                    }
+                    debug!(elapsed = ?started.elapsed(), zoom = view.zoom, "Generated");
+                    // a script fractal that fails is drawn as if every point escaped straight away
+                    if let Some(e) = script::take_render_error() {
+                        flags.toasts.error(format!("{}: {}", tr("Script error"), e));
+                        flags.console.error(e);
+                    }
+                    if let Some(e) = renderers.take_shader_error() {
+                        shader_failed(&mut flags, e);
+                    }
+                    if let Some(key) = cache_key {
+                        frame_cache.insert(key, pixels.frame().to_vec());
+                    }
                     freeze_frame.copy_from_slice(pixels.frame());
                 } 
+                else if let Some(renderer) = progressive.as_mut().filter(|renderer| !renderer.finished()) {
+                    renderer.accumulate();
+                    renderer.draw(pixels.frame_mut());
+                    freeze_frame.copy_from_slice(pixels.frame());
+                }
                 else {
                     // If the code reaches here it means no new fractal or zoom box was generated
                     // so we just used the previously generated frame.
                     // This keeps the ui animations smooth since generating fractals takes too much time per frame
-                    pixels.frame_mut().copy_from_slice(&freeze_frame);
+                    // the rest of a frame that is generated a band at a time, the freeze frame has the real colours
+                    // as the displayed frame may have been changed by the colour blind simulation
+                    if render_queue.busy() {
+                        let result = if flags.screenshot {
+                            render_queue.finish(&mut renderers, flags.renderer, fractal.as_ref(), freeze_frame, &mut frame_cache)
+                        } else {
+                            render_queue.run(&mut renderers, flags.renderer, fractal.as_ref(), freeze_frame, &mut frame_cache)
+                        };
+                        if let Err(e) = result {
+                            renderer_failed(&mut flags, e);
+                        }
+                    }
+                    pixels.frame_mut().copy_from_slice(freeze_frame);
                 }
+                flags.render_stats.update(&render_queue, !render_queue.busy() && progressive.as_ref().is_none_or(|renderer| renderer.finished()));
                 
+                // the adjustments and simulation are only applied to the displayed frame, the freeze frame keeps the real colours
+                if !flags.adjustments.is_neutral() {
+                    flags.adjustments.apply(pixels.frame_mut());
+                }
+                // both need the counts, which are shown as they are until they arrive
+                if flags.highlight.enabled || flags.stereo.mode != StereoMode::Off || flags.palette_previews || (flags.inspector && flags.zoom_box.is_some()) {
+                    flags.iteration_counts.update(fractal.as_ref(), view.viewport(width, height).resized(buffer_width as i32, buffer_height as i32));
+                    if let Some(counts) = flags.iteration_counts.get((buffer_width * buffer_height) as usize) {
+                        if flags.highlight.enabled {
+                            flags.highlight.apply(pixels.frame_mut(), counts);
+                        }
+                        flags.stereo.apply(pixels.frame_mut(), buffer_width as usize, counts, flags.iteration_counts.max_iterations());
+                    }
+                }
+                // the camera gets the frame as it's shown, apart from the simulation which is only a preview
+                if let Some(device) = flags.start_camera.take() {
+                    match VirtualCamera::start(std::path::Path::new(&device), buffer_width, buffer_height) {
+                        Ok(camera) => {
+                            flags.toasts.success(format!("{} {}", tr("Publishing frames to"), device));
+                            flags.virtual_camera = Some(camera);
+                        }
+                        Err(e) => flags.toasts.error(format!("{}: {}", tr("Couldn't start the virtual camera"), e)),
+                    }
+                }
+                if let Some(Err(e)) = flags.virtual_camera.as_mut().map(|camera| camera.send(pixels.frame(), buffer_width, buffer_height)) {
+                    flags.toasts.error(format!("{}: {}", tr("Virtual camera stopped"), e));
+                    flags.virtual_camera = None;
+                }
+                if let Some(simulation) = flags.colour_blind_simulation {
+                    colour_blind::simulate(pixels.frame_mut(), simulation);
+                }
+
                 // Render
+                // Screenshots of just the fractal come straight from the frame,
+                // the gui only exists on the gpu so it has to be rendered again into a texture that can be read back
+                let capture_ui = flags.screenshot && flags.screenshot_ui && !flags.hide_ui;
+                if flags.screenshot && flags.screenshot_ui && flags.hide_ui {
+                    flags.toasts.warning(tr("The UI is hidden so it won't be in the screenshot"));
+                }
+                // the bloom only exists on the gpu so screenshots with it are read back from there too
+                let capture_bloom = flags.screenshot && !capture_ui && flags.bloom.enabled;
+                if flags.screenshot && !capture_ui && !capture_bloom {
+                    // with the adjustments but not the highlight, stereo or simulation, which are only previews
+                    let mut image = freeze_frame.to_vec();
+                    if !flags.adjustments.is_neutral() {
+                        flags.adjustments.apply(&mut image);
+                    }
+                    let saved = flags.watermark.apply(&image, buffer_width, buffer_height, *view)
+                        .and_then(|(image, width, height)| export::save_image(&image, width, height));
+                    match saved {
+                        Ok(path) => flags.toasts.success(format!("{} {}", tr("Saved image to"), path.display())),
+                        Err(e) => flags.toasts.error(format!("{}: {}", tr("Failed to save image"), e)),
+                    }
+                }
+                let surface_format = pixels.surface_texture_format();
+                let mut capture = None;
+                let stretched = (buffer_width, buffer_height) != (width, height);
+
+                let _gpu_span = trace_span!("gpu_render").entered();
                 let render_result = pixels.render_with(|encoder, render_target, context| {
                     // Render the fractal
-                    context.scaling_renderer.render(encoder, render_target);
+                    if flags.bloom.enabled {
+                        bloom.render(encoder, render_target, context, &flags.bloom);
+                    } else if stretched {
+                        scaler.render(encoder, render_target, context);
+                    } else {
+                        context.scaling_renderer.render(encoder, render_target);
+                    }
                     // Render egui
-                    framework.render(encoder, render_target, context);
+                    // the zoom box is drawn by the gui, and is still needed when the rest of it is hidden
+                    if !flags.hide_ui || flags.zoom_box.is_some() {
+                        framework.render(encoder, render_target, context);
+                    }
+                    if capture_ui || capture_bloom {
+                        let texture = WindowCapture::create_texture(context, surface_format, width, height);
+                        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                        if flags.bloom.enabled {
+                            bloom.render(encoder, &view, context, &flags.bloom);
+                        } else if stretched {
+                            scaler.render(encoder, &view, context);
+                        } else {
+                            context.scaling_renderer.render(encoder, &view);
+                        }
+                        if capture_ui {
+                            framework.render(encoder, &view, context);
+                        }
+                        capture = Some(WindowCapture::copy(encoder, context, &texture));
+                    }
                     Ok(())
                 });
+                flags.screenshot = false;
 
-                // Basic error handling
-                if let Err(_) = render_result {
+                if let Some(capture) = capture {
+                    match capture.save(pixels.device(), &flags.watermark, *view) {
+                        Ok(path) => flags.toasts.success(format!("{} {}", tr("Saved image to"), path.display())),
+                        Err(e) => flags.toasts.error(format!("{}: {}", tr("Failed to save image"), e)),
+                    }
+                }
+
+                // Keep running after errors so they can be shown, unless frames keep failing and nothing can be drawn
+                match render_result {
+                    Ok(()) => render_failures = 0,
+                    Err(e) => {
+                        warn!(error = %e, "Frame failed to render");
+                        render_failures += 1;
+                        if render_failures > MAX_RENDER_FAILURES {
+                            error::fatal(&format!("Rendering keeps failing: {}", e));
+                        }
+                        if let Some(dialog) = error::render_error(e, pixels, width, height) {
+                            flags.error.get_or_insert(dialog);
+                        }
+                    }
+                }
+                if let Some(dialog) = error::take_gpu_error() {
+                    flags.error.get_or_insert(dialog);
+                }
+                if flags.error.is_some() {
+                    flags.hide_ui = false;
+                }
+                // keep drawing only while something is moving, otherwise sleep until there's input,
+                // the gui or a toast needs drawing, or it's time to check on work in the background
+                let animating = timeline.playing || timeline.motion.active() || timeline.sweep.playing() || flags.audio.is_some()
+                    || progressive.as_ref().is_some_and(|renderer| !renderer.finished()) || render_queue.busy() || flags.panning.is_some();
+                let mut wait = framework.repaint_after().min(IDLE_WAKE);
+                if let Some(toasts) = flags.toasts.next_update() {
+                    wait = wait.min(toasts);
+                }
+                if flags.video_export.is_some() || flags.mask_export.is_some() || flags.gallery_export.is_some() || flags.console.automation.is_some() || flags.final_render.is_some() || flags.iteration_counts.suggesting()
+                    || flags.refine_pending || flags.refinement.is_some() || flags.cpu_pass.is_some() {
+                    wait = wait.min(BACKGROUND_POLL);
+                }
+                // the surface is recreated at the start of the next event
+                #[cfg(not(target_arch = "wasm32"))]
+                if flags.graphics.is_some() {
+                    wait = Duration::ZERO;
+                }
+                if animating || wait.is_zero() {
+                    control_flow.set_poll();
+                } else {
+                    control_flow.set_wait_timeout(wait);
+                }
+
+                if flags.quit {
+                    // quitting from an error dialog keeps the autosave, so the sessions can be restored next time
+                    if flags.recovered.is_none() {
+                        if let Err(e) = autosaver.save(&sessions) {
+                            warn!(error = %e, "Autosave failed");
+                        }
+                    }
                     *control_flow = ControlFlow::Exit;
                 }
                 
@@ -158,78 +1275,130 @@ fn main() {
          // For everything else, for let winit_input_helper collect events to build its state.
         // It returns `true` when it is time to update our game state and request a redraw.
         if input.update(&event) {
+            let _span = trace_span!("input").entered();
+            #[cfg(target_arch = "wasm32")]
+            web::fit_to_page(&window);
+            let (width, height) = (window.inner_size().width, window.inner_size().height);
+            let buffer_width = pixels.context().texture_extent.width;
+            let session = sessions.current_mut();
+            let Session { fractal, view, compare, freeze_frame, snapshots, .. } = session;
             // If the user scrolls the mouse wheel, zoom in/out
             let scroll = input.scroll_diff();
             if scroll != 0.0 {
-                let zoom_factor = 1.0 + (0.1 * zoom_amount * -scroll.signum());
-                zoom *= zoom_factor as f64;
+                view.zoom *= flags.navigation.zoom_factor.powf(-scroll.signum() as f64);
                 flags.generate_fractal = true;
             }
+            // Escape abandons the current zoom box, divider drag or context menu.
+            // Checked before the mouse buttons as the mouse is still held while dragging
+            else if keybindings.pressed(&input, Action::Cancel) {
+                perform(Action::Cancel, &mut flags, view, compare, (width, height));
+            }
             // Left click
             else if input.mouse_pressed(0) {
                 if !flags.window_event {
                     // if the mouse click is inside the ui window, don't do anything
                     if let Some((x,y)) = input.mouse() {
-                        freeze_frame.copy_from_slice(pixels.frame());
-                        zoom_start = (x,y);
-                        zoom_end = (x,y); // reset the end point to the start point
-                        flags.render_zoom_box = true;
+                        // grab the compare divider instead of starting a zoom box
+                        if compare.enabled && compare.over_divider(x, width) {
+                            compare.dragging = true;
+                        } else {
+                            flags.zoom_box = Some(((x,y), (x,y)));
+                        }
+                    }
+                }
+            }
+            // Right click opens the context menu
+            else if input.mouse_pressed(1) {
+                if !flags.window_event && !flags.hide_ui {
+                    if let Some((x,y)) = input.mouse() {
+                        // the same pixel to complex plane mapping used by the generators
+                        let pixel = (x.floor() as f64, y.floor() as f64);
+                        let point = view.viewport(width, height).pixel_to_complex(pixel.0, pixel.1);
+                        flags.context_menu = Some(ContextMenu { position: (x,y), pixel, point });
                     }
                 }
             }
             // Hold left click
             else if input.mouse_held(0) {
+                if compare.dragging {
+                    // move the divider, both renders are cached so only the composite needs updating
+                    if let Some((x,_)) = input.mouse() {
+                        compare.drag_to(x, width);
+                        compare.composite(freeze_frame, buffer_width);
+                    }
+                }
                 // update the zoom box end point
-                if let Some((x,y)) = input.mouse() {
-                    let (width, height): (f32, f32) = window.inner_size().into();
+                else if let (Some((_, zoom_end)), Some((x,y))) = (flags.zoom_box.as_mut(), input.mouse()) {
                     // clamp the mouse position to the window size
-                    zoom_end = (x.clamp(0.0, width-1.0), y.clamp(0.0, height-1.0));
+                    *zoom_end = (x.clamp(0.0, width as f32 - 1.0), y.clamp(0.0, height as f32 - 1.0));
                 }
             }
             // Release left click
             else if input.mouse_released(0) {
+                compare.dragging = false;
                 // zoom after finishing the drag select
-                if flags.render_zoom_box {
-                    flags.render_zoom_box = false;
-                    let (window_width, window_height) = (window.inner_size().width, window.inner_size().height);
-                    // set offset
-                    let (start_x, start_y) = zoom_start;
-                    let (end_x, end_y) = zoom_end;
-                    let box_width = (start_x - end_x).abs();
-                    let box_height = (start_y - end_y).abs();
-                    let top_left_box = (start_x.min(end_x), start_y.min(end_y));
-                    // center camera on the middle of the zoom box
-                    offset_x += ((top_left_box.0 + box_width/2.0)  - window_width as f32 / 2.0) as f64 * zoom;
-                    offset_y += ((top_left_box.1 + box_height/2.0)  - window_height as f32 / 2.0) as f64 * zoom;
-                    
-                    // set zoom
-                    let box_area = box_width * box_height;
-                    // if the box is too small, don't zoom
-                    if box_area >= 100.0 { 
-                        let screen_area = window_width * window_height;
-                        let zoom_coeff = 10.0;
-                        // how many times smaller is the box than the screen
-                        // clamp so that it doesnt zoom out when the zoom box is too big
-                        zoom *= ((box_area as f64 / screen_area as f64) * zoom_coeff).clamp(0.00001, 0.8);
+                // or render the region in the box again instead, when one is being picked
+                if flags.select_region && flags.zoom_box.is_some() {
+                    flags.select_region = false;
+                    flags.start_region_render = flags.zoom_box.take();
+                }
+                else if let Some(((start_x, start_y), (end_x, end_y))) = flags.zoom_box.take() {
+                    let box_area = (start_x - end_x).abs() * (start_y - end_y).abs();
+                    let box_centre = ((start_x + end_x) as f64 / 2.0, (start_y + end_y) as f64 / 2.0);
+                    let screen_area = width * height;
+                    let mut viewport = view.viewport(width, height);
+
+                    // a click, or a box too small to mean anything, just centres on the point
+                    if !flags.navigation.is_box((start_x, start_y), (end_x, end_y)) {
+                        viewport = viewport.centred_on(start_x as f64, start_y as f64);
                     }
+                    // with shift the whole view is shrunk into the box, zooming out
+                    else if input.held_shift() {
+                        viewport.scale *= (screen_area as f64 / box_area as f64).sqrt().min(MAX_ZOOM_OUT);
+                        // what was in the middle of the screen ends up in the middle of the box
+                        let (centre_x, centre_y) = viewport.screen_centre();
+                        let (dx, dy) = viewport.delta_to_complex(box_centre.0 - centre_x, box_centre.1 - centre_y);
+                        viewport = viewport.moved((-dx, -dy));
+                    }
+                    else {
+                        // center camera on the middle of the zoom box
+                        viewport = viewport.centred_on(box_centre.0, box_centre.1);
+                        // by how many times smaller the box is than the screen, clamped so a big box still zooms in
+                        viewport.scale *= flags.navigation.box_scale(box_area as f64 / screen_area as f64);
+                    }
+                    view.set_viewport(viewport);
                     flags.generate_fractal = true;
                 }
             }
-            else if input.key_pressed(winit::event::VirtualKeyCode::W) || input.key_pressed(winit::event::VirtualKeyCode::Right) {
-                offset_y -= 0.5 * (zoom / INIT_ZOOM); // adjust the move distance based on the zoom level so that the movements dont become massive
-                flags.generate_fractal = true;
-            }
-            else if input.key_pressed(winit::event::VirtualKeyCode::S) || input.key_pressed(winit::event::VirtualKeyCode::Down) {
-                offset_y += 0.5 * (zoom / INIT_ZOOM);
-                flags.generate_fractal = true;
+            // the other keys, apart from the ones typed into the gui
+            else if let Some(action) = keybindings.pressed_action(&input)
+                .filter(|action| !flags.window_event || action.while_typing())
+                .filter(|action| !(flags.navigation.continuous_pan && action.pan_direction().is_some())) {
+                perform(action, &mut flags, view, compare, (width, height));
             }
-            else if input.key_pressed(winit::event::VirtualKeyCode::A) || input.key_pressed(winit::event::VirtualKeyCode::Left) {
-                offset_x -= 0.5 * (zoom / INIT_ZOOM);
-                flags.generate_fractal = true;
+            // Ctrl+Z steps back through the changes to the view and settings, Ctrl+Y or Ctrl+Shift+Z forward again
+            else if input.held_control() && !flags.window_event && (input.key_pressed(VirtualKeyCode::Z) || input.key_pressed(VirtualKeyCode::Y)) {
+                let redo = input.key_pressed(VirtualKeyCode::Y) || input.held_shift();
+                flags.history_step = Some(if redo { Step::Redo } else { Step::Undo });
             }
-            else if input.key_pressed(winit::event::VirtualKeyCode::D) || input.key_pressed(winit::event::VirtualKeyCode::Right) {
-                offset_x += 0.5 * (zoom / INIT_ZOOM);
-                flags.generate_fractal = true;
+            // Ctrl and a number key saves a snapshot to that slot, the number key alone goes back to it
+            else if let Some(slot) = snapshots::slot_pressed(&input).filter(|_| !flags.window_event) {
+                if input.held_control() {
+                    snapshots.save(slot, Snapshot::of(fractal.as_ref(), *view, compare, flags.adjustments));
+                    flags.toasts.info(format!("{} {}", tr("Saved snapshot"), slot));
+                } else if let Some(snapshot) = snapshots.get(slot) {
+                    *fractal = snapshot.fractal.clone();
+                    *view = snapshot.view;
+                    compare.enabled = snapshot.compare.is_some();
+                    if let Some(b) = &snapshot.compare {
+                        compare.fractal = b.clone();
+                    }
+                    flags.adjustments = snapshot.adjustments;
+                    flags.generate_fractal = true;
+                    flags.toasts.info(format!("{} {}", tr("Snapshot"), slot));
+                } else {
+                    flags.toasts.info(format!("{} {}", tr("Nothing saved yet, Ctrl + the number saves to slot"), slot));
+                }
             }
     
             // Update the scale factor
@@ -237,53 +1406,243 @@ fn main() {
                 framework.scale_factor(scale_factor);
             }
             // Resize the window
-            else if let Some(size) = input.window_resized() {
-                
-                if let Err(_) = pixels.resize_surface(size.width, size.height) {
-                    *control_flow = ControlFlow::Exit;
-                    return;
+            // Minimised windows have no size, they keep their buffer until they are restored
+            else if let Some(size) = input.window_resized().filter(|size| size.width > 0 && size.height > 0) {
+                match pixels.resize_surface(size.width, size.height) {
+                    // the pixel buffer is resized to the render scale and regenerated on the next redraw
+                    Ok(()) => framework.resize(size.width, size.height),
+                    Err(e) => {
+                        // shrink the window to the largest texture the gpu supports
+                        let max = pixels.device().limits().max_texture_dimension_2d;
+                        window.set_inner_size(PhysicalSize::new(size.width.min(max), size.height.min(max)));
+                        flags.error.get_or_insert(ErrorDialog::new("Couldn't resize the window", e.to_string()));
+                    }
                 }
+            }
 
-                if let Err(_) = pixels.resize_buffer(size.width, size.height) {
-                    *control_flow = ControlFlow::Exit;
-                    return;
+            // Held pan keys move the view a little every frame, by how long the frame took so the speed is steady
+            let last_pan = flags.panning.take();
+            if flags.navigation.continuous_pan && !flags.window_event {
+                if let Some((dx, dy)) = held_direction(&input, &keybindings) {
+                    let now = Instant::now();
+                    // the first frame of a hold moves as far as a typical frame would
+                    let elapsed = last_pan.map_or(1.0 / 60.0, |last| (now - last).as_secs_f64().min(0.1));
+                    let distance = flags.navigation.pan_speed * height as f64 * elapsed;
+                    let (dx, dy) = view.viewport(width, height).delta_to_complex(dx * distance, dy * distance);
+                    view.pan(dx, dy);
+                    flags.generate_fractal = true;
+                    flags.panning = Some(now);
+                }
+            }
+
+            // Track the point under the mouse for the pixel inspector, orbit plot and Julia preview
+            flags.hover = None;
+            if flags.inspector || flags.orbit_follow || flags.julia_preview {
+                if let Some((x,y)) = input.mouse() {
+                    flags.hover = Some(((x,y), view.viewport(width, height).pixel_to_complex(x.floor() as f64, y.floor() as f64)));
                 }
-                // resize the frame buffer
-                freeze_frame = vec![0; (4 * size.width * size.height) as usize]; 
-                framework.resize(size.width, size.height);
             }
             window.request_redraw();
         }
+    };
+    // in the browser the event loop has to be handed over without blocking
+    #[cfg(target_arch = "wasm32")]
+    winit::platform::web::EventLoopExtWebSys::spawn(event_loop, event_handler);
+    #[cfg(not(target_arch = "wasm32"))]
+    event_loop.run(event_handler);
+}
 
-        
-    });
+/// Run `f` on a background thread. The browser has no threads so there it runs straight away
+pub fn background(f: impl FnOnce() + Send + 'static) {
+    #[cfg(not(target_arch = "wasm32"))]
+    std::thread::spawn(f);
+    #[cfg(target_arch = "wasm32")]
+    f();
 }
 
-/// Draw a box around the selected area.
-/// Start and end are the top left and bottom right corners of the box
-fn draw_zoom_box(pixels: &mut [u8], (x1,y1): (f32, f32), (x2,y2): (f32, f32), screen_width: u32) {
-    let width = (x2-x1).abs() as usize;
-    let screen_width = screen_width as usize;
-    let (x1,y1,x2,y2) = (x1 as usize, y1 as usize, x2 as usize, y2 as usize);
-    
-    // (0,0) is in top left of window
-    let top_left_pixel = (y1.min(y2) * screen_width + x1.min(x2)) * 4; // each pixel has r,g,b,a channels
-    let bottom_left_pixel = (y1.max(y2) * screen_width + x1.min(x2)) * 4;
-
-    // draw top and bottom borders
-    let border_width = 3;
-    let border = vec![255; (width+border_width)*4]; // +4 otherwise the corner pixels are not drawn
-    for i in 0..border_width {
-        let y_offset = i * screen_width * 4;
-        pixels[top_left_pixel+y_offset .. top_left_pixel+y_offset+(width+border_width)*4].copy_from_slice(&border);
-        pixels[bottom_left_pixel-y_offset .. bottom_left_pixel-y_offset+(width+border_width)*4].copy_from_slice(&border);
+/// The monitor picked to go fullscreen on if it's still connected, None for the one the window is on
+fn chosen_monitor(window: &Window, flags: &mut Flags) -> Option<MonitorHandle> {
+    let name = flags.fullscreen_monitor.as_ref()?;
+    let monitor = window.available_monitors().find(|monitor| monitor.name().as_ref() == Some(name));
+    if monitor.is_none() {
+        flags.toasts.warning(format!("{} \"{}\" {}", tr("Monitor"), name, tr("isn't connected, going fullscreen on this one")));
     }
-    
-    // draw left and right borders
-    let border = vec![255; border_width*4];
-    for y in y1.min(y2)..y1.max(y2) {
-        let pixel = (y*screen_width + x1.min(x2)) * 4;
-        pixels[pixel .. pixel+(border_width*4)].copy_from_slice(&border);
-        pixels[pixel+(width*4) .. pixel+(width*4)+(border_width*4)].copy_from_slice(&border);
+    monitor
+}
+
+/// Do what an action's keys do, for the keys and the command palette
+fn perform(action: Action, flags: &mut Flags, view: &mut View, compare: &mut Compare, (width, height): (u32, u32)) {
+    if let Some((dx, dy)) = action.pan_direction() {
+        // move relative to the screen so panning still makes sense when the view is rotated,
+        // the viewport's scale keeps the step the same share of the window at any zoom
+        let step = flags.navigation.pan_step * height as f64;
+        let (dx, dy) = view.viewport(width, height).delta_to_complex(dx * step, dy * step);
+        view.pan(dx, dy);
+        flags.generate_fractal = true;
+        return;
     }
+    match action {
+        // Escape abandons the current zoom box, divider drag or context menu
+        Action::Cancel => {
+            if flags.zoom_box.take().is_some() && !flags.select_region {
+                flags.toasts.info(tr("Zoom cancelled"));
+            }
+            if std::mem::take(&mut flags.select_region) {
+                flags.toasts.info(tr("Region render cancelled"));
+            }
+            flags.context_menu = None;
+            compare.dragging = false;
+        }
+        Action::HideUi => {
+            flags.hide_ui = !flags.hide_ui;
+            flags.context_menu = None;
+        }
+        Action::Inspector => flags.inspector = !flags.inspector,
+        Action::Bookmarks => flags.show_bookmarks = !flags.show_bookmarks,
+        Action::Timeline => flags.show_timeline = !flags.show_timeline,
+        Action::Fullscreen => flags.set_fullscreen = Some(!flags.fullscreen),
+        Action::Help => flags.show_help = !flags.show_help,
+        Action::Screenshot => flags.screenshot = true,
+        Action::FinalRender => flags.start_final_render = true,
+        Action::RenderRegion => {
+            flags.select_region = true;
+            flags.toasts.info(tr("Draw a box around the region to render again"));
+        }
+        Action::RotateLeft | Action::RotateRight => {
+            let step = if action == Action::RotateLeft { -ROTATION_STEP } else { ROTATION_STEP };
+            view.rotation = wrap_degrees(view.rotation + step);
+            flags.generate_fractal = true;
+        }
+        Action::PanUp | Action::PanDown | Action::PanLeft | Action::PanRight => {}
+    }
+}
+
+/// Returns the direction of the pan keys held down in screen space, diagonal if two are held at once
+fn held_direction(input: &WinitInputHelper, keybindings: &KeyBindings) -> Option<(f64, f64)> {
+    let axis = |negative, positive| keybindings.held(input, positive) as i32 as f64 - keybindings.held(input, negative) as i32 as f64;
+    let (dx, dy) = (axis(Action::PanLeft, Action::PanRight), axis(Action::PanUp, Action::PanDown));
+    let length = dx.hypot(dy);
+    (length > 0.0).then(|| (dx / length, dy / length))
+}
+
+/// Keep an angle in degrees within -180..=180
+fn wrap_degrees(degrees: f64) -> f64 {
+    (degrees + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Go back to the CPU after the chosen renderer failed, the frame has already been drawn on the CPU instead
+fn renderer_failed(flags: &mut Flags, error: String) {
+    flags.toasts.error(format!("{}: {}", tr("Rendering failed, switched to the CPU"), error));
+    flags.renderer = Backend::Cpu;
+}
+
+/// Start at the session file or bookmark given on the command line instead of the default fractal
+fn open_at_startup(target: &str, sessions: &mut Sessions, bookmarks: &Bookmarks, window_height: u32, flags: &mut Flags) {
+    let path = std::path::Path::new(target);
+    let session = if path.is_file() {
+        match import::import_file(path, window_height) {
+            Ok(Imported::Session(file)) => Session::from(file),
+            Ok(_) => {
+                flags.toasts.error(format!("{} {}: {}", tr("Couldn't open"), target, tr("only sessions and bookmarks can be opened at startup")));
+                return;
+            }
+            Err(e) => {
+                flags.toasts.error(format!("{} {}: {}", tr("Couldn't open"), target, e));
+                return;
+            }
+        }
+    } else if let Some(bookmark) = bookmarks.list.iter().find(|bookmark| bookmark.name.eq_ignore_ascii_case(target)) {
+        Session { view: bookmark.view, ..Session::new(bookmark.fractal.clone()) }
+    } else {
+        flags.toasts.error(format!("{} {}: {}", tr("Couldn't open"), target, tr("there's no file or bookmark with that name")));
+        return;
+    };
+    *sessions = Sessions::new(session);
+    // the start panel would offer to go somewhere else
+    flags.show_start = false;
+}
+
+/// Load a parameter file that turned up in the watched folder into the current tab
+#[cfg(not(target_arch = "wasm32"))]
+fn load_watched(path: &std::path::Path, sessions: &mut Sessions, window_height: u32, flags: &mut Flags) {
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    // anything else in the folder, like the temporary files of whatever is writing there, is left alone
+    if !path.is_file() || !matches!(extension.as_str(), "json" | "fvz" | "kfr") {
+        return;
+    }
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    match import::import_file(path, window_height) {
+        Ok(Imported::Session(file)) => {
+            let session = sessions.current_mut();
+            session.fractal = file.fractal;
+            session.view = file.view;
+            flags.generate_fractal = true;
+            flags.console.output(format!("{} {}", tr("Opened"), file_name));
+        }
+        Ok(_) => {}
+        // a file that's still being written fails to load, it's loaded again once it has been written
+        Err(e) => flags.console.error(format!("{} {}: {}", tr("Couldn't open"), file_name, e)),
+    }
+}
+
+/// Pick up an edited shader or script fractal, compile errors are shown in the console.
+/// A shader that doesn't compile leaves the last one that did in use until the file is fixed and saved again.
+#[cfg(not(target_arch = "wasm32"))]
+fn reload_file(path: &std::path::Path, sessions: &mut Sessions, renderers: &mut Renderers, flags: &mut Flags) {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    if renderer::shader_path().is_some_and(|shader| shader == path) {
+        match renderers.reload_shader() {
+            Ok(true) => {
+                flags.console.output(format!("{} {}", tr("Reloaded"), file_name));
+                flags.generate_fractal = true;
+            }
+            Ok(false) => {}
+            Err(e) => shader_failed(flags, e),
+        }
+        return;
+    }
+
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            flags.console.error(format!("{} {}: {}", tr("Couldn't open"), file_name, e));
+            return;
+        }
+    };
+    let mut result = None;
+    let current = sessions.current;
+    for (index, session) in sessions.list.iter_mut().enumerate() {
+        let Some(script) = session.fractal.downcast_mut::<ScriptFractal>().filter(|script| script.path.as_deref() == Some(path)) else {
+            continue;
+        };
+        result = Some(script.reload(source.clone()));
+        // other sessions are drawn again when they're switched to
+        if index == current {
+            flags.generate_fractal = true;
+        } else {
+            session.freeze_frame.clear();
+        }
+    }
+    match result {
+        Some(Ok(())) => flags.console.output(format!("{} {}", tr("Reloaded"), file_name)),
+        Some(Err(e)) => {
+            flags.console.error(format!("{}: {}", file_name, e));
+            flags.show_console = true;
+        }
+        None => {}
+    }
+}
+
+/// The custom shader didn't compile, the GPU renderer carries on with the shader it had
+fn shader_failed(flags: &mut Flags, error: String) {
+    flags.toasts.error(tr("The custom shader didn't compile, see the console"));
+    flags.console.error(format!("escape_time.wgsl: {}", error));
+    flags.show_console = true;
+}
+
+/// Size of the pixel buffer for a window at the render scale.
+/// The scale is lowered if the buffer would be bigger than the gpu can hold, keeping the window's shape
+fn buffer_size(width: u32, height: u32, scale: f32, max: u32) -> (u32, u32) {
+    let scale = scale.min(max as f32 / width.max(height) as f32);
+    (((width as f32 * scale).round() as u32).max(1), ((height as f32 * scale).round() as u32).max(1))
 }
\ No newline at end of file
@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::view::View;
+
+use crate::profiles::Profiles;
+
+/// Remembers the last parameters and view used for each type of fractal,
+/// so switching to another fractal and back doesn't lose either of them
+pub struct FractalMemory {
+    saved: HashMap<&'static str, (Box<dyn Fractal>, View)>,
+}
+
+impl FractalMemory {
+    pub fn new() -> Self {
+        Self { saved: HashMap::new() }
+    }
+
+    /// Remember the current fractal and view, then return what was last used for the fractal called `name`.
+    /// Box<dyn Fractal> that haven't been used yet start from their profile.
+    pub fn switch(&mut self, current: &dyn Fractal, view: View, name: &str, profiles: &Profiles) -> (Box<dyn Fractal>, View) {
+        self.saved.insert(current.name(), (current.clone_box(), view));
+        self.saved.get(name).cloned().unwrap_or_else(|| (profiles.create(name), profiles.view(name)))
+    }
+
+    /// The last parameters used for the fractal called `name`, without changing what is remembered
+    pub fn parameters(&self, name: &str, profiles: &Profiles) -> Box<dyn Fractal> {
+        self.saved.get(name).map(|(fractal, _)| fractal.clone()).unwrap_or_else(|| profiles.create(name))
+    }
+}
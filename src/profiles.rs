@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use fractal_viz_core::fractals::{self, Fractal};
+use fractal_viz_core::view::View;
+use serde::{Deserialize, Serialize};
+
+/// What a type of fractal starts with in place of its built in defaults
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Profile {
+    pub max_iterations: u32,
+    /// Only for the escape time fractals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escape_radius: Option<f64>,
+    pub palette: String,
+    pub view: View,
+}
+
+impl Profile {
+    /// A profile holding the fractal's current settings and the view
+    pub fn of(fractal: &mut dyn Fractal, view: View) -> Self {
+        Self {
+            max_iterations: fractal.max_iterations(),
+            escape_radius: fractal.parameter("Escape Radius"),
+            palette: fractal.colour_gradient_mut().clone(),
+            view,
+        }
+    }
+
+    fn apply(&self, fractal: &mut dyn Fractal) {
+        fractal.set_parameter("Max Iterations", self.max_iterations as f64);
+        if let Some(escape_radius) = self.escape_radius {
+            fractal.set_parameter("Escape Radius", escape_radius);
+        }
+        self.palette.clone_into(fractal.colour_gradient_mut());
+    }
+}
+
+/// The user's default profile for each type of fractal, by name.
+/// Fractals without one keep the defaults they're built with
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Profiles(BTreeMap<String, Profile>);
+
+impl Profiles {
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.0.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.0.get_mut(name)
+    }
+
+    pub fn set(&mut self, name: &str, profile: Profile) {
+        self.0.insert(name.to_string(), profile);
+    }
+
+    /// Go back to the built in defaults for the fractal called `name`
+    pub fn remove(&mut self, name: &str) {
+        self.0.remove(name);
+    }
+
+    /// A new fractal called `name` set up with its profile
+    pub fn create(&self, name: &str) -> Box<dyn Fractal> {
+        let mut fractal = fractals::create(name);
+        if let Some(profile) = self.get(name) {
+            profile.apply(fractal.as_mut());
+        }
+        fractal
+    }
+
+    /// The fractal's default parameters with its profile put over them
+    pub fn reset(&self, fractal: &dyn Fractal) -> Box<dyn Fractal> {
+        let mut fractal = fractal.default_parameters();
+        if let Some(profile) = self.get(fractal.name()) {
+            profile.apply(fractal.as_mut());
+        }
+        fractal
+    }
+
+    /// The view the fractal called `name` starts at
+    pub fn view(&self, name: &str) -> View {
+        self.get(name).map_or_else(View::default, |profile| profile.view)
+    }
+}
@@ -0,0 +1,343 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use fractal_viz_core::fractals::{string_to_colour_gradient, Fractal};
+use fractal_viz_core::transfer::Levels;
+use fractal_viz_core::viewport::Viewport;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use web_time::{Duration, Instant};
+
+use crate::export::timestamped_dir;
+use crate::renderer::Colouring;
+use crate::schedule::time_left;
+
+/// Rows of the image drawn between progress updates
+const BAND_ROWS: i32 = 16;
+/// Side of the squares adaptive sampling decides on, in pixels
+const TILE: i32 = 16;
+/// Tiles drawn again between progress updates
+const TILE_GROUP: usize = 64;
+
+/// How much work goes into each pixel
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Quality {
+    /// Samples along each side of a pixel that are averaged together, 2 is 4x supersampling
+    pub supersampling: u32,
+    /// The fractal's max iterations are multiplied by this
+    pub iteration_factor: u32,
+    /// Colour by the continuous iteration count so there are no bands between counts
+    pub smooth: bool,
+    /// Draw with one sample per pixel first and only supersample the tiles whose colours vary more than the tolerance
+    pub adaptive: bool,
+    /// Mean difference between neighbouring pixels, out of 255, a tile can have and still be left at one sample per pixel
+    pub tolerance: f32,
+}
+
+impl Default for Quality {
+    fn default() -> Self {
+        Self { supersampling: 1, iteration_factor: 1, smooth: false, adaptive: false, tolerance: 3.0 }
+    }
+}
+
+impl Quality {
+    /// True if this draws the fractal just as its settings say, so the usual renderers can be used
+    pub fn is_plain(&self) -> bool {
+        self.supersampling <= 1 && self.iteration_factor <= 1 && !self.smooth
+    }
+}
+
+/// Exploring is kept fast while final renders, started with a key, take their time to look their best
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QualityProfiles {
+    pub exploring: Quality,
+    pub final_render: Quality,
+    /// Draw the pixels at max iterations on the boundary again with more iterations after a frame is generated
+    pub refine_boundary: bool,
+    pub time_lapse: TimeLapse,
+}
+
+/// Save final renders as numbered pngs while they're drawn, so long renders can be watched filling in as a time-lapse
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeLapse {
+    pub enabled: bool,
+    /// Seconds between frames
+    pub interval: f32,
+}
+
+impl Default for TimeLapse {
+    fn default() -> Self {
+        Self { enabled: false, interval: 2.0 }
+    }
+}
+
+impl TimeLapse {
+    /// Time between frames if time-lapses are being saved
+    pub fn interval(&self) -> Option<Duration> {
+        self.enabled.then(|| Duration::from_secs_f32(self.interval.max(0.1)))
+    }
+}
+
+impl Default for QualityProfiles {
+    fn default() -> Self {
+        Self { exploring: Quality::default(), final_render: Quality { supersampling: 2, iteration_factor: 4, smooth: true, ..Quality::default() }, refine_boundary: true, time_lapse: TimeLapse::default() }
+    }
+}
+
+/// Draw the fractal at the quality into `pixels`, which is the size of the viewport, with the palette stretched across `levels`.
+/// `progress` gets the fraction of rows done and the image so far after each band, returning false stops the drawing and this returns false
+pub fn draw(fractal: &dyn Fractal, pixels: &mut [u8], viewport: Viewport, quality: Quality, levels: Levels, mut progress: impl FnMut(f32, &[u8]) -> bool) -> bool {
+    if quality.adaptive && quality.supersampling > 1 {
+        draw_adaptive(fractal, pixels, viewport, quality, levels, &mut progress)
+    } else {
+        draw_supersampled(fractal, pixels, viewport, quality, levels, &mut progress)
+    }
+}
+
+/// Draw every pixel with all of the quality's samples
+fn draw_supersampled(fractal: &dyn Fractal, pixels: &mut [u8], viewport: Viewport, quality: Quality, levels: Levels, progress: &mut dyn FnMut(f32, &[u8]) -> bool) -> bool {
+    let mut fractal = fractal.clone_box();
+    if quality.iteration_factor > 1 {
+        if let Some(max_iterations) = fractal.parameter("Max Iterations") {
+            fractal.set_parameter("Max Iterations", max_iterations * quality.iteration_factor as f64);
+        }
+    }
+    // other colourings don't come from the iteration count
+    let smooth = quality.smooth && Colouring::of(fractal.as_ref()) == Colouring::Escape;
+    let gradient = string_to_colour_gradient(fractal.colour_gradient_mut());
+    let max_iterations = fractal.max_iterations() as f64;
+
+    let scale = quality.supersampling.max(1) as i32;
+    let (width, height) = (viewport.width, viewport.height);
+    // the samples of a pixel are spread around its centre rather than starting at its corner
+    let samples = viewport.resized(width * scale, height * scale);
+    let samples = samples.moved(samples.delta_to_complex(-(scale as f64 - 1.0) / 2.0, -(scale as f64 - 1.0) / 2.0));
+
+    let row_len = width as usize * 4;
+    let scale = scale as usize;
+    for start in (0..height).step_by(BAND_ROWS as usize) {
+        let rows = BAND_ROWS.min(height - start);
+        let band = samples.rows(start * scale as i32, rows * scale as i32);
+        let band_width = band.width as usize;
+        let mut sampled = vec![0; band_width * band.height as usize * 4];
+        if smooth {
+            let mut values = vec![0.0; band_width * band.height as usize];
+            fractal.draw_values(&mut values, band);
+            sampled.par_chunks_mut(4).zip(values.par_iter()).for_each(|(pixel, value)| {
+                pixel.copy_from_slice(&gradient.at(levels.stretch(value / max_iterations)).to_rgba8());
+            });
+        } else {
+            fractal.draw_levels(&mut sampled, band, levels);
+        }
+
+        // average each block of samples into one pixel
+        let output = &mut pixels[start as usize * row_len..(start + rows) as usize * row_len];
+        output.par_chunks_mut(row_len).enumerate().for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_mut(4).enumerate() {
+                for (channel, value) in pixel.iter_mut().enumerate() {
+                    let mut sum = 0;
+                    for sample_y in y * scale..(y + 1) * scale {
+                        for sample_x in x * scale..(x + 1) * scale {
+                            sum += sampled[(sample_y * band_width + sample_x) * 4 + channel] as usize;
+                        }
+                    }
+                    *value = ((sum + scale * scale / 2) / (scale * scale)) as u8;
+                }
+            }
+        });
+        if !progress((start + rows) as f32 / height as f32, pixels) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Draw with one sample per pixel, then draw the tiles that vary more than the quality's tolerance again with all of its samples.
+/// The interior and the far outside take up most of a big image and look the same either way, so the samples go where the detail is
+fn draw_adaptive(fractal: &dyn Fractal, pixels: &mut [u8], viewport: Viewport, quality: Quality, levels: Levels, progress: &mut dyn FnMut(f32, &[u8]) -> bool) -> bool {
+    // the first pass is one sample out of the supersampling's for each pixel
+    let first_share = 1.0 / (quality.supersampling * quality.supersampling) as f32;
+    let single = Quality { supersampling: 1, adaptive: false, ..quality };
+    if !draw_supersampled(fractal, pixels, viewport, single, levels, &mut |fraction, image| progress(fraction * first_share, image)) {
+        return false;
+    }
+
+    let (width, height) = (viewport.width, viewport.height);
+    let tiles: Vec<(i32, i32)> = (0..height).step_by(TILE as usize)
+        .flat_map(|y| (0..width).step_by(TILE as usize).map(move |x| (x, y)))
+        .collect();
+    let busy: Vec<(i32, i32)> = tiles.par_iter().copied()
+        .filter(|&tile| variation(pixels, width, height, tile) > quality.tolerance)
+        .collect();
+    tracing::debug!(tiles = tiles.len(), supersampled = busy.len(), "Adaptive sampling");
+
+    let full = Quality { adaptive: false, ..quality };
+    let row_len = width as usize * 4;
+    for (group, tiles) in busy.chunks(TILE_GROUP).enumerate() {
+        let drawn: Vec<(i32, i32, i32, Vec<u8>)> = tiles.par_iter().map(|&(x, y)| {
+            let (tile_width, tile_height) = (TILE.min(width - x), TILE.min(height - y));
+            let mut tile = vec![0; (tile_width * tile_height * 4) as usize];
+            draw_supersampled(fractal, &mut tile, viewport.region(x, y, tile_width, tile_height), full, levels, &mut |_, _| true);
+            (x, y, tile_width, tile)
+        }).collect();
+        for (x, y, tile_width, tile) in drawn {
+            let tile_row = tile_width as usize * 4;
+            for (i, row) in tile.chunks(tile_row).enumerate() {
+                let start = (y as usize + i) * row_len + x as usize * 4;
+                pixels[start..start + tile_row].copy_from_slice(row);
+            }
+        }
+        let done = ((group + 1) * TILE_GROUP).min(busy.len()) as f32 / busy.len() as f32;
+        if !progress(first_share + (1.0 - first_share) * done, pixels) {
+            return false;
+        }
+    }
+    true
+}
+
+/// How much the colours of the tile with its top left corner at `(x, y)` vary, as the mean difference between neighbouring pixels.
+/// Edges and noise that supersampling smooths out vary a lot, flat areas and smooth gradients hardly at all
+fn variation(pixels: &[u8], width: i32, height: i32, (x, y): (i32, i32)) -> f32 {
+    let at = |x: i32, y: i32| (y as usize * width as usize + x as usize) * 4;
+    let (mut sum, mut count) = (0, 0);
+    for py in y..(y + TILE).min(height) {
+        for px in x..(x + TILE).min(width) {
+            // the pixels just past the right and bottom of the tile count too, so an edge along its side isn't missed
+            for (nx, ny) in [(px + 1, py), (px, py + 1)] {
+                if nx < width && ny < height {
+                    let (a, b) = (at(px, py), at(nx, ny));
+                    sum += (0..3).map(|c| pixels[a + c].abs_diff(pixels[b + c]) as u32).sum::<u32>();
+                    count += 3;
+                }
+            }
+        }
+    }
+    sum as f32 / count.max(1) as f32
+}
+
+enum FinalProgress {
+    Rows(f32),
+    /// The folder the time-lapse went into and how many frames it has, sent once the render is finished or the frames can't be saved
+    TimeLapse(Result<(PathBuf, usize), String>),
+    Done(Vec<u8>),
+}
+
+/// Numbered pngs of a render saved as it's drawn
+struct TimeLapseFrames {
+    dir: PathBuf,
+    interval: Duration,
+    last: Instant,
+    count: usize,
+}
+
+impl TimeLapseFrames {
+    fn new(interval: Duration) -> Self {
+        Self { dir: timestamped_dir("timelapse"), interval, last: Instant::now(), count: 0 }
+    }
+
+    /// Save the image as the next frame if the interval has passed since the last one, or straight away if `now`
+    fn capture(&mut self, image: &[u8], (width, height): (i32, i32), now: bool) -> Result<(), String> {
+        if !now && self.last.elapsed() < self.interval {
+            return Ok(());
+        }
+        if self.count == 0 {
+            std::fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        }
+        self.count += 1;
+        let path = self.dir.join(format!("frame_{:04}.png", self.count));
+        image::save_buffer(path, image, width as u32, height as u32, image::ColorType::Rgba8).map_err(|e| e.to_string())?;
+        self.last = Instant::now();
+        Ok(())
+    }
+}
+
+/// Draws the current view, or a rectangle of it, at the final quality on a background thread.
+/// Dropping it stops the thread, it is dropped whenever the view changes
+pub struct FinalRender {
+    receiver: mpsc::Receiver<FinalProgress>,
+    /// Fraction of the rows drawn so far
+    pub progress: f32,
+    started: Instant,
+    /// The part of the frame being drawn as (x, y, width, height) in pixels
+    region: (i32, i32, i32, i32),
+    /// Size of the frame the render is composited into
+    frame_size: (i32, i32),
+    /// Where the time-lapse was saved, once the render is finished or saving it failed
+    pub time_lapse: Option<Result<(PathBuf, usize), String>>,
+}
+
+impl FinalRender {
+    /// With a `time_lapse` interval the image is also saved that often while it's drawn
+    pub fn start(fractal: Box<dyn Fractal>, viewport: Viewport, quality: Quality, levels: Levels, time_lapse: Option<Duration>) -> Self {
+        Self::start_region(fractal, viewport, quality, levels, (0, 0, viewport.width, viewport.height), time_lapse)
+    }
+
+    /// Draw just a rectangle of the frame, given as (x, y, width, height) in pixels of the viewport,
+    /// for when only a small area is lacking detail
+    pub fn start_region(fractal: Box<dyn Fractal>, viewport: Viewport, quality: Quality, levels: Levels, region: (i32, i32, i32, i32), time_lapse: Option<Duration>) -> Self {
+        let (x, y, width, height) = region;
+        let region_viewport = viewport.region(x, y, width, height);
+        let (sender, receiver) = mpsc::channel();
+        crate::background(move || {
+            let mut frame = vec![0; (width * height * 4) as usize];
+            let mut frames = time_lapse.map(TimeLapseFrames::new);
+            let finished = draw(fractal.as_ref(), &mut frame, region_viewport, quality, levels, |fraction, image| {
+                // a failed frame stops the time-lapse but not the render
+                if let Some(Err(e)) = frames.as_mut().map(|frames| frames.capture(image, (width, height), false)) {
+                    let _ = sender.send(FinalProgress::TimeLapse(Err(e)));
+                    frames = None;
+                }
+                sender.send(FinalProgress::Rows(fraction)).is_ok()
+            });
+            if !finished {
+                return;
+            }
+            if let Some(mut frames) = frames {
+                let result = frames.capture(&frame, (width, height), true).map(|_| (frames.dir, frames.count));
+                let _ = sender.send(FinalProgress::TimeLapse(result));
+            }
+            let _ = sender.send(FinalProgress::Done(frame));
+        });
+        Self { receiver, progress: 0.0, started: Instant::now(), region, frame_size: (viewport.width, viewport.height), time_lapse: None }
+    }
+
+    /// How long the rest of the render is likely to take going by how fast the bands have been drawn so far
+    pub fn time_left(&self) -> Option<Duration> {
+        time_left(self.started, self.progress)
+    }
+
+    /// True if only part of the frame is being drawn
+    pub fn is_region(&self) -> bool {
+        self.region != (0, 0, self.frame_size.0, self.frame_size.1)
+    }
+
+    /// Copy a finished render into its place in `frame`, returning false if the frame is no longer the size it was started for
+    pub fn composite(&self, render: &[u8], frame: &mut [u8]) -> bool {
+        let (frame_width, frame_height) = self.frame_size;
+        if frame.len() != (frame_width * frame_height * 4) as usize {
+            return false;
+        }
+        let (x, y, width, _) = self.region;
+        let row_len = width as usize * 4;
+        for (i, row) in render.chunks(row_len).enumerate() {
+            let start = ((y as usize + i) * frame_width as usize + x as usize) * 4;
+            frame[start..start + row_len].copy_from_slice(row);
+        }
+        true
+    }
+
+    /// Update the progress, returning the frame once it has been drawn
+    pub fn poll(&mut self) -> Option<Vec<u8>> {
+        for progress in self.receiver.try_iter() {
+            match progress {
+                FinalProgress::Rows(fraction) => self.progress = fraction,
+                FinalProgress::TimeLapse(result) => self.time_lapse = Some(result),
+                FinalProgress::Done(frame) => return Some(frame),
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,159 @@
+use std::f64::consts::TAU;
+
+use num::complex::Complex64;
+
+/// Rays start this far out, where the Mandelbrot set's exterior is almost a circle
+const RAY_ESCAPE_RADIUS: f64 = 65536.0;
+/// Newton steps taken each time the ray's depth goes up, more is slower but follows the ray more closely
+const SHARPNESS: u32 = 4;
+/// How many times the angle is doubled before the ray stops
+const MAX_DEPTH: u32 = 40;
+const NEWTON_STEPS: usize = 64;
+/// Points on each equipotential curve
+const EQUIPOTENTIAL_SAMPLES: u64 = 512;
+
+/// An angle in turns, kept as a fraction so it can be doubled exactly
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Angle {
+    numerator: u64,
+    denominator: u64,
+}
+
+impl Angle {
+    /// Read a fraction like 1/3 or a decimal like 0.25, angles are wrapped into a single turn
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (numerator, denominator) = match text.split_once('/') {
+            Some((numerator, denominator)) => (numerator.trim().parse().ok()?, denominator.trim().parse().ok()?),
+            None => {
+                let turns: f64 = text.parse().ok()?;
+                if !turns.is_finite() || turns < 0.0 {
+                    return None;
+                }
+                // as a binary fraction, which is exact for any decimal f64 holds
+                let denominator = 1u64 << 32;
+                ((turns.fract() * denominator as f64).round() as u64, denominator)
+            }
+        };
+        (denominator > 0).then_some(Self { numerator: numerator % denominator, denominator })
+    }
+
+    fn turns(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// z -> z^2 doubles the angle of the ray
+    fn double(&mut self) {
+        self.numerator = (2 * self.numerator as u128 % self.denominator as u128) as u64;
+    }
+}
+
+/// Read a comma separated list of angles, returning the first one that couldn't be read on failure
+pub fn parse_angles(text: &str) -> Result<Vec<Angle>, String> {
+    text.split(',')
+        .filter(|angle| !angle.trim().is_empty())
+        .map(|angle| Angle::parse(angle).ok_or_else(|| angle.trim().to_string()))
+        .collect()
+}
+
+/// External rays and equipotential curves of the Mandelbrot set as lines in the complex plane
+pub struct RayOverlay {
+    pub rays: Vec<Vec<Complex64>>,
+    pub equipotentials: Vec<Vec<Complex64>>,
+}
+
+impl RayOverlay {
+    /// Equipotentials are spaced so each one is roughly where the escape time goes up by one with an escape radius of 2
+    pub fn compute(angles: &[Angle], equipotentials: u32) -> Self {
+        let rays = angles.iter().map(|angle| {
+            let mut ray = Ray::new(*angle);
+            std::iter::once(ray.c).chain((0..MAX_DEPTH * SHARPNESS).map(|_| ray.step())).collect()
+        }).collect();
+
+        let levels: Vec<f64> = (0..equipotentials).map(|k| 2f64.ln() / 2f64.powi(k as i32)).collect();
+        let mut curves = vec![Vec::with_capacity(EQUIPOTENTIAL_SAMPLES as usize + 1); levels.len()];
+        for i in 0..EQUIPOTENTIAL_SAMPLES {
+            // each sample follows a ray in and records where it crosses each level
+            let mut ray = Ray::new(Angle { numerator: i, denominator: EQUIPOTENTIAL_SAMPLES });
+            for (curve, level) in curves.iter_mut().zip(&levels) {
+                while ray.next_log_radius() > *level {
+                    ray.step();
+                }
+                curve.push(ray.solve_at(*level));
+            }
+        }
+        // close the loops
+        for curve in curves.iter_mut() {
+            if let Some(first) = curve.first() {
+                curve.push(*first);
+            }
+        }
+        Self { rays, equipotentials: curves }
+    }
+}
+
+/// Follows an external ray inwards with Newton's method.
+/// At depth n the point c solves z_n(c) = r e^(2πi 2^n θ) for a shrinking radius r,
+/// once r has halved in log scale the depth goes up and the angle doubles.
+struct Ray {
+    angle: Angle,
+    c: Complex64,
+    depth: u32,
+    step: u32,
+}
+
+impl Ray {
+    fn new(angle: Angle) -> Self {
+        Self { angle, c: Complex64::from_polar(RAY_ESCAPE_RADIUS, TAU * angle.turns()), depth: 0, step: 0 }
+    }
+
+    /// Log of |z_depth| the next step aims for
+    fn next_log_target(&self) -> f64 {
+        RAY_ESCAPE_RADIUS.ln() * 0.5f64.powf((self.step as f64 + 0.5) / SHARPNESS as f64)
+    }
+
+    /// Log of the potential radius the next step moves to, comparable across depths
+    fn next_log_radius(&self) -> f64 {
+        self.next_log_target() / 2f64.powi(self.depth as i32)
+    }
+
+    fn step(&mut self) -> Complex64 {
+        let target = Complex64::from_polar(self.next_log_target().exp(), TAU * self.angle.turns());
+        self.c = newton(self.c, self.depth, target);
+        self.step += 1;
+        if self.step == SHARPNESS {
+            self.step = 0;
+            self.depth += 1;
+            self.angle.double();
+        }
+        self.c
+    }
+
+    /// The point on the ray at a potential radius between the current point and the next step
+    fn solve_at(&self, log_radius: f64) -> Complex64 {
+        let target = Complex64::from_polar((log_radius * 2f64.powi(self.depth as i32)).exp(), TAU * self.angle.turns());
+        newton(self.c, self.depth, target)
+    }
+}
+
+/// Solve z_n(c) = target for c starting from a nearby guess
+fn newton(mut c: Complex64, n: u32, target: Complex64) -> Complex64 {
+    for _ in 0..NEWTON_STEPS {
+        let mut z = Complex64::new(0.0, 0.0);
+        let mut dc = Complex64::new(0.0, 0.0);
+        for _ in 0..=n {
+            dc = 2.0 * z * dc + 1.0;
+            z = z * z + c;
+        }
+        let next = c - (z - target) / dc;
+        if !next.is_finite() {
+            break;
+        }
+        let converged = (next - c).norm_sqr() <= c.norm_sqr() * 1e-30;
+        c = next;
+        if converged {
+            break;
+        }
+    }
+    c
+}
@@ -0,0 +1,93 @@
+use std::sync::mpsc;
+
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::transfer::Levels;
+use fractal_viz_core::viewport::Viewport;
+use rayon::prelude::*;
+
+use crate::renderer::Colouring;
+
+/// The max iterations the boundary is drawn again with, as a multiple of the fractal's
+const REFINE_FACTOR: f64 = 8.0;
+/// Share of the pixels at max iterations that have to border escaped pixels before the boundary is drawn again.
+/// Below this the cap is high enough and the few pixels on the edge aren't worth it
+const REFINE_THRESHOLD: f32 = 0.05;
+
+/// Draws the pixels that reached max iterations next to ones that escaped again with a much higher cap,
+/// on a background thread once the iteration counts of the frame are known.
+/// The boundary comes out crisp without making the rest of the frame slower. Dropping it stops the thread
+pub struct Refinement {
+    receiver: mpsc::Receiver<Vec<(usize, [u8; 4])>>,
+    /// Pixels in the frame the refinement was started for
+    frame_pixels: usize,
+}
+
+impl Refinement {
+    /// True for fractals whose boundary is drawn differently with more iterations.
+    /// Escape time colouring gives points at the cap the gradient's last colour, the same as the latest points to escape,
+    /// so there's nothing to sharpen
+    pub fn applies(fractal: &dyn Fractal) -> bool {
+        matches!(Colouring::of(fractal), Colouring::Period | Colouring::AtomDomains | Colouring::Roots)
+            && fractal.parameter("Max Iterations").is_some()
+    }
+
+    /// Start drawing the boundary of a frame again from the iteration count of each of its pixels
+    pub fn start(fractal: Box<dyn Fractal>, viewport: Viewport, levels: Levels, counts: Vec<u32>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let frame_pixels = counts.len();
+        crate::background(move || {
+            let _ = sender.send(refine(fractal, viewport, levels, &counts));
+        });
+        Self { receiver, frame_pixels }
+    }
+
+    /// The pixels drawn again with their new colours once they're done, empty if the boundary didn't need it
+    pub fn poll(&self) -> Option<Vec<(usize, [u8; 4])>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Copy the pixels drawn again into `frame`, returning false if it is no longer the size the refinement was started for
+    pub fn merge(&self, pixels: &[(usize, [u8; 4])], frame: &mut [u8]) -> bool {
+        if frame.len() != self.frame_pixels * 4 {
+            return false;
+        }
+        for (index, colour) in pixels {
+            frame[index * 4..index * 4 + 4].copy_from_slice(colour);
+        }
+        true
+    }
+}
+
+/// The pixels at the cap next to escaped pixels, drawn again with the higher cap
+fn refine(mut fractal: Box<dyn Fractal>, viewport: Viewport, levels: Levels, counts: &[u32]) -> Vec<(usize, [u8; 4])> {
+    let (width, height) = (viewport.width as usize, viewport.height as usize);
+    let max_iterations = fractal.max_iterations();
+    let at_cap = |x: usize, y: usize| counts[y * width + x] >= max_iterations;
+    let boundary: Vec<usize> = (0..counts.len()).into_par_iter().filter(|&i| {
+        let (x, y) = (i % width, i / width);
+        at_cap(x, y)
+            && ((x > 0 && !at_cap(x - 1, y)) || (x + 1 < width && !at_cap(x + 1, y))
+                || (y > 0 && !at_cap(x, y - 1)) || (y + 1 < height && !at_cap(x, y + 1)))
+    }).collect();
+    let capped = counts.par_iter().filter(|&&count| count >= max_iterations).count();
+    if boundary.is_empty() || (boundary.len() as f32) < capped as f32 * REFINE_THRESHOLD {
+        return Vec::new();
+    }
+
+    fractal.set_parameter("Max Iterations", max_iterations as f64 * REFINE_FACTOR);
+    // neighbouring pixels along a row are drawn together, so each draw isn't just one pixel
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for &i in &boundary {
+        match runs.last_mut() {
+            Some((start, len)) if *start + *len == i && i % width != 0 => *len += 1,
+            _ => runs.push((i, 1)),
+        }
+    }
+    runs.par_iter().flat_map_iter(|&(start, len)| {
+        let mut run = vec![0; len * 4];
+        fractal.draw_levels(&mut run, viewport.region((start % width) as i32, (start / width) as i32, len as i32, 1), levels);
+        run.chunks_exact(4).enumerate()
+            .map(|(offset, colour)| (start + offset, [colour[0], colour[1], colour[2], colour[3]]))
+            .collect::<Vec<_>>()
+    }).collect()
+}
@@ -0,0 +1,351 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use fractal_viz_core::fractals::{self, Fractal};
+use fractal_viz_core::view::View;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use winit::event_loop::EventLoopProxy;
+
+use crate::bookmarks::Bookmarks;
+
+/// Port the server listens on when `--remote` isn't given one
+const DEFAULT_PORT: u16 = 7878;
+/// Largest width or height a client can render, as big as the gallery export allows
+const MAX_RENDER_SIZE: u32 = 16384;
+
+/// Something a remote client asked for, sent as json with the name of the command in a "command" field
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Command {
+    /// The fractal, its settings and the view
+    State,
+    /// Switch to another type of fractal, keeping the colours
+    Fractal { name: String },
+    /// Set one of the fractal's parameters, like "Max Iterations"
+    Set { name: String, value: f64 },
+    Colour { name: String },
+    /// Move the view, anything left out stays the same
+    View { centre: Option<(f64, f64)>, zoom: Option<f64>, rotation: Option<f64> },
+    /// Zoom in by a factor, 2 shows half the width
+    Zoom { factor: f64 },
+    /// Move by a fraction of the window, relative to the screen so it follows the rotation
+    Pan { x: f64, y: f64 },
+    Rotate { degrees: f64 },
+    GoTo { bookmark: String },
+    /// Back to the default view
+    Reset,
+    /// Save a png of the view, the size of the window unless given. The path is a file name in the current directory
+    Render { width: Option<u32>, height: Option<u32>, path: Option<PathBuf> },
+}
+
+/// A command waiting to be carried out by the app, the client gets the result back as json
+pub struct Request {
+    command: Command,
+    reply: mpsc::Sender<Result<Value, String>>,
+}
+
+impl Request {
+    /// Carry out the command on the current fractal and view, returns true if the fractal has to be drawn again.
+    /// Renders run in the background and reply when they're saved.
+    pub fn handle(self, fractal: &mut Box<dyn Fractal>, view: &mut View, bookmarks: &Bookmarks, window: (u32, u32)) -> bool {
+        tracing::debug!(command = ?self.command, "Remote command");
+        let (result, changed) = match self.command {
+            Command::State => (Ok(state(fractal.as_ref(), view)), false),
+            Command::Fractal { name } => {
+                if fractals::names().contains(&name.as_str()) {
+                    let mut new_fractal = fractals::create(&name);
+                    *new_fractal.colour_gradient_mut() = fractal.colour_gradient_mut().clone();
+                    (*fractal, *view) = (new_fractal, View::default());
+                    (Ok(state(fractal.as_ref(), view)), true)
+                } else {
+                    (Err(format!("no fractal called \"{}\"", name)), false)
+                }
+            }
+            Command::Set { name, value } => match fractal.parameter(&name).map(|_| fractals::check_parameter(&name, value)) {
+                Some(Ok(())) => {
+                    fractal.set_parameter(&name, value);
+                    (Ok(state(fractal.as_ref(), view)), true)
+                }
+                Some(Err(e)) => (Err(e), false),
+                None => (Err(format!("the fractal has no parameter \"{}\"", name)), false),
+            },
+            Command::Colour { name } => {
+                *fractal.colour_gradient_mut() = name;
+                (Ok(state(fractal.as_ref(), view)), true)
+            }
+            Command::View { centre, zoom, rotation } if zoom.is_some_and(|zoom| !(zoom > 0.0 && zoom.is_finite()))
+                || centre.is_some_and(|(x, y)| !(x.is_finite() && y.is_finite()))
+                || rotation.is_some_and(|rotation| !rotation.is_finite()) => {
+                (Err("the zoom has to be above 0, and the centre, zoom and rotation finite".into()), false)
+            }
+            Command::View { centre, zoom, rotation } => {
+                if let Some(centre) = centre {
+                    view.set_centre(centre);
+                }
+                view.zoom = zoom.unwrap_or(view.zoom);
+                view.rotation = rotation.map_or(view.rotation, crate::wrap_degrees);
+                (Ok(state(fractal.as_ref(), view)), true)
+            }
+            Command::Zoom { factor } if factor > 0.0 && (view.zoom / factor).is_finite() && view.zoom / factor > 0.0 => {
+                view.zoom /= factor;
+                (Ok(state(fractal.as_ref(), view)), true)
+            }
+            Command::Zoom { factor } => (Err(format!("can't zoom by {}", factor)), false),
+            Command::Pan { x, y } if x.is_finite() && y.is_finite() => {
+                let (dx, dy) = view.viewport(window.0, window.1).delta_to_complex(x * window.0 as f64, y * window.1 as f64);
+                view.pan(dx, dy);
+                (Ok(state(fractal.as_ref(), view)), true)
+            }
+            Command::Pan { x, y } => (Err(format!("can't pan by {}, {}", x, y)), false),
+            Command::Rotate { degrees } if degrees.is_finite() => {
+                view.rotation = crate::wrap_degrees(view.rotation + degrees);
+                (Ok(state(fractal.as_ref(), view)), true)
+            }
+            Command::Rotate { degrees } => (Err(format!("can't rotate by {}", degrees)), false),
+            Command::GoTo { bookmark } => match bookmarks.list.iter().find(|b| b.name == bookmark) {
+                Some(bookmark) => {
+                    (*fractal, *view) = (bookmark.fractal.clone(), bookmark.view);
+                    (Ok(state(fractal.as_ref(), view)), true)
+                }
+                None => (Err(format!("no bookmark called \"{}\"", bookmark)), false),
+            },
+            Command::Reset => {
+                *view = View::default();
+                (Ok(state(fractal.as_ref(), view)), true)
+            }
+            Command::Render { width, height, path } => {
+                let size = (width.unwrap_or(window.0), height.unwrap_or(window.1));
+                let path = path.map_or_else(|| Ok(crate::export::timestamped_path("fractal", "png")), export_path);
+                match path {
+                    _ if !(1..=MAX_RENDER_SIZE).contains(&size.0) || !(1..=MAX_RENDER_SIZE).contains(&size.1) => {
+                        (Err(format!("can't render an image {} by {}, the most is {} on a side", size.0, size.1, MAX_RENDER_SIZE)), false)
+                    }
+                    Err(e) => (Err(e), false),
+                    Ok(path) => {
+                        let (fractal, view, reply) = (fractal.clone(), *view, self.reply);
+                        crate::background(move || {
+                            let result = crate::export::render_image(fractal.as_ref(), view, window.0, size, &path)
+                                .map(|_| json!({ "path": path }))
+                                .map_err(|e| e.to_string());
+                            let _ = reply.send(result);
+                        });
+                        return false;
+                    }
+                }
+            }
+        };
+        // the client may have disconnected while waiting
+        let _ = self.reply.send(result);
+        changed
+    }
+}
+
+/// Only let clients save pngs next to the app's other exports, not over any file the user can write
+fn export_path(path: PathBuf) -> Result<PathBuf, String> {
+    let file_name = path.file_name().filter(|name| Path::new(name) == path);
+    let png = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+    match file_name {
+        Some(_) if png => Ok(path),
+        _ => Err(format!("\"{}\" isn't the file name of a png, renders are saved in the current directory", path.display())),
+    }
+}
+
+/// What the client sees of the app
+fn state(fractal: &dyn Fractal, view: &View) -> Value {
+    let parameters: serde_json::Map<String, Value> = fractal.animatable_parameters().iter()
+        .filter_map(|name| Some((name.to_string(), fractal.parameter(name)?.into())))
+        .collect();
+    json!({
+        "fractal": fractal.name(),
+        "settings": fractal.to_json(),
+        "parameters": parameters,
+        "view": view,
+    })
+}
+
+/// Start the server if the app was run with `--remote` or `--remote=PORT`
+pub fn from_args(wake: EventLoopProxy<()>) -> Option<Result<Server, String>> {
+    let arg = std::env::args().skip(1).find(|arg| arg == "--remote" || arg.starts_with("--remote="))?;
+    let port = match arg.strip_prefix("--remote=") {
+        Some(port) => match port.parse() {
+            Ok(port) => port,
+            Err(_) => return Some(Err(format!("\"{}\" isn't a port number", port))),
+        },
+        None => DEFAULT_PORT,
+    };
+    Some(Server::start(port, wake))
+}
+
+/// Takes commands over HTTP and WebSocket connections on localhost, they're carried out between frames
+pub struct Server {
+    receiver: mpsc::Receiver<Request>,
+}
+
+impl Server {
+    /// `wake` gets the event loop going when a command arrives while the app is idle
+    pub fn start(port: u16, wake: EventLoopProxy<()>) -> Result<Self, String> {
+        let (sender, receiver) = mpsc::channel();
+        let token = server::listen(port, sender, wake)?;
+        // on stderr and in the debug panel's log, which is shown by default
+        tracing::info!(port, token, "Remote control listening");
+        Ok(Self { receiver })
+    }
+
+    /// Commands received since the last poll
+    pub fn poll(&self) -> Vec<Request> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+#[cfg(feature = "remote")]
+mod server {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    use std::io::{Read, Write};
+    use std::sync::{mpsc, Arc};
+
+    use serde_json::{json, Value};
+    use tiny_http::{Header, Method, Response};
+    use tungstenite::handshake::derive_accept_key;
+    use tungstenite::protocol::Role;
+    use tungstenite::{Message, WebSocket};
+    use winit::event_loop::EventLoopProxy;
+
+    use super::{Command, Request};
+
+    /// Passes commands from the connection threads to the app
+    #[derive(Clone)]
+    struct RequestSender {
+        sender: mpsc::Sender<Request>,
+        wake: EventLoopProxy<()>,
+    }
+
+    impl RequestSender {
+        fn send(&self, request: Request) -> Result<(), String> {
+            self.sender.send(request).map_err(|_| "the app is closing".to_string())?;
+            // if the event loop has gone the app is closing, the reply channel says so
+            let _ = self.wake.send_event(());
+            Ok(())
+        }
+    }
+
+    /// Accept connections on a thread of their own, each connection is handled on its own thread
+    /// so a slow render doesn't hold up other clients. Returns the token clients have to send.
+    pub fn listen(port: u16, sender: mpsc::Sender<Request>, wake: EventLoopProxy<()>) -> Result<String, String> {
+        // only on localhost, and only for clients that were given the token since anyone who can connect can write files
+        let server = tiny_http::Server::http(("127.0.0.1", port)).map_err(|e| e.to_string())?;
+        let sender = RequestSender { sender, wake };
+        let token: Arc<str> = new_token().into();
+        let shared = token.clone();
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let (sender, token) = (sender.clone(), shared.clone());
+                std::thread::spawn(move || handle(request, sender, &token));
+            }
+        });
+        Ok(token.to_string())
+    }
+
+    /// 128 random bits as hex, the standard library seeds its hash keys from the operating system
+    fn new_token() -> String {
+        (0..2).map(|_| format!("{:016x}", RandomState::new().build_hasher().finish())).collect()
+    }
+
+    /// Web pages can reach localhost too, browsers always say which page a request came from
+    /// so those are turned away, and everything else has to carry the token
+    fn refusal(request: &tiny_http::Request, query: &str, token: &str) -> Option<(u16, &'static str)> {
+        let header = |name: &'static str| request.headers().iter().find(|header| header.field.equiv(name)).map(|header| header.value.as_str());
+        if header("Origin").is_some() {
+            return Some((403, "requests from web pages aren't accepted"));
+        }
+        let given = header("Authorization").and_then(|value| value.strip_prefix("Bearer "))
+            .or_else(|| query.split('&').find_map(|pair| pair.strip_prefix("token=")));
+        (given != Some(token)).then_some((401, "send the token logged at startup as \"Authorization: Bearer TOKEN\" or ?token=TOKEN"))
+    }
+
+    fn handle(mut request: tiny_http::Request, sender: RequestSender, token: &str) {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        if let Some((status, error)) = refusal(&request, query, token) {
+            let _ = request.respond(json_response(reply(Err(error.into())), status));
+            return;
+        }
+        let websocket_key = request.headers().iter()
+            .find(|header| header.field.equiv("Sec-WebSocket-Key"))
+            .map(|header| derive_accept_key(header.value.as_bytes()));
+        let result = match (request.method(), path, websocket_key) {
+            (Method::Get, "/ws", Some(accept)) => {
+                let response = Response::empty(101)
+                    .with_header(Header::from_bytes("Sec-WebSocket-Accept", accept).expect("the accept key is ascii"));
+                let stream = request.upgrade("websocket", response);
+                websocket(WebSocket::from_raw_socket(stream, Role::Server, None), &sender);
+                return;
+            }
+            (Method::Get, "/state", _) => send(Command::State, &sender),
+            (Method::Post, "/", _) => {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body)
+                    .map_err(|e| e.to_string())
+                    .and_then(|_| parse(&body))
+                    .and_then(|command| send(command, &sender))
+            }
+            _ => Err("not found, POST a command to / or connect a WebSocket to /ws".into()),
+        };
+        let status = if result.is_ok() { 200 } else { 400 };
+        let _ = request.respond(json_response(reply(result), status));
+    }
+
+    fn json_response(value: Value, status: u16) -> Response<std::io::Cursor<Vec<u8>>> {
+        Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(Header::from_bytes("Content-Type", "application/json").expect("the header is ascii"))
+    }
+
+    /// Carry out each text message as a command, replying in the order they arrive
+    fn websocket<S: Read + Write>(mut socket: WebSocket<S>, sender: &RequestSender) {
+        while let Ok(message) = socket.read() {
+            let result = match message {
+                Message::Text(text) => parse(&text).and_then(|command| send(command, sender)),
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            if socket.send(Message::Text(reply(result).to_string())).is_err() {
+                break;
+            }
+        }
+    }
+
+    fn parse(text: &str) -> Result<Command, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Pass the command to the app and wait for it to be carried out
+    fn send(command: Command, sender: &RequestSender) -> Result<Value, String> {
+        let (reply, receiver) = mpsc::channel();
+        sender.send(Request { command, reply })?;
+        receiver.recv().map_err(|_| "the app is closing".to_string())?
+    }
+
+    fn reply(result: Result<Value, String>) -> Value {
+        match result {
+            Ok(value) => json!({ "ok": value }),
+            Err(e) => json!({ "error": e }),
+        }
+    }
+}
+
+#[cfg(not(feature = "remote"))]
+mod server {
+    use std::sync::mpsc;
+
+    use winit::event_loop::EventLoopProxy;
+
+    use super::Request;
+
+    /// Stand in for builds without the remote feature
+    pub fn listen(_port: u16, _sender: mpsc::Sender<Request>, _wake: EventLoopProxy<()>) -> Result<String, String> {
+        Err("Built without remote control support, rebuild with --features remote".into())
+    }
+}
+
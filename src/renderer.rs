@@ -0,0 +1,209 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+use fractal_viz_core::fractals::{Fractal, Julia, Mandelbrot};
+use fractal_viz_core::transfer::Levels;
+use fractal_viz_core::viewport::Viewport;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod gpu;
+
+pub const BACKENDS: [Backend; 3] = [Backend::Cpu, Backend::Gpu, Backend::Hybrid];
+
+/// Neighbouring pixels have to be at least this many float steps apart for the image not to go blocky
+const PRECISION_MARGIN: f64 = 16.0;
+
+/// Where a replacement for the GPU renderer's shader can be put, it is reloaded whenever it's saved
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shader_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("fractal-viz").join("escape_time.wgsl"))
+}
+
+/// Which renderer draws the fractal, picked in the settings
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Backend {
+    Cpu,
+    Gpu,
+    /// The GPU's preview drawn again on the CPU once it's past the GPU's precision
+    Hybrid,
+}
+
+impl Backend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Cpu => "CPU",
+            Backend::Gpu => "GPU",
+            Backend::Hybrid => "Hybrid",
+        }
+    }
+
+    /// What the backend draws itself, anything else is drawn on the CPU
+    pub fn description(&self) -> &'static str {
+        match self {
+            Backend::Cpu => "Draws every fractal and colouring in 64 bit floats, using every core",
+            Backend::Gpu => "Draws the Mandelbrot and Julia sets with escape time colouring in 32 bit floats. \
+                Other fractals and colourings, and zooms past the limit of 32 bit floats, are drawn on the CPU",
+            Backend::Hybrid => "Draws on the GPU while exploring, even past the limit of 32 bit floats where it goes blocky. \
+                Views past the limit are then drawn again on the CPU in the background and shown band by band as they finish",
+        }
+    }
+}
+
+/// How a fractal is coloured with its current settings
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Colouring {
+    /// By the number of iterations before escaping
+    Escape,
+    /// Interior points by the period of their cycle
+    Period,
+    AtomDomains,
+    /// By which root a Newton point converges to
+    Roots,
+    /// Just the boundary, by inverse iteration
+    InverseIteration,
+}
+
+impl Colouring {
+    pub fn of(fractal: &dyn Fractal) -> Self {
+        if let Some(mandelbrot) = fractal.downcast_ref::<Mandelbrot>() {
+            if mandelbrot.period_tint {
+                Colouring::Period
+            } else if mandelbrot.atom_domains {
+                Colouring::AtomDomains
+            } else {
+                Colouring::Escape
+            }
+        } else if fractal.downcast_ref::<Julia>().is_some_and(|julia| julia.inverse_iteration) {
+            Colouring::InverseIteration
+        } else if fractal.name() == "Newton" {
+            Colouring::Roots
+        } else {
+            Colouring::Escape
+        }
+    }
+}
+
+/// What a renderer is able to draw
+#[derive(Clone, Copy, Debug)]
+pub struct Capabilities {
+    /// Machine epsilon of the floats points are iterated in, which limits how far it can zoom
+    pub epsilon: f64,
+    /// Names of the fractals it draws, None for all of them
+    pub fractals: Option<&'static [&'static str]>,
+    pub colourings: &'static [Colouring],
+}
+
+impl Capabilities {
+    /// Whether the fractal can be drawn at all, however blocky
+    pub fn draws(&self, fractal: &dyn Fractal) -> bool {
+        self.fractals.is_none_or(|fractals| fractals.contains(&fractal.name()))
+            && self.colourings.contains(&Colouring::of(fractal))
+    }
+
+    /// Whether the fractal can be drawn in this viewport without going blocky
+    pub fn supports(&self, fractal: &dyn Fractal, viewport: Viewport) -> bool {
+        let magnitude = viewport.centre.0.abs().max(viewport.centre.1.abs()).max(1.0);
+        self.draws(fractal) && viewport.scale / magnitude >= self.epsilon * PRECISION_MARGIN
+    }
+}
+
+/// Draws fractals into an RGBA frame
+pub trait FractalRenderer {
+    fn capabilities(&self) -> Capabilities;
+
+    /// Render into an RGBA buffer the size of the viewport, with the palette stretched across `levels`
+    fn draw(&mut self, fractal: &dyn Fractal, pixels: &mut [u8], viewport: Viewport, levels: Levels) -> Result<(), String>;
+}
+
+/// Each fractal's own generator, spread across the cores with rayon
+#[derive(Default)]
+pub struct CpuRenderer;
+
+impl FractalRenderer for CpuRenderer {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            epsilon: f64::EPSILON,
+            fractals: None,
+            colourings: &[Colouring::Escape, Colouring::Period, Colouring::AtomDomains, Colouring::Roots, Colouring::InverseIteration],
+        }
+    }
+
+    fn draw(&mut self, fractal: &dyn Fractal, pixels: &mut [u8], viewport: Viewport, levels: Levels) -> Result<(), String> {
+        fractal.draw_levels(pixels, viewport, levels);
+        Ok(())
+    }
+}
+
+/// The renderers for each backend, the gpu one is set up the first time it's used
+#[derive(Default)]
+pub struct Renderers {
+    cpu: CpuRenderer,
+    #[cfg(not(target_arch = "wasm32"))]
+    gpu: Option<gpu::GpuRenderer>,
+    /// Why the custom shader couldn't be used when the gpu renderer was set up
+    shader_error: Option<String>,
+}
+
+impl Renderers {
+    /// Draw with the backend's renderer, or on the CPU if it can't draw this fractal in this viewport.
+    /// The hybrid backend draws on the GPU past its precision as the view is drawn again on the CPU afterwards.
+    /// If the backend fails the frame is still drawn on the CPU and the error is returned.
+    pub fn draw(&mut self, backend: Backend, fractal: &dyn Fractal, pixels: &mut [u8], viewport: Viewport, levels: Levels) -> Result<(), String> {
+        let drawn = match self.renderer(backend) {
+            Ok(renderer) if renderer.capabilities().supports(fractal, viewport) || (backend == Backend::Hybrid && renderer.capabilities().draws(fractal)) => {
+                renderer.draw(fractal, pixels, viewport, levels).map(|_| true)
+            }
+            Ok(_) => Ok(false),
+            Err(e) => Err(e),
+        };
+        if drawn != Ok(true) {
+            self.cpu.draw(fractal, pixels, viewport, levels)?;
+        }
+        drawn.map(|_| ())
+    }
+
+    /// True if the backend draws this view as a preview to be drawn again on the CPU,
+    /// which the hybrid backend does once the view is past the GPU's precision
+    pub fn refines_on_cpu(&mut self, backend: Backend, fractal: &dyn Fractal, viewport: Viewport) -> bool {
+        backend == Backend::Hybrid && self.renderer(backend).is_ok_and(|renderer| {
+            let capabilities = renderer.capabilities();
+            capabilities.draws(fractal) && !capabilities.supports(fractal, viewport)
+        })
+    }
+
+    /// Load the custom shader again after it has been edited, returns false if the gpu renderer isn't set up yet,
+    /// it picks up the shader when it is
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload_shader(&mut self) -> Result<bool, String> {
+        match &mut self.gpu {
+            Some(gpu) => gpu.load_custom_shader(),
+            None => Ok(false),
+        }
+    }
+
+    /// Take the error from loading the custom shader, if there was one
+    pub fn take_shader_error(&mut self) -> Option<String> {
+        self.shader_error.take()
+    }
+
+    fn renderer(&mut self, backend: Backend) -> Result<&mut dyn FractalRenderer, String> {
+        match backend {
+            Backend::Cpu => Ok(&mut self.cpu),
+            #[cfg(not(target_arch = "wasm32"))]
+            Backend::Gpu | Backend::Hybrid => {
+                if self.gpu.is_none() {
+                    let mut gpu = gpu::GpuRenderer::new()?;
+                    // a shader that doesn't compile shouldn't stop the built in one from being used
+                    if let Err(e) = gpu.load_custom_shader() {
+                        self.shader_error = Some(e);
+                    }
+                    self.gpu = Some(gpu);
+                }
+                Ok(self.gpu.as_mut().expect("the gpu renderer was just set up"))
+            }
+            #[cfg(target_arch = "wasm32")]
+            Backend::Gpu | Backend::Hybrid => Err("The GPU renderer isn't available in the browser".into()),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// Escape time Mandelbrot and Julia sets, one invocation per pixel
+
+struct Params {
+    // the point of the top left pixel and the step to the next pixel along and down
+    origin: vec2<f32>,
+    step_x: vec2<f32>,
+    step_y: vec2<f32>,
+    // the constant of a Julia set
+    c: vec2<f32>,
+    width: u32,
+    height: u32,
+    max_iterations: u32,
+    julia: u32,
+    escape_radius_squared: f32,
+    // 0 for a circle, 1 for a square and 2 for a strip, as in the Bailout enum
+    bailout: u32,
+    _padding_1: u32,
+    _padding_2: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+// the colour for each iteration count, packed as rgba bytes
+@group(0) @binding(1) var<storage, read> colours: array<u32>;
+@group(0) @binding(2) var<storage, read_write> frame: array<u32>;
+
+// compared with the escape radius squared, the same as Bailout::norm on the cpu
+fn bailout_norm(z: vec2<f32>) -> f32 {
+    switch params.bailout {
+        case 1u: {
+            return max(z.x * z.x, z.y * z.y);
+        }
+        case 2u: {
+            return z.x * z.x;
+        }
+        default: {
+            return dot(z, z);
+        }
+    }
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.width || id.y >= params.height {
+        return;
+    }
+    let point = params.origin + f32(id.x) * params.step_x + f32(id.y) * params.step_y;
+
+    var z = vec2<f32>(0.0, 0.0);
+    var c = point;
+    if params.julia != 0u {
+        z = point;
+        c = params.c;
+    }
+    var iteration = 0u;
+    // the Mandelbrot set keeps points on the escape radius, the Julia set doesn't, as on the cpu
+    loop {
+        let norm = bailout_norm(z);
+        let escaped = norm > params.escape_radius_squared || (params.julia != 0u && norm == params.escape_radius_squared);
+        if escaped || iteration >= params.max_iterations {
+            break;
+        }
+        z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+        iteration += 1u;
+    }
+    frame[id.y * params.width + id.x] = colours[iteration];
+}
@@ -0,0 +1,206 @@
+use std::sync::mpsc;
+
+use fractal_viz_core::fractals::{string_to_colour_gradient, Bailout, Fractal, Julia, Mandelbrot};
+use fractal_viz_core::transfer::Levels;
+use fractal_viz_core::viewport::Viewport;
+use pixels::wgpu;
+use pixels::wgpu::util::DeviceExt;
+
+use super::{Capabilities, Colouring, FractalRenderer};
+
+/// Pixels along each side of a workgroup, the same as in the shader
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Escape time fractals drawn with a compute shader on a device of their own, separate from the window's.
+/// Most gpus only have 32 bit floats so it can't zoom as far as the CPU.
+pub struct GpuRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+/// The fractal's settings in the layout of the shader's `Params`
+struct Params {
+    c: (f64, f64),
+    max_iterations: u32,
+    julia: bool,
+    escape_radius: f64,
+    bailout: Bailout,
+    colour_gradient: String,
+}
+
+impl Params {
+    fn new(fractal: &dyn Fractal) -> Option<Self> {
+        if let Some(mandelbrot) = fractal.downcast_ref::<Mandelbrot>() {
+            Some(Self { c: (0.0, 0.0), max_iterations: mandelbrot.max_iterations, julia: false, escape_radius: mandelbrot.escape_radius, bailout: mandelbrot.bailout, colour_gradient: mandelbrot.colour_gradient.clone() })
+        } else {
+            fractal.downcast_ref::<Julia>()
+                .map(|julia| Self { c: julia.c, max_iterations: julia.max_iterations, julia: true, escape_radius: julia.escape_radius, bailout: julia.bailout, colour_gradient: julia.colour_gradient.clone() })
+        }
+    }
+
+    fn bytes(&self, viewport: Viewport) -> Vec<u8> {
+        let origin = viewport.pixel_to_complex(0.0, 0.0);
+        let step_x = viewport.delta_to_complex(1.0, 0.0);
+        let step_y = viewport.delta_to_complex(0.0, 1.0);
+        let mut bytes = Vec::with_capacity(64);
+        for (x, y) in [origin, step_x, step_y, self.c] {
+            bytes.extend((x as f32).to_le_bytes());
+            bytes.extend((y as f32).to_le_bytes());
+        }
+        for value in [viewport.width as u32, viewport.height as u32, self.max_iterations, self.julia as u32] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(((self.escape_radius * self.escape_radius) as f32).to_le_bytes());
+        // numbered as in the shader's bailout_norm
+        let bailout: u32 = match self.bailout {
+            Bailout::Circle => 0,
+            Bailout::Square => 1,
+            Bailout::Strip => 2,
+        };
+        bytes.extend(bailout.to_le_bytes());
+        bytes.resize(64, 0);
+        bytes
+    }
+
+    /// The colour for each iteration count, the same as the CPU generators give
+    fn colours(&self, levels: Levels) -> Vec<u8> {
+        let gradient = string_to_colour_gradient(&self.colour_gradient);
+        (0..=self.max_iterations)
+            .flat_map(|iteration| gradient.at(levels.stretch(iteration as f64 / self.max_iterations as f64)).to_rgba8())
+            .collect()
+    }
+}
+
+impl GpuRenderer {
+    pub fn new() -> Result<Self, String> {
+        pollster::block_on(async {
+            let instance = wgpu::Instance::default();
+            let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                ..Default::default()
+            }).await.ok_or("No GPU that supports compute shaders was found")?;
+            let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+                label: Some("Fractal renderer"),
+                features: wgpu::Features::empty(),
+                limits: adapter.limits(),
+            }, None).await.map_err(|e| e.to_string())?;
+            tracing::info!(adapter = ?adapter.get_info(), "GPU renderer ready");
+
+            let pipeline = pipeline(&device, include_str!("escape_time.wgsl"))?;
+            Ok(Self { device, queue, pipeline })
+        })
+    }
+
+    /// Switch to the shader in the config directory if there is one, returns false if there isn't.
+    /// The current shader is kept if it doesn't compile.
+    pub fn load_custom_shader(&mut self) -> Result<bool, String> {
+        let Some(path) = super::shader_path().filter(|path| path.exists()) else {
+            return Ok(false);
+        };
+        let source = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        self.pipeline = pipeline(&self.device, &source)?;
+        tracing::info!(path = %path.display(), "Loaded custom shader");
+        Ok(true)
+    }
+}
+
+/// Compile the shader, returning the compile errors rather than passing them to the device's error handler
+fn pipeline(device: &wgpu::Device, source: &str) -> Result<wgpu::ComputePipeline, String> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Escape time"),
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    });
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Escape time"),
+        layout: None,
+        module: &shader,
+        entry_point: "main",
+    });
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(e) => Err(e.to_string()),
+        None => Ok(pipeline),
+    }
+}
+
+impl FractalRenderer for GpuRenderer {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            epsilon: f32::EPSILON as f64,
+            fractals: Some(&["Mandelbrot", "Julia"]),
+            colourings: &[Colouring::Escape],
+        }
+    }
+
+    fn draw(&mut self, fractal: &dyn Fractal, pixels: &mut [u8], viewport: Viewport, levels: Levels) -> Result<(), String> {
+        let params = Params::new(fractal).ok_or_else(|| format!("The GPU can't draw the {} fractal", fractal.name()))?;
+        let size = pixels.len() as wgpu::BufferAddress;
+        let limits = self.device.limits();
+        if size > limits.max_storage_buffer_binding_size as u64 || size > limits.max_buffer_size {
+            return Err("The window is too large for the GPU to draw in one go".into());
+        }
+
+        self.device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let params_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Params"),
+            contents: &params.bytes(viewport),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let colours = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Colours"),
+            contents: &params.colours(levels),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let frame = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Escape time"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: colours.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: frame.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Escape time") });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("Escape time") });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups((viewport.width as u32).div_ceil(WORKGROUP_SIZE), (viewport.height as u32).div_ceil(WORKGROUP_SIZE), 1);
+        }
+        encoder.copy_buffer_to_buffer(&frame, 0, &readback, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        // wait for the frame to be copied back, the CPU path blocks until it's done too
+        let (sender, receiver) = mpsc::channel();
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        let validation = pollster::block_on(self.device.pop_error_scope());
+        let out_of_memory = pollster::block_on(self.device.pop_error_scope());
+        if let Some(e) = validation.or(out_of_memory) {
+            return Err(e.to_string());
+        }
+        receiver.recv().map_err(|e| e.to_string())?.map_err(|e| e.to_string())?;
+        pixels.copy_from_slice(&slice.get_mapped_range());
+        readback.unmap();
+        Ok(())
+    }
+}
+
@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::view::View;
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use crate::animation::{Easing, Keyframe};
+
+/// Longest time a jump (like a box zoom) is smoothed over when replaying
+const REPLAY_TRANSITION: f32 = 1.0;
+/// Pauses while exploring are shortened to this so replays don't drag on
+const REPLAY_MAX_HOLD: f32 = 1.0;
+/// Changes closer together than this are one continuous movement, like dragging a slider
+const CONTINUOUS_GAP: f32 = 0.1;
+
+/// The fractal and view at a point in a recording
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    /// Seconds since the recording started
+    pub time: f32,
+    pub fractal: Box<dyn Fractal>,
+    pub view: View,
+}
+
+/// A recording of navigating a session, saved as json with the .replay extension
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&json).map_err(|e| e.to_string())
+    }
+
+    /// Save in the current directory, returning the path it was saved to
+    pub fn save(&self) -> std::io::Result<PathBuf> {
+        let path = crate::export::timestamped_path("session", "replay");
+        let json = serde_json::to_string(self).map_err(std::io::Error::other)?;
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    /// Turn the recording into timeline keyframes.
+    /// Jumps are eased over a short transition and long pauses are shortened so the replay plays smoothly.
+    pub fn keyframes(&self) -> Vec<Keyframe> {
+        let mut keyframes = Vec::new();
+        for pair in self.events.windows(2) {
+            let (event, next) = (&pair[0], &pair[1]);
+            let gap = next.time - event.time;
+            let transition = gap.min(REPLAY_TRANSITION);
+            let hold = (gap - transition).min(REPLAY_MAX_HOLD);
+            if hold > 0.0 {
+                keyframes.push(Keyframe { fractal: event.fractal.clone(), view: event.view, duration: hold, easing: Easing::Linear });
+            }
+            let easing = if gap < CONTINUOUS_GAP { Easing::Linear } else { Easing::EaseInOut };
+            keyframes.push(Keyframe { fractal: event.fractal.clone(), view: event.view, duration: transition, easing });
+        }
+        if let Some(last) = self.events.last() {
+            keyframes.push(Keyframe { fractal: last.fractal.clone(), view: last.view, duration: REPLAY_TRANSITION, easing: Easing::Linear });
+        }
+        keyframes
+    }
+}
+
+/// Records every change to the fractal and view with the time it happened
+pub struct Recorder {
+    start: Instant,
+    events: Vec<ReplayEvent>,
+}
+
+impl Recorder {
+    pub fn new(fractal: &dyn Fractal, view: View) -> Self {
+        Self { start: Instant::now(), events: vec![ReplayEvent { time: 0.0, fractal: fractal.clone_box(), view }] }
+    }
+
+    /// Called every frame, only changes are kept
+    pub fn record(&mut self, fractal: &dyn Fractal, view: View) {
+        let unchanged = self.events.last().is_some_and(|last| *last.fractal == *fractal && last.view == view);
+        if !unchanged {
+            self.events.push(ReplayEvent { time: self.start.elapsed().as_secs_f32(), fractal: fractal.clone_box(), view });
+        }
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn finish(self) -> Replay {
+        Replay { events: self.events }
+    }
+}
@@ -0,0 +1,189 @@
+use std::sync::mpsc;
+
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::transfer::Levels;
+use fractal_viz_core::viewport::Viewport;
+use web_time::{Duration, Instant};
+
+use crate::cache::FrameCache;
+use crate::renderer::{Backend, Renderers};
+
+/// How long each frame can spend generating the fractal, which leaves the gui enough of a frame to keep up 60 fps
+const FRAME_BUDGET: Duration = Duration::from_millis(12);
+/// Fewest rows drawn at a time, so a band is enough work to spread across the cores
+const MIN_BAND_ROWS: i32 = 16;
+/// Rows the hybrid backend's CPU pass draws at a time, each band is shown as soon as it's done
+const CPU_PASS_ROWS: i32 = 32;
+
+/// Generates frames a band of rows at a time, stopping each frame once its time budget is used up.
+/// A frame that takes longer is spread across as many frames as it needs, drawn over the last one from the top down
+/// while the gui carries on at full speed. The browser has no threads to render on so this also keeps the page responsive
+#[derive(Default)]
+pub struct RenderQueue {
+    job: Option<Job>,
+    /// How long a row of the last band took, bands are sized from it to fill the budget
+    row_time: Duration,
+}
+
+struct Job {
+    viewport: Viewport,
+    levels: Levels,
+    next_row: i32,
+    started: Instant,
+    /// Key the frame is cached under once it's finished
+    cache_key: Option<String>,
+}
+
+impl RenderQueue {
+    /// Start generating a frame of the viewport, dropping the one in progress
+    pub fn start(&mut self, viewport: Viewport, levels: Levels, cache_key: Option<String>) {
+        self.job = Some(Job { viewport, levels, next_row: 0, started: Instant::now(), cache_key });
+    }
+
+    /// Stop generating the frame, for when it has been drawn over or belongs to another session
+    pub fn cancel(&mut self) {
+        self.job = None;
+    }
+
+    /// True while part of the frame is still to be drawn
+    pub fn busy(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// How much of the frame is drawn with the time it's likely to take to draw the rest, None once it's all there
+    pub fn progress(&self) -> Option<(f32, Option<Duration>)> {
+        let job = self.job.as_ref()?;
+        let fraction = job.next_row as f32 / job.viewport.height.max(1) as f32;
+        Some((fraction, time_left(job.started, fraction)))
+    }
+
+    /// Draw bands into the frame until this frame's budget is used up, it's cached once the last band is drawn.
+    /// The rest of the frame is still drawn if the backend fails, the error is returned once the budget is used up.
+    pub fn run(&mut self, renderers: &mut Renderers, backend: Backend, fractal: &dyn Fractal, frame: &mut [u8], cache: &mut FrameCache) -> Result<(), String> {
+        self.draw(renderers, backend, fractal, frame, cache, FRAME_BUDGET)
+    }
+
+    /// Draw the rest of the frame straight away, for animations and screenshots that need all of it
+    pub fn finish(&mut self, renderers: &mut Renderers, backend: Backend, fractal: &dyn Fractal, frame: &mut [u8], cache: &mut FrameCache) -> Result<(), String> {
+        self.draw(renderers, backend, fractal, frame, cache, Duration::MAX)
+    }
+
+    fn draw(&mut self, renderers: &mut Renderers, backend: Backend, fractal: &dyn Fractal, frame: &mut [u8], cache: &mut FrameCache, budget: Duration) -> Result<(), String> {
+        let Some(job) = self.job.as_mut() else {
+            return Ok(());
+        };
+        let started = Instant::now();
+        let (width, height) = (job.viewport.width as usize, job.viewport.height);
+        let mut result = Ok(());
+        while job.next_row < height && started.elapsed() < budget {
+            // as many rows as fit in what's left of the budget going by the last band
+            let left = budget.saturating_sub(started.elapsed());
+            let rows = if self.row_time.is_zero() { MIN_BAND_ROWS } else { (left.as_secs_f64() / self.row_time.as_secs_f64()) as i32 };
+            let rows = rows.max(MIN_BAND_ROWS).min(height - job.next_row);
+            let band_started = Instant::now();
+            let band = &mut frame[job.next_row as usize * width * 4..(job.next_row + rows) as usize * width * 4];
+            result = result.and(renderers.draw(backend, fractal, band, job.viewport.rows(job.next_row, rows), job.levels));
+            self.row_time = band_started.elapsed() / rows as u32;
+            job.next_row += rows;
+        }
+        if job.next_row >= height {
+            if let Some(key) = job.cache_key.take() {
+                cache.insert(key, frame.to_vec());
+            }
+            self.job = None;
+        }
+        result
+    }
+}
+
+/// Time a piece of work will take to finish at the rate its chunks have been done so far, None until some of it is done
+pub fn time_left(started: Instant, fraction: f32) -> Option<Duration> {
+    if fraction <= 0.0 {
+        return None;
+    }
+    let per_whole = started.elapsed().as_secs_f64() / fraction as f64;
+    Some(Duration::from_secs_f64(per_whole * (1.0 - fraction.min(1.0)) as f64))
+}
+
+/// Timing of the frames being generated, for the render stats overlay
+#[derive(Default)]
+pub struct RenderStats {
+    /// When the frame being drawn was started
+    started: Option<Instant>,
+    /// How long the last frame took from being started to the last of it being drawn
+    pub last_frame: Option<Duration>,
+    /// How much of the frame being drawn a band at a time is there, with the time left
+    pub drawing: Option<(f32, Option<Duration>)>,
+}
+
+impl RenderStats {
+    /// Start timing a new frame
+    pub fn start(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    /// Note how far through the frame the queue is, timing it once there's nothing left to draw
+    pub fn update(&mut self, queue: &RenderQueue, finished: bool) {
+        self.drawing = queue.progress();
+        if finished {
+            if let Some(started) = self.started.take() {
+                self.last_frame = Some(started.elapsed());
+            }
+        }
+    }
+}
+
+/// The hybrid backend's second pass, which draws the GPU's preview again on the CPU in 64 bit floats on a background thread
+/// once the view is past the precision of 32 bit floats. Bands are copied over the preview as they finish and the whole frame
+/// is cached in place of the preview. Dropping it stops the thread
+pub struct CpuPass {
+    receiver: mpsc::Receiver<(i32, Vec<u8>)>,
+    viewport: Viewport,
+    rows_done: i32,
+    /// Key the frame is cached under once it's finished
+    cache_key: Option<String>,
+}
+
+impl CpuPass {
+    pub fn start(fractal: Box<dyn Fractal>, viewport: Viewport, levels: Levels, cache_key: Option<String>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        crate::background(move || {
+            let row_len = viewport.width as usize * 4;
+            for start in (0..viewport.height).step_by(CPU_PASS_ROWS as usize) {
+                let rows = CPU_PASS_ROWS.min(viewport.height - start);
+                let mut band = vec![0; row_len * rows as usize];
+                fractal.draw_levels(&mut band, viewport.rows(start, rows), levels);
+                if sender.send((start, band)).is_err() {
+                    return;
+                }
+            }
+        });
+        Self { receiver, viewport, rows_done: 0, cache_key }
+    }
+
+    /// How much of the frame has been drawn on the CPU
+    pub fn progress(&self) -> f32 {
+        self.rows_done as f32 / self.viewport.height.max(1) as f32
+    }
+
+    /// Copy the bands finished since the last call into the frame, returning true once the whole frame is there
+    /// or the frame is no longer the size the pass was started for
+    pub fn composite(&mut self, frame: &mut [u8], cache: &mut FrameCache) -> bool {
+        let row_len = self.viewport.width as usize * 4;
+        if frame.len() != row_len * self.viewport.height as usize {
+            return true;
+        }
+        for (start, band) in self.receiver.try_iter() {
+            let offset = start as usize * row_len;
+            frame[offset..offset + band.len()].copy_from_slice(&band);
+            self.rows_done += (band.len() / row_len) as i32;
+        }
+        if self.rows_done < self.viewport.height {
+            return false;
+        }
+        if let Some(key) = self.cache_key.take() {
+            cache.insert(key, frame.to_vec());
+        }
+        true
+    }
+}
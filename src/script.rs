@@ -0,0 +1,431 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+
+use fractal_viz_core::fractals::{self, smooth_iterations, string_to_colour_gradient, Fractal, PointSample, Registration, SettingsUi};
+use fractal_viz_core::transfer::Levels;
+use fractal_viz_core::view::View;
+use fractal_viz_core::viewport::Viewport;
+use num::complex::Complex64;
+use rayon::prelude::*;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Scope, AST};
+use serde::{Deserialize, Serialize};
+
+/// Shown when a script fractal is picked from the list before any script has been loaded
+const EXAMPLE_SCRIPT: &str = "\
+// the burning ship, z -> (|re z| + i|im z|)^2 + c
+fn iterate(z, c) {
+    let w = complex(z.re.abs(), z.im.abs());
+    w * w + c
+}
+";
+
+/// Operations one call of `start` or `iterate` can take, so a script stuck in a loop stops with an error instead of hanging the render
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// The first error a script fractal hit while rendering, so it can be shown once instead of for every pixel
+static RENDER_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// Add the script fractal to the fractals that can be chosen and loaded from files
+pub fn register() {
+    fractals::register(Registration::new::<ScriptFractal>("Script"));
+}
+
+/// Take the error the last render of a script fractal hit, if there was one
+pub fn take_render_error() -> Option<String> {
+    RENDER_ERROR.lock().unwrap().take()
+}
+
+/// Complex numbers and the usual operations on them, for both kinds of script
+fn register_complex(engine: &mut Engine) {
+    engine.register_type_with_name::<Complex64>("Complex")
+        .register_fn("complex", Complex64::new)
+        .register_get("re", |z: &mut Complex64| z.re)
+        .register_get("im", |z: &mut Complex64| z.im)
+        .register_fn("+", |a: Complex64, b: Complex64| a + b)
+        .register_fn("+", |a: Complex64, b: f64| a + b)
+        .register_fn("+", |a: f64, b: Complex64| a + b)
+        .register_fn("-", |a: Complex64, b: Complex64| a - b)
+        .register_fn("-", |a: Complex64, b: f64| a - b)
+        .register_fn("-", |a: f64, b: Complex64| a - b)
+        .register_fn("-", |z: Complex64| -z)
+        .register_fn("*", |a: Complex64, b: Complex64| a * b)
+        .register_fn("*", |a: Complex64, b: f64| a * b)
+        .register_fn("*", |a: f64, b: Complex64| a * b)
+        .register_fn("/", |a: Complex64, b: Complex64| a / b)
+        .register_fn("/", |a: Complex64, b: f64| a / b)
+        .register_fn("/", |a: f64, b: Complex64| a / b)
+        .register_fn("abs", |z: Complex64| z.norm())
+        .register_fn("arg", |z: Complex64| z.arg())
+        .register_fn("conj", |z: Complex64| z.conj())
+        .register_fn("exp", |z: Complex64| z.exp())
+        .register_fn("ln", |z: Complex64| z.ln())
+        .register_fn("sqrt", |z: Complex64| z.sqrt())
+        .register_fn("sin", |z: Complex64| z.sin())
+        .register_fn("cos", |z: Complex64| z.cos())
+        .register_fn("tan", |z: Complex64| z.tan())
+        .register_fn("pow", |z: Complex64, n: f64| z.powf(n))
+        .register_fn("pow", |z: Complex64, n: Complex64| z.powc(n))
+        .register_fn("to_string", |z: &mut Complex64| format!("{} {:+}i", z.re, z.im))
+        .register_fn("to_debug", |z: &mut Complex64| format!("{} {:+}i", z.re, z.im));
+}
+
+/// Shared by every script fractal, the scripts are compiled into their own ASTs
+fn fractal_engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut engine = Engine::new();
+        register_complex(&mut engine);
+        // printing from every pixel would flood the console
+        engine.on_print(|_| {});
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine
+    })
+}
+
+/// True if the script defines a fractal rather than automating the app
+pub fn defines_fractal(source: &str) -> bool {
+    fractal_engine().compile(source).is_ok_and(|ast| ast.iter_functions().any(|f| f.name == "iterate" && f.params.len() == 2))
+}
+
+/// A fractal defined by a script, z -> iterate(z, c) starting from z = start(c),
+/// or 0 if the script doesn't define `start`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScriptFractal {
+    pub max_iterations: u32,
+    pub escape_radius: f64,
+    pub colour_gradient: String,
+    /// Name of the file the script was loaded from
+    pub script_name: String,
+    pub source: String,
+    /// The file the script was loaded from, watched so edits to it are picked up
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    #[serde(skip)]
+    compiled: OnceLock<Result<Arc<AST>, String>>,
+}
+
+impl Default for ScriptFractal {
+    fn default() -> Self {
+        Self::new("Example".into(), EXAMPLE_SCRIPT.into())
+    }
+}
+
+impl ScriptFractal {
+    pub fn new(script_name: String, source: String) -> Self {
+        Self { max_iterations: 50, escape_radius: 2.0, colour_gradient: "Magma".into(), script_name, source, #[cfg(not(target_arch = "wasm32"))] path: None, compiled: OnceLock::new() }
+    }
+
+    /// Swap in an edited version of the script, keeping the other settings
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload(&mut self, source: String) -> Result<(), String> {
+        self.source = source;
+        self.compiled = OnceLock::new();
+        self.compile().map(|_| ())
+    }
+
+    /// Check the script compiles and defines `iterate`
+    pub fn compile(&self) -> Result<&AST, String> {
+        let compiled = self.compiled.get_or_init(|| {
+            let ast = fractal_engine().compile(&self.source).map_err(|e| e.to_string())?;
+            if !ast.iter_functions().any(|f| f.name == "iterate" && f.params.len() == 2) {
+                return Err("the script needs a function iterate(z, c)".into());
+            }
+            Ok(Arc::new(ast))
+        });
+        compiled.as_deref().map_err(Clone::clone)
+    }
+
+    /// Run the iteration from a point, calling `visit` with each value of z.
+    /// Returns the number of iterations and the last z.
+    fn iterate(&self, (real, imaginary): (f64, f64), mut visit: impl FnMut(Complex64)) -> Result<(u32, Complex64), String> {
+        let ast = self.compile()?;
+        let engine = fractal_engine();
+        let mut scope = Scope::new();
+        // the top level of the script only has to run once, not for every call
+        let options = || CallFnOptions::new().eval_ast(false);
+        let c = Complex64::new(real, imaginary);
+        let mut z = if ast.iter_functions().any(|f| f.name == "start") {
+            engine.call_fn_with_options(options(), &mut scope, ast, "start", (c,)).map_err(|e| call_error(&e))?
+        } else {
+            Complex64::new(0.0, 0.0)
+        };
+        visit(z);
+        let r = self.escape_radius * self.escape_radius;
+        let mut iteration = 0;
+        while z.norm_sqr() <= r && iteration < self.max_iterations {
+            z = engine.call_fn_with_options(options(), &mut scope, ast, "iterate", (z, c)).map_err(|e| call_error(&e))?;
+            visit(z);
+            iteration += 1;
+        }
+        Ok((iteration, z))
+    }
+}
+
+/// Explain hitting the operation limit, rhai only says there were too many
+fn call_error(e: &EvalAltResult) -> String {
+    match e {
+        EvalAltResult::ErrorTooManyOperations(_) => format!("a step took more than {} operations, is there a loop that never ends?", MAX_OPERATIONS),
+        e => e.to_string(),
+    }
+}
+
+impl Fractal for ScriptFractal {
+    fn name(&self) -> &'static str {
+        "Script"
+    }
+
+    fn sample(&self, point: (f64, f64)) -> PointSample {
+        let (iterations, z) = self.iterate(point, |_| {}).unwrap_or_else(|e| {
+            RENDER_ERROR.lock().unwrap().get_or_insert(e);
+            (0, Complex64::new(0.0, 0.0))
+        });
+        let smooth = smooth_iterations(iterations, self.max_iterations, z.norm_sqr());
+        PointSample { iterations, smooth, z, root: None, period: None, atom_domain: None }
+    }
+
+    fn orbit(&self, point: (f64, f64)) -> Vec<Complex64> {
+        let mut orbit = Vec::new();
+        let _ = self.iterate(point, |z| orbit.push(z));
+        orbit
+    }
+
+    fn draw_levels(&self, pixels: &mut [u8], viewport: Viewport, levels: Levels) {
+        let colour_gradient = string_to_colour_gradient(&self.colour_gradient);
+        pixels.par_chunks_mut(4).enumerate().for_each(|(i, pixel)| {
+            let point = viewport.point(i);
+            let iterations = self.sample(point).iterations;
+            pixel.copy_from_slice(&colour_gradient.at(levels.stretch(iterations as f64 / self.max_iterations as f64)).to_rgba8());
+        });
+    }
+
+    fn settings(&mut self, ui: &mut dyn SettingsUi) {
+        ui.label(&self.script_name);
+        ui.iterations(&mut self.max_iterations);
+        ui.slider("Escape Radius", &mut self.escape_radius, 1.0..=10.0);
+    }
+
+    fn default_parameters(&self) -> Box<dyn Fractal> {
+        Box::new(Self { colour_gradient: self.colour_gradient.clone(), ..Self::new(self.script_name.clone(), self.source.clone()) })
+    }
+
+    fn max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+
+    fn colour_gradient_mut(&mut self) -> &mut String {
+        &mut self.colour_gradient
+    }
+
+    fn animatable_parameters(&self) -> &'static [&'static str] {
+        &["Escape Radius", "Max Iterations"]
+    }
+
+    fn parameter(&self, name: &str) -> Option<f64> {
+        match name {
+            "Max Iterations" => Some(self.max_iterations as f64),
+            "Escape Radius" => Some(self.escape_radius),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) {
+        match name {
+            "Max Iterations" => self.max_iterations = value.round().max(1.0) as u32,
+            "Escape Radius" => self.escape_radius = value,
+            _ => {}
+        }
+    }
+
+    fn rule(&self, point: (f64, f64)) -> String {
+        format!("z → iterate(z, c),  c = {:.6} {:+.6}i", point.0, point.1)
+    }
+
+    fn escape_radius(&self) -> Option<f64> {
+        Some(self.escape_radius)
+    }
+}
+
+/// Something an automation script did, sent back to the app
+pub enum ScriptEvent {
+    Output(String),
+    Error(String),
+    /// Show this fractal and view in the window
+    Show(Box<dyn Fractal>, View),
+}
+
+/// What an automation script is working on, a copy of the app's state when it started
+struct AutomationState {
+    fractal: Box<dyn Fractal>,
+    view: View,
+    bookmarks: Vec<(String, Box<dyn Fractal>, View)>,
+    /// Renders cover the same area as a window of this size
+    window: (u32, u32),
+}
+
+/// A script that drives the app, like rendering a list of bookmarks at a high resolution.
+/// It runs on a background thread and works on its own copy of the fractal and view.
+pub struct Automation {
+    receiver: mpsc::Receiver<ScriptEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Automation {
+    pub fn start(source: String, fractal: Box<dyn Fractal>, view: View, bookmarks: Vec<(String, Box<dyn Fractal>, View)>, window: (u32, u32)) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let state = Arc::new(Mutex::new(AutomationState { fractal, view, bookmarks, window }));
+        let engine = automation_engine(state, sender.clone(), stop.clone());
+        crate::background(move || {
+            if let Err(e) = engine.run(&source) {
+                let message = match *e {
+                    EvalAltResult::ErrorTerminated(..) => "Stopped".to_string(),
+                    e => e.to_string(),
+                };
+                let _ = sender.send(ScriptEvent::Error(message));
+            }
+        });
+        Self { receiver, stop }
+    }
+
+    /// Events since the last poll, None once the script has finished and every event has been taken
+    pub fn poll(&self) -> Option<Vec<ScriptEvent>> {
+        let mut events = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => events.push(event),
+                Err(mpsc::TryRecvError::Empty) => return Some(events),
+                Err(mpsc::TryRecvError::Disconnected) => return (!events.is_empty()).then_some(events),
+            }
+        }
+    }
+
+    /// Ask the script to stop, it stops at its next operation
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// An engine with the functions automation scripts use to control the fractal and view
+fn automation_engine(state: Arc<Mutex<AutomationState>>, sender: mpsc::Sender<ScriptEvent>, stop: Arc<AtomicBool>) -> Engine {
+    let mut engine = Engine::new();
+    register_complex(&mut engine);
+    let print_sender = Mutex::new(sender.clone());
+    engine.on_print(move |text| {
+        let _ = print_sender.lock().unwrap().send(ScriptEvent::Output(text.to_string()));
+    });
+    engine.on_progress(move |_| stop.load(Ordering::Relaxed).then_some(Dynamic::UNIT));
+
+    let shared = state.clone();
+    engine.register_fn("bookmarks", move || -> Array {
+        shared.lock().unwrap().bookmarks.iter().map(|(name, ..)| name.clone().into()).collect()
+    });
+    let shared = state.clone();
+    engine.register_fn("go_to", move |name: &str| -> ScriptResult<()> {
+        let mut state = shared.lock().unwrap();
+        let (_, fractal, view) = state.bookmarks.iter().find(|(bookmark, ..)| bookmark == name)
+            .ok_or_else(|| format!("no bookmark called \"{}\"", name))?;
+        (state.fractal, state.view) = (fractal.clone(), *view);
+        Ok(())
+    });
+    let shared = state.clone();
+    engine.register_fn("fractal", move |name: &str| -> ScriptResult<()> {
+        if !fractals::names().contains(&name) {
+            return Err(format!("no fractal called \"{}\"", name).into());
+        }
+        let mut state = shared.lock().unwrap();
+        let mut fractal = fractals::create(name);
+        *fractal.colour_gradient_mut() = state.fractal.colour_gradient_mut().clone();
+        (state.fractal, state.view) = (fractal, View::default());
+        Ok(())
+    });
+    let shared = state.clone();
+    engine.register_fn("set", move |name: &str, value: f64| -> ScriptResult<()> {
+        let mut state = shared.lock().unwrap();
+        state.fractal.parameter(name).ok_or_else(|| format!("the fractal has no parameter \"{}\"", name))?;
+        state.fractal.set_parameter(name, value);
+        Ok(())
+    });
+    let shared = state.clone();
+    engine.register_fn("get", move |name: &str| -> ScriptResult<f64> {
+        Ok(shared.lock().unwrap().fractal.parameter(name).ok_or_else(|| format!("the fractal has no parameter \"{}\"", name))?)
+    });
+    let shared = state.clone();
+    engine.register_fn("colour", move |name: &str| {
+        *shared.lock().unwrap().fractal.colour_gradient_mut() = name.to_string();
+    });
+    let shared = state.clone();
+    engine.register_fn("centre", move |x: f64, y: f64| {
+        shared.lock().unwrap().view.set_centre((x, y));
+    });
+    let shared = state.clone();
+    engine.register_fn("zoom", move |factor: f64| {
+        shared.lock().unwrap().view.zoom /= factor;
+    });
+    let shared = state.clone();
+    engine.register_fn("rotate", move |degrees: f64| {
+        let view = &mut shared.lock().unwrap().view;
+        view.rotation = crate::wrap_degrees(view.rotation + degrees);
+    });
+    let shared = state.clone();
+    let render_sender = Mutex::new(sender.clone());
+    engine.register_fn("render", move |width: i64, height: i64, path: &str| -> ScriptResult<()> {
+        let state = shared.lock().unwrap();
+        let path = render(&state, width, height, path)?;
+        let _ = render_sender.lock().unwrap().send(ScriptEvent::Output(format!("Saved {}", path)));
+        Ok(())
+    });
+    let shared = state.clone();
+    let render_sender = Mutex::new(sender.clone());
+    engine.register_fn("render", move |width: i64, height: i64| -> ScriptResult<()> {
+        let state = shared.lock().unwrap();
+        let path = crate::export::timestamped_path("fractal", "png");
+        let path = render(&state, width, height, &path.to_string_lossy())?;
+        let _ = render_sender.lock().unwrap().send(ScriptEvent::Output(format!("Saved {}", path)));
+        Ok(())
+    });
+    let show_sender = Mutex::new(sender);
+    engine.register_fn("show", move || {
+        let state = state.lock().unwrap();
+        let _ = show_sender.lock().unwrap().send(ScriptEvent::Show(state.fractal.clone(), state.view));
+    });
+    engine
+}
+
+/// Render the script's fractal and view to a png, covering the same area as the window does
+fn render(state: &AutomationState, width: i64, height: i64, path: &str) -> ScriptResult<String> {
+    let (Ok(width @ 1..), Ok(height @ 1..)) = (u32::try_from(width), u32::try_from(height)) else {
+        return Err(format!("can't render an image {} by {}", width, height).into());
+    };
+    crate::export::render_image(state.fractal.as_ref(), state.view, state.window.0, (width, height), path.as_ref()).map_err(|e| e.to_string())?;
+    Ok(path.to_string())
+}
+
+/// A line in the console, errors are highlighted
+pub struct ConsoleLine {
+    pub text: String,
+    pub error: bool,
+}
+
+/// Output from scripts and the automation script that is running
+#[derive(Default)]
+pub struct Console {
+    pub lines: Vec<ConsoleLine>,
+    /// Code typed into the console, run when enter is pressed
+    pub input: String,
+    pub automation: Option<Automation>,
+}
+
+impl Console {
+    pub fn output(&mut self, text: impl Into<String>) {
+        self.lines.push(ConsoleLine { text: text.into(), error: false });
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.lines.push(ConsoleLine { text: text.into(), error: true });
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::inverse_iteration::InverseIteration;
+use fractal_viz_core::transfer::Levels;
+use fractal_viz_core::view::View;
+use serde::{Deserialize, Serialize};
+
+use crate::animation::Timeline;
+use crate::breadcrumbs::Breadcrumbs;
+use crate::compare::Compare;
+use crate::history::History;
+use crate::memory::FractalMemory;
+use crate::profiles::Profiles;
+use crate::replay::Recorder;
+use crate::snapshots::Snapshots;
+
+/// An independent exploration with its own fractal, view and cached frame.
+/// Each tab in the gui is a session.
+pub struct Session {
+    pub fractal: Box<dyn Fractal>,
+    pub view: View,
+    /// The second parameter set shown when the A/B compare mode is enabled
+    pub compare: Compare,
+    /// The last settings and view used for each type of fractal
+    pub memory: FractalMemory,
+    /// The last rendered frame, so switching between sessions doesn't need a re-render
+    pub freeze_frame: Vec<u8>,
+    /// Keyframes of the session's animation
+    pub timeline: Timeline,
+    /// Records navigation while a replay is being recorded
+    pub recorder: Option<Recorder>,
+    /// Renderer still adding points to the frame, for fractals drawn progressively
+    pub progressive: Option<InverseIteration>,
+    /// Quick save slots, kept until the session is closed
+    pub snapshots: Snapshots,
+    /// Views passed on the way down to the current one
+    pub breadcrumbs: Breadcrumbs,
+    /// Changes to the view and settings that can be undone
+    pub history: History,
+    /// The part of the iteration range the palette is spread across in the session's frame, narrowed by auto levels
+    pub levels: Levels,
+}
+
+impl Session {
+    pub fn new(fractal: Box<dyn Fractal>) -> Self {
+        Self {
+            compare: Compare::new(fractal.clone()),
+            fractal,
+            view: View::default(),
+            memory: FractalMemory::new(),
+            freeze_frame: Vec::new(),
+            timeline: Timeline::default(),
+            recorder: None,
+            progressive: None,
+            snapshots: Snapshots::default(),
+            breadcrumbs: Breadcrumbs::default(),
+            history: History::default(),
+            levels: Levels::FULL,
+        }
+    }
+
+    /// A session for a new tab. The default fractal starts from its profile if it has one
+    pub fn with_profile(default_fractal: &dyn Fractal, profiles: &Profiles) -> Self {
+        let name = default_fractal.name();
+        let fractal = if profiles.get(name).is_some() { profiles.create(name) } else { default_fractal.clone_box() };
+        Self { view: profiles.view(name), ..Session::new(fractal) }
+    }
+
+    /// The name shown on the session's tab
+    pub fn title(&self) -> &'static str {
+        self.fractal.name()
+    }
+}
+
+/// The parts of a session that are saved to a session file
+#[derive(Serialize, Deserialize)]
+pub struct SessionFile {
+    pub fractal: Box<dyn Fractal>,
+    pub view: View,
+}
+
+impl SessionFile {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&json).map_err(|e| e.to_string())
+    }
+
+    /// Save as json in the current directory, returning the path it was saved to
+    pub fn save(&self) -> std::io::Result<PathBuf> {
+        let path = crate::export::timestamped_path("session", "json");
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+}
+
+impl From<SessionFile> for Session {
+    fn from(file: SessionFile) -> Self {
+        Self { view: file.view, ..Session::new(file.fractal) }
+    }
+}
+
+/// All open sessions and which one is being shown
+pub struct Sessions {
+    pub list: Vec<Session>,
+    pub current: usize,
+}
+
+impl Sessions {
+    pub fn new(session: Session) -> Self {
+        Self { list: vec![session], current: 0 }
+    }
+
+    pub fn current_mut(&mut self) -> &mut Session {
+        &mut self.list[self.current]
+    }
+
+    /// Add a session and switch to it
+    pub fn add(&mut self, session: Session) {
+        self.list.push(session);
+        self.current = self.list.len() - 1;
+    }
+
+    /// Close a session, the last session can't be closed
+    pub fn close(&mut self, index: usize) {
+        if self.list.len() > 1 && index < self.list.len() {
+            self.list.remove(index);
+            if self.current > index || self.current == self.list.len() {
+                self.current -= 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use fractal_viz_core::fractals::Fractal;
+use fractal_viz_core::view::View;
+use winit::event::VirtualKeyCode;
+use winit_input_helper::WinitInputHelper;
+
+use crate::adjust::Adjustments;
+use crate::compare::Compare;
+
+/// Number of quick save slots, one for each of the number keys 1 to 9
+pub const SLOTS: usize = 9;
+
+/// Everything needed to come back to how the session looked when it was saved
+#[derive(Clone)]
+pub struct Snapshot {
+    pub fractal: Box<dyn Fractal>,
+    pub view: View,
+    /// The B parameter set and whether it was being compared
+    pub compare: Option<Box<dyn Fractal>>,
+    pub adjustments: Adjustments,
+}
+
+impl Snapshot {
+    pub fn of(fractal: &dyn Fractal, view: View, compare: &Compare, adjustments: Adjustments) -> Self {
+        Self {
+            fractal: fractal.clone_box(),
+            view,
+            compare: compare.enabled.then(|| compare.fractal.clone()),
+            adjustments,
+        }
+    }
+}
+
+/// Quick save slots for hopping back and forth between a few settings while exploring.
+/// They're only kept in memory, bookmarks are for anything worth keeping
+#[derive(Clone, Default)]
+pub struct Snapshots([Option<Snapshot>; SLOTS]);
+
+impl Snapshots {
+    /// Save to a slot numbered from 1, replacing what was in it
+    pub fn save(&mut self, slot: usize, snapshot: Snapshot) {
+        if let Some(saved) = slot.checked_sub(1).and_then(|index| self.0.get_mut(index)) {
+            *saved = Some(snapshot);
+        }
+    }
+
+    /// The snapshot in a slot numbered from 1, None if nothing has been saved to it
+    pub fn get(&self, slot: usize) -> Option<&Snapshot> {
+        slot.checked_sub(1).and_then(|index| self.0.get(index)).and_then(Option::as_ref)
+    }
+}
+
+/// The slot whose number key was pressed this frame, on the top row or the number pad
+pub fn slot_pressed(input: &WinitInputHelper) -> Option<usize> {
+    use VirtualKeyCode::*;
+    const KEYS: [[VirtualKeyCode; 2]; SLOTS] = [
+        [Key1, Numpad1], [Key2, Numpad2], [Key3, Numpad3], [Key4, Numpad4], [Key5, Numpad5],
+        [Key6, Numpad6], [Key7, Numpad7], [Key8, Numpad8], [Key9, Numpad9],
+    ];
+    KEYS.iter().position(|keys| keys.iter().any(|key| input.key_pressed(*key))).map(|index| index + 1)
+}
@@ -0,0 +1,77 @@
+use rayon::prelude::*;
+
+pub const STEREO_MODES: [StereoMode; 3] = [StereoMode::Off, StereoMode::Anaglyph, StereoMode::SideBySide];
+
+/// How the two eyes' views are put into one frame
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StereoMode {
+    Off,
+    /// Red for the left eye and cyan for the right, for red-cyan glasses
+    Anaglyph,
+    /// Each eye squeezed into half the width, for 3D displays and headsets
+    SideBySide,
+}
+
+impl StereoMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StereoMode::Off => "Off",
+            StereoMode::Anaglyph => "Red-cyan anaglyph",
+            StereoMode::SideBySide => "Side by side",
+        }
+    }
+}
+
+/// Fake 3D for the flat fractals, points that take longer to escape stand out towards the viewer.
+/// Each eye sees the frame with every pixel shifted sideways by its depth.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stereo {
+    pub mode: StereoMode,
+    /// Difference in pixels between where the two eyes see the nearest and furthest points
+    pub separation: f32,
+}
+
+impl Default for Stereo {
+    fn default() -> Self {
+        Self { mode: StereoMode::Off, separation: 10.0 }
+    }
+}
+
+impl Stereo {
+    /// Turn an rgba frame into a stereo pair, with an iteration count for each pixel giving its depth
+    pub fn apply(&self, pixels: &mut [u8], width: usize, counts: &[u32], max_iterations: u32) {
+        if self.mode == StereoMode::Off || width == 0 {
+            return;
+        }
+        // log so the detail near the boundary doesn't all end up at one depth
+        let scale = 1.0 / (max_iterations.max(1) as f32).ln_1p();
+        let depths: Vec<f32> = counts.par_iter().map(|&count| (count as f32).ln_1p() * scale).collect();
+        let source = pixels.to_vec();
+        let separation = self.separation;
+        // the eyes look from either side, so a near point is further right for the left eye.
+        // Halfway back is level with the screen
+        let eye = |x: usize, y: usize, side: f32| -> &[u8] {
+            let row = y * width;
+            let shift = (side * separation * (depths[row + x] - 0.5) / 2.0).round() as isize;
+            let x = (x as isize - shift).clamp(0, width as isize - 1) as usize;
+            &source[(row + x) * 4..(row + x) * 4 + 4]
+        };
+        let mode = self.mode;
+        pixels.par_chunks_mut(width * 4).enumerate().for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                match mode {
+                    StereoMode::Anaglyph => {
+                        pixel[0] = eye(x, y, 1.0)[0];
+                        pixel[1..3].copy_from_slice(&eye(x, y, -1.0)[1..3]);
+                    }
+                    StereoMode::SideBySide => {
+                        // the left half is every other pixel as the left eye sees it, the right half the same for the right eye
+                        let (side, source_x) = if x < width / 2 { (1.0, x * 2) } else { (-1.0, (x - width / 2) * 2) };
+                        pixel.copy_from_slice(eye(source_x.min(width - 1), y, side));
+                    }
+                    StereoMode::Off => {}
+                }
+            }
+        });
+    }
+}
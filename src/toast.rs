@@ -0,0 +1,90 @@
+use egui::{Color32, Context, FontId, RichText};
+use web_time::{Duration, Instant};
+
+/// How long a toast is shown for
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+struct Toast {
+    text: String,
+    kind: ToastKind,
+    created: Instant,
+    /// Whether it has been drawn yet, toasts added after the gui has run this frame haven't
+    shown: bool,
+}
+
+/// Short non-blocking notifications shown in the bottom right of the window
+#[derive(Default)]
+pub struct Toasts {
+    list: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn add(&mut self, kind: ToastKind, text: impl Into<String>) {
+        let text = text.into();
+        match kind {
+            ToastKind::Error => tracing::warn!("{}", text),
+            _ => tracing::debug!("{}", text),
+        }
+        self.list.push(Toast { text, kind, created: Instant::now(), shown: false });
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.add(ToastKind::Info, text);
+    }
+
+    pub fn success(&mut self, text: impl Into<String>) {
+        self.add(ToastKind::Success, text);
+    }
+
+    pub fn warning(&mut self, text: impl Into<String>) {
+        self.add(ToastKind::Warning, text);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.add(ToastKind::Error, text);
+    }
+
+    /// How long until the toasts need drawing again, because one is new or one expires.
+    /// None if there are none to draw.
+    pub fn next_update(&self) -> Option<Duration> {
+        self.list.iter()
+            .filter_map(|toast| {
+                let remaining = TOAST_DURATION.checked_sub(toast.created.elapsed())?;
+                Some(if toast.shown { remaining } else { Duration::ZERO })
+            })
+            .min()
+    }
+
+    /// Draw the toasts, newest at the bottom, removing any that have expired
+    pub fn show(&mut self, ctx: &Context, font: &FontId) {
+        self.list.retain(|toast| toast.created.elapsed() < TOAST_DURATION);
+        if self.list.is_empty() {
+            return;
+        }
+        egui::Area::new("Toasts")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            for toast in &mut self.list {
+                toast.shown = true;
+                let colour = match toast.kind {
+                    ToastKind::Info => ui.visuals().strong_text_color(),
+                    ToastKind::Success => Color32::from_rgb(60, 180, 60),
+                    ToastKind::Warning => ui.visuals().warn_fg_color,
+                    ToastKind::Error => ui.visuals().error_fg_color,
+                };
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(RichText::new(&toast.text).font(font.clone()).color(colour));
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use winit::event_loop::EventLoopProxy;
+
+/// Watches the shader and script files loaded from disk, so they can be edited while the app is running,
+/// and a folder that parameter files from other programs are dropped into
+pub struct FileWatcher {
+    watcher: notify::RecommendedWatcher,
+    receiver: mpsc::Receiver<notify::Result<notify::Event>>,
+    files: HashSet<PathBuf>,
+    /// Directories already being watched, a watch is added once for all the files in one
+    directories: HashSet<PathBuf>,
+    /// Every file in this folder is reported, not just the ones asked for
+    folder: Option<PathBuf>,
+}
+
+/// The folder to watch given on the command line with `--watch=DIR`
+pub fn folder_from_args() -> Option<PathBuf> {
+    std::env::args().skip(1).find_map(|arg| arg.strip_prefix("--watch=").map(PathBuf::from))
+}
+
+impl FileWatcher {
+    /// `wake` gets the event loop going when a file changes while the app is idle
+    pub fn new(wake: EventLoopProxy<()>) -> Result<Self, String> {
+        let (sender, receiver) = mpsc::channel();
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+            let _ = wake.send_event(());
+        }).map_err(|e| e.to_string())?;
+        Ok(Self { watcher, receiver, files: HashSet::new(), directories: HashSet::new(), folder: None })
+    }
+
+    /// Report changes to the file from now on, it doesn't have to exist yet.
+    /// The directory is watched rather than the file as editors often save by replacing the file,
+    /// which would end a watch on the file itself. Returns the absolute path changes are reported with.
+    pub fn watch(&mut self, path: &Path) -> Result<PathBuf, String> {
+        let path = std::path::absolute(path).map_err(|e| e.to_string())?;
+        let directory = path.parent().ok_or("The file isn't in a directory")?.to_path_buf();
+        if !self.directories.contains(&directory) {
+            self.watcher.watch(&directory, RecursiveMode::NonRecursive).map_err(|e| e.to_string())?;
+            self.directories.insert(directory);
+        }
+        self.files.insert(path.clone());
+        Ok(path)
+    }
+
+    /// Report every file created or changed in the folder from now on, in place of the last folder.
+    /// None stops watching a folder. Returns the absolute path of the folder.
+    pub fn watch_folder(&mut self, folder: Option<&Path>) -> Result<Option<PathBuf>, String> {
+        let folder = match folder {
+            Some(folder) if !folder.is_dir() => return Err(format!("{} isn't a folder", folder.display())),
+            Some(folder) => Some(std::path::absolute(folder).map_err(|e| e.to_string())?),
+            None => None,
+        };
+        if let Some(old) = self.folder.take() {
+            // the directory is still needed if a watched file is in it
+            if !self.directories.contains(&old) {
+                let _ = self.watcher.unwatch(&old);
+            }
+        }
+        if let Some(folder) = &folder {
+            if !self.directories.contains(folder) {
+                self.watcher.watch(folder, RecursiveMode::NonRecursive).map_err(|e| e.to_string())?;
+            }
+        }
+        self.folder.clone_from(&folder);
+        Ok(folder)
+    }
+
+    /// True if the file is in the watched folder rather than one of the watched files
+    pub fn in_folder(&self, path: &Path) -> bool {
+        self.folder.as_deref().is_some_and(|folder| path.parent() == Some(folder)) && !self.files.contains(path)
+    }
+
+    /// Watched files written or created since the last poll, each only once however many times it was saved
+    pub fn poll(&self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for event in self.receiver.try_iter() {
+            match event {
+                Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                    for path in event.paths {
+                        if (self.files.contains(&path) || self.in_folder(&path)) && !changed.contains(&path) {
+                            changed.push(path);
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "File watcher failed"),
+            }
+        }
+        changed
+    }
+}
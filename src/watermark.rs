@@ -0,0 +1,164 @@
+use std::path::PathBuf;
+
+use ab_glyph::{point, Font, FontArc, PxScale, ScaleFont};
+use fractal_viz_core::view::{View, INIT_ZOOM};
+use serde::{Deserialize, Serialize};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Height of the caption text as a share of the image height
+const CAPTION_SIZE: f32 = 1.0 / 40.0;
+/// Smallest caption in pixels, so it stays readable on small images
+const MIN_CAPTION_SIZE: f32 = 12.0;
+/// Height of the logo as a share of the image height
+const LOGO_SIZE: f32 = 1.0 / 8.0;
+/// The font egui draws the gui with, which is built into it
+const FONT: &str = "Ubuntu-Light";
+
+/// A caption, logo and border drawn onto saved images, so finds that are posted carry the coordinates to reproduce them
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Watermark {
+    /// Text at the start of the caption, such as a title or name
+    pub text: String,
+    pub coordinates: bool,
+    pub zoom: bool,
+    pub date: bool,
+    pub caption_colour: [u8; 3],
+    /// Image drawn in the bottom right corner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo: Option<PathBuf>,
+    /// Width of the border around the image in pixels, 0 for none
+    pub border: u32,
+    pub border_colour: [u8; 3],
+}
+
+impl Default for Watermark {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            coordinates: false,
+            zoom: false,
+            date: false,
+            caption_colour: [255, 255, 255],
+            logo: None,
+            border: 0,
+            border_colour: [0, 0, 0],
+        }
+    }
+}
+
+impl Watermark {
+    /// True if saved images are left as they are
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && !self.coordinates && !self.zoom && !self.date && self.logo.is_none() && self.border == 0
+    }
+
+    /// The caption for an image of a view, its parts separated by dots
+    pub fn caption(&self, view: View) -> String {
+        let mut parts = Vec::new();
+        if !self.text.trim().is_empty() {
+            parts.push(self.text.trim().to_string());
+        }
+        if self.coordinates {
+            parts.push(format!("{} {:+}i", view.offset_x, view.offset_y));
+            if view.rotation != 0.0 {
+                parts.push(format!("{}°", view.rotation));
+            }
+        }
+        if self.zoom {
+            parts.push(format!("{:.3e}x", INIT_ZOOM / view.zoom));
+        }
+        if self.date {
+            parts.push(today());
+        }
+        parts.join("  ·  ")
+    }
+
+    /// Draw the caption and logo onto an rgba image of a view and put the border around it.
+    /// Returns the new image and its size, which is bigger than the original when there is a border
+    pub fn apply(&self, frame: &[u8], width: u32, height: u32, view: View) -> image::ImageResult<(Vec<u8>, u32, u32)> {
+        if self.is_empty() {
+            return Ok((frame.to_vec(), width, height));
+        }
+        let mut image = image::RgbaImage::from_raw(width, height, frame.to_vec())
+            .ok_or_else(|| image::ImageError::IoError(std::io::Error::other("the frame doesn't match its size")))?;
+
+        let margin = (height as f32 * CAPTION_SIZE).max(MIN_CAPTION_SIZE);
+        if let Some(path) = &self.logo {
+            let logo = image::open(path)?.to_rgba8();
+            let logo_height = ((height as f32 * LOGO_SIZE) as u32).clamp(1, logo.height().max(1));
+            let logo_width = (logo.width() as u64 * logo_height as u64 / logo.height().max(1) as u64).max(1) as u32;
+            let logo = image::imageops::resize(&logo, logo_width, logo_height, image::imageops::FilterType::Triangle);
+            let x = width as i64 - logo_width as i64 - margin as i64;
+            let y = height as i64 - logo_height as i64 - margin as i64;
+            image::imageops::overlay(&mut image, &logo, x, y);
+        }
+
+        let caption = self.caption(view);
+        if !caption.is_empty() {
+            let font = egui::FontDefinitions::default().font_data.remove(FONT)
+                .and_then(|data| FontArc::try_from_vec(data.font.into_owned()).ok())
+                .ok_or_else(|| image::ImageError::IoError(std::io::Error::other("the caption font couldn't be loaded")))?;
+            let size = (height as f32 * CAPTION_SIZE).max(MIN_CAPTION_SIZE);
+            let origin = (margin, height as f32 - margin);
+            // a soft shadow so the caption can be read over any part of the fractal
+            let shadow = size / 16.0;
+            draw_text(&mut image, &font, size, (origin.0 + shadow.max(1.0), origin.1 + shadow.max(1.0)), &caption, [0, 0, 0], 0.6);
+            draw_text(&mut image, &font, size, origin, &caption, self.caption_colour, 1.0);
+        }
+
+        let border = self.border;
+        let (framed_width, framed_height) = (width + border * 2, height + border * 2);
+        let [r, g, b] = self.border_colour;
+        let mut framed = image::RgbaImage::from_pixel(framed_width, framed_height, image::Rgba([r, g, b, 255]));
+        image::imageops::replace(&mut framed, &image, border as i64, border as i64);
+        Ok((framed.into_raw(), framed_width, framed_height))
+    }
+}
+
+/// Blend a line of text onto an image, `origin` is the left end of its baseline
+fn draw_text(image: &mut image::RgbaImage, font: &FontArc, size: f32, origin: (f32, f32), text: &str, colour: [u8; 3], opacity: f32) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut x = origin.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            x += scaled.kern(previous, id);
+        }
+        previous = Some(id);
+        let glyph = id.with_scale_and_position(size, point(x, origin.1));
+        x += scaled.h_advance(id);
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let (px, py) = (bounds.min.x as i64 + gx as i64, bounds.min.y as i64 + gy as i64);
+            if px < 0 || py < 0 || px >= image.width() as i64 || py >= image.height() as i64 {
+                return;
+            }
+            let pixel = image.get_pixel_mut(px as u32, py as u32);
+            let alpha = coverage.clamp(0.0, 1.0) * opacity;
+            for channel in 0..3 {
+                pixel[channel] = (pixel[channel] as f32 * (1.0 - alpha) + colour[channel] as f32 * alpha).round() as u8;
+            }
+        });
+    }
+}
+
+/// Today's date in UTC as year-month-day
+fn today() -> String {
+    let days = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |t| t.as_secs() / 86400) as i64;
+    // days since 1970 to the civil calendar, from Howard Hinnant's date algorithms
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,26 @@
+use winit::dpi::LogicalSize;
+use winit::platform::web::WindowExtWebSys;
+use winit::window::Window;
+
+/// Add the window's canvas to the page and make it fill it
+pub fn attach_canvas(window: &Window) {
+    let body = web_sys::window().and_then(|page| page.document()).and_then(|document| document.body());
+    if body.and_then(|body| body.append_child(&window.canvas()).ok()).is_none() {
+        crate::error::fatal("Couldn't add the canvas to the page");
+    }
+    fit_to_page(window);
+}
+
+/// Resize the window to the page, winit doesn't do this itself in the browser
+pub fn fit_to_page(window: &Window) {
+    let Some(page) = web_sys::window() else {
+        return;
+    };
+    let size = (page.inner_width().ok().and_then(|w| w.as_f64()), page.inner_height().ok().and_then(|h| h.as_f64()));
+    if let (Some(width), Some(height)) = size {
+        let size = LogicalSize::new(width, height);
+        if window.inner_size() != size.to_physical(window.scale_factor()) {
+            window.set_inner_size(size);
+        }
+    }
+}